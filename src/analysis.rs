@@ -7,10 +7,12 @@ pub(crate) mod semantic_cache;
 // Re-export crate-internal types and functions
 pub(crate) use result_id::next_result_id;
 pub(crate) use selection::handle_selection_range;
-pub(crate) use semantic::{LEGEND_MODIFIERS, LEGEND_TYPES, calculate_delta_or_full};
+pub(crate) use semantic::{
+    BridgedSemanticToken, LEGEND_MODIFIERS, LEGEND_TYPES, calculate_delta_or_full,
+};
 pub(crate) use semantic_cache::{InjectionMap, InjectionTokenCache, SemanticTokenCache};
 
 // Re-export crate-internal functions used by LSP layer
 pub(crate) use semantic::{
-    handle_semantic_tokens_full, handle_semantic_tokens_range_parallel_async,
+    handle_semantic_tokens_full_with_overlay, handle_semantic_tokens_range_parallel_async,
 };
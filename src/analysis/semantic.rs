@@ -2,6 +2,7 @@ mod delta;
 mod finalize;
 mod injection;
 mod legend;
+mod overlay;
 mod parallel;
 mod range;
 mod token_collector;
@@ -13,6 +14,7 @@ use tree_sitter::{Query, Tree};
 // Re-export crate-internal API from submodules
 pub(crate) use delta::calculate_delta_or_full;
 pub(crate) use legend::{LEGEND_MODIFIERS, LEGEND_TYPES};
+pub(crate) use overlay::BridgedSemanticToken;
 pub(crate) use range::handle_semantic_tokens_range_parallel_async;
 
 // Re-export for parallel processing
@@ -20,6 +22,7 @@ use parallel::collect_injection_tokens_parallel;
 
 // Internal re-exports for production code
 use finalize::finalize_tokens;
+use overlay::bridged_token_to_raw;
 use token_collector::{RawToken, collect_host_tokens};
 
 // Test-only imports
@@ -54,6 +57,36 @@ pub(crate) async fn handle_semantic_tokens_full(
     capture_mappings: Option<CaptureMappings>,
     coordinator: std::sync::Arc<crate::language::LanguageCoordinator>,
     supports_multiline: bool,
+) -> Option<SemanticTokensResult> {
+    handle_semantic_tokens_full_with_overlay(
+        text,
+        tree,
+        query,
+        filetype,
+        capture_mappings,
+        coordinator,
+        supports_multiline,
+        Vec::new(),
+    )
+    .await
+}
+
+/// Handle semantic tokens full request, overlaying tokens from bridged servers.
+///
+/// Same as [`handle_semantic_tokens_full`], but `bridged_tokens` (already in host
+/// coordinates) are remapped into the kakehashi legend and take priority over
+/// tree-sitter tokens of the injection region they fall in. Token types that
+/// cannot be represented in the legend are dropped, leaving tree-sitter tokens.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn handle_semantic_tokens_full_with_overlay(
+    text: String,
+    tree: Tree,
+    query: std::sync::Arc<Query>,
+    filetype: Option<String>,
+    capture_mappings: Option<CaptureMappings>,
+    coordinator: std::sync::Arc<crate::language::LanguageCoordinator>,
+    supports_multiline: bool,
+    bridged_tokens: Vec<BridgedSemanticToken>,
 ) -> Option<SemanticTokensResult> {
     tokio::task::spawn_blocking(move || {
         let mut all_tokens: Vec<RawToken> = Vec::with_capacity(1000);
@@ -89,6 +122,9 @@ pub(crate) async fn handle_semantic_tokens_full(
         // Merge injection tokens with host tokens
         all_tokens.extend(injection_tokens);
 
+        // Overlay bridged server tokens; the sweep line in finalize resolves overlaps
+        all_tokens.extend(bridged_tokens.iter().filter_map(bridged_token_to_raw));
+
         finalize_tokens(all_tokens, &active_injection_regions, &lines)
     })
     .await
@@ -0,0 +1,185 @@
+//! Semantic tokens contributed by bridged language servers.
+//!
+//! Servers like rust-analyzer or clangd classify tokens with type information
+//! that tree-sitter queries cannot see (traits vs structs, constants, ...).
+//! Their tokens arrive already decoded against the server's own legend and are
+//! remapped here into kakehashi's `LEGEND_TYPES`/`LEGEND_MODIFIERS`.
+//!
+//! Remapped tokens are given the injection depth of a top-level region and the
+//! highest node depth, so the sweep line in `finalize_tokens()` lets them win
+//! over tree-sitter tokens of the same region while deeper (nested) injections
+//! keep their own tokens. Positions the server does not classify keep their
+//! tree-sitter tokens.
+
+use super::legend::{LEGEND_MODIFIERS, LEGEND_TYPES};
+use super::token_collector::RawToken;
+
+/// A semantic token from a bridged server, in host document coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BridgedSemanticToken {
+    /// 0-indexed line number in the host document
+    pub line: u32,
+    /// UTF-16 column position within the line
+    pub start: u32,
+    /// Length in UTF-16 code units
+    pub length: u32,
+    /// Token type name from the server's legend (e.g., "struct", "builtinType")
+    pub token_type: String,
+    /// Token modifier names from the server's legend
+    pub modifiers: Vec<String>,
+}
+
+/// Injection depth assigned to bridged tokens (bridged regions are top-level injections).
+const BRIDGED_TOKEN_DEPTH: usize = 1;
+
+/// Map a server-specific token type onto a type in `LEGEND_TYPES`.
+///
+/// Standard LSP types pass through; a few common non-standard types are mapped
+/// onto their closest standard type. Returns the mapped name plus an implied
+/// modifier, or `None` when the type has no counterpart (e.g., punctuation).
+fn remap_token_type(token_type: &str) -> Option<(&str, Option<&'static str>)> {
+    let mapped = match token_type {
+        "builtinType" => ("type", Some("defaultLibrary")),
+        "typeAlias" => ("type", None),
+        "union" => ("struct", None),
+        "concept" => ("interface", None),
+        "selfKeyword" | "selfTypeKeyword" | "boolean" => ("keyword", None),
+        "lifetime" => ("typeParameter", None),
+        "attribute" | "builtinAttribute" | "derive" => ("decorator", None),
+        other => (other, None),
+    };
+    LEGEND_TYPES
+        .iter()
+        .any(|t| t.as_str() == mapped.0)
+        .then_some(mapped)
+}
+
+/// Map a server-specific modifier onto a modifier in `LEGEND_MODIFIERS`.
+fn remap_token_modifier(modifier: &str) -> Option<&str> {
+    let mapped = match modifier {
+        "constant" => "readonly",
+        other => other,
+    };
+    LEGEND_MODIFIERS
+        .iter()
+        .any(|m| m.as_str() == mapped)
+        .then_some(mapped)
+}
+
+/// Convert a bridged token into a `RawToken` using kakehashi's legend.
+///
+/// Returns `None` for token types that cannot be represented; modifiers without
+/// a counterpart are dropped.
+pub(super) fn bridged_token_to_raw(token: &BridgedSemanticToken) -> Option<RawToken> {
+    let (token_type, implied_modifier) = remap_token_type(&token.token_type)?;
+    let mut mapped_name = token_type.to_string();
+    // Keep the first occurrence of each modifier, wherever its duplicate is
+    let mut modifiers: Vec<&str> = Vec::new();
+    for modifier in token
+        .modifiers
+        .iter()
+        .filter_map(|m| remap_token_modifier(m))
+        .chain(implied_modifier)
+    {
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+        }
+    }
+    for modifier in modifiers {
+        mapped_name.push('.');
+        mapped_name.push_str(modifier);
+    }
+
+    Some(RawToken {
+        line: token.line as usize,
+        column: token.start as usize,
+        length: token.length as usize,
+        mapped_name,
+        depth: BRIDGED_TOKEN_DEPTH,
+        pattern_index: usize::MAX,
+        node_depth: usize::MAX,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bridged(token_type: &str, modifiers: &[&str]) -> BridgedSemanticToken {
+        BridgedSemanticToken {
+            line: 3,
+            start: 4,
+            length: 5,
+            token_type: token_type.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn standard_type_and_modifiers_pass_through() {
+        let raw = bridged_token_to_raw(&bridged("interface", &["declaration", "static"])).unwrap();
+        assert_eq!(raw.mapped_name, "interface.declaration.static");
+        assert_eq!((raw.line, raw.column, raw.length), (3, 4, 5));
+        assert_eq!(raw.depth, BRIDGED_TOKEN_DEPTH);
+    }
+
+    #[test]
+    fn non_standard_types_are_remapped() {
+        let raw = bridged_token_to_raw(&bridged("builtinType", &[])).unwrap();
+        assert_eq!(raw.mapped_name, "type.defaultLibrary");
+        let raw = bridged_token_to_raw(&bridged("selfKeyword", &[])).unwrap();
+        assert_eq!(raw.mapped_name, "keyword");
+    }
+
+    #[test]
+    fn implied_modifier_is_not_repeated() {
+        let raw = bridged_token_to_raw(&bridged("builtinType", &["defaultLibrary", "declaration"]))
+            .unwrap();
+        assert_eq!(raw.mapped_name, "type.defaultLibrary.declaration");
+    }
+
+    #[test]
+    fn unknown_modifiers_are_dropped() {
+        let raw = bridged_token_to_raw(&bridged("variable", &["mutable", "constant"])).unwrap();
+        assert_eq!(raw.mapped_name, "variable.readonly");
+    }
+
+    #[test]
+    fn bridged_token_wins_over_tree_sitter_token_in_region() {
+        use super::super::finalize::finalize_tokens;
+        use tower_lsp_server::ls_types::SemanticTokensResult;
+
+        // Tree-sitter classifies `Point` as a type; the server knows it is a struct
+        let tree_sitter = RawToken {
+            line: 3,
+            column: 4,
+            length: 5,
+            mapped_name: "type".to_string(),
+            depth: 1,
+            pattern_index: 7,
+            node_depth: 4,
+        };
+        let server = bridged_token_to_raw(&bridged("struct", &[])).unwrap();
+        let lines = ["```rust", "struct Point;", "", "use Point;"];
+
+        let Some(SemanticTokensResult::Tokens(tokens)) =
+            finalize_tokens(vec![tree_sitter, server], &[], &lines)
+        else {
+            panic!("expected tokens");
+        };
+
+        let struct_index = LEGEND_TYPES
+            .iter()
+            .position(|t| t.as_str() == "struct")
+            .unwrap() as u32;
+        assert_eq!(tokens.data.len(), 1);
+        assert_eq!(tokens.data[0].delta_start, 4);
+        assert_eq!(tokens.data[0].token_type, struct_index);
+    }
+
+    #[test]
+    fn unrepresentable_types_are_skipped() {
+        assert!(bridged_token_to_raw(&bridged("punctuation", &[])).is_none());
+        assert!(bridged_token_to_raw(&bridged("unresolvedReference", &[])).is_none());
+    }
+}
@@ -1,5 +1,15 @@
 use clap::{Parser, Subcommand};
-use kakehashi::install::{default_data_dir, metadata, parser, queries};
+use kakehashi::config::WorkspaceSettings;
+use kakehashi::doctor::{self, CheckStatus};
+use kakehashi::install::lockfile::{self, LockedLanguage, Lockfile};
+use kakehashi::install::manifest::Manifest;
+use kakehashi::install::sources::InstallSources;
+use kakehashi::install::{
+    bundle, default_data_dir, integrity, metadata, parser, prune, queries, record_in_manifest,
+    update,
+};
+use kakehashi::language::LanguageCoordinator;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// A Language Server Protocol (LSP) server using Tree-sitter for parsing
@@ -17,21 +27,50 @@ enum Commands {
     /// Manage language parsers and queries
     Language {
         #[command(subcommand)]
-        action: LanguageAction,
+        action: Box<LanguageAction>,
     },
     /// Manage configuration files
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Check the toolchain, configuration and installed languages for problems
+    Doctor {
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
 enum LanguageAction {
     /// Install a Tree-sitter parser and its queries for a language
+    ///
+    /// Versions pinned in kakehashi.lock (searched from the current directory
+    /// upwards) are installed instead of the latest ones.
     Install {
-        /// The language to install (e.g., lua, rust, python)
-        language: String,
+        /// Languages to install (e.g., lua, rust, python).
+        /// Omit to install every language pinned in the lockfile.
+        languages: Vec<String>,
+
+        /// Also install the languages listed in FILE, one per line
+        /// (blank lines and `#` comments are ignored)
+        #[arg(long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
+
+        /// Also install the language of FILE and the languages injected into
+        /// it (e.g., code blocks in Markdown). Can be given several times.
+        #[arg(long = "for", value_name = "FILE")]
+        for_files: Vec<PathBuf>,
+
+        /// Number of languages to build in parallel (default: number of CPUs;
+        /// always 1 with --verbose)
+        #[arg(long, short, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
 
         /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
         #[arg(long)]
@@ -48,12 +87,135 @@ enum LanguageAction {
         /// Bypass the metadata cache and fetch fresh data from network
         #[arg(long)]
         no_cache: bool,
+
+        /// Lockfile to install pinned versions from (default: kakehashi.lock
+        /// in the current directory or an ancestor)
+        #[arg(long, conflicts_with = "no_lockfile")]
+        lockfile: Option<PathBuf>,
+
+        /// Ignore kakehashi.lock and install the latest versions
+        #[arg(long)]
+        no_lockfile: bool,
+
+        /// Build the parser from a local grammar directory (works offline)
+        #[arg(
+            long,
+            value_name = "DIR",
+            requires = "languages",
+            conflicts_with = "git"
+        )]
+        from_path: Option<PathBuf>,
+
+        /// Build the parser from a git repository instead of nvim-treesitter's metadata
+        #[arg(long, value_name = "URL", requires = "languages")]
+        git: Option<String>,
+
+        /// Git branch, tag or commit to build (default: the repository's default branch)
+        #[arg(long, requires = "git")]
+        rev: Option<String>,
+
+        /// Subdirectory of the git repository holding the grammar
+        #[arg(long, value_name = "SUBDIR", requires = "git")]
+        location: Option<String>,
+
+        #[command(flatten)]
+        sources: SourceArgs,
+    },
+    /// Write kakehashi.lock pinning the installed parser and query versions
+    Lock {
+        /// Languages to lock (default: every installed language)
+        languages: Vec<String>,
+
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// Where to write the lockfile (default: ./kakehashi.lock)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Archive installed parsers and queries into a bundle for offline machines
+    ///
+    /// Queries the languages inherit from (e.g., ecma for typescript) are
+    /// included. Bundles only work on the OS and architecture they were
+    /// packed on.
+    Pack {
+        /// Languages to pack (e.g., lua, rust, python)
+        #[arg(required_unless_present = "installed")]
+        languages: Vec<String>,
+
+        /// Pack every language with an installed parser
+        #[arg(long, conflicts_with = "languages")]
+        installed: bool,
+
+        /// Bundle file to write (a tar archive)
+        #[arg(long, short)]
+        output: PathBuf,
+
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+    },
+    /// Verify a bundle made by 'language pack' and install its languages
+    ///
+    /// Nothing is installed unless every file matches its recorded checksum.
+    Unpack {
+        /// Bundle file to install from
+        bundle: PathBuf,
+
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+    },
+    /// List installed languages whose parser changed upstream
+    Outdated {
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// Bypass the metadata cache and fetch fresh data from network
+        #[arg(long)]
+        no_cache: bool,
+
+        #[command(flatten)]
+        sources: SourceArgs,
+    },
+    /// Rebuild installed languages whose parser changed upstream
+    ///
+    /// Each language is built in a staging directory and swapped in only on
+    /// success; the replaced files are kept under <data-dir>/rollback/.
+    Update {
+        /// Languages to update (e.g., lua, rust, python)
+        #[arg(required_unless_present = "all")]
+        languages: Vec<String>,
+
+        /// Update every outdated language
+        #[arg(long, conflicts_with = "languages")]
+        all: bool,
+
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// Print verbose output
+        #[arg(long, short)]
+        verbose: bool,
+
+        /// Bypass the metadata cache and fetch fresh data from network
+        #[arg(long)]
+        no_cache: bool,
+
+        #[command(flatten)]
+        sources: SourceArgs,
     },
     /// List supported languages for installation
     List {
         /// Bypass the metadata cache and fetch fresh data from network
         #[arg(long)]
         no_cache: bool,
+
+        #[command(flatten)]
+        sources: SourceArgs,
     },
     /// Show installed languages and their status
     Status {
@@ -65,6 +227,35 @@ enum LanguageAction {
         #[arg(long, short)]
         verbose: bool,
     },
+    /// Check installed parsers and queries against the SHA-256 recorded at
+    /// install time
+    ///
+    /// Exits with an error if any file changed or disappeared since it was
+    /// installed. Files without an install record are listed but not errors.
+    Verify {
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+    },
+    /// Remove languages that have not been used for a while
+    ///
+    /// A language is unused when the language server has not loaded it and it
+    /// was not installed within --older-than. Query directories still inherited
+    /// by a remaining language are kept. Entries of the failed parser list
+    /// without an installed parser are cleared too.
+    Prune {
+        /// Maximum age of a language in use, e.g. 90d, 12w or 36h
+        #[arg(long, default_value = "90d", value_parser = parse_age)]
+        older_than: u64,
+
+        /// Only print what would be removed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Custom data directory (default: ~/.local/share/kakehashi on Linux)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+    },
     /// Remove installed parser and queries for a language
     Uninstall {
         /// The language to uninstall (e.g., lua, rust, python)
@@ -85,6 +276,24 @@ enum LanguageAction {
     },
 }
 
+/// Mirrors to download from, overriding the `install` section of the user config
+#[derive(clap::Args)]
+struct SourceArgs {
+    /// URL of nvim-treesitter's parsers.lua
+    #[arg(long, value_name = "URL")]
+    metadata_url: Option<String>,
+
+    /// Base URL of the query files (http(s):// or file://); `{revision}` is
+    /// replaced with the query commit
+    #[arg(long, value_name = "URL")]
+    queries_url: Option<String>,
+
+    /// Clone git repositories whose URL starts with PREFIX from REPLACEMENT
+    /// instead (repeatable)
+    #[arg(long, value_name = "PREFIX=REPLACEMENT", value_parser = parse_url_rewrite)]
+    rewrite_url: Vec<(String, String)>,
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Generate a default configuration template
@@ -106,22 +315,108 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Language { action }) => match action {
+        Some(Commands::Language { action }) => match *action {
             LanguageAction::Install {
-                language,
+                languages,
+                from_file,
+                for_files,
+                jobs,
                 data_dir,
                 force,
                 verbose,
                 no_cache,
+                lockfile,
+                no_lockfile,
+                from_path,
+                git,
+                rev,
+                location,
+                sources,
+            } => {
+                let source = match (from_path, git) {
+                    (Some(path), _) => Some(parser::ParserSource::Path(path)),
+                    (None, Some(url)) => {
+                        Some(parser::ParserSource::Git(metadata::ParserMetadata {
+                            url,
+                            revision: rev.unwrap_or_else(|| "HEAD".to_string()),
+                            location,
+                        }))
+                    }
+                    (None, None) => None,
+                };
+                run_install(
+                    languages,
+                    from_file,
+                    for_files,
+                    jobs.map(usize::from),
+                    data_dir,
+                    force,
+                    verbose,
+                    no_cache,
+                    lockfile,
+                    no_lockfile,
+                    source,
+                    resolve_install_sources(sources),
+                );
+            }
+            LanguageAction::Lock {
+                languages,
+                data_dir,
+                output,
+            } => {
+                run_language_lock(languages, data_dir, output);
+            }
+            LanguageAction::Pack {
+                languages,
+                installed,
+                output,
+                data_dir,
+            } => {
+                run_language_pack(languages, installed, output, data_dir);
+            }
+            LanguageAction::Unpack { bundle, data_dir } => {
+                run_language_unpack(bundle, data_dir);
+            }
+            LanguageAction::Outdated {
+                data_dir,
+                no_cache,
+                sources,
+            } => {
+                run_language_outdated(data_dir, no_cache, resolve_install_sources(sources));
+            }
+            LanguageAction::Update {
+                languages,
+                all,
+                data_dir,
+                verbose,
+                no_cache,
+                sources,
             } => {
-                run_install(&language, data_dir, force, verbose, no_cache);
+                run_language_update(
+                    languages,
+                    all,
+                    data_dir,
+                    verbose,
+                    no_cache,
+                    resolve_install_sources(sources),
+                );
             }
-            LanguageAction::List { no_cache } => {
-                run_list_languages(no_cache);
+            LanguageAction::List { no_cache, sources } => {
+                run_list_languages(no_cache, resolve_install_sources(sources));
             }
             LanguageAction::Status { data_dir, verbose } => {
                 run_language_status(data_dir, verbose);
             }
+            LanguageAction::Verify { data_dir } => {
+                run_language_verify(data_dir);
+            }
+            LanguageAction::Prune {
+                older_than,
+                dry_run,
+                data_dir,
+            } => {
+                run_language_prune(older_than, dry_run, data_dir);
+            }
             LanguageAction::Uninstall {
                 language,
                 data_dir,
@@ -136,6 +431,9 @@ fn main() {
                 run_config_init(output, force);
             }
         },
+        Some(Commands::Doctor { data_dir, json }) => {
+            run_doctor(data_dir, json);
+        }
         None => {
             // Start LSP server (backward compatible default behavior)
             // Only create tokio runtime for LSP mode to avoid conflicts with reqwest::blocking
@@ -144,12 +442,72 @@ fn main() {
     }
 }
 
+/// Parse a `--rewrite-url` value of the form `PREFIX=REPLACEMENT`.
+fn parse_url_rewrite(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((prefix, replacement)) if !prefix.is_empty() && !replacement.is_empty() => {
+            Ok((prefix.to_string(), replacement.to_string()))
+        }
+        _ => Err("expected PREFIX=REPLACEMENT".to_string()),
+    }
+}
+
+/// Parse an age such as `90d` into seconds (units: s, m, h, d, w).
+fn parse_age(value: &str) -> Result<u64, String> {
+    let split = value.len() - value.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| "expected a number followed by s, m, h, d or w".to_string())?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "unknown unit '{}' (expected s, m, h, d or w)",
+                unit
+            ));
+        }
+    };
+    number
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| "age is too large".to_string())
+}
+
+/// Combine the `install` section of the user config with the command line
+/// flags, which take precedence.
+fn resolve_install_sources(args: SourceArgs) -> InstallSources {
+    let mut sources = match kakehashi::config::load_user_config() {
+        Ok(settings) => settings
+            .and_then(|settings| settings.install)
+            .map(|config| InstallSources::from(&config))
+            .unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            InstallSources::default()
+        }
+    };
+    if args.metadata_url.is_some() {
+        sources.metadata_url = args.metadata_url;
+    }
+    if args.queries_url.is_some() {
+        sources.queries_url = args.queries_url;
+    }
+    // Added last, so they win over configured rewrites of the same prefix
+    sources.url_rewrites.extend(args.rewrite_url);
+    sources
+}
+
 /// Run the list-languages command
-fn run_list_languages(no_cache: bool) {
+fn run_list_languages(no_cache: bool, sources: InstallSources) {
     let data_dir = default_data_dir();
     let options = metadata::FetchOptions {
         data_dir: data_dir.as_deref(),
         use_cache: !no_cache,
+        metadata_url: sources.metadata_url.as_deref(),
     };
 
     if no_cache {
@@ -192,19 +550,7 @@ fn run_language_status(data_dir: Option<PathBuf>, verbose: bool) {
     // Collect all installed languages from both parser and queries directories
     let mut languages = BTreeSet::new();
 
-    // Scan parser directory for .so, .dylib, .dll files
-    if let Ok(entries) = fs::read_dir(&parser_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            let is_parser = path
-                .extension()
-                .map(|ext| ext == "so" || ext == "dylib" || ext == "dll")
-                .unwrap_or(false);
-            if is_parser && let Some(stem) = path.file_stem() {
-                languages.insert(stem.to_string_lossy().to_string());
-            }
-        }
-    }
+    languages.extend(installed_parsers(&parser_dir));
 
     // Also check queries directory for languages that might only have queries
     if let Ok(entries) = fs::read_dir(&queries_dir) {
@@ -225,6 +571,7 @@ fn run_language_status(data_dir: Option<PathBuf>, verbose: bool) {
     }
 
     eprintln!("Installed languages (data dir: {}):", data_dir.display());
+    let manifest = Manifest::load(&data_dir).unwrap_or_default();
 
     for lang in &languages {
         let parser_path = find_parser_file(&parser_dir, lang);
@@ -242,7 +589,16 @@ fn run_language_status(data_dir: Option<PathBuf>, verbose: bool) {
             "✗ queries (missing)"
         };
 
-        println!("  {:<12} {}  {}", lang, parser_status, queries_status);
+        match manifest.languages.get(lang) {
+            Some(record) => println!(
+                "  {:<12} {}  {}  from {}",
+                lang,
+                parser_status,
+                queries_status,
+                record.origin()
+            ),
+            None => println!("  {:<12} {}  {}", lang, parser_status, queries_status),
+        }
 
         if verbose {
             if let Some(ref p) = parser_path {
@@ -254,10 +610,131 @@ fn run_language_status(data_dir: Option<PathBuf>, verbose: bool) {
                     queries_path.parent().unwrap().display()
                 );
             }
+            if let Some(record) = manifest.languages.get(lang) {
+                if let Some(parser) = &record.parser {
+                    if !parser.url.is_empty() {
+                        println!("               parser source: {}", parser.url);
+                    }
+                    println!("               parser revision: {}", parser.revision);
+                }
+                if let Some(queries) = &record.queries {
+                    println!("               queries revision: {}", queries.revision);
+                }
+            }
         }
     }
 }
 
+/// Run the language verify command
+fn run_language_verify(data_dir: Option<PathBuf>) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+
+    let manifest = Manifest::load(&data_dir).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to read install manifest: {}", e);
+        std::process::exit(1);
+    });
+    let issues = integrity::verify_data_dir(&data_dir).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to verify {}: {}", data_dir.display(), e);
+        std::process::exit(1);
+    });
+    let (tampered, unrecorded): (Vec<_>, Vec<_>) =
+        issues.iter().partition(|issue| issue.is_tampered());
+
+    for issue in &unrecorded {
+        eprintln!("Note: {}", issue);
+    }
+    if !unrecorded.is_empty() {
+        eprintln!(
+            "Reinstall these with 'kakehashi language install --force <language>' to record them."
+        );
+    }
+    if tampered.is_empty() {
+        eprintln!(
+            "✓ {} recorded language(s) in {} match their install records.",
+            manifest.languages.len(),
+            data_dir.display()
+        );
+        return;
+    }
+    for issue in &tampered {
+        eprintln!("✗ {}", issue);
+    }
+    eprintln!(
+        "{} file(s) changed since installation. Reinstall the affected languages with 'kakehashi language install --force <language>'.",
+        tampered.len()
+    );
+    std::process::exit(1);
+}
+
+fn run_language_prune(older_than: u64, dry_run: bool, data_dir: Option<PathBuf>) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let plan = prune::plan_prune(&data_dir, older_than, now).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to inspect {}: {}", data_dir.display(), e);
+        std::process::exit(1);
+    });
+    if plan.is_empty() {
+        eprintln!("Nothing to prune in {}.", data_dir.display());
+        return;
+    }
+
+    let action = if dry_run { "Would remove" } else { "Removing" };
+    for stale in &plan.languages {
+        let last_loaded = match stale.last_loaded {
+            Some(time) => format!(
+                "last loaded {} day(s) ago",
+                now.saturating_sub(time) / 86400
+            ),
+            None => "never loaded".to_string(),
+        };
+        eprintln!("{} {} ({}):", action, stale.language, last_loaded);
+        for path in stale.parser.iter().chain(&stale.queries) {
+            eprintln!("  {}", path.display());
+        }
+    }
+    for language in &plan.failed_parsers {
+        eprintln!("{} failed parser entry: {}", action, language);
+    }
+    if dry_run {
+        eprintln!("\nRun without --dry-run to remove them.");
+        return;
+    }
+
+    if let Err(e) = prune::apply_prune(&data_dir, &plan) {
+        eprintln!("✗ Failed to prune {}: {}", data_dir.display(), e);
+        std::process::exit(1);
+    }
+    eprintln!("\n✓ Pruned {} language(s).", plan.languages.len());
+}
+
+/// Languages with a parser library (.so, .dylib, .dll) in `parser_dir`.
+fn installed_parsers(parser_dir: &std::path::Path) -> std::collections::BTreeSet<String> {
+    let mut languages = std::collections::BTreeSet::new();
+    if let Ok(entries) = std::fs::read_dir(parser_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_parser = path
+                .extension()
+                .map(|ext| ext == "so" || ext == "dylib" || ext == "dll")
+                .unwrap_or(false);
+            if is_parser && let Some(stem) = path.file_stem() {
+                languages.insert(stem.to_string_lossy().to_string());
+            }
+        }
+    }
+    languages
+}
+
 /// Run the language uninstall command
 fn run_language_uninstall(
     language: Option<String>,
@@ -371,6 +848,12 @@ fn run_language_uninstall(
             }
         }
 
+        if let Err(e) = Manifest::update(&data_dir, |manifest| {
+            manifest.remove(lang);
+        }) {
+            eprintln!("Warning: Failed to update install manifest: {}", e);
+        }
+
         if removed_something {
             any_removed = true;
         } else if !all {
@@ -448,70 +931,877 @@ fn run_config_init(output: Option<PathBuf>, force: bool) {
     }
 }
 
+/// Run the health checks and print the report.
+fn run_doctor(data_dir: Option<PathBuf>, json: bool) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+    let root = std::env::current_dir().ok();
+    let report = doctor::run(&data_dir, root.as_deref());
+
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Error: Failed to serialize report: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        let mut category = None;
+        for check in &report.checks {
+            if category != Some(check.category) {
+                category = Some(check.category);
+                eprintln!("{}:", check.category.title());
+            }
+            let marker = match check.status {
+                CheckStatus::Ok => "✓",
+                CheckStatus::Warning => "!",
+                CheckStatus::Error => "✗",
+            };
+            eprintln!("  {} {}: {}", marker, check.name, check.message);
+            for detail in &check.details {
+                eprintln!("      {}", detail);
+            }
+        }
+        eprintln!(
+            "\n{} ok, {} warning(s), {} error(s)",
+            report.count(CheckStatus::Ok),
+            report.count(CheckStatus::Warning),
+            report.count(CheckStatus::Error)
+        );
+    }
+
+    if report.has_errors() {
+        std::process::exit(1);
+    }
+}
+
+/// `eprintln!` into an [`InstallLog`].
+macro_rules! log_line {
+    ($log:expr, $($arg:tt)*) => {
+        $log.line(format_args!($($arg)*))
+    };
+}
+
 /// Run the install command (synchronous - no tokio runtime)
+#[allow(clippy::too_many_arguments)]
 fn run_install(
-    language: &str,
+    languages: Vec<String>,
+    from_file: Option<PathBuf>,
+    for_files: Vec<PathBuf>,
+    jobs: Option<usize>,
     data_dir: Option<PathBuf>,
     force: bool,
     verbose: bool,
     no_cache: bool,
+    lockfile_path: Option<PathBuf>,
+    no_lockfile: bool,
+    source: Option<parser::ParserSource>,
+    sources: InstallSources,
 ) {
     let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
         eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
         std::process::exit(1);
     });
 
+    // An explicit source replaces whatever the lockfile pins
+    let lockfile_path = if no_lockfile || source.is_some() {
+        None
+    } else {
+        lockfile_path.or_else(|| {
+            std::env::current_dir()
+                .ok()
+                .and_then(|cwd| lockfile::find_lockfile(&cwd))
+        })
+    };
+    let lock = lockfile_path.as_ref().map(|path| {
+        Lockfile::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: Failed to read {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+    if let Some(path) = &lockfile_path {
+        eprintln!("Using lockfile {}", path.display());
+    }
+
+    let explicit = !languages.is_empty() || from_file.is_some() || !for_files.is_empty();
+    let mut languages = match &lock {
+        _ if explicit => languages,
+        Some(lock) => lock.languages.keys().cloned().collect(),
+        None => {
+            eprintln!(
+                "Error: No language given and no {} found.",
+                lockfile::LOCKFILE_NAME
+            );
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = &from_file {
+        languages.extend(read_language_list(path).unwrap_or_else(|e| {
+            eprintln!("Error: Failed to read {}: {}", path.display(), e);
+            std::process::exit(1);
+        }));
+    }
+
+    // Documents of --for, with their detected language. Languages that are
+    // already installed are only loaded to find the injections.
+    let coordinator = (!for_files.is_empty()).then(|| {
+        let coordinator = LanguageCoordinator::new();
+        coordinator.load_settings(WorkspaceSettings {
+            search_paths: vec![data_dir.to_string_lossy().into_owned()],
+            ..WorkspaceSettings::default()
+        });
+        coordinator
+    });
+    let installed = installed_parsers(&data_dir.join("parser"));
+    let needs_install = |language: &str| force || !installed.contains(language);
+    let documents: Vec<(String, String)> = for_files
+        .iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+                eprintln!("Error: Failed to read {}: {}", path.display(), e);
+                std::process::exit(1);
+            });
+            let host = coordinator
+                .as_ref()
+                .and_then(|coordinator| {
+                    coordinator.detect_document_language(&path.to_string_lossy(), &text)
+                })
+                .unwrap_or_else(|| {
+                    eprintln!(
+                        "Error: Could not detect the language of {}.",
+                        path.display()
+                    );
+                    std::process::exit(1);
+                });
+            (host, text)
+        })
+        .collect();
+    languages.extend(
+        documents
+            .iter()
+            .map(|(host, _)| host.clone())
+            .filter(|host| needs_install(host)),
+    );
+    dedup_languages(&mut languages);
+
+    if source.is_some() && languages.len() > 1 {
+        eprintln!("Error: --from-path and --git build a single language.");
+        std::process::exit(1);
+    }
+
+    // Verbose build output is printed as it happens, so parallel builds
+    // would interleave it
+    let jobs = match jobs {
+        _ if verbose => 1,
+        Some(jobs) => jobs,
+        None => std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get),
+    };
+    let install = |language: &str, log: &mut InstallLog| {
+        let pins = lock.as_ref().and_then(|lock| lock.languages.get(language));
+        if lock.is_some() && pins.is_none() {
+            log_line!(
+                log,
+                "Note: '{}' is not pinned in the lockfile; installing the latest version.",
+                language
+            );
+        }
+        install_language(
+            language,
+            &data_dir,
+            force,
+            verbose,
+            no_cache,
+            pins,
+            source.clone(),
+            &sources,
+            log,
+        )
+    };
+    let mut outcomes = install_languages(&languages, jobs, install);
+
+    // Injections can only be found once the document's parser is installed,
+    // and injections nested in them once the injected parsers are
+    if let Some(coordinator) = &coordinator {
+        loop {
+            let mut injected: Vec<String> = documents
+                .iter()
+                .flat_map(|(host, text)| coordinator.injected_languages(host, text))
+                .filter(|language| {
+                    !outcomes.iter().any(|(done, _)| done == language) && needs_install(language)
+                })
+                .collect();
+            dedup_languages(&mut injected);
+            if injected.is_empty() {
+                break;
+            }
+            eprintln!("Installing injected languages: {}", injected.join(", "));
+            outcomes.extend(install_languages(&injected, jobs, install));
+        }
+    }
+
+    let failed: Vec<&str> = outcomes
+        .iter()
+        .filter(|(_, success)| !success)
+        .map(|(language, _)| language.as_str())
+        .collect();
+    if outcomes.len() > 1 {
+        eprintln!(
+            "\nInstalled {} of {} language(s).",
+            outcomes.len() - failed.len(),
+            outcomes.len()
+        );
+        if !failed.is_empty() {
+            eprintln!("✗ Failed: {}", failed.join(", "));
+        }
+    } else if outcomes.is_empty() {
+        eprintln!("✓ Nothing to install; every language is already installed.");
+    }
+
+    if !failed.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Read a list of languages, one per line; blank lines and `#` comments
+/// are ignored.
+fn read_language_list(path: &std::path::Path) -> std::io::Result<Vec<String>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Remove repeated languages, keeping the first occurrence of each.
+fn dedup_languages(languages: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    languages.retain(|language| seen.insert(language.clone()));
+}
+
+/// Install `languages` on up to `jobs` threads, returning whether each one
+/// was installed, in the order given.
+///
+/// With several threads, the output of each language is printed in one
+/// piece once it finishes rather than interleaved.
+fn install_languages(
+    languages: &[String],
+    jobs: usize,
+    install: impl Fn(&str, &mut InstallLog) -> bool + Sync,
+) -> Vec<(String, bool)> {
+    let jobs = jobs.min(languages.len());
+    if jobs <= 1 {
+        return languages
+            .iter()
+            .map(|language| (language.clone(), install(language, &mut InstallLog::live())))
+            .collect();
+    }
+
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let results = std::sync::Mutex::new(vec![false; languages.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    let Some(language) = languages.get(index) else {
+                        break;
+                    };
+                    let mut log = InstallLog::buffered();
+                    let success = install(language, &mut log);
+                    log.flush();
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = success;
+                }
+            });
+        }
+    });
+    let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    languages.iter().cloned().zip(results).collect()
+}
+
+/// Output of one language install: printed as it is written, or kept and
+/// printed in one piece while other languages install in parallel.
+struct InstallLog {
+    buffer: Option<String>,
+}
+
+impl InstallLog {
+    fn live() -> Self {
+        Self { buffer: None }
+    }
+
+    fn buffered() -> Self {
+        Self {
+            buffer: Some(String::new()),
+        }
+    }
+
+    fn line(&mut self, line: std::fmt::Arguments) {
+        match &mut self.buffer {
+            Some(buffer) => {
+                use std::fmt::Write;
+                let _ = writeln!(buffer, "{}", line);
+            }
+            None => eprintln!("{}", line),
+        }
+    }
+
+    /// Print the kept output, if any.
+    fn flush(self) {
+        if let Some(buffer) = self.buffer {
+            eprint!("{}", buffer);
+        }
+    }
+}
+
+/// Install the parser and queries of one language, recording them in the
+/// manifest. Returns whether both were installed.
+///
+/// `source` overrides where the parser is built from; otherwise the
+/// lockfile pins (if any) or the latest nvim-treesitter metadata are used.
+#[allow(clippy::too_many_arguments)]
+fn install_language(
+    language: &str,
+    data_dir: &std::path::Path,
+    force: bool,
+    verbose: bool,
+    no_cache: bool,
+    pins: Option<&LockedLanguage>,
+    source: Option<parser::ParserSource>,
+    sources: &InstallSources,
+    log: &mut InstallLog,
+) -> bool {
+    // Languages already installed at the pinned versions are left alone;
+    // installed languages at other versions are replaced to honour the lock.
+    let mut force = force;
+    if let Some(pins) = pins {
+        let installed = Manifest::load(data_dir)
+            .ok()
+            .and_then(|manifest| manifest.languages.get(language).cloned());
+        match installed {
+            Some(record) if is_pinned_install(&record, pins) && !force => {
+                log_line!(log, "✓ '{}' matches the lockfile", language);
+                return true;
+            }
+            Some(_) => force = true,
+            None => {}
+        }
+    }
+
     // Track success/failure for exit code
     let mut parser_success = true;
     let mut queries_success = true;
 
     // Install parser
-    eprintln!("Installing parser for '{}' to {:?}...", language, data_dir);
+    log_line!(
+        log,
+        "Installing parser for '{}' to {:?}...",
+        language,
+        data_dir
+    );
 
     let options = parser::InstallOptions {
-        data_dir: data_dir.clone(),
+        data_dir: data_dir.to_path_buf(),
         force,
         verbose,
         no_cache,
+        source: source.or_else(|| {
+            pins.and_then(|pins| pins.parser.as_ref())
+                .map(|parser| parser.source())
+        }),
+        sources: sources.clone(),
     };
+    let source_kind = options
+        .source
+        .as_ref()
+        .map_or(parser::SourceKind::Metadata, |source| source.kind());
 
     match parser::install_parser(language, &options) {
-        Ok(result) => {
-            eprintln!("✓ Parser installed: {}", result.install_path.display());
+        Ok(mut result) => {
+            log_line!(log, "✓ Parser installed: {}", result.install_path.display());
             if verbose {
-                eprintln!("  Revision: {}", result.revision);
+                log_line!(log, "  Revision: {}", result.revision);
+            }
+            let bundled_queries = result.bundled_queries.take();
+            if let Err(e) = record_in_manifest(data_dir, |manifest| manifest.record_parser(&result))
+            {
+                log_line!(log, "Warning: Failed to update install manifest: {}", e);
+            }
+            // Queries shipped with a custom grammar take the place of nvim-treesitter's
+            if let Some(queries) = bundled_queries {
+                log_line!(
+                    log,
+                    "✓ Queries installed from the grammar: {}",
+                    queries.install_path.display()
+                );
+                if verbose {
+                    log_line!(log, "  Files: {}", queries.files_downloaded.join(", "));
+                }
+                if let Err(e) = record_in_manifest(data_dir, |manifest| {
+                    manifest.record_queries_with_inherited(&queries)
+                }) {
+                    log_line!(log, "Warning: Failed to update install manifest: {}", e);
+                }
+                log_line!(
+                    log,
+                    "\nSuccessfully installed '{}' language support.",
+                    language
+                );
+                return true;
             }
         }
         Err(e) => {
-            eprintln!("✗ Parser installation failed: {}", e);
+            log_line!(log, "✗ Parser installation failed: {}", e);
             parser_success = false;
         }
     }
 
-    // Install queries (with inherited dependencies)
-    eprintln!("Installing queries for '{}' to {:?}...", language, data_dir);
+    // A local grammar stays offline: without bundled queries there is nothing to install
+    if source_kind == parser::SourceKind::Path {
+        if parser_success {
+            log_line!(
+                log,
+                "Note: The grammar has no queries/ directory; configure languages.{}.queries to add highlighting.",
+                language
+            );
+        }
+        return parser_success;
+    }
 
-    match queries::install_queries_with_dependencies(language, &data_dir, force) {
+    // Install queries (with inherited dependencies)
+    log_line!(
+        log,
+        "Installing queries for '{}' to {:?}...",
+        language,
+        data_dir
+    );
+
+    let pinned_queries = pins.and_then(|pins| pins.queries.as_ref());
+    let query_revision = pinned_queries.map(|queries| queries.revision.as_str());
+    match queries::install_queries_with_dependencies(
+        language,
+        data_dir,
+        force,
+        query_revision,
+        sources,
+    ) {
+        // Files downloaded at a pinned commit must be the ones that were locked
+        Ok(result)
+            if let Some(pinned) = pinned_queries.filter(|_| sources.queries_are_versioned())
+                && let Err(problems) = check_locked_queries(language, data_dir, pinned) =>
+        {
+            log_line!(
+                log,
+                "✗ Queries downloaded for '{}' do not match the lockfile:",
+                language
+            );
+            for problem in problems {
+                log_line!(log, "  {}", problem);
+            }
+            if let Err(e) = std::fs::remove_dir_all(&result.install_path) {
+                log_line!(
+                    log,
+                    "Warning: Failed to remove {}: {}",
+                    result.install_path.display(),
+                    e
+                );
+            }
+            queries_success = false;
+        }
         Ok(result) => {
-            eprintln!("✓ Queries installed: {}", result.install_path.display());
+            log_line!(
+                log,
+                "✓ Queries installed: {}",
+                result.install_path.display()
+            );
+            if let Some(reason) = &result.unresolved {
+                log_line!(
+                    log,
+                    "Note: could not resolve nvim-treesitter commit ({}); using {}",
+                    reason,
+                    queries::DEFAULT_QUERIES_BRANCH
+                );
+            }
             if verbose {
-                eprintln!("  Files: {}", result.files_downloaded.join(", "));
+                log_line!(log, "  Files: {}", result.files_downloaded.join(", "));
+                log_line!(log, "  Revision: {}", result.revision);
             }
+            if let Err(e) = record_in_manifest(data_dir, |manifest| {
+                manifest.record_queries_with_inherited(&result)
+            }) {
+                log_line!(log, "Warning: Failed to update install manifest: {}", e);
+            }
+        }
+        // nvim-treesitter has no queries for most custom grammars
+        Err(queries::QueryInstallError::LanguageNotSupported(_)) if source_kind.is_custom() => {
+            log_line!(
+                log,
+                "Note: Neither the grammar nor nvim-treesitter provides queries for '{}'.",
+                language
+            );
         }
         Err(e) => {
-            eprintln!("✗ Query installation failed: {}", e);
+            log_line!(log, "✗ Query installation failed: {}", e);
             queries_success = false;
         }
     }
 
     // Summary
     if parser_success && queries_success {
-        eprintln!("\nSuccessfully installed '{}' language support.", language);
+        log_line!(
+            log,
+            "\nSuccessfully installed '{}' language support.",
+            language
+        );
     } else if !parser_success && !queries_success {
-        eprintln!("\nFailed to install '{}' language support.", language);
+        log_line!(log, "\nFailed to install '{}' language support.", language);
+    } else {
+        log_line!(
+            log,
+            "\nPartially installed '{}' language support.",
+            language
+        );
+    }
+    parser_success && queries_success
+}
+
+/// Check the query files of `language` against the hashes in the lockfile,
+/// returning what does not match.
+fn check_locked_queries(
+    language: &str,
+    data_dir: &std::path::Path,
+    pinned: &kakehashi::install::manifest::QueriesRecord,
+) -> Result<(), Vec<String>> {
+    let issues = integrity::verify_query_files(language, data_dir, &pinned.files)
+        .map_err(|e| vec![format!("Failed to read the installed queries: {}", e)])?;
+    let problems: Vec<String> = issues
+        .iter()
+        .filter(|issue| issue.is_tampered())
+        .map(|issue| issue.to_string())
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Whether an install record matches the versions pinned for it.
+fn is_pinned_install(
+    record: &kakehashi::install::manifest::LanguageRecord,
+    pins: &LockedLanguage,
+) -> bool {
+    let parser_matches = match (&record.parser, &pins.parser) {
+        (Some(installed), Some(pinned)) => {
+            installed.url == pinned.url
+                && installed.revision == pinned.revision
+                && installed.location == pinned.location
+        }
+        (_, None) => true,
+        (None, Some(_)) => false,
+    };
+    let queries_matches = match (&record.queries, &pins.queries) {
+        (Some(installed), Some(pinned)) => installed.revision == pinned.revision,
+        (_, None) => true,
+        (None, Some(_)) => false,
+    };
+    parser_matches && queries_matches
+}
+
+/// Run the language lock command
+fn run_language_lock(languages: Vec<String>, data_dir: Option<PathBuf>, output: Option<PathBuf>) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+    let output = output.unwrap_or_else(|| PathBuf::from(lockfile::LOCKFILE_NAME));
+
+    let manifest = Manifest::load(&data_dir).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to read install manifest: {}", e);
         std::process::exit(1);
+    });
+    let lock = Lockfile::from_manifest(&manifest, &languages).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    if lock.languages.is_empty() {
+        eprintln!(
+            "Error: No installed languages are recorded in {}.",
+            data_dir.display()
+        );
+        eprintln!("Use 'kakehashi language install <language>' to install one.");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = lock.save(&output) {
+        eprintln!("Error: Failed to write {}: {}", output.display(), e);
+        std::process::exit(1);
+    }
+    eprintln!(
+        "Locked {} language(s) in {}:",
+        lock.languages.len(),
+        output.display()
+    );
+    for (language, pins) in &lock.languages {
+        let parser = pins
+            .parser
+            .as_ref()
+            .map_or("-", |parser| parser.revision.as_str());
+        let queries = pins
+            .queries
+            .as_ref()
+            .map_or("-", |queries| queries.revision.as_str());
+        println!("  {:<12} parser {}  queries {}", language, parser, queries);
+    }
+}
+
+/// Run the language pack command
+fn run_language_pack(
+    languages: Vec<String>,
+    installed: bool,
+    output: PathBuf,
+    data_dir: Option<PathBuf>,
+) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+    let languages: Vec<String> = if installed {
+        installed_parsers(&data_dir.join("parser"))
+            .into_iter()
+            .collect()
     } else {
-        eprintln!("\nPartially installed '{}' language support.", language);
+        languages
+    };
+    if languages.is_empty() {
+        eprintln!("Error: No parsers installed in {}.", data_dir.display());
+        std::process::exit(1);
+    }
+
+    match bundle::pack_bundle(&languages, &data_dir, &output) {
+        Ok(manifest) => {
+            eprintln!(
+                "✓ Packed {} language(s) into {} ({}):",
+                manifest.languages.len(),
+                output.display(),
+                manifest.platform
+            );
+            print_bundle_languages(&manifest);
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to pack bundle: {}", e);
+            if matches!(e, bundle::BundleError::NotInstalled(_)) {
+                eprintln!("Use 'kakehashi language install <language>' to install it first.");
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run the language unpack command
+fn run_language_unpack(bundle: PathBuf, data_dir: Option<PathBuf>) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+
+    let manifest = bundle::read_bundle_manifest(&bundle).unwrap_or_else(|e| {
+        eprintln!("✗ Failed to read {}: {}", bundle.display(), e);
+        std::process::exit(1);
+    });
+    match bundle::unpack_bundle(&bundle, &data_dir, None) {
+        Ok(languages) => {
+            eprintln!(
+                "✓ Unpacked {} language(s) from {} into {}:",
+                languages.len(),
+                bundle.display(),
+                data_dir.display()
+            );
+            print_bundle_languages(&manifest);
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to unpack {}: {}", bundle.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Print the parser and query revisions of each language in a bundle.
+fn print_bundle_languages(manifest: &bundle::BundleManifest) {
+    for (language, record) in &manifest.languages {
+        let parser = record
+            .parser
+            .as_ref()
+            .map_or("-", |parser| parser.revision.as_str());
+        let queries = record
+            .queries
+            .as_ref()
+            .map_or("-", |queries| queries.revision.as_str());
+        println!("  {:<12} parser {}  queries {}", language, parser, queries);
+    }
+}
+
+/// Load the install manifest and the current parser metadata, exiting on failure.
+fn load_manifest_and_metadata(
+    data_dir: &std::path::Path,
+    no_cache: bool,
+    sources: &InstallSources,
+) -> (Manifest, HashMap<String, metadata::ParserMetadata>) {
+    let manifest = Manifest::load(data_dir).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to read install manifest: {}", e);
+        std::process::exit(1);
+    });
+    let options = metadata::FetchOptions {
+        data_dir: Some(data_dir),
+        use_cache: !no_cache,
+        metadata_url: sources.metadata_url.as_deref(),
+    };
+    let metadata = metadata::fetch_all_parser_metadata(Some(&options)).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to fetch parser metadata: {}", e);
+        std::process::exit(1);
+    });
+    (manifest, metadata)
+}
+
+/// Run the language outdated command
+fn run_language_outdated(data_dir: Option<PathBuf>, no_cache: bool, sources: InstallSources) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+
+    let (manifest, metadata) = load_manifest_and_metadata(&data_dir, no_cache, &sources);
+    let outdated = update::outdated_languages(&manifest, &metadata);
+    if outdated.is_empty() {
+        eprintln!("All installed languages are up to date.");
+        return;
+    }
+
+    eprintln!("Outdated languages ({} total):", outdated.len());
+    for entry in &outdated {
+        println!(
+            "  {:<12} {} -> {}",
+            entry.language, entry.installed.revision, entry.latest.revision
+        );
+    }
+    eprintln!("\nRun 'kakehashi language update --all' to update them.");
+}
+
+/// Run the language update command
+fn run_language_update(
+    languages: Vec<String>,
+    all: bool,
+    data_dir: Option<PathBuf>,
+    verbose: bool,
+    no_cache: bool,
+    sources: InstallSources,
+) {
+    let data_dir = data_dir.or_else(default_data_dir).unwrap_or_else(|| {
+        eprintln!("Error: Could not determine data directory. Please specify --data-dir.");
+        std::process::exit(1);
+    });
+
+    let (manifest, metadata) = load_manifest_and_metadata(&data_dir, no_cache, &sources);
+    let outdated: HashMap<String, update::OutdatedLanguage> =
+        update::outdated_languages(&manifest, &metadata)
+            .into_iter()
+            .map(|entry| (entry.language.clone(), entry))
+            .collect();
+
+    let mut all_success = true;
+    let mut targets = Vec::new();
+    if all {
+        let mut names: Vec<&String> = outdated.keys().collect();
+        names.sort();
+        targets.extend(names.into_iter().map(|name| (name.clone(), None)));
+    } else {
+        for language in &languages {
+            let custom_source = manifest
+                .languages
+                .get(language)
+                .and_then(|record| record.parser.as_ref())
+                .is_some_and(|parser| parser.kind.is_custom());
+            if custom_source {
+                eprintln!(
+                    "✗ '{}' was installed from a custom source; reinstall it with 'kakehashi language install --force'",
+                    language
+                );
+                all_success = false;
+                continue;
+            }
+            let Some(latest) = metadata.get(language) else {
+                eprintln!(
+                    "✗ '{}' is not listed in nvim-treesitter's parser metadata",
+                    language
+                );
+                all_success = false;
+                continue;
+            };
+            if manifest.languages.contains_key(language) {
+                if !outdated.contains_key(language) {
+                    eprintln!("✓ '{}' is up to date", language);
+                    continue;
+                }
+            } else if parser::parser_file_exists(language, &data_dir).is_none() {
+                eprintln!(
+                    "✗ '{}' is not installed. Use 'kakehashi language install {}' first.",
+                    language, language
+                );
+                all_success = false;
+                continue;
+            }
+            targets.push((language.clone(), Some(latest.clone())));
+        }
+    }
+
+    if targets.is_empty() && all_success {
+        eprintln!("All installed languages are up to date.");
+        return;
+    }
+
+    for (language, latest) in targets {
+        let (previous, latest) = match outdated.get(&language) {
+            Some(entry) => (entry.installed.revision.as_str(), entry.latest.clone()),
+            None => ("unknown", latest.expect("explicit targets carry metadata")),
+        };
+        eprintln!(
+            "Updating '{}' ({} -> {})...",
+            language, previous, latest.revision
+        );
+        match update::update_language(&language, &data_dir, latest, &sources, verbose) {
+            Ok(result) => {
+                eprintln!("✓ Parser updated: {}", result.parser.install_path.display());
+                if let Some(queries) = &result.queries {
+                    eprintln!("✓ Queries updated: {}", queries.install_path.display());
+                    if let Some(reason) = &queries.unresolved {
+                        eprintln!(
+                            "Note: could not resolve nvim-treesitter commit ({}); using {}",
+                            reason,
+                            queries::DEFAULT_QUERIES_BRANCH
+                        );
+                    }
+                    if verbose {
+                        eprintln!("  Revision: {}", queries.revision);
+                    }
+                }
+                if verbose
+                    && let Some(rollback) = update::rollback_parser_path(&language, &data_dir)
+                {
+                    eprintln!("  Previous parser kept at: {}", rollback.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("✗ Update of '{}' failed: {}", language, e);
+                eprintln!("  The installed version was left unchanged.");
+                all_success = false;
+            }
+        }
+    }
+
+    if !all_success {
         std::process::exit(1);
     }
 }
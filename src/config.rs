@@ -1,13 +1,14 @@
 pub mod defaults;
 pub mod settings;
-pub(crate) mod user;
+pub mod user;
 
 pub use settings::{
-    BridgeServerConfig, CaptureMapping, CaptureMappings, LanguageConfig, LanguageSettings,
-    QueryItem, QueryKind, QueryTypeMappings, TreeSitterSettings, WorkspaceSettings,
+    BridgeMergeConfig, BridgeMergeStrategy, BridgeServerConfig, CaptureMapping, CaptureMappings,
+    InstallSourcesConfig, LanguageConfig, LanguageSettings, QueryItem, QueryKind,
+    QueryTypeMappings, TreeSitterSettings, WorkspaceSettings,
 };
 use std::collections::HashMap;
-pub(crate) use user::load_user_config;
+pub use user::load_user_config;
 
 /// Wildcard key for default configurations in HashMap-based settings.
 /// Used in capture_mappings, languages, and language_servers for fallback values.
@@ -100,6 +101,14 @@ pub(crate) fn resolve_language_server_with_wildcard(
                     (None, None) => None,
                 },
                 workspace_type: s.workspace_type.or(w.workspace_type),
+                priority: s.priority.or(w.priority),
+                semantic_tokens: s.semantic_tokens.or(w.semantic_tokens),
+                connect: s.connect.clone().or_else(|| w.connect.clone()),
+                restart: s.restart.clone().or_else(|| w.restart.clone()),
+                timeouts: s.timeouts.clone().or_else(|| w.timeouts.clone()),
+                env: s.env.clone().or_else(|| w.env.clone()),
+                cwd: s.cwd.clone().or_else(|| w.cwd.clone()),
+                root_markers: s.root_markers.clone().or_else(|| w.root_markers.clone()),
             })
         }
         (Some(w), None) => Some(w.clone()),
@@ -117,7 +126,7 @@ pub(crate) fn resolve_language_server_with_wildcard(
 /// - If neither exists: return None
 ///
 /// The merge creates a new LanguageSettings where specific values override wildcard values.
-/// This is used by get_bridge_config_for_language to look up host language settings.
+/// This is used by the bridge server lookup to resolve host language settings.
 pub(crate) fn resolve_language_settings_with_wildcard(
     map: &HashMap<String, LanguageSettings>,
     key: &str,
@@ -135,8 +144,11 @@ pub(crate) fn resolve_language_settings_with_wildcard(
                 queries: s.queries.clone().or_else(|| w.queries.clone()),
                 // Deep merge bridge HashMaps: wildcard + specific
                 bridge: merge_bridge_maps(&w.bridge, &s.bridge),
+                host: s.host.clone().or_else(|| w.host.clone()),
                 // Aliases are not inherited from wildcard - they're specific to each language
                 aliases: s.aliases.clone(),
+                // Neither is the parser source
+                source: s.source.clone(),
             })
         }
         (Some(w), None) => Some(w.clone()),
@@ -197,6 +209,15 @@ pub(crate) fn merge_settings(
                     fallback.language_servers,
                     primary.language_servers,
                 ),
+
+                // Merge per-feature strategies field by field
+                bridge_merge: settings::BridgeMergeConfig::merge(
+                    fallback.bridge_merge,
+                    primary.bridge_merge,
+                ),
+
+                // Merge mirrors field by field, and URL rewrites per prefix
+                install: settings::InstallSourcesConfig::merge(fallback.install, primary.install),
             };
             Some(merged)
         }
@@ -209,7 +230,9 @@ impl From<&LanguageConfig> for LanguageSettings {
             parser: config.parser.clone(),
             queries: config.queries.clone(),
             bridge: config.bridge.clone(),
+            host: config.host.clone(),
             aliases: config.aliases.clone(),
+            source: config.source.clone(),
         }
     }
 }
@@ -220,7 +243,9 @@ impl From<&LanguageSettings> for LanguageConfig {
             parser: settings.parser.clone(),
             queries: settings.queries.clone(),
             bridge: settings.bridge.clone(),
+            host: settings.host.clone(),
             aliases: settings.aliases.clone(),
+            source: settings.source.clone(),
         }
     }
 }
@@ -253,13 +278,17 @@ impl From<&TreeSitterSettings> for WorkspaceSettings {
             .clone()
             .unwrap_or_else(default_search_paths);
 
-        WorkspaceSettings::with_language_servers(
-            search_paths,
-            languages,
-            capture_mappings,
-            settings.auto_install.unwrap_or(true), // Default to true for zero-config
-            settings.language_servers.clone(),
-        )
+        WorkspaceSettings {
+            bridge_merge: settings.bridge_merge.clone(),
+            install: settings.install.clone(),
+            ..WorkspaceSettings::with_language_servers(
+                search_paths,
+                languages,
+                capture_mappings,
+                settings.auto_install.unwrap_or(true), // Default to true for zero-config
+                settings.language_servers.clone(),
+            )
+        }
     }
 }
 
@@ -303,6 +332,8 @@ impl From<&WorkspaceSettings> for TreeSitterSettings {
             capture_mappings,
             auto_install: Some(settings.auto_install),
             language_servers: settings.language_servers.clone(),
+            bridge_merge: settings.bridge_merge.clone(),
+            install: settings.install.clone(),
         }
     }
 }
@@ -334,10 +365,18 @@ fn merge_languages(
                     .bridge
                     .clone()
                     .or_else(|| base_config.bridge.clone());
+                base_config.host = overlay_config
+                    .host
+                    .clone()
+                    .or_else(|| base_config.host.clone());
                 base_config.aliases = overlay_config
                     .aliases
                     .clone()
                     .or_else(|| base_config.aliases.clone());
+                base_config.source = overlay_config
+                    .source
+                    .clone()
+                    .or_else(|| base_config.source.clone());
             })
             .or_insert(overlay_config);
     }
@@ -378,6 +417,34 @@ fn merge_language_servers(
                         };
                         base_config.workspace_type =
                             overlay_config.workspace_type.or(base_config.workspace_type);
+                        base_config.priority = overlay_config.priority.or(base_config.priority);
+                        base_config.semantic_tokens = overlay_config
+                            .semantic_tokens
+                            .or(base_config.semantic_tokens);
+                        base_config.connect = overlay_config
+                            .connect
+                            .clone()
+                            .or_else(|| base_config.connect.clone());
+                        base_config.restart = overlay_config
+                            .restart
+                            .clone()
+                            .or_else(|| base_config.restart.clone());
+                        base_config.timeouts = overlay_config
+                            .timeouts
+                            .clone()
+                            .or_else(|| base_config.timeouts.clone());
+                        base_config.env = overlay_config
+                            .env
+                            .clone()
+                            .or_else(|| base_config.env.clone());
+                        base_config.cwd = overlay_config
+                            .cwd
+                            .clone()
+                            .or_else(|| base_config.cwd.clone());
+                        base_config.root_markers = overlay_config
+                            .root_markers
+                            .clone()
+                            .or_else(|| base_config.root_markers.clone());
                     })
                     .or_insert(overlay_config);
             }
@@ -476,8 +543,11 @@ pub(crate) fn resolve_language_with_wildcard(
                 queries: s.queries.clone().or_else(|| w.queries.clone()),
                 // Deep merge bridge HashMaps: wildcard + specific
                 bridge: merge_bridge_maps(&w.bridge, &s.bridge),
+                host: s.host.clone().or_else(|| w.host.clone()),
                 // Aliases are not merged from wildcard - they're specific to each language
                 aliases: s.aliases.clone(),
+                // Neither is the parser source
+                source: s.source.clone(),
             })
         }
         (Some(w), None) => Some(w.clone()),
@@ -530,6 +600,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let result = merge_settings(Some(fallback.clone()), None).unwrap();
         assert_eq!(
@@ -546,6 +618,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let result = merge_settings(None, Some(primary.clone())).unwrap();
         assert_eq!(
@@ -554,6 +628,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_settings_merges_bridge_merge_per_feature() {
+        let fallback = TreeSitterSettings {
+            bridge_merge: Some(settings::BridgeMergeConfig {
+                hover: Some(BridgeMergeStrategy::First),
+                references: Some(BridgeMergeStrategy::First),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let primary = TreeSitterSettings {
+            bridge_merge: Some(settings::BridgeMergeConfig {
+                hover: Some(BridgeMergeStrategy::Merge),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let merged = merge_settings(Some(fallback), Some(primary))
+            .unwrap()
+            .bridge_merge
+            .unwrap();
+        assert_eq!(merged.hover, Some(BridgeMergeStrategy::Merge));
+        assert_eq!(merged.references, Some(BridgeMergeStrategy::First));
+        assert_eq!(merged.completion, None);
+    }
+
+    #[test]
+    fn test_merge_settings_merges_install_sources_per_field() {
+        let fallback = TreeSitterSettings {
+            install: Some(settings::InstallSourcesConfig {
+                metadata_url: Some("https://user.internal/parsers.lua".to_string()),
+                queries_url: Some("https://user.internal/{revision}".to_string()),
+                url_rewrites: Some(HashMap::from([
+                    (
+                        "https://github.com/".to_string(),
+                        "https://user.internal/".to_string(),
+                    ),
+                    (
+                        "https://gitlab.com/".to_string(),
+                        "https://user.internal/gitlab/".to_string(),
+                    ),
+                ])),
+                bundle_dir: Some("/srv/kakehashi/bundles".to_string()),
+                verify: None,
+            }),
+            ..Default::default()
+        };
+        let primary = TreeSitterSettings {
+            install: Some(settings::InstallSourcesConfig {
+                metadata_url: Some("https://project.internal/parsers.lua".to_string()),
+                url_rewrites: Some(HashMap::from([(
+                    "https://github.com/".to_string(),
+                    "https://project.internal/".to_string(),
+                )])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let merged = merge_settings(Some(fallback), Some(primary))
+            .unwrap()
+            .install
+            .unwrap();
+        assert_eq!(
+            merged.metadata_url.as_deref(),
+            Some("https://project.internal/parsers.lua")
+        );
+        assert_eq!(
+            merged.queries_url.as_deref(),
+            Some("https://user.internal/{revision}")
+        );
+        let rewrites = merged.url_rewrites.unwrap();
+        assert_eq!(rewrites["https://github.com/"], "https://project.internal/");
+        assert_eq!(
+            rewrites["https://gitlab.com/"],
+            "https://user.internal/gitlab/"
+        );
+        assert_eq!(merged.bundle_dir.as_deref(), Some("/srv/kakehashi/bundles"));
+    }
+
     #[test]
     fn test_merge_settings_prefer_primary() {
         let mut fallback_languages = HashMap::new();
@@ -571,6 +726,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let mut primary_languages = HashMap::new();
@@ -588,6 +745,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_settings(Some(fallback), Some(primary)).unwrap();
@@ -633,6 +792,8 @@ mod tests {
             capture_mappings: fallback_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let mut primary_mappings = HashMap::new();
@@ -658,6 +819,8 @@ mod tests {
             capture_mappings: primary_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_settings(Some(fallback), Some(primary)).unwrap();
@@ -724,6 +887,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -752,6 +917,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -773,6 +940,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -796,6 +965,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None, // Not specified
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -815,6 +986,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -830,6 +1003,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(false),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let workspace: WorkspaceSettings = WorkspaceSettings::from(&settings);
@@ -896,6 +1071,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let result = merge_all(&[Some(config.clone())]);
         assert!(result.is_some());
@@ -914,6 +1091,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let project_config = TreeSitterSettings {
             search_paths: Some(vec!["/project/path".to_string()]),
@@ -921,6 +1100,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(false),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -941,6 +1122,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let user_config = TreeSitterSettings {
             search_paths: None, // Not overriding, should inherit from defaults
@@ -948,6 +1131,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let project_config = TreeSitterSettings {
             search_paths: Some(vec!["/project/path".to_string()]),
@@ -955,6 +1140,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None, // Not overriding, should inherit
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
         let session_config = TreeSitterSettings {
             search_paths: None, // Not overriding
@@ -962,6 +1149,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(false), // Session wins
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[
@@ -989,6 +1178,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: Some(true),
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[None, Some(config.clone()), None]);
@@ -1063,6 +1254,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Project overrides queries for python
@@ -1086,6 +1279,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1128,6 +1323,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let mut project_languages = HashMap::new();
@@ -1145,6 +1342,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1184,6 +1383,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: Some(WorkspaceType::Cargo),
+                ..Default::default()
             },
         );
 
@@ -1193,6 +1393,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: Some(user_servers),
+            bridge_merge: None,
+            install: None,
         };
 
         // Project only adds initializationOptions
@@ -1204,6 +1406,7 @@ mod tests {
                 languages: vec![], // Empty, should inherit from user
                 initialization_options: Some(json!({ "linkedProjects": ["./Cargo.toml"] })),
                 workspace_type: None, // Should inherit from user
+                ..Default::default()
             },
         );
 
@@ -1213,6 +1416,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: Some(project_servers),
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1253,6 +1458,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -1262,6 +1468,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: Some(user_servers),
+            bridge_merge: None,
+            install: None,
         };
 
         let mut project_servers = HashMap::new();
@@ -1272,6 +1480,7 @@ mod tests {
                 languages: vec!["python".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -1281,6 +1490,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: Some(project_servers),
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1338,6 +1549,8 @@ mod tests {
             capture_mappings: user_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Project only overrides variable.builtin
@@ -1363,6 +1576,8 @@ mod tests {
             capture_mappings: project_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1404,6 +1619,8 @@ mod tests {
             capture_mappings: user_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let mut project_mappings = HashMap::new();
@@ -1425,6 +1642,8 @@ mod tests {
             capture_mappings: project_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -1475,6 +1694,8 @@ mod tests {
             capture_mappings: user_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Project overrides one locals, adds one folds
@@ -1502,6 +1723,8 @@ mod tests {
             capture_mappings: project_mappings,
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -2077,6 +2300,7 @@ mod tests {
             languages: vec!["any".to_string()],
             initialization_options: None,
             workspace_type: Some(settings::WorkspaceType::Generic),
+            ..Default::default()
         };
         let servers = build_servers_map(Some(wildcard), None);
 
@@ -2109,6 +2333,7 @@ mod tests {
             languages: vec!["rust".to_string()],
             initialization_options: None,
             workspace_type: Some(settings::WorkspaceType::Cargo),
+            ..Default::default()
         };
         let servers = build_servers_map(None, Some(specific));
 
@@ -2147,12 +2372,14 @@ mod tests {
             languages: vec!["any".to_string()],
             initialization_options: Some(json!({ "defaultOption": true })),
             workspace_type: Some(settings::WorkspaceType::Generic),
+            ..Default::default()
         };
         let specific = settings::BridgeServerConfig {
             cmd: vec!["rust-analyzer".to_string()],
             languages: vec![], // Empty means inherit from wildcard
             initialization_options: Some(json!({ "linkedProjects": ["./Cargo.toml"] })),
             workspace_type: Some(settings::WorkspaceType::Cargo),
+            ..Default::default()
         };
         let servers = build_servers_map(Some(wildcard), Some(specific));
 
@@ -2218,6 +2445,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "feature1": true })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2229,6 +2457,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: Some(json!({ "feature2": true })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2266,6 +2495,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: Some(json!({ "baseOpt": 1 })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2277,6 +2507,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "overlayOpt": 2 })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2318,6 +2549,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "opt": 1 })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2328,6 +2560,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "opt": 2 })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2360,6 +2593,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "a": { "b": 1 } })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2370,6 +2604,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "a": { "c": 2 } })),
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2530,6 +2765,7 @@ mod tests {
                 languages: vec![],
                 initialization_options: Some(json!({ "checkOnSave": true })),
                 workspace_type: Some(settings::WorkspaceType::Generic),
+                ..Default::default()
             },
         );
 
@@ -2541,6 +2777,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None, // Should inherit from wildcard
                 workspace_type: None,         // Should inherit from wildcard
+                ..Default::default()
             },
         );
 
@@ -2579,6 +2816,7 @@ mod tests {
                 languages: vec!["rust".to_string(), "python".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -2590,10 +2828,11 @@ mod tests {
                 languages: vec![], // Empty - should inherit from wildcard
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
-        // Simulate the lookup logic from get_bridge_config_for_language:
+        // Simulate the lookup logic from get_all_configs_for_language:
         // For each server (excluding "_"), resolve it and check if it handles "rust"
         let injection_language = "rust";
         let mut found_server: Option<BridgeServerConfig> = None;
@@ -2716,6 +2955,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Project only adds queries, doesn't set aliases
@@ -2738,6 +2979,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -2785,6 +3028,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Project overrides aliases
@@ -2803,6 +3048,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         let result = merge_all(&[Some(user_config), Some(project_config)]);
@@ -17,6 +17,8 @@ pub fn default_settings() -> TreeSitterSettings {
         capture_mappings: default_capture_mappings(),
         auto_install: Some(true),
         language_servers: None,
+        bridge_merge: None,
+        install: None,
     }
 }
 
@@ -32,10 +32,12 @@ pub struct BridgeLanguageConfig {
 ///
 /// This is used to configure external language servers (like rust-analyzer, pyright)
 /// that kakehashi can redirect requests to for injection regions.
-#[derive(Debug, Clone, Deserialize, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize, PartialEq, Eq)]
 pub struct BridgeServerConfig {
     /// Command array: first element is the program, rest are arguments
     /// e.g., ["rust-analyzer"] or ["pyright-langserver", "--stdio"]
+    /// May be omitted when `connect` is set.
+    #[serde(default)]
     pub cmd: Vec<String>,
     /// Languages this server handles (e.g., ["rust"], ["python"])
     pub languages: Vec<String>,
@@ -45,6 +47,190 @@ pub struct BridgeServerConfig {
     /// Workspace type for this server (defaults to None, meaning Generic)
     #[serde(rename = "workspaceType")]
     pub workspace_type: Option<WorkspaceType>,
+    /// Priority among servers handling the same language (higher first, defaults to 0).
+    /// Servers with equal priority are ordered by name.
+    pub priority: Option<i32>,
+    /// Overlay this server's semantic tokens on tree-sitter tokens inside
+    /// injection regions (defaults to false)
+    #[serde(rename = "semanticTokens")]
+    pub semantic_tokens: Option<bool>,
+    /// Address of an already running server to connect to instead of spawning
+    /// `cmd`: `host:port` (TCP) or a Unix socket path (`unix:/path` or `/path`)
+    pub connect: Option<String>,
+    /// Restart policy applied when the server crashes or fails to initialize
+    pub restart: Option<BridgeRestartPolicy>,
+    /// Handshake, request, liveness and idle timeouts (ADR-0018)
+    pub timeouts: Option<BridgeTimeoutConfig>,
+    /// Extra environment variables for the server process
+    pub env: Option<HashMap<String, String>>,
+    /// Working directory for the server process (defaults to kakehashi's)
+    pub cwd: Option<String>,
+    /// File or directory names (`*` wildcards allowed) marking a project root.
+    /// When set, the root is found by walking up from the host document and a
+    /// separate server instance is started for each root.
+    #[serde(rename = "rootMarkers")]
+    pub root_markers: Option<Vec<String>>,
+}
+
+/// Restart policy for a crashing bridge server.
+///
+/// A server that fails more than `maxRestarts` times within `windowSecs` is
+/// given up on until kakehashi restarts. Consecutive restarts inside the window
+/// are delayed with exponential backoff starting at `backoffMs`.
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct BridgeRestartPolicy {
+    /// Maximum restarts within the window before giving up (defaults to 5)
+    #[serde(rename = "maxRestarts")]
+    pub max_restarts: Option<u32>,
+    /// Length of the sliding restart window in seconds (defaults to 180)
+    #[serde(rename = "windowSecs")]
+    pub window_secs: Option<u64>,
+    /// Delay before the second restart in the window, doubled for each further
+    /// restart (defaults to 1000)
+    #[serde(rename = "backoffMs")]
+    pub backoff_ms: Option<u64>,
+    /// Upper bound for the backoff delay (defaults to 30000)
+    #[serde(rename = "maxBackoffMs")]
+    pub max_backoff_ms: Option<u64>,
+}
+
+/// Timeouts for a bridge server, in seconds (ADR-0018 timeout hierarchy).
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct BridgeTimeoutConfig {
+    /// Tier 0: time allowed for the initialize handshake (defaults to 30)
+    pub initialize: Option<u64>,
+    /// Tier 1: time allowed for a single request (defaults to 30)
+    pub request: Option<u64>,
+    /// Tier 2: time without any server output while requests are pending
+    /// before the connection is considered hung (defaults to 60)
+    pub liveness: Option<u64>,
+    /// Shut the server down after it has had no open virtual documents for
+    /// this long (defaults to never)
+    pub idle: Option<u64>,
+}
+
+/// How results from multiple bridge servers for the same language are combined.
+#[derive(Debug, Clone, Copy, Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeMergeStrategy {
+    /// Use the first non-empty result, asking servers in priority order
+    First,
+    /// Combine the results of all servers (feature-specific, e.g. hovers are
+    /// concatenated and completion lists are unioned)
+    Merge,
+}
+
+/// Per-feature merge strategies for languages served by multiple bridge servers.
+///
+/// Omitted features use their default strategy (see [`BridgeMergeConfig::strategy_for`]).
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct BridgeMergeConfig {
+    pub hover: Option<BridgeMergeStrategy>,
+    pub completion: Option<BridgeMergeStrategy>,
+    #[serde(rename = "signatureHelp")]
+    pub signature_help: Option<BridgeMergeStrategy>,
+    pub references: Option<BridgeMergeStrategy>,
+    #[serde(rename = "documentHighlight")]
+    pub document_highlight: Option<BridgeMergeStrategy>,
+    pub definition: Option<BridgeMergeStrategy>,
+}
+
+impl BridgeMergeConfig {
+    /// Strategy configured for an LSP method, falling back to the feature default.
+    ///
+    /// Defaults: hover, completion, references and documentHighlight merge;
+    /// signatureHelp and definition take the first non-empty result.
+    /// Returns `None` for methods that are not merged across servers.
+    pub fn strategy_for(config: Option<&Self>, method: &str) -> Option<BridgeMergeStrategy> {
+        use BridgeMergeStrategy::{First, Merge};
+        let config = config.cloned().unwrap_or_default();
+        let (configured, default) = match method {
+            "textDocument/hover" => (config.hover, Merge),
+            "textDocument/completion" => (config.completion, Merge),
+            "textDocument/signatureHelp" => (config.signature_help, First),
+            "textDocument/references" => (config.references, Merge),
+            "textDocument/documentHighlight" => (config.document_highlight, Merge),
+            "textDocument/definition" => (config.definition, First),
+            _ => return None,
+        };
+        Some(configured.unwrap_or(default))
+    }
+
+    /// Merge two configs field by field, preferring `overlay`.
+    pub fn merge(base: Option<Self>, overlay: Option<Self>) -> Option<Self> {
+        match (base, overlay) {
+            (None, None) => None,
+            (Some(config), None) | (None, Some(config)) => Some(config),
+            (Some(base), Some(overlay)) => Some(Self {
+                hover: overlay.hover.or(base.hover),
+                completion: overlay.completion.or(base.completion),
+                signature_help: overlay.signature_help.or(base.signature_help),
+                references: overlay.references.or(base.references),
+                document_highlight: overlay.document_highlight.or(base.document_highlight),
+                definition: overlay.definition.or(base.definition),
+            }),
+        }
+    }
+}
+
+/// Where installs download from, for networks that only reach mirrors, and
+/// whether installed parsers must match their recorded hashes to be loaded.
+///
+/// Example:
+/// ```toml
+/// [install]
+/// metadataUrl = "https://proxy.internal/nvim-treesitter/main/lua/nvim-treesitter/parsers.lua"
+/// queriesUrl = "https://proxy.internal/nvim-treesitter/{revision}/runtime/queries"
+///
+/// [install.urlRewrites]
+/// "https://github.com/" = "https://git.internal/mirror/"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct InstallSourcesConfig {
+    /// URL of nvim-treesitter's parsers.lua (defaults to GitHub)
+    #[serde(rename = "metadataUrl")]
+    pub metadata_url: Option<String>,
+    /// Base URL of the query files, `http(s)://` or `file://`; `{revision}` is
+    /// replaced with the query commit and files are read from
+    /// `<queriesUrl>/<language>/<file>.scm` (defaults to nvim-treesitter on GitHub)
+    #[serde(rename = "queriesUrl")]
+    pub queries_url: Option<String>,
+    /// Git repository URL prefixes and the prefixes to replace them with
+    #[serde(rename = "urlRewrites")]
+    pub url_rewrites: Option<HashMap<String, String>>,
+    /// Directory of bundles made by `kakehashi language pack`; auto-install
+    /// unpacks a language from the first bundle containing it before trying
+    /// the network
+    #[serde(rename = "bundleDir")]
+    pub bundle_dir: Option<String>,
+    /// Refuse to load parser libraries whose SHA-256 differs from the one
+    /// recorded at install time (default: load them with a warning)
+    pub verify: Option<bool>,
+}
+
+impl InstallSourcesConfig {
+    /// Merge two configs field by field, preferring `overlay`.
+    ///
+    /// URL rewrites are merged per prefix.
+    pub fn merge(base: Option<Self>, overlay: Option<Self>) -> Option<Self> {
+        match (base, overlay) {
+            (None, None) => None,
+            (Some(config), None) | (None, Some(config)) => Some(config),
+            (Some(base), Some(overlay)) => Some(Self {
+                metadata_url: overlay.metadata_url.or(base.metadata_url),
+                queries_url: overlay.queries_url.or(base.queries_url),
+                url_rewrites: match (base.url_rewrites, overlay.url_rewrites) {
+                    (Some(mut base), Some(overlay)) => {
+                        base.extend(overlay);
+                        Some(base)
+                    }
+                    (base, overlay) => overlay.or(base),
+                },
+                bundle_dir: overlay.bundle_dir.or(base.bundle_dir),
+                verify: overlay.verify.or(base.verify),
+            }),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, serde::Serialize, Default, PartialEq, Eq)]
@@ -114,6 +300,27 @@ pub fn infer_query_kind(path: &str) -> Option<QueryKind> {
     }
 }
 
+/// Where auto-install builds a language's parser from, instead of
+/// nvim-treesitter's metadata.
+/// Example: `{ path = "./tree-sitter-mylang" }` or
+/// `{ git = "https://example.com/tree-sitter-mylang", rev = "v1.0.0" }`
+#[derive(Debug, Clone, Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum LanguageSourceConfig {
+    /// Local grammar directory (relative paths are resolved against the workspace root)
+    Path { path: String },
+    /// Git repository, optionally at a revision and in a subdirectory
+    Git {
+        git: String,
+        /// Branch, tag or commit (default: the repository's default branch)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+        /// Subdirectory holding the grammar (for monorepos)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        location: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone, Default, Deserialize, serde::Serialize)]
 pub struct LanguageConfig {
     pub parser: Option<String>,
@@ -125,10 +332,18 @@ pub struct LanguageConfig {
     /// - Some({}): Bridge NOTHING (disable bridging for this host)
     /// - Some({ python: { enabled: true } }): Bridge only enabled languages
     pub bridge: Option<HashMap<String, BridgeLanguageConfig>>,
+    /// Bridge the whole host document to the language servers configured for
+    /// this language (e.g. marksman for markdown), alongside injection regions.
+    /// - None (omitted): Host document is not bridged (default behavior)
+    /// - Some({ enabled: true }): Requests outside injections go to the host server
+    pub host: Option<BridgeLanguageConfig>,
     /// Alternative languageId values that map to this language.
     /// Example: `[languages.markdown]` with `aliases = ["rmd", "qmd"]`
     /// allows editors sending languageId "rmd" or "qmd" to use the markdown parser.
     pub aliases: Option<Vec<String>>,
+    /// Where auto-install builds the parser from (a local directory or git
+    /// repository), for grammars not listed in nvim-treesitter.
+    pub source: Option<LanguageSourceConfig>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, serde::Serialize)]
@@ -146,6 +361,11 @@ pub struct TreeSitterSettings {
     /// Map of server name to server configuration.
     #[serde(rename = "languageServers")]
     pub language_servers: Option<HashMap<String, BridgeServerConfig>>,
+    /// How results are combined when several language servers bridge the same language.
+    #[serde(rename = "bridgeMerge")]
+    pub bridge_merge: Option<BridgeMergeConfig>,
+    /// Mirrors for parser metadata, query files and grammar repositories.
+    pub install: Option<InstallSourcesConfig>,
 }
 
 // Domain types - internal representations used throughout the application
@@ -165,10 +385,16 @@ pub struct LanguageSettings {
     /// - Some({}): Bridge NOTHING (disable bridging for this host)
     /// - Some({ python: { enabled: true } }): Bridge only enabled languages
     pub bridge: Option<HashMap<String, BridgeLanguageConfig>>,
+    /// Whether the whole host document is bridged to the host language's servers.
+    /// - None (omitted): Host document is not bridged (default behavior)
+    /// - Some({ enabled: true }): Requests outside injections go to the host server
+    pub host: Option<BridgeLanguageConfig>,
     /// Alternative languageId values that should use this parser.
     /// E.g., `aliases = ["rmd", "qmd"]` for markdown allows editors sending
     /// "rmd" or "qmd" as languageId to use the markdown parser.
     pub aliases: Option<Vec<String>>,
+    /// Where auto-install builds the parser from, instead of nvim-treesitter's metadata.
+    pub source: Option<LanguageSourceConfig>,
 }
 
 impl LanguageSettings {
@@ -177,7 +403,9 @@ impl LanguageSettings {
             parser,
             queries,
             bridge: None,
+            host: None,
             aliases: None,
+            source: None,
         }
     }
 
@@ -191,7 +419,9 @@ impl LanguageSettings {
             parser,
             queries,
             bridge,
+            host: None,
             aliases: None,
+            source: None,
         }
     }
 
@@ -211,6 +441,13 @@ impl LanguageSettings {
                 .is_some_and(|config| config.enabled),
         }
     }
+
+    /// Check if the whole host document is bridged to the host language's servers.
+    ///
+    /// Returns `true` only if `host` is set with `enabled: true`.
+    pub fn is_host_bridged(&self) -> bool {
+        self.host.as_ref().is_some_and(|config| config.enabled)
+    }
 }
 
 /// Workspace-wide Tree-sitter configuration as required by the domain.
@@ -221,6 +458,8 @@ pub struct WorkspaceSettings {
     pub capture_mappings: CaptureMappings,
     pub auto_install: bool,
     pub language_servers: Option<HashMap<String, BridgeServerConfig>>,
+    pub bridge_merge: Option<BridgeMergeConfig>,
+    pub install: Option<InstallSourcesConfig>,
 }
 
 impl WorkspaceSettings {
@@ -235,6 +474,8 @@ impl WorkspaceSettings {
             capture_mappings,
             auto_install: true, // Default to true for zero-config experience
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         }
     }
 
@@ -250,6 +491,8 @@ impl WorkspaceSettings {
             capture_mappings,
             auto_install,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         }
     }
 
@@ -266,6 +509,8 @@ impl WorkspaceSettings {
             capture_mappings,
             auto_install,
             language_servers,
+            bridge_merge: None,
+            install: None,
         }
     }
 }
@@ -647,6 +892,8 @@ mod tests {
             capture_mappings: HashMap::new(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         // Add multiple language configurations
@@ -772,6 +1019,42 @@ mod tests {
         assert!(config.initialization_options.is_none());
     }
 
+    #[test]
+    fn should_parse_bridge_server_config_with_connect_instead_of_cmd() {
+        let config_json = r#"{
+            "connect": "127.0.0.1:6005",
+            "languages": ["gdscript"]
+        }"#;
+
+        let config: BridgeServerConfig = serde_json::from_str(config_json).unwrap();
+
+        assert!(config.cmd.is_empty());
+        assert_eq!(config.connect.as_deref(), Some("127.0.0.1:6005"));
+    }
+
+    #[test]
+    fn should_parse_bridge_server_restart_policy_and_timeouts() {
+        let config_json = r#"{
+            "cmd": ["pyright-langserver", "--stdio"],
+            "languages": ["python"],
+            "restart": { "maxRestarts": 3, "windowSecs": 60, "backoffMs": 500 },
+            "timeouts": { "initialize": 45, "request": 10, "idle": 300 }
+        }"#;
+
+        let config: BridgeServerConfig = serde_json::from_str(config_json).unwrap();
+
+        let restart = config.restart.unwrap();
+        assert_eq!(restart.max_restarts, Some(3));
+        assert_eq!(restart.window_secs, Some(60));
+        assert_eq!(restart.backoff_ms, Some(500));
+        assert_eq!(restart.max_backoff_ms, None);
+        let timeouts = config.timeouts.unwrap();
+        assert_eq!(timeouts.initialize, Some(45));
+        assert_eq!(timeouts.request, Some(10));
+        assert_eq!(timeouts.liveness, None);
+        assert_eq!(timeouts.idle, Some(300));
+    }
+
     #[test]
     fn should_parse_configuration_with_injections_query() {
         // Test that injection queries can be specified in the unified queries field
@@ -989,6 +1272,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_language_config_with_host_bridging() {
+        let config_json = r#"{
+            "host": { "enabled": true }
+        }"#;
+
+        let config: LanguageConfig = serde_json::from_str(config_json).unwrap();
+        let settings = LanguageSettings {
+            host: config.host,
+            ..LanguageSettings::new(None, None)
+        };
+
+        assert!(
+            settings.is_host_bridged(),
+            "host document should be bridged"
+        );
+        assert!(
+            !LanguageSettings::new(None, None).is_host_bridged(),
+            "host bridging is opt-in"
+        );
+    }
+
+    #[test]
+    fn should_parse_language_source_from_toml() {
+        let config_toml = r#"
+            [languages.mylang.source]
+            path = "./tree-sitter-mylang"
+
+            [languages.otherlang.source]
+            git = "https://example.com/tree-sitter-otherlang"
+            rev = "v0.3.0"
+            location = "grammar"
+        "#;
+
+        let settings: TreeSitterSettings = toml::from_str(config_toml).unwrap();
+
+        assert_eq!(
+            settings.languages["mylang"].source,
+            Some(LanguageSourceConfig::Path {
+                path: "./tree-sitter-mylang".to_string()
+            })
+        );
+        assert_eq!(
+            settings.languages["otherlang"].source,
+            Some(LanguageSourceConfig::Git {
+                git: "https://example.com/tree-sitter-otherlang".to_string(),
+                rev: Some("v0.3.0".to_string()),
+                location: Some("grammar".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_install_sources() {
+        let config_toml = r#"
+            [install]
+            metadataUrl = "https://proxy.internal/parsers.lua"
+            queriesUrl = "https://proxy.internal/nvim-treesitter/{revision}/runtime/queries"
+            verify = true
+
+            [install.urlRewrites]
+            "https://github.com/" = "https://git.internal/mirror/"
+        "#;
+
+        let settings: TreeSitterSettings = toml::from_str(config_toml).unwrap();
+        let install = settings.install.unwrap();
+
+        assert_eq!(
+            install.metadata_url.as_deref(),
+            Some("https://proxy.internal/parsers.lua")
+        );
+        assert_eq!(
+            install.queries_url.as_deref(),
+            Some("https://proxy.internal/nvim-treesitter/{revision}/runtime/queries")
+        );
+        assert_eq!(
+            install.url_rewrites.unwrap()["https://github.com/"],
+            "https://git.internal/mirror/"
+        );
+        assert_eq!(install.verify, Some(true));
+    }
+
     #[test]
     fn test_bridge_filter_disabled_language() {
         // PBI-120: Languages with enabled: false should not be bridgeable
@@ -1052,6 +1417,50 @@ mod tests {
         assert_eq!(py.languages, vec!["python".to_string()]);
     }
 
+    #[test]
+    fn should_parse_priority_and_bridge_merge() {
+        let config_json = r#"{
+            "languageServers": {
+                "ruff": {
+                    "cmd": ["ruff", "server"],
+                    "languages": ["python"],
+                    "priority": 10
+                }
+            },
+            "bridgeMerge": {
+                "hover": "first",
+                "documentHighlight": "first"
+            }
+        }"#;
+
+        let settings: TreeSitterSettings = serde_json::from_str(config_json).unwrap();
+
+        let servers = settings.language_servers.as_ref().unwrap();
+        assert_eq!(servers["ruff"].priority, Some(10));
+        let merge = settings.bridge_merge.as_ref();
+        assert_eq!(
+            BridgeMergeConfig::strategy_for(merge, "textDocument/hover"),
+            Some(BridgeMergeStrategy::First)
+        );
+        assert_eq!(
+            BridgeMergeConfig::strategy_for(merge, "textDocument/documentHighlight"),
+            Some(BridgeMergeStrategy::First)
+        );
+        // Unconfigured features keep their defaults
+        assert_eq!(
+            BridgeMergeConfig::strategy_for(merge, "textDocument/completion"),
+            Some(BridgeMergeStrategy::Merge)
+        );
+        assert_eq!(
+            BridgeMergeConfig::strategy_for(None, "textDocument/definition"),
+            Some(BridgeMergeStrategy::First)
+        );
+        assert_eq!(
+            BridgeMergeConfig::strategy_for(merge, "textDocument/rename"),
+            None
+        );
+    }
+
     #[test]
     fn should_parse_language_servers_empty() {
         // PBI-119: Empty languageServers should be valid
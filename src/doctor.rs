@@ -0,0 +1,680 @@
+//! Environment and installation health checks (`kakehashi doctor`).
+//!
+//! Every check yields a [`Check`] with a status, so the CLI can render the
+//! report for humans or as JSON for CI. The checks only inspect state:
+//! parsers are loaded to read their ABI version but never used for parsing,
+//! and the failed-parser list is read without performing crash recovery.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+use crate::config::{BridgeServerConfig, WorkspaceSettings};
+use crate::install::integrity;
+use crate::install::parser::{find_tree_sitter, shared_lib_extension};
+use crate::language::loader::ParserLoadError;
+use crate::language::query_loader::ParseFailure;
+use crate::language::{FailedParserRegistry, ParserLoader, QueryLoader};
+use crate::lsp::{SettingsEventKind, load_settings};
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// What a check is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckCategory {
+    Toolchain,
+    Config,
+    DataDir,
+    Parser,
+    Query,
+    FailedParser,
+    LanguageServer,
+}
+
+impl CheckCategory {
+    /// Heading used in the human-readable report.
+    pub fn title(self) -> &'static str {
+        match self {
+            CheckCategory::Toolchain => "Toolchain",
+            CheckCategory::Config => "Configuration",
+            CheckCategory::DataDir => "Data directory",
+            CheckCategory::Parser => "Parsers",
+            CheckCategory::Query => "Queries",
+            CheckCategory::FailedParser => "Failed parsers",
+            CheckCategory::LanguageServer => "Language servers",
+        }
+    }
+}
+
+/// A single health check result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub category: CheckCategory,
+    /// What was checked (a tool, a language, a query file, ...).
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Further lines explaining a warning or error.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl Check {
+    fn new(
+        category: CheckCategory,
+        name: impl Into<String>,
+        status: CheckStatus,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            category,
+            name: name.into(),
+            status,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// All check results, in the order they were run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// Number of checks with the given status.
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Whether any check failed.
+    pub fn has_errors(&self) -> bool {
+        self.count(CheckStatus::Error) > 0
+    }
+}
+
+/// Run every check against `data_dir` and the configuration found from
+/// `root` (the project directory).
+pub fn run(data_dir: &Path, root: Option<&Path>) -> DoctorReport {
+    let mut checks = check_toolchain();
+
+    let outcome = load_settings(root, None);
+    checks.extend(outcome.events.iter().map(|event| {
+        let status = match event.kind {
+            SettingsEventKind::Info => CheckStatus::Ok,
+            SettingsEventKind::Warning => CheckStatus::Warning,
+        };
+        Check::new(CheckCategory::Config, "settings", status, &event.message)
+    }));
+    let settings = outcome.settings.unwrap_or_default();
+    let verify = settings
+        .install
+        .as_ref()
+        .and_then(|install| install.verify)
+        .unwrap_or(false);
+
+    checks.push(check_data_dir(data_dir));
+    checks.extend(check_parsers(data_dir, verify));
+    checks.extend(check_failed_parsers(data_dir));
+    checks.extend(check_language_servers(&settings));
+    DoctorReport { checks }
+}
+
+/// Check the tools needed to install parsers.
+fn check_toolchain() -> Vec<Check> {
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    vec![
+        tool_check("tree-sitter", find_tree_sitter(), "needed to build parsers"),
+        tool_check(
+            &compiler,
+            find_executable(&compiler),
+            "needed to build parsers",
+        ),
+        tool_check("git", find_executable("git"), "needed to fetch grammars"),
+    ]
+}
+
+fn tool_check(name: &str, path: Option<PathBuf>, purpose: &str) -> Check {
+    let Some(path) = path else {
+        return Check::new(
+            CheckCategory::Toolchain,
+            name,
+            CheckStatus::Warning,
+            format!("not found ({purpose})"),
+        );
+    };
+    let version = Command::new(&path)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            stdout
+                .lines()
+                .chain(stderr.lines())
+                .next()
+                .map(|line| line.trim().to_string())
+        });
+    match version {
+        Some(version) => Check::new(
+            CheckCategory::Toolchain,
+            name,
+            CheckStatus::Ok,
+            format!("{version} ({})", path.display()),
+        ),
+        None => Check::new(
+            CheckCategory::Toolchain,
+            name,
+            CheckStatus::Warning,
+            format!("{} does not run ({purpose})", path.display()),
+        ),
+    }
+}
+
+/// Check that the data directory exists and is writable.
+fn check_data_dir(data_dir: &Path) -> Check {
+    let name = data_dir.display().to_string();
+    if !data_dir.exists() {
+        return Check::new(
+            CheckCategory::DataDir,
+            name,
+            CheckStatus::Warning,
+            "does not exist yet; the first install creates it",
+        );
+    }
+    if !data_dir.is_dir() {
+        return Check::new(
+            CheckCategory::DataDir,
+            name,
+            CheckStatus::Error,
+            "is not a directory",
+        );
+    }
+    match tempfile::tempfile_in(data_dir) {
+        Ok(_) => Check::new(CheckCategory::DataDir, name, CheckStatus::Ok, "writable"),
+        Err(e) => Check::new(
+            CheckCategory::DataDir,
+            name,
+            CheckStatus::Error,
+            format!("not writable: {e}"),
+        ),
+    }
+}
+
+/// Load every installed parser, check its ABI version and integrity, and
+/// compile its queries.
+///
+/// With `verify` (`install.verify`), a library the server would refuse to
+/// load is reported as an error without being loaded.
+fn check_parsers(data_dir: &Path, verify: bool) -> Vec<Check> {
+    let tampered: HashMap<String, Vec<String>> = integrity::verify_data_dir(data_dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|issue| issue.is_tampered())
+        .fold(HashMap::new(), |mut map, issue| {
+            map.entry(issue.language.clone())
+                .or_default()
+                .push(issue.to_string());
+            map
+        });
+
+    let mut checks = Vec::new();
+    let mut loader = ParserLoader::new();
+    for (language, library) in installed_parsers(data_dir) {
+        if verify && let Some(check) = refused_parser(&language, &library) {
+            checks.push(check);
+            continue;
+        }
+        let loaded = loader.load_language(&library.to_string_lossy(), &language);
+        let ts_language = match loaded {
+            Ok(ts_language) => ts_language,
+            // Queries cannot compile against a language tree-sitter rejects
+            Err(ParserLoadError::IncompatibleAbi(version)) => {
+                checks.push(Check::new(
+                    CheckCategory::Parser,
+                    &language,
+                    CheckStatus::Error,
+                    format!(
+                        "ABI version {version} is outside the supported range \
+                         {MIN_COMPATIBLE_LANGUAGE_VERSION}-{LANGUAGE_VERSION}; reinstall with --force"
+                    ),
+                ));
+                continue;
+            }
+            Err(e) => {
+                checks.push(Check::new(
+                    CheckCategory::Parser,
+                    &language,
+                    CheckStatus::Error,
+                    format!("failed to load: {e}"),
+                ));
+                continue;
+            }
+        };
+
+        let abi = ts_language.abi_version();
+        let check = if let Some(issues) = tampered.get(&language) {
+            Check::new(
+                CheckCategory::Parser,
+                &language,
+                CheckStatus::Warning,
+                format!("ABI version {abi}; installed files changed since installation"),
+            )
+            .with_details(issues.clone())
+        } else {
+            Check::new(
+                CheckCategory::Parser,
+                &language,
+                CheckStatus::Ok,
+                format!("ABI version {abi}"),
+            )
+        };
+        checks.push(check);
+        checks.extend(
+            query_files(data_dir, &language)
+                .iter()
+                .map(|file| check_query(&ts_language, data_dir, &language, file)),
+        );
+    }
+    checks
+}
+
+/// Error check for a parser library `install.verify` refuses to load, if it
+/// does.
+fn refused_parser(language: &str, library: &Path) -> Option<Check> {
+    let details = match integrity::check_parser_library(language, library) {
+        Ok(Some(issue)) if issue.refuses_parser() => vec![issue.to_string()],
+        Ok(_) => return None,
+        Err(e) => vec![format!("could not verify {}: {e}", library.display())],
+    };
+    Some(
+        Check::new(
+            CheckCategory::Parser,
+            language,
+            CheckStatus::Error,
+            "not loaded: refused by install.verify; reinstall with --force",
+        )
+        .with_details(details),
+    )
+}
+
+/// Installed parser libraries in `data_dir`, sorted by language.
+fn installed_parsers(data_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(data_dir.join("parser")) else {
+        return Vec::new();
+    };
+    let mut parsers: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == shared_lib_extension())
+        })
+        .filter_map(|path| {
+            let language = path.file_stem()?.to_string_lossy().into_owned();
+            Some((language, path))
+        })
+        .collect();
+    parsers.sort();
+    parsers
+}
+
+/// Query files installed for `language`, sorted by name.
+fn query_files(data_dir: &Path, language: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(data_dir.join("queries").join(language)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".scm"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Compile a query file (with the queries it inherits) against its parser.
+fn check_query(ts_language: &Language, data_dir: &Path, language: &str, file: &str) -> Check {
+    let name = format!("{language}/{file}");
+    let bases = [data_dir.to_string_lossy().into_owned()];
+    let result = match QueryLoader::load_query_with_inheritance(ts_language, &bases, language, file)
+    {
+        Ok(result) => result,
+        Err(e) => {
+            return Check::new(
+                CheckCategory::Query,
+                name,
+                CheckStatus::Error,
+                e.to_string(),
+            );
+        }
+    };
+
+    let mut details: Vec<String> = result
+        .skipped
+        .iter()
+        .map(|pattern| {
+            format!(
+                "lines {}-{}: {}",
+                pattern.start_line, pattern.end_line, pattern.error
+            )
+        })
+        .collect();
+    if result.used_inheritance && !details.is_empty() {
+        details.push("line numbers refer to the query combined with its parents".to_string());
+    }
+
+    let Some(query) = result.query else {
+        let message = match result.failure_reason {
+            Some(ParseFailure::PatternSplitFailed(e)) => format!("malformed query: {e}"),
+            Some(ParseFailure::CombinationFailed(e)) => {
+                format!("valid patterns failed to combine: {e}")
+            }
+            Some(ParseFailure::AllPatternsInvalid) | None => {
+                "no pattern compiles against the parser".to_string()
+            }
+        };
+        return Check::new(CheckCategory::Query, name, CheckStatus::Error, message)
+            .with_details(details);
+    };
+
+    let patterns = query.pattern_count();
+    if result.skipped.is_empty() {
+        Check::new(
+            CheckCategory::Query,
+            name,
+            CheckStatus::Ok,
+            format!("{patterns} patterns"),
+        )
+    } else {
+        Check::new(
+            CheckCategory::Query,
+            name,
+            CheckStatus::Warning,
+            format!(
+                "{} of {} patterns are skipped",
+                result.skipped.len(),
+                patterns + result.skipped.len()
+            ),
+        )
+        .with_details(details)
+    }
+}
+
+/// Report parsers disabled after crashing the server.
+fn check_failed_parsers(data_dir: &Path) -> Vec<Check> {
+    let registry = FailedParserRegistry::new(data_dir);
+    if let Err(e) = registry.load_failed_parsers() {
+        return vec![Check::new(
+            CheckCategory::FailedParser,
+            "failed_parsers",
+            CheckStatus::Error,
+            format!("cannot read: {e}"),
+        )];
+    }
+    let mut failed = registry.failed_parsers();
+    failed.sort();
+    failed
+        .into_iter()
+        .map(|language| {
+            Check::new(
+                CheckCategory::FailedParser,
+                language,
+                CheckStatus::Warning,
+                "disabled after crashing while parsing; reinstall it to re-enable",
+            )
+        })
+        .collect()
+}
+
+/// Check that every configured language server can be started.
+fn check_language_servers(settings: &WorkspaceSettings) -> Vec<Check> {
+    let Some(servers) = &settings.language_servers else {
+        return Vec::new();
+    };
+    let mut names: Vec<&String> = servers.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| check_language_server(name, &servers[name]))
+        .collect()
+}
+
+fn check_language_server(name: &str, config: &BridgeServerConfig) -> Check {
+    if let Some(address) = &config.connect {
+        return Check::new(
+            CheckCategory::LanguageServer,
+            name,
+            CheckStatus::Ok,
+            format!("connects to {address}"),
+        );
+    }
+    let Some(program) = config.cmd.first() else {
+        return Check::new(
+            CheckCategory::LanguageServer,
+            name,
+            CheckStatus::Error,
+            "neither cmd nor connect is configured",
+        );
+    };
+    match find_executable(program) {
+        Some(path) => Check::new(
+            CheckCategory::LanguageServer,
+            name,
+            CheckStatus::Ok,
+            path.display().to_string(),
+        ),
+        None => Check::new(
+            CheckCategory::LanguageServer,
+            name,
+            CheckStatus::Error,
+            format!("command '{program}' not found on PATH"),
+        ),
+    }
+}
+
+/// Resolve a program the way a shell would: paths as given, bare names on PATH.
+fn find_executable(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+    let dirs = std::env::var_os("PATH")?;
+    std::env::split_paths(&dirs).find_map(|dir| {
+        let candidates = [dir.join(program), dir.join(format!("{program}.exe"))];
+        candidates
+            .into_iter()
+            .take(if cfg!(windows) { 2 } else { 1 })
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn server(cmd: &[&str], connect: Option<&str>) -> BridgeServerConfig {
+        serde_json::from_value(serde_json::json!({
+            "cmd": cmd,
+            "languages": ["rust"],
+            "connect": connect,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_data_dir() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(check_data_dir(temp.path()).status, CheckStatus::Ok);
+        assert_eq!(
+            check_data_dir(&temp.path().join("missing")).status,
+            CheckStatus::Warning
+        );
+
+        let file = temp.path().join("file");
+        fs::write(&file, "").unwrap();
+        assert_eq!(check_data_dir(&file).status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_check_parsers_does_not_load_refused_parser_in_verify_mode() {
+        let temp = TempDir::new().unwrap();
+        let parser_dir = temp.path().join("parser");
+        fs::create_dir_all(&parser_dir).unwrap();
+        // Not a real library, and without a record in the manifest
+        fs::write(
+            parser_dir.join(format!("fakelang.{}", shared_lib_extension())),
+            "tampered",
+        )
+        .unwrap();
+
+        let checks = check_parsers(temp.path(), true);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, CheckStatus::Error);
+        assert!(
+            checks[0].message.contains("refused by install.verify"),
+            "{:?}",
+            checks[0]
+        );
+
+        // Without verify mode the library is loaded (and fails to)
+        let checks = check_parsers(temp.path(), false);
+        assert!(
+            checks[0].message.starts_with("failed to load"),
+            "{:?}",
+            checks[0]
+        );
+    }
+
+    #[test]
+    fn test_check_query_reports_skipped_patterns() {
+        let temp = TempDir::new().unwrap();
+        let queries = temp.path().join("queries/rust");
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(
+            queries.join("highlights.scm"),
+            "(line_comment) @comment\n(not_a_rust_node) @error\n",
+        )
+        .unwrap();
+        fs::write(queries.join("folds.scm"), "(block) @fold\n").unwrap();
+        let rust: Language = tree_sitter_rust::LANGUAGE.into();
+
+        let check = check_query(&rust, temp.path(), "rust", "folds.scm");
+        assert_eq!(check.status, CheckStatus::Ok);
+
+        let check = check_query(&rust, temp.path(), "rust", "highlights.scm");
+        assert_eq!(check.status, CheckStatus::Warning);
+        assert_eq!(check.message, "1 of 2 patterns are skipped");
+        assert_eq!(check.details.len(), 1);
+        assert!(
+            check.details[0].starts_with("lines 2-2:"),
+            "{:?}",
+            check.details
+        );
+
+        let check = check_query(&rust, temp.path(), "rust", "missing.scm");
+        assert_eq!(check.status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_check_failed_parsers_does_not_recover_crashes() {
+        let temp = TempDir::new().unwrap();
+        assert!(check_failed_parsers(temp.path()).is_empty());
+
+        fs::write(temp.path().join("failed_parsers"), "yaml\nlua\n").unwrap();
+        fs::write(temp.path().join("parsing_in_progress"), "rust").unwrap();
+        let checks = check_failed_parsers(temp.path());
+        let names: Vec<&str> = checks.iter().map(|check| check.name.as_str()).collect();
+        assert_eq!(names, ["lua", "yaml"]);
+        assert!(
+            checks
+                .iter()
+                .all(|check| check.status == CheckStatus::Warning)
+        );
+        assert!(temp.path().join("parsing_in_progress").exists());
+    }
+
+    #[test]
+    fn test_check_language_servers() {
+        let settings = WorkspaceSettings {
+            language_servers: Some(HashMap::from([
+                ("a-present".to_string(), server(&["sh", "-c", "true"], None)),
+                (
+                    "b-missing".to_string(),
+                    server(&["kakehashi-test-no-such-server"], None),
+                ),
+                ("c-remote".to_string(), server(&[], Some("127.0.0.1:9000"))),
+            ])),
+            ..WorkspaceSettings::default()
+        };
+        let checks = check_language_servers(&settings);
+        let statuses: Vec<(&str, CheckStatus)> = checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("a-present", CheckStatus::Ok),
+                ("b-missing", CheckStatus::Error),
+                ("c-remote", CheckStatus::Ok),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_serializes_for_ci() {
+        let report = DoctorReport {
+            checks: vec![
+                Check::new(CheckCategory::DataDir, "/data", CheckStatus::Ok, "writable"),
+                Check::new(CheckCategory::FailedParser, "lua", CheckStatus::Error, "x")
+                    .with_details(vec!["detail".to_string()]),
+            ],
+        };
+        assert!(report.has_errors());
+        assert_eq!(report.count(CheckStatus::Ok), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][0]["category"], "data-dir");
+        assert_eq!(json["checks"][0]["status"], "ok");
+        assert!(json["checks"][0].get("details").is_none());
+        assert_eq!(json["checks"][1]["category"], "failed-parser");
+        assert_eq!(json["checks"][1]["details"][0], "detail");
+    }
+}
@@ -224,6 +224,16 @@ impl DocumentStore {
         self.documents.get(uri).map(|doc| doc.text().to_string())
     }
 
+    /// URIs and texts of the documents detected as `language_id`.
+    // Lock safety: iter() holds one shard read lock at a time; owned clones are returned
+    pub fn documents_with_language(&self, language_id: &str) -> Vec<(Url, String)> {
+        self.documents
+            .iter()
+            .filter(|entry| entry.value().language_id() == Some(language_id))
+            .map(|entry| (entry.key().clone(), entry.value().text().to_string()))
+            .collect()
+    }
+
     // Lock safety: Single remove() call - no read lock held before or during write
     pub fn remove(&self, uri: &Url) -> Option<Document> {
         self.parse_states.remove(uri);
@@ -347,6 +357,36 @@ mod tests {
         assert_eq!(doc.text(), &text);
     }
 
+    #[test]
+    fn test_documents_with_language() {
+        let store = DocumentStore::new();
+        let lua = Url::parse("file:///init.lua").unwrap();
+        store.insert(
+            lua.clone(),
+            "local x".to_string(),
+            Some("lua".to_string()),
+            None,
+        );
+        store.insert(
+            Url::parse("file:///main.rs").unwrap(),
+            "fn main() {}".to_string(),
+            Some("rust".to_string()),
+            None,
+        );
+        store.insert(
+            Url::parse("file:///notes.txt").unwrap(),
+            String::new(),
+            None,
+            None,
+        );
+
+        assert_eq!(
+            store.documents_with_language("lua"),
+            vec![(lua, "local x".to_string())]
+        );
+        assert!(store.documents_with_language("python").is_empty());
+    }
+
     #[test]
     fn test_update_document_preserves_language() {
         let store = DocumentStore::new();
@@ -3,11 +3,19 @@
 //! This module provides functionality to download and install Tree-sitter
 //! query files and compile parser shared libraries.
 
+pub mod bundle;
 pub mod cache;
+pub mod integrity;
+pub mod lockfile;
+pub mod manifest;
 pub mod metadata;
 pub mod parser;
+pub mod prune;
 pub mod queries;
+pub mod sources;
 pub(crate) mod support_check;
+pub mod update;
+pub mod usage;
 
 /// Test helper module for setting up mock metadata cache.
 #[cfg(test)]
@@ -68,10 +76,17 @@ impl InstallResult {
 /// * `language` - The language to install (e.g., "lua", "rust")
 /// * `data_dir` - The base data directory for kakehashi
 /// * `force` - Whether to overwrite existing files
+/// * `source` - Where to build the parser from (latest nvim-treesitter metadata if `None`)
+/// * `sources` - Mirrors for the metadata, query files and grammar repositories
+/// * `bundle` - Offline bundle to unpack the language from; the network is
+///   used only if unpacking fails
 pub(crate) async fn install_language_async(
     language: String,
     data_dir: PathBuf,
     force: bool,
+    source: Option<parser::ParserSource>,
+    sources: sources::InstallSources,
+    bundle: Option<PathBuf>,
 ) -> InstallResult {
     let lang = language.clone();
     let dir = data_dir.clone();
@@ -85,27 +100,92 @@ pub(crate) async fn install_language_async(
             queries_error: None,
         };
 
+        if let Some(bundle) = bundle {
+            match bundle::unpack_bundle(&bundle, &dir, Some(std::slice::from_ref(&lang))) {
+                Ok(_) => {
+                    result.parser_path = parser::parser_file_exists(&lang, &dir);
+                    let queries_dir = dir.join("queries").join(&lang);
+                    if queries_dir.is_dir() {
+                        result.queries_path = Some(queries_dir);
+                    } else {
+                        result.queries_error =
+                            Some(format!("no queries for '{}' in {}", lang, bundle.display()));
+                    }
+                    return result;
+                }
+                Err(e) => log::warn!(
+                    target: "kakehashi::install",
+                    "Failed to unpack '{}' from {}, installing from the network: {}",
+                    lang,
+                    bundle.display(),
+                    e
+                ),
+            }
+        }
+
         // Install parser
         // For async/auto-install, always use cache (background operation)
+        let from_path = matches!(source, Some(parser::ParserSource::Path(_)));
         let parser_options = parser::InstallOptions {
             data_dir: dir.clone(),
             force,
             verbose: false,
             no_cache: false,
+            source,
+            sources,
         };
 
         match parser::install_parser(&lang, &parser_options) {
-            Ok(parser_result) => {
+            Ok(mut parser_result) => {
+                let bundled_queries = parser_result.bundled_queries.take();
+                log_manifest_error(
+                    &dir,
+                    record_in_manifest(&dir, |manifest| {
+                        manifest.record_parser(&parser_result)?;
+                        match &bundled_queries {
+                            Some(queries) => manifest.record_queries_with_inherited(queries),
+                            None => Ok(()),
+                        }
+                    }),
+                );
                 result.parser_path = Some(parser_result.install_path);
+                if let Some(queries) = bundled_queries {
+                    result.queries_path = Some(queries.install_path);
+                    return result;
+                }
             }
             Err(e) => {
                 result.parser_error = Some(e.to_string());
             }
         }
 
+        // A local grammar without bundled queries stays offline
+        if from_path {
+            result.queries_error = Some(format!(
+                "no query files in the grammar's queries/ directory for '{}'",
+                lang
+            ));
+            return result;
+        }
+
         // Install queries
-        match queries::install_queries(&lang, &dir, force) {
+        match queries::install_queries(&lang, &dir, force, None, &parser_options.sources) {
             Ok(query_result) => {
+                if let Some(reason) = &query_result.unresolved {
+                    log::warn!(
+                        target: "kakehashi::install",
+                        "Could not resolve nvim-treesitter commit for '{}' queries ({}); using {}",
+                        lang,
+                        reason,
+                        queries::DEFAULT_QUERIES_BRANCH
+                    );
+                }
+                log_manifest_error(
+                    &dir,
+                    record_in_manifest(&dir, |manifest| {
+                        manifest.record_queries_with_inherited(&query_result)
+                    }),
+                );
                 result.queries_path = Some(query_result.install_path);
             }
             Err(e) => {
@@ -124,6 +204,29 @@ pub(crate) async fn install_language_async(
     })
 }
 
+/// Record an install in the data directory's manifest.
+pub fn record_in_manifest(
+    data_dir: &std::path::Path,
+    record: impl FnOnce(&mut manifest::Manifest) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let mut outcome = Ok(());
+    manifest::Manifest::update(data_dir, |manifest| outcome = record(manifest))?;
+    outcome
+}
+
+/// Log a failed manifest update; the manifest is bookkeeping, so the install
+/// itself still counts as successful.
+fn log_manifest_error(data_dir: &std::path::Path, result: std::io::Result<()>) {
+    if let Err(e) = result {
+        log::warn!(
+            target: "kakehashi::install",
+            "Failed to update install manifest in {}: {}",
+            data_dir.display(),
+            e
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +257,46 @@ mod tests {
         };
         assert!(!failure.is_success());
     }
+
+    #[tokio::test]
+    async fn test_install_language_async_unpacks_from_bundle() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        std::fs::create_dir_all(source.join("parser")).unwrap();
+        std::fs::write(
+            source
+                .join("parser")
+                .join(format!("lua.{}", parser::shared_lib_extension())),
+            "lua",
+        )
+        .unwrap();
+        std::fs::create_dir_all(source.join("queries/lua")).unwrap();
+        std::fs::write(
+            source.join("queries/lua/highlights.scm"),
+            "(comment) @comment",
+        )
+        .unwrap();
+        let bundle_path = temp.path().join("offline.tar");
+        bundle::pack_bundle(&["lua".to_string()], &source, &bundle_path).unwrap();
+
+        let data_dir = temp.path().join("data");
+        let result = install_language_async(
+            "lua".to_string(),
+            data_dir.clone(),
+            false,
+            None,
+            sources::InstallSources::default(),
+            Some(bundle_path),
+        )
+        .await;
+
+        assert!(result.is_success(), "{:?}", result);
+        assert_eq!(
+            result.parser_path,
+            parser::parser_file_exists("lua", &data_dir)
+        );
+        assert_eq!(result.queries_path, Some(data_dir.join("queries/lua")));
+        let manifest = manifest::Manifest::load(&data_dir).unwrap();
+        assert_eq!(manifest.languages["lua"].origin(), "bundle offline.tar");
+    }
 }
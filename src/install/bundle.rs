@@ -0,0 +1,652 @@
+//! Offline bundles of compiled parsers and queries.
+//!
+//! `language pack` archives installed languages, the queries they inherit
+//! from, and a `bundle.toml` recording where each language was built from and
+//! the SHA-256 of every file. `language unpack` extracts a bundle into a
+//! staging directory inside the data directory, verifies every file against
+//! `bundle.toml`, and only then moves the files into place, so neither network
+//! access nor a C toolchain is needed on the target machine.
+//!
+//! Bundles are plain tar files written and read with the system `tar`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use super::manifest::{LanguageRecord, Manifest, ParserRecord, QueriesRecord, sha256_file};
+use super::parser::{SourceKind, parser_file_exists};
+use super::queries::installed_query_parents;
+use super::record_in_manifest;
+
+/// File name of the bundle manifest within a bundle.
+pub const BUNDLE_MANIFEST: &str = "bundle.toml";
+
+/// Bundle format written by this version.
+const BUNDLE_FORMAT: u32 = 1;
+
+/// Revision recorded for files whose origin was not in the manifest.
+const UNKNOWN_REVISION: &str = "unknown";
+
+/// Contents of `bundle.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle format version.
+    pub format: u32,
+    /// Version of kakehashi that created the bundle.
+    pub kakehashi: String,
+    /// `<os>-<arch>` the parsers were compiled for.
+    pub platform: String,
+    /// Bundled languages; inherited queries appear without a parser.
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageRecord>,
+}
+
+/// Error types for bundle operations.
+#[derive(Debug)]
+pub enum BundleError {
+    /// A language to pack has no installed parser.
+    NotInstalled(String),
+    /// Running `tar` failed.
+    TarError(String),
+    /// The bundle is malformed.
+    InvalidBundle(String),
+    /// The bundle was built for another platform.
+    PlatformMismatch { bundle: String, current: String },
+    /// A bundled file does not match its recorded SHA-256.
+    ChecksumMismatch(String),
+    /// File system operation failed.
+    IoError(io::Error),
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInstalled(lang) => write!(f, "No parser installed for '{}'", lang),
+            Self::TarError(msg) => write!(f, "tar failed: {}", msg),
+            Self::InvalidBundle(msg) => write!(f, "Invalid bundle: {}", msg),
+            Self::PlatformMismatch { bundle, current } => write!(
+                f,
+                "Bundle was built for {}, but this machine is {}",
+                bundle, current
+            ),
+            Self::ChecksumMismatch(file) => {
+                write!(f, "Checksum mismatch for {} in bundle", file)
+            }
+            Self::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl From<io::Error> for BundleError {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+/// `<os>-<arch>` of the running binary, e.g. `linux-x86_64`.
+pub fn current_platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Archive `languages` installed in `data_dir`, with the queries they inherit
+/// from, into the tar file `output`.
+///
+/// Returns the manifest written into the bundle.
+pub fn pack_bundle(
+    languages: &[String],
+    data_dir: &Path,
+    output: &Path,
+) -> Result<BundleManifest, BundleError> {
+    for language in languages {
+        if parser_file_exists(language, data_dir).is_none() {
+            return Err(BundleError::NotInstalled(language.clone()));
+        }
+    }
+    let installed = Manifest::load(data_dir)?;
+    let staging = tempfile::tempdir()?;
+
+    let mut bundle = BundleManifest {
+        format: BUNDLE_FORMAT,
+        kakehashi: env!("CARGO_PKG_VERSION").to_string(),
+        platform: current_platform(),
+        languages: BTreeMap::new(),
+    };
+    for language in with_query_parents(languages, data_dir) {
+        let record = installed
+            .languages
+            .get(&language)
+            .cloned()
+            .unwrap_or_default();
+        let record = stage_language(&language, record, data_dir, staging.path())?;
+        bundle.languages.insert(language, record);
+    }
+
+    let content = toml::to_string_pretty(&bundle)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(staging.path().join(BUNDLE_MANIFEST), content)?;
+
+    let mut args: Vec<OsString> = vec!["-cf".into(), std::path::absolute(output)?.into()];
+    args.extend(["-C".into(), staging.path().into(), BUNDLE_MANIFEST.into()]);
+    for dir in ["parser", "queries"] {
+        if staging.path().join(dir).is_dir() {
+            args.push(dir.into());
+        }
+    }
+    run_tar(&args)?;
+    Ok(bundle)
+}
+
+/// `languages` followed by every language whose installed queries they
+/// inherit from, transitively.
+fn with_query_parents(languages: &[String], data_dir: &Path) -> BTreeSet<String> {
+    let mut closure = BTreeSet::new();
+    let mut pending: Vec<String> = languages.to_vec();
+    while let Some(language) = pending.pop() {
+        if !closure.insert(language.clone()) {
+            continue;
+        }
+        pending.extend(
+            installed_query_parents(&language, data_dir)
+                .into_iter()
+                .filter(|parent| data_dir.join("queries").join(parent).is_dir()),
+        );
+    }
+    closure
+}
+
+/// Copy the parser and queries of `language` into `staging`, returning its
+/// record with hashes of the copied files.
+fn stage_language(
+    language: &str,
+    mut record: LanguageRecord,
+    data_dir: &Path,
+    staging: &Path,
+) -> Result<LanguageRecord, BundleError> {
+    record.parser = match parser_file_exists(language, data_dir) {
+        Some(library) => {
+            let file_name = library.file_name().expect("parser file has a name");
+            fs::create_dir_all(staging.join("parser"))?;
+            fs::copy(&library, staging.join("parser").join(file_name))?;
+            let sha256 = sha256_file(&library)?;
+            Some(match record.parser {
+                Some(parser) => ParserRecord { sha256, ..parser },
+                None => ParserRecord {
+                    url: String::new(),
+                    revision: UNKNOWN_REVISION.to_string(),
+                    location: None,
+                    sha256,
+                    kind: SourceKind::Metadata,
+                },
+            })
+        }
+        None => None,
+    };
+
+    let queries_dir = data_dir.join("queries").join(language);
+    record.queries = if queries_dir.is_dir() {
+        let staged = staging.join("queries").join(language);
+        fs::create_dir_all(&staged)?;
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(&queries_dir)? {
+            let path = entry?.path();
+            if path.is_file()
+                && let Some(name) = path.file_name()
+            {
+                fs::copy(&path, staged.join(name))?;
+                files.insert(name.to_string_lossy().into_owned(), sha256_file(&path)?);
+            }
+        }
+        let revision = record
+            .queries
+            .map_or_else(|| UNKNOWN_REVISION.to_string(), |queries| queries.revision);
+        Some(QueriesRecord { revision, files })
+    } else {
+        None
+    };
+    record.bundle = None;
+    Ok(record)
+}
+
+/// Read `bundle.toml` from a bundle without extracting anything else.
+pub fn read_bundle_manifest(bundle: &Path) -> Result<BundleManifest, BundleError> {
+    let output = run_tar(&["-xOf".into(), bundle.into(), BUNDLE_MANIFEST.into()])?;
+    parse_bundle_manifest(&String::from_utf8_lossy(&output))
+}
+
+fn parse_bundle_manifest(content: &str) -> Result<BundleManifest, BundleError> {
+    let manifest: BundleManifest = toml::from_str(content)
+        .map_err(|e| BundleError::InvalidBundle(format!("{}: {}", BUNDLE_MANIFEST, e)))?;
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(BundleError::InvalidBundle(format!(
+            "format {} is newer than this kakehashi supports ({})",
+            manifest.format, BUNDLE_FORMAT
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Extract `bundle`, verify it, and install its languages into `data_dir`.
+///
+/// With `only`, just those languages and the queries they inherit from are
+/// installed. Existing files of the installed languages are replaced, and the
+/// manifest records the bundle as their origin. Nothing is installed if any
+/// file fails verification. Returns the installed languages.
+pub fn unpack_bundle(
+    bundle: &Path,
+    data_dir: &Path,
+    only: Option<&[String]>,
+) -> Result<Vec<String>, BundleError> {
+    fs::create_dir_all(data_dir)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".unpack-")
+        .tempdir_in(data_dir)?;
+    run_tar(&[
+        "-xf".into(),
+        bundle.into(),
+        "-C".into(),
+        staging.path().into(),
+    ])?;
+
+    let manifest =
+        parse_bundle_manifest(&fs::read_to_string(staging.path().join(BUNDLE_MANIFEST))?)?;
+    let current = current_platform();
+    if manifest.platform != current {
+        return Err(BundleError::PlatformMismatch {
+            bundle: manifest.platform,
+            current,
+        });
+    }
+
+    let languages: BTreeSet<String> = match only {
+        Some(languages) => {
+            for language in languages {
+                if !manifest.languages.contains_key(language) {
+                    return Err(BundleError::InvalidBundle(format!(
+                        "'{}' is not in the bundle",
+                        language
+                    )));
+                }
+            }
+            with_query_parents(languages, staging.path())
+        }
+        None => manifest.languages.keys().cloned().collect(),
+    };
+    for language in &languages {
+        let record = manifest.languages.get(language).ok_or_else(|| {
+            BundleError::InvalidBundle(format!("queries of '{}' are not recorded", language))
+        })?;
+        verify_language(language, record, staging.path())?;
+    }
+
+    for language in &languages {
+        if let Some(library) = parser_file_exists(language, staging.path()) {
+            let target = data_dir
+                .join("parser")
+                .join(library.file_name().expect("parser file has a name"));
+            replace(&library, &target)?;
+        }
+        let queries = staging.path().join("queries").join(language);
+        if queries.is_dir() {
+            replace(&queries, &data_dir.join("queries").join(language))?;
+        }
+    }
+
+    let bundle_name = bundle
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+    let installed_at = super::manifest::unix_now();
+    record_in_manifest(data_dir, |installed| {
+        for language in &languages {
+            let mut record = manifest.languages[language].clone();
+            record.installed_at = installed_at;
+            record.bundle = bundle_name.clone();
+            installed.languages.insert(language.clone(), record);
+        }
+        Ok(())
+    })?;
+    Ok(languages.into_iter().collect())
+}
+
+/// Check the staged files of `language` against its record.
+fn verify_language(
+    language: &str,
+    record: &LanguageRecord,
+    staging: &Path,
+) -> Result<(), BundleError> {
+    if !is_safe_name(language) {
+        return Err(BundleError::InvalidBundle(format!(
+            "invalid language name '{}'",
+            language
+        )));
+    }
+
+    // Every staged parser is installed, so it must have a recorded hash
+    match (&record.parser, parser_file_exists(language, staging)) {
+        (Some(parser), Some(library)) => {
+            if sha256_file(&library)? != parser.sha256 {
+                return Err(BundleError::ChecksumMismatch(format!(
+                    "parser/{}",
+                    library.file_name().unwrap_or_default().to_string_lossy()
+                )));
+            }
+        }
+        (Some(_), None) => {
+            return Err(BundleError::InvalidBundle(format!(
+                "parser of '{}' is missing",
+                language
+            )));
+        }
+        (None, Some(_)) => {
+            return Err(BundleError::InvalidBundle(format!(
+                "parser of '{}' has no recorded checksum",
+                language
+            )));
+        }
+        (None, None) => {}
+    }
+
+    let queries_dir = staging.join("queries").join(language);
+    let recorded = record
+        .queries
+        .as_ref()
+        .map(|queries| &queries.files)
+        .cloned()
+        .unwrap_or_default();
+    for (file, sha256) in &recorded {
+        let path = queries_dir.join(file);
+        if !is_safe_name(file) || !path.is_file() {
+            return Err(BundleError::InvalidBundle(format!(
+                "query file queries/{}/{} is missing",
+                language, file
+            )));
+        }
+        if sha256_file(&path)? != *sha256 {
+            return Err(BundleError::ChecksumMismatch(format!(
+                "queries/{}/{}",
+                language, file
+            )));
+        }
+    }
+    if queries_dir.is_dir() {
+        for entry in fs::read_dir(&queries_dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if !recorded.contains_key(&name) {
+                return Err(BundleError::InvalidBundle(format!(
+                    "unexpected file queries/{}/{}",
+                    language, name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is a plain file or language name (no path separators).
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// Move `staged` to `target`, replacing whatever is there.
+fn replace(staged: &Path, target: &Path) -> io::Result<()> {
+    if target.is_dir() {
+        fs::remove_dir_all(target)?;
+    } else if target.exists() {
+        fs::remove_file(target)?;
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(staged, target)
+}
+
+/// The first bundle (by file name) in `bundle_dir` that has a parser for
+/// `language` built for this platform.
+pub fn find_bundle_with_language(bundle_dir: &Path, language: &str) -> Option<PathBuf> {
+    let mut bundles: Vec<PathBuf> = fs::read_dir(bundle_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tar"))
+        .collect();
+    bundles.sort();
+    let platform = current_platform();
+    bundles.into_iter().find(|bundle| {
+        read_bundle_manifest(bundle).is_ok_and(|manifest| {
+            manifest.platform == platform
+                && manifest
+                    .languages
+                    .get(language)
+                    .is_some_and(|record| record.parser.is_some())
+        })
+    })
+}
+
+/// Run `tar` with `args`, returning its standard output.
+fn run_tar(args: &[OsString]) -> Result<Vec<u8>, BundleError> {
+    let output = Command::new("tar")
+        .args(args)
+        .output()
+        .map_err(|e| BundleError::TarError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(BundleError::TarError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::parser::shared_lib_extension;
+    use tempfile::TempDir;
+
+    fn parser_name(language: &str) -> String {
+        format!("{}.{}", language, shared_lib_extension())
+    }
+
+    fn install(data_dir: &Path, language: &str, parser: Option<&str>, highlights: &str) {
+        if let Some(content) = parser {
+            fs::create_dir_all(data_dir.join("parser")).unwrap();
+            fs::write(data_dir.join("parser").join(parser_name(language)), content).unwrap();
+        }
+        let queries = data_dir.join("queries").join(language);
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(queries.join("highlights.scm"), highlights).unwrap();
+    }
+
+    #[test]
+    fn test_pack_and_unpack_round_trip_with_inherited_queries() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        install(&source, "typescript", Some("ts"), "; inherits: ecma\n");
+        install(&source, "ecma", None, "(comment) @comment");
+        install(&source, "lua", Some("lua"), "(comment) @comment");
+        let bundle_path = temp.path().join("bundle.tar");
+
+        let packed = pack_bundle(&["typescript".to_string()], &source, &bundle_path).unwrap();
+        assert_eq!(
+            packed.languages.keys().collect::<Vec<_>>(),
+            vec!["ecma", "typescript"]
+        );
+        assert!(packed.languages["ecma"].parser.is_none());
+        assert_eq!(read_bundle_manifest(&bundle_path).unwrap(), packed);
+
+        let target = temp.path().join("target");
+        let installed = unpack_bundle(&bundle_path, &target, None).unwrap();
+        assert_eq!(installed, vec!["ecma", "typescript"]);
+        assert_eq!(
+            fs::read_to_string(target.join("parser").join(parser_name("typescript"))).unwrap(),
+            "ts"
+        );
+        assert!(target.join("queries/ecma/highlights.scm").is_file());
+        let manifest = Manifest::load(&target).unwrap();
+        assert_eq!(
+            manifest.languages["typescript"].bundle.as_deref(),
+            Some("bundle.tar")
+        );
+        assert_eq!(
+            manifest.languages["typescript"].origin(),
+            "bundle bundle.tar"
+        );
+        // Staging directories are cleaned up
+        assert_eq!(
+            fs::read_dir(&target)
+                .unwrap()
+                .filter(|entry| entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".unpack-"))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_pack_requires_installed_parser() {
+        let temp = TempDir::new().unwrap();
+        install(temp.path(), "ecma", None, "(comment) @comment");
+
+        let result = pack_bundle(
+            &["ecma".to_string()],
+            temp.path(),
+            &temp.path().join("bundle.tar"),
+        );
+        assert!(matches!(result, Err(BundleError::NotInstalled(lang)) if lang == "ecma"));
+    }
+
+    #[test]
+    fn test_unpack_rejects_tampered_files_without_installing() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        install(&source, "lua", Some("lua"), "(comment) @comment");
+        let bundle_path = temp.path().join("bundle.tar");
+        pack_bundle(&["lua".to_string()], &source, &bundle_path).unwrap();
+
+        // Rebuild the archive with a modified query file
+        let extracted = temp.path().join("extracted");
+        fs::create_dir_all(&extracted).unwrap();
+        run_tar(&[
+            "-xf".into(),
+            bundle_path.clone().into(),
+            "-C".into(),
+            extracted.clone().into(),
+        ])
+        .unwrap();
+        fs::write(extracted.join("queries/lua/highlights.scm"), "tampered").unwrap();
+        run_tar(&[
+            "-cf".into(),
+            bundle_path.clone().into(),
+            "-C".into(),
+            extracted.into(),
+            ".".into(),
+        ])
+        .unwrap();
+
+        let target = temp.path().join("target");
+        let result = unpack_bundle(&bundle_path, &target, None);
+        assert!(
+            matches!(&result, Err(BundleError::ChecksumMismatch(file)) if file == "queries/lua/highlights.scm"),
+            "Got: {:?}",
+            result
+        );
+        assert!(!target.join("queries/lua").exists());
+        assert!(parser_file_exists("lua", &target).is_none());
+    }
+
+    #[test]
+    fn test_unpack_rejects_parser_without_recorded_checksum() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        install(&source, "lua", Some("lua"), "(comment) @comment");
+        let bundle_path = temp.path().join("bundle.tar");
+        pack_bundle(&["lua".to_string()], &source, &bundle_path).unwrap();
+
+        // Rebuild the archive without the parser's record
+        let extracted = temp.path().join("extracted");
+        fs::create_dir_all(&extracted).unwrap();
+        run_tar(&[
+            "-xf".into(),
+            bundle_path.clone().into(),
+            "-C".into(),
+            extracted.clone().into(),
+        ])
+        .unwrap();
+        let manifest_path = extracted.join(BUNDLE_MANIFEST);
+        let mut manifest =
+            parse_bundle_manifest(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        manifest.languages.get_mut("lua").unwrap().parser = None;
+        fs::write(&manifest_path, toml::to_string(&manifest).unwrap()).unwrap();
+        run_tar(&[
+            "-cf".into(),
+            bundle_path.clone().into(),
+            "-C".into(),
+            extracted.into(),
+            ".".into(),
+        ])
+        .unwrap();
+
+        let target = temp.path().join("target");
+        let result = unpack_bundle(&bundle_path, &target, None);
+        assert!(
+            matches!(&result, Err(BundleError::InvalidBundle(msg)) if msg.contains("no recorded checksum")),
+            "Got: {:?}",
+            result
+        );
+        assert!(parser_file_exists("lua", &target).is_none());
+    }
+
+    #[test]
+    fn test_parse_bundle_manifest_rejects_newer_format() {
+        let content = format!(
+            "format = {}\nkakehashi = \"9.9.9\"\nplatform = \"{}\"\n",
+            BUNDLE_FORMAT + 1,
+            current_platform()
+        );
+        assert!(matches!(
+            parse_bundle_manifest(&content),
+            Err(BundleError::InvalidBundle(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_bundle_with_language_and_partial_unpack() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        install(&source, "lua", Some("lua"), "(comment) @comment");
+        install(&source, "rust", Some("rust"), "(comment) @comment");
+        let bundles = temp.path().join("bundles");
+        fs::create_dir_all(&bundles).unwrap();
+        pack_bundle(
+            &["lua".to_string(), "rust".to_string()],
+            &source,
+            &bundles.join("base.tar"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_bundle_with_language(&bundles, "rust"),
+            Some(bundles.join("base.tar"))
+        );
+        assert_eq!(find_bundle_with_language(&bundles, "python"), None);
+
+        let target = temp.path().join("target");
+        let installed = unpack_bundle(
+            &bundles.join("base.tar"),
+            &target,
+            Some(&["rust".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(installed, vec!["rust"]);
+        assert!(parser_file_exists("lua", &target).is_none());
+    }
+}
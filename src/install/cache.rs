@@ -66,8 +66,10 @@ impl MetadataCache {
         // Ensure cache directory exists
         fs::create_dir_all(&self.cache_dir)?;
 
-        // Write content
-        fs::write(self.cache_path(), content)?;
+        // Write content atomically; parallel installs may read it meanwhile
+        let mut temp = tempfile::NamedTempFile::new_in(&self.cache_dir)?;
+        io::Write::write_all(&mut temp, content.as_bytes())?;
+        temp.persist(self.cache_path()).map_err(|e| e.error)?;
 
         Ok(())
     }
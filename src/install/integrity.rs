@@ -0,0 +1,386 @@
+//! Integrity checks of installed parsers and queries.
+//!
+//! Every install records the SHA-256 of the parser library and of each query
+//! file in the data directory's `manifest.toml`. Parser libraries are checked
+//! against it before they are loaded, `language verify` re-checks a whole
+//! data directory, and installs pinned by a lockfile check the downloaded
+//! query files against the lockfile's hashes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::manifest::{Manifest, sha256_file};
+use super::parser::shared_lib_extension;
+
+/// How an installed file differs from its install record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityProblem {
+    /// The file's SHA-256 differs from the recorded one.
+    Modified { expected: String, actual: String },
+    /// A recorded file no longer exists.
+    Missing,
+    /// The file has no install record.
+    Unrecorded,
+}
+
+/// A file failing verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityIssue {
+    /// Language the file belongs to.
+    pub language: String,
+    /// Path of the file.
+    pub path: PathBuf,
+    /// What is wrong with it.
+    pub problem: IntegrityProblem,
+}
+
+impl IntegrityIssue {
+    /// Whether the file changed or disappeared since it was installed, as
+    /// opposed to merely having no record.
+    pub fn is_tampered(&self) -> bool {
+        self.problem != IntegrityProblem::Unrecorded
+    }
+
+    /// Whether `install.verify` refuses to load the parser library this
+    /// issue is about: it was tampered with, or it sits in a data directory
+    /// (where every install is recorded) without a record.
+    pub fn refuses_parser(&self) -> bool {
+        self.is_tampered() || parser_data_dir(&self.language, &self.path).is_some()
+    }
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            IntegrityProblem::Modified { expected, actual } => write!(
+                f,
+                "{} changed since installation (expected sha256 {}, found {})",
+                self.path.display(),
+                expected,
+                actual
+            ),
+            IntegrityProblem::Missing => write!(f, "{} is missing", self.path.display()),
+            IntegrityProblem::Unrecorded => {
+                write!(f, "{} has no install record", self.path.display())
+            }
+        }
+    }
+}
+
+/// Check a parser library against the manifest of the data directory it is in.
+///
+/// Returns `Ok(None)` when the library matches its record, and an
+/// [`IntegrityProblem::Unrecorded`] issue when there is nothing to compare
+/// with (e.g., a library outside a data directory).
+pub fn check_parser_library(language: &str, library: &Path) -> io::Result<Option<IntegrityIssue>> {
+    let issue = |problem| IntegrityIssue {
+        language: language.to_string(),
+        path: library.to_path_buf(),
+        problem,
+    };
+    // Only `<data_dir>/parser/<language>.<ext>` has a record for `language`
+    let Some(data_dir) = parser_data_dir(language, library) else {
+        return Ok(Some(issue(IntegrityProblem::Unrecorded)));
+    };
+
+    let manifest = Manifest::load(data_dir)?;
+    let Some(expected) = manifest
+        .languages
+        .get(language)
+        .and_then(|record| record.parser.as_ref())
+        .map(|parser| parser.sha256.clone())
+    else {
+        return Ok(Some(issue(IntegrityProblem::Unrecorded)));
+    };
+    let actual = sha256_file(library)?;
+    Ok((actual != expected).then(|| issue(IntegrityProblem::Modified { expected, actual })))
+}
+
+/// Data directory holding `library` as the parser of `language`, i.e.,
+/// `<data_dir>` of `<data_dir>/parser/<language>.<ext>`.
+pub(crate) fn parser_data_dir<'a>(language: &str, library: &'a Path) -> Option<&'a Path> {
+    library
+        .parent()
+        .filter(|dir| dir.file_name().is_some_and(|name| name == "parser"))
+        .and_then(Path::parent)
+        .filter(|_| library.file_stem().is_some_and(|stem| stem == language))
+}
+
+/// Check the query files of `language` in `data_dir` against `expected`
+/// (file name to SHA-256).
+///
+/// Query files without an entry in `expected` are reported as unrecorded.
+pub fn verify_query_files(
+    language: &str,
+    data_dir: &Path,
+    expected: &BTreeMap<String, String>,
+) -> io::Result<Vec<IntegrityIssue>> {
+    let queries_dir = data_dir.join("queries").join(language);
+    let mut issues = Vec::new();
+    for (file, sha256) in expected {
+        let path = queries_dir.join(file);
+        if let Some(problem) = compare_file(&path, sha256)? {
+            issues.push(IntegrityIssue {
+                language: language.to_string(),
+                path,
+                problem,
+            });
+        }
+    }
+    for path in list_dir(&queries_dir)? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_file() && !expected.contains_key(name.as_ref()) {
+            issues.push(IntegrityIssue {
+                language: language.to_string(),
+                path,
+                problem: IntegrityProblem::Unrecorded,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Check every parser library and query file in `data_dir` against the
+/// manifest, sorted by path.
+///
+/// Files the manifest does not know about are reported as unrecorded.
+pub fn verify_data_dir(data_dir: &Path) -> io::Result<Vec<IntegrityIssue>> {
+    let manifest = Manifest::load(data_dir)?;
+    let parser_dir = data_dir.join("parser");
+    let mut issues = Vec::new();
+
+    for (language, record) in &manifest.languages {
+        if let Some(parser) = &record.parser {
+            let path = parser_dir.join(format!("{}.{}", language, shared_lib_extension()));
+            if let Some(problem) = compare_file(&path, &parser.sha256)? {
+                issues.push(IntegrityIssue {
+                    language: language.clone(),
+                    path,
+                    problem,
+                });
+            }
+        }
+        if let Some(queries) = &record.queries {
+            issues.extend(verify_query_files(language, data_dir, &queries.files)?);
+        }
+    }
+
+    let record = |language: &str| manifest.languages.get(language);
+    for path in list_dir(&parser_dir)? {
+        let is_parser = path
+            .extension()
+            .is_some_and(|ext| ext == shared_lib_extension());
+        let language = path.file_stem().unwrap_or_default().to_string_lossy();
+        if is_parser && record(&language).is_none_or(|record| record.parser.is_none()) {
+            issues.push(IntegrityIssue {
+                language: language.into_owned(),
+                path,
+                problem: IntegrityProblem::Unrecorded,
+            });
+        }
+    }
+    for path in list_dir(&data_dir.join("queries"))? {
+        let language = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() && record(&language).is_none_or(|record| record.queries.is_none()) {
+            issues.push(IntegrityIssue {
+                language: language.into_owned(),
+                path,
+                problem: IntegrityProblem::Unrecorded,
+            });
+        }
+    }
+
+    issues.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(issues)
+}
+
+/// Compare a file with its recorded SHA-256.
+fn compare_file(path: &Path, expected: &str) -> io::Result<Option<IntegrityProblem>> {
+    match sha256_file(path) {
+        Ok(actual) if actual == expected => Ok(None),
+        Ok(actual) => Ok(Some(IntegrityProblem::Modified {
+            expected: expected.to_string(),
+            actual,
+        })),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Some(IntegrityProblem::Missing)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Entries of a directory; a missing directory has none.
+fn list_dir(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::manifest::{LanguageRecord, ParserRecord, QueriesRecord};
+    use crate::install::queries::install_queries_with_dependencies;
+    use crate::install::sources::InstallSources;
+    use tempfile::TempDir;
+
+    /// Install a fake `lua` parser and highlights query, recorded in the manifest.
+    fn install_lua(data_dir: &Path) -> PathBuf {
+        let library = data_dir
+            .join("parser")
+            .join(format!("lua.{}", shared_lib_extension()));
+        fs::create_dir_all(library.parent().unwrap()).unwrap();
+        fs::write(&library, "parser").unwrap();
+        let queries = data_dir.join("queries/lua");
+        fs::create_dir_all(&queries).unwrap();
+        fs::write(queries.join("highlights.scm"), "(comment) @comment").unwrap();
+
+        Manifest::update(data_dir, |manifest| {
+            manifest.languages.insert(
+                "lua".to_string(),
+                LanguageRecord {
+                    parser: Some(ParserRecord {
+                        url: "https://example.com/lua".to_string(),
+                        revision: "v1".to_string(),
+                        location: None,
+                        sha256: sha256_file(&library).unwrap(),
+                        kind: Default::default(),
+                    }),
+                    queries: Some(QueriesRecord {
+                        revision: "abc".to_string(),
+                        files: BTreeMap::from([(
+                            "highlights.scm".to_string(),
+                            sha256_file(&queries.join("highlights.scm")).unwrap(),
+                        )]),
+                    }),
+                    ..LanguageRecord::default()
+                },
+            );
+        })
+        .unwrap();
+        library
+    }
+
+    #[test]
+    fn test_check_parser_library_detects_modification() {
+        let temp = TempDir::new().unwrap();
+        let library = install_lua(temp.path());
+        assert_eq!(check_parser_library("lua", &library).unwrap(), None);
+
+        fs::write(&library, "tampered").unwrap();
+        let issue = check_parser_library("lua", &library).unwrap().unwrap();
+        assert!(issue.is_tampered());
+        assert!(matches!(issue.problem, IntegrityProblem::Modified { .. }));
+    }
+
+    #[test]
+    fn test_check_parser_library_without_record_is_unrecorded() {
+        let temp = TempDir::new().unwrap();
+        let library = install_lua(temp.path());
+
+        // A library loaded under another language name has no record to match
+        let issue = check_parser_library("luau", &library).unwrap().unwrap();
+        assert_eq!(issue.problem, IntegrityProblem::Unrecorded);
+        assert!(!issue.is_tampered());
+
+        let outside = temp.path().join("lua.so");
+        fs::write(&outside, "parser").unwrap();
+        let issue = check_parser_library("lua", &outside).unwrap().unwrap();
+        assert_eq!(issue.problem, IntegrityProblem::Unrecorded);
+        assert!(!issue.refuses_parser());
+    }
+
+    #[test]
+    fn test_unrecorded_parser_in_data_dir_is_refused() {
+        let temp = TempDir::new().unwrap();
+        let library = install_lua(temp.path());
+        fs::remove_file(Manifest::path(temp.path())).unwrap();
+
+        let issue = check_parser_library("lua", &library).unwrap().unwrap();
+        assert_eq!(issue.problem, IntegrityProblem::Unrecorded);
+        assert!(issue.refuses_parser());
+    }
+
+    #[test]
+    fn test_inherited_queries_are_recorded() {
+        let temp = TempDir::new().unwrap();
+        let runtime = temp.path().join("runtime");
+        for (language, content) in [
+            ("typescript", "; inherits: ecma\n(type_identifier) @type"),
+            ("ecma", "(comment) @comment"),
+        ] {
+            fs::create_dir_all(runtime.join(language)).unwrap();
+            fs::write(runtime.join(language).join("highlights.scm"), content).unwrap();
+        }
+        let sources = InstallSources {
+            queries_url: Some(format!("file://{}", runtime.display())),
+            ..InstallSources::default()
+        };
+        let data_dir = temp.path().join("data");
+
+        let result =
+            install_queries_with_dependencies("typescript", &data_dir, false, None, &sources)
+                .unwrap();
+        Manifest::update(&data_dir, |manifest| {
+            manifest.record_queries_with_inherited(&result).unwrap();
+        })
+        .unwrap();
+
+        assert!(verify_data_dir(&data_dir).unwrap().is_empty());
+        assert!(
+            Manifest::load(&data_dir).unwrap().languages["ecma"]
+                .queries
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_verify_data_dir_reports_every_problem() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let library = install_lua(data_dir);
+        assert!(verify_data_dir(data_dir).unwrap().is_empty());
+
+        fs::remove_file(&library).unwrap();
+        fs::write(data_dir.join("queries/lua/highlights.scm"), "changed").unwrap();
+        fs::write(data_dir.join("queries/lua/extra.scm"), "extra").unwrap();
+        fs::create_dir_all(data_dir.join("queries/rust")).unwrap();
+
+        let issues = verify_data_dir(data_dir).unwrap();
+        let problems: Vec<(String, &IntegrityProblem)> = issues
+            .iter()
+            .map(|issue| {
+                let path = issue.path.strip_prefix(data_dir).unwrap();
+                (path.to_string_lossy().into_owned(), &issue.problem)
+            })
+            .collect();
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert_eq!(
+            problems[0],
+            (
+                format!("parser/lua.{}", shared_lib_extension()),
+                &IntegrityProblem::Missing
+            )
+        );
+        assert_eq!(
+            problems[1],
+            (
+                "queries/lua/extra.scm".to_string(),
+                &IntegrityProblem::Unrecorded
+            )
+        );
+        assert!(matches!(
+            problems[2],
+            (ref path, IntegrityProblem::Modified { .. }) if path == "queries/lua/highlights.scm"
+        ));
+        assert_eq!(
+            problems[3],
+            ("queries/rust".to_string(), &IntegrityProblem::Unrecorded)
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Project lockfile (`kakehashi.lock`) pinning parser and query sources.
+//!
+//! `language lock` writes the lockfile from the data directory's manifest;
+//! `language install` reads it (from the current directory or an ancestor)
+//! and installs the pinned parser revision and query commit, so everyone
+//! working on a project gets the same parsers and queries.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::manifest::{Manifest, ParserRecord, QueriesRecord};
+use super::parser::is_commit_hash;
+
+/// File name of the project lockfile.
+pub const LOCKFILE_NAME: &str = "kakehashi.lock";
+
+/// Lockfile format version written by this build.
+pub const LOCKFILE_VERSION: u32 = 1;
+
+/// Header prepended to generated lockfiles.
+const LOCKFILE_HEADER: &str =
+    "# This file is generated by `kakehashi language lock`. Do not edit by hand.\n\n";
+
+/// Error types for lockfile operations.
+#[derive(Debug)]
+pub enum LockfileError {
+    /// File system operation failed.
+    IoError(io::Error),
+    /// The lockfile could not be parsed.
+    ParseError(String),
+    /// The lockfile was written by a newer kakehashi.
+    UnsupportedVersion(u32),
+    /// Requested languages have no install record in the manifest.
+    NotRecorded(Vec<String>),
+    /// Queries of these languages (with their recorded revision) were not
+    /// downloaded from an nvim-treesitter commit, so there is nothing to pin.
+    UnpinnedQueries(Vec<(String, String)>),
+}
+
+impl std::fmt::Display for LockfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "IO error: {}", e),
+            Self::ParseError(msg) => write!(f, "Invalid lockfile: {}", msg),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Lockfile version {} is not supported (expected {})",
+                version, LOCKFILE_VERSION
+            ),
+            Self::NotRecorded(languages) => write!(
+                f,
+                "No install record for: {}. Install them with 'kakehashi language install --force <language>' first.",
+                languages.join(", ")
+            ),
+            Self::UnpinnedQueries(languages) => write!(
+                f,
+                "Queries of {} were not downloaded from an nvim-treesitter commit. Reinstall them with 'kakehashi language install --force <language>' once the commit can be resolved.",
+                languages
+                    .iter()
+                    .map(|(language, revision)| format!("{} (revision '{}')", language, revision))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LockfileError {}
+
+impl From<io::Error> for LockfileError {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+/// Pinned parser and query sources of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Format version.
+    pub version: u32,
+    /// Pins keyed by language name.
+    #[serde(default)]
+    pub languages: BTreeMap<String, LockedLanguage>,
+}
+
+/// Pins for one language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedLanguage {
+    /// Parser source and library hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parser: Option<ParserRecord>,
+    /// Query commit and file hashes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queries: Option<QueriesRecord>,
+}
+
+impl Lockfile {
+    /// Build a lockfile from the manifest.
+    ///
+    /// Locks `languages`, or every recorded language if empty. Queries from
+    /// nvim-treesitter must have been downloaded from a commit; those shipped
+    /// with a custom grammar are pinned by the grammar's revision instead.
+    pub fn from_manifest(manifest: &Manifest, languages: &[String]) -> Result<Self, LockfileError> {
+        let missing: Vec<String> = languages
+            .iter()
+            .filter(|language| !manifest.languages.contains_key(*language))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(LockfileError::NotRecorded(missing));
+        }
+
+        let records = manifest
+            .languages
+            .iter()
+            .filter(|(language, _)| languages.is_empty() || languages.contains(language));
+        let unpinned: Vec<(String, String)> = records
+            .clone()
+            .filter(|(_, record)| {
+                record
+                    .parser
+                    .as_ref()
+                    .is_none_or(|parser| !parser.kind.is_custom())
+            })
+            .filter_map(|(language, record)| {
+                let queries = record.queries.as_ref()?;
+                (!is_commit_hash(&queries.revision))
+                    .then(|| (language.clone(), queries.revision.clone()))
+            })
+            .collect();
+        if !unpinned.is_empty() {
+            return Err(LockfileError::UnpinnedQueries(unpinned));
+        }
+
+        let languages = records
+            .map(|(language, record)| {
+                (
+                    language.clone(),
+                    LockedLanguage {
+                        parser: record.parser.clone(),
+                        queries: record.queries.clone(),
+                    },
+                )
+            })
+            .collect();
+        Ok(Self {
+            version: LOCKFILE_VERSION,
+            languages,
+        })
+    }
+
+    /// Read a lockfile.
+    pub fn load(path: &Path) -> Result<Self, LockfileError> {
+        let content = fs::read_to_string(path)?;
+        let lockfile: Self =
+            toml::from_str(&content).map_err(|e| LockfileError::ParseError(e.to_string()))?;
+        if lockfile.version > LOCKFILE_VERSION {
+            return Err(LockfileError::UnsupportedVersion(lockfile.version));
+        }
+        Ok(lockfile)
+    }
+
+    /// Write the lockfile to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), LockfileError> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| LockfileError::ParseError(e.to_string()))?;
+        fs::write(path, format!("{}{}", LOCKFILE_HEADER, content))?;
+        Ok(())
+    }
+}
+
+/// Find `kakehashi.lock` in `start` or its nearest ancestor.
+pub fn find_lockfile(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(LOCKFILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::manifest::LanguageRecord;
+    use crate::install::parser::SourceKind;
+    use crate::install::queries::UNRESOLVED_QUERIES_REVISION;
+    use tempfile::TempDir;
+
+    fn manifest() -> Manifest {
+        let mut manifest = Manifest::default();
+        for (language, revision) in [("lua", "v0.4.0"), ("rust", "v0.24.0")] {
+            manifest.languages.insert(
+                language.to_string(),
+                LanguageRecord {
+                    parser: Some(ParserRecord {
+                        url: format!("https://github.com/tree-sitter/tree-sitter-{}", language),
+                        revision: revision.to_string(),
+                        location: None,
+                        sha256: "00".repeat(32),
+                        kind: SourceKind::Metadata,
+                    }),
+                    queries: Some(QueriesRecord {
+                        revision: "0123456789abcdef0123456789abcdef01234567".to_string(),
+                        files: BTreeMap::from([("highlights.scm".to_string(), "11".repeat(32))]),
+                    }),
+                    installed_at: 1_700_000_000,
+                    bundle: None,
+                },
+            );
+        }
+        manifest
+    }
+
+    #[test]
+    fn test_from_manifest_locks_all_or_selected_languages() {
+        let manifest = manifest();
+
+        let all = Lockfile::from_manifest(&manifest, &[]).unwrap();
+        assert_eq!(all.version, LOCKFILE_VERSION);
+        assert_eq!(all.languages.len(), 2);
+
+        let lua = Lockfile::from_manifest(&manifest, &["lua".to_string()]).unwrap();
+        assert_eq!(lua.languages.keys().collect::<Vec<_>>(), vec!["lua"]);
+        assert_eq!(
+            lua.languages["lua"].parser.as_ref().unwrap().revision,
+            "v0.4.0"
+        );
+
+        let missing = Lockfile::from_manifest(&manifest, &["python".to_string()]);
+        assert!(matches!(missing, Err(LockfileError::NotRecorded(langs)) if langs == ["python"]));
+    }
+
+    #[test]
+    fn test_from_manifest_refuses_queries_not_from_a_commit() {
+        let mut manifest = manifest();
+        let lua = manifest.languages.get_mut("lua").unwrap();
+        lua.queries.as_mut().unwrap().revision = UNRESOLVED_QUERIES_REVISION.to_string();
+
+        let unpinned = Lockfile::from_manifest(&manifest, &[]);
+        assert!(matches!(
+            unpinned,
+            Err(LockfileError::UnpinnedQueries(langs))
+                if langs == [("lua".to_string(), UNRESOLVED_QUERIES_REVISION.to_string())]
+        ));
+        assert!(Lockfile::from_manifest(&manifest, &["rust".to_string()]).is_ok());
+
+        // Queries shipped with a custom grammar carry the grammar's revision
+        let lua = manifest.languages.get_mut("lua").unwrap();
+        lua.parser.as_mut().unwrap().kind = SourceKind::Git;
+        assert!(Lockfile::from_manifest(&manifest, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCKFILE_NAME);
+        let lockfile = Lockfile::from_manifest(&manifest(), &[]).unwrap();
+
+        lockfile.save(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# This file is generated"));
+        assert!(!content.contains("installed_at"));
+
+        assert_eq!(Lockfile::load(&path).unwrap(), lockfile);
+    }
+
+    #[test]
+    fn test_load_rejects_newer_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCKFILE_NAME);
+        fs::write(&path, "version = 99\n").unwrap();
+
+        assert!(matches!(
+            Lockfile::load(&path),
+            Err(LockfileError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_find_lockfile_searches_ancestors() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_lockfile(&nested), None);
+
+        fs::write(temp_dir.path().join(LOCKFILE_NAME), "version = 1\n").unwrap();
+        assert_eq!(
+            find_lockfile(&nested),
+            Some(temp_dir.path().join(LOCKFILE_NAME))
+        );
+    }
+}
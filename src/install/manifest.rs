@@ -0,0 +1,334 @@
+//! Installation manifest recording what is installed in a data directory.
+//!
+//! Every `language install` records, per language, where the parser was built
+//! from, which nvim-treesitter commit the queries came from, the SHA-256 of
+//! each installed file and when it was installed. The manifest lives at
+//! `<data_dir>/manifest.toml` and is the source for `language lock`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::metadata::ParserMetadata;
+use super::parser::{ParserInstallResult, ParserSource, SourceKind};
+use super::queries::QueryInstallResult;
+
+/// File name of the manifest within the data directory.
+pub const MANIFEST_FILE: &str = "manifest.toml";
+
+/// Serializes read-modify-write cycles on the manifest within this process
+/// (the LSP server may install several languages concurrently).
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Installed languages of a data directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Records keyed by language name.
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageRecord>,
+}
+
+/// What was installed for one language.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageRecord {
+    /// Parser source and library hash, if a parser was installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parser: Option<ParserRecord>,
+    /// Query source and file hashes, if queries were installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queries: Option<QueriesRecord>,
+    /// Unix time (seconds) of the most recent install.
+    pub installed_at: u64,
+    /// File name of the bundle the language was unpacked from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<String>,
+}
+
+impl LanguageRecord {
+    /// Short description of where the language came from, for `language status`.
+    pub fn origin(&self) -> String {
+        if let Some(bundle) = &self.bundle {
+            return format!("bundle {}", bundle);
+        }
+        match self.parser.as_ref().map(|parser| parser.kind) {
+            Some(SourceKind::Git) => "git".to_string(),
+            Some(SourceKind::Path) => "path".to_string(),
+            Some(SourceKind::Metadata) | None => "nvim-treesitter".to_string(),
+        }
+    }
+}
+
+/// Source and hash of an installed parser.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParserRecord {
+    /// Git repository the parser was built from.
+    pub url: String,
+    /// Git revision (commit hash or tag).
+    pub revision: String,
+    /// Subdirectory within the repository (for monorepos).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// SHA-256 of the installed shared library.
+    pub sha256: String,
+    /// Kind of source; `url` is a local directory for `path` sources.
+    #[serde(default, skip_serializing_if = "is_metadata_kind")]
+    pub kind: SourceKind,
+}
+
+fn is_metadata_kind(kind: &SourceKind) -> bool {
+    !kind.is_custom()
+}
+
+impl ParserRecord {
+    /// The recorded url, revision and location.
+    pub fn metadata(&self) -> ParserMetadata {
+        ParserMetadata {
+            url: self.url.clone(),
+            revision: self.revision.clone(),
+            location: self.location.clone(),
+        }
+    }
+
+    /// The parser source, as used by `parser::InstallOptions::source`.
+    pub fn source(&self) -> ParserSource {
+        match self.kind {
+            SourceKind::Metadata => ParserSource::Metadata(self.metadata()),
+            SourceKind::Git => ParserSource::Git(self.metadata()),
+            SourceKind::Path => ParserSource::Path(PathBuf::from(&self.url)),
+        }
+    }
+}
+
+/// Source and hashes of installed query files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueriesRecord {
+    /// nvim-treesitter commit the files were downloaded from.
+    pub revision: String,
+    /// SHA-256 of each query file, keyed by file name.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Path of the manifest in `data_dir`.
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(MANIFEST_FILE)
+    }
+
+    /// Load the manifest of `data_dir`; a missing manifest is empty.
+    pub fn load(data_dir: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(Self::path(data_dir)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the manifest to `data_dir`, replacing the previous one atomically.
+    pub fn save(&self, data_dir: &Path) -> io::Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::create_dir_all(data_dir)?;
+        let mut temp = tempfile::NamedTempFile::new_in(data_dir)?;
+        io::Write::write_all(&mut temp, content.as_bytes())?;
+        temp.persist(Self::path(data_dir)).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Load, modify and save the manifest of `data_dir`.
+    pub fn update(data_dir: &Path, modify: impl FnOnce(&mut Self)) -> io::Result<()> {
+        let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut manifest = Self::load(data_dir)?;
+        modify(&mut manifest);
+        manifest.save(data_dir)
+    }
+
+    /// Record a parser install, hashing the installed library.
+    pub fn record_parser(&mut self, result: &ParserInstallResult) -> io::Result<()> {
+        let record = ParserRecord {
+            url: result.url.clone(),
+            revision: result.revision.clone(),
+            location: result.location.clone(),
+            sha256: sha256_file(&result.install_path)?,
+            kind: result.kind,
+        };
+        let entry = self.languages.entry(result.language.clone()).or_default();
+        entry.parser = Some(record);
+        entry.installed_at = unix_now();
+        entry.bundle = None;
+        Ok(())
+    }
+
+    /// Record a queries install, hashing every installed query file.
+    pub fn record_queries(&mut self, result: &QueryInstallResult) -> io::Result<()> {
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(&result.install_path)? {
+            let path = entry?.path();
+            if path.is_file()
+                && let Some(name) = path.file_name()
+            {
+                files.insert(name.to_string_lossy().into_owned(), sha256_file(&path)?);
+            }
+        }
+        let entry = self.languages.entry(result.language.clone()).or_default();
+        entry.queries = Some(QueriesRecord {
+            revision: result.revision.clone(),
+            files,
+        });
+        entry.installed_at = unix_now();
+        Ok(())
+    }
+
+    /// Record the query files of `result` and of every inherited language
+    /// installed along with them.
+    pub fn record_queries_with_inherited(&mut self, result: &QueryInstallResult) -> io::Result<()> {
+        self.record_queries(result)?;
+        for parent in &result.inherited {
+            self.record_queries_with_inherited(parent)?;
+        }
+        Ok(())
+    }
+
+    /// Forget a language (after uninstalling it).
+    pub fn remove(&mut self, language: &str) -> Option<LanguageRecord> {
+        self.languages.remove(language)
+    }
+}
+
+/// Hex-encoded SHA-256 of a file's contents.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sha256_file_matches_known_digest() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_load_missing_manifest_is_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            Manifest::load(temp_dir.path()).unwrap(),
+            Manifest::default()
+        );
+    }
+
+    #[test]
+    fn test_record_and_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
+        let parser_path = data_dir.join("parser").join("lua.so");
+        fs::create_dir_all(parser_path.parent().unwrap()).unwrap();
+        fs::write(&parser_path, "binary").unwrap();
+        let queries_dir = data_dir.join("queries").join("lua");
+        fs::create_dir_all(&queries_dir).unwrap();
+        fs::write(queries_dir.join("highlights.scm"), "(comment) @comment").unwrap();
+
+        Manifest::update(data_dir, |manifest| {
+            manifest
+                .record_parser(&ParserInstallResult {
+                    language: "lua".to_string(),
+                    install_path: parser_path.clone(),
+                    url: "https://github.com/tree-sitter-grammars/tree-sitter-lua".to_string(),
+                    revision: "v0.4.0".to_string(),
+                    location: None,
+                    kind: SourceKind::Metadata,
+                    bundled_queries: None,
+                })
+                .unwrap();
+            manifest
+                .record_queries(&QueryInstallResult {
+                    language: "lua".to_string(),
+                    install_path: queries_dir.clone(),
+                    files_downloaded: vec!["highlights.scm".to_string()],
+                    revision: "0123abc".to_string(),
+                    unresolved: None,
+                    inherited: Vec::new(),
+                })
+                .unwrap();
+        })
+        .unwrap();
+
+        let manifest = Manifest::load(data_dir).unwrap();
+        let record = &manifest.languages["lua"];
+        let parser = record.parser.as_ref().unwrap();
+        assert_eq!(parser.revision, "v0.4.0");
+        assert_eq!(parser.sha256, sha256_file(&parser_path).unwrap());
+        let queries = record.queries.as_ref().unwrap();
+        assert_eq!(queries.revision, "0123abc");
+        assert_eq!(queries.files.len(), 1);
+        assert!(record.installed_at > 0);
+
+        Manifest::update(data_dir, |manifest| {
+            manifest.remove("lua");
+        })
+        .unwrap();
+        assert!(Manifest::load(data_dir).unwrap().languages.is_empty());
+    }
+
+    #[test]
+    fn test_custom_source_kind_round_trips() {
+        let record = ParserRecord {
+            url: "/home/me/tree-sitter-mylang".to_string(),
+            revision: "local".to_string(),
+            location: None,
+            sha256: "00".repeat(32),
+            kind: SourceKind::Path,
+        };
+        let content = toml::to_string(&record).unwrap();
+        assert!(content.contains("kind = \"path\""), "Got: {}", content);
+        let parsed: ParserRecord = toml::from_str(&content).unwrap();
+        assert_eq!(
+            parsed.source(),
+            ParserSource::Path(PathBuf::from("/home/me/tree-sitter-mylang"))
+        );
+
+        // Records from nvim-treesitter's metadata omit the kind
+        let metadata_record = ParserRecord {
+            kind: SourceKind::Metadata,
+            ..record
+        };
+        assert!(!toml::to_string(&metadata_record).unwrap().contains("kind"));
+    }
+}
@@ -31,10 +31,12 @@ pub struct FetchOptions<'a> {
     pub data_dir: Option<&'a Path>,
     /// Whether to use the cache (if false, always fetch fresh).
     pub use_cache: bool,
+    /// URL to fetch parsers.lua from (nvim-treesitter on GitHub if None).
+    pub metadata_url: Option<&'a str>,
 }
 
 /// Parser metadata containing repository URL and revision.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParserMetadata {
     /// Git repository URL for the parser.
     pub url: String,
@@ -116,7 +118,10 @@ fn fetch_parsers_lua_with_options(
         .build()
         .map_err(|e| MetadataError::HttpError(e.to_string()))?;
 
-    let response = client.get(PARSERS_LUA_URL).send().map_err(|e| {
+    let url = options
+        .and_then(|opts| opts.metadata_url)
+        .unwrap_or(PARSERS_LUA_URL);
+    let response = client.get(url).send().map_err(|e| {
         if e.is_timeout() {
             MetadataError::Timeout
         } else {
@@ -126,8 +131,9 @@ fn fetch_parsers_lua_with_options(
 
     if !response.status().is_success() {
         return Err(MetadataError::HttpError(format!(
-            "HTTP {} fetching parsers.lua",
-            response.status()
+            "HTTP {} fetching {}",
+            response.status(),
+            url
         )));
     }
 
@@ -279,6 +285,15 @@ pub fn fetch_parser_metadata(
         .ok_or_else(|| MetadataError::LanguageNotFound(language.to_string()))
 }
 
+/// Fetch metadata for every parser listed in nvim-treesitter's parsers.lua.
+///
+/// Use `options` to enable caching and avoid repeated HTTP requests.
+pub fn fetch_all_parser_metadata(
+    options: Option<&FetchOptions>,
+) -> Result<HashMap<String, ParserMetadata>, MetadataError> {
+    fetch_parsers_lua_with_options(options)
+}
+
 /// List all supported languages by fetching from nvim-treesitter.
 ///
 /// This returns all languages that nvim-treesitter supports (300+ languages).
@@ -319,6 +334,7 @@ mod tests {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         // Fetch metadata with caching enabled - this should write to cache
@@ -467,6 +483,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         // First, populate the cache by fetching any language (or mock the cache)
@@ -506,6 +523,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         // Mock the cache with parsers.lua content that does NOT include 'fake_lang_xyz'
@@ -538,6 +556,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         // Mock the cache with parsers.lua content
@@ -593,6 +612,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         let mock_parsers_lua = "return {}";
@@ -7,7 +7,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::metadata::{FetchOptions, MetadataError, fetch_parser_metadata};
+use serde::{Deserialize, Serialize};
+use tree_sitter::{LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+use super::metadata::{FetchOptions, MetadataError, ParserMetadata, fetch_parser_metadata};
+use super::queries::{QueryInstallResult, install_bundled_queries};
+use super::sources::InstallSources;
+use crate::language::loader::{ParserLoadError, ParserLoader};
 
 /// Error types for parser installation.
 #[derive(Debug)]
@@ -24,6 +30,10 @@ pub enum ParserInstallError {
     IoError(std::io::Error),
     /// Parser already exists.
     AlreadyExists(PathBuf),
+    /// Local grammar directory does not exist.
+    SourceNotFound(PathBuf),
+    /// The built parser has an ABI version kakehashi cannot load.
+    IncompatibleAbi(usize),
 }
 
 impl std::fmt::Display for ParserInstallError {
@@ -46,6 +56,15 @@ impl std::fmt::Display for ParserInstallError {
                     path.display()
                 )
             }
+            Self::SourceNotFound(path) => {
+                write!(f, "Grammar directory not found: {}", path.display())
+            }
+            Self::IncompatibleAbi(version) => write!(
+                f,
+                "Built parser uses tree-sitter ABI version {}, but kakehashi supports ABI \
+                 versions {} to {}. Update the tree-sitter CLI and reinstall.",
+                version, MIN_COMPATIBLE_LANGUAGE_VERSION, LANGUAGE_VERSION
+            ),
         }
     }
 }
@@ -64,14 +83,66 @@ impl From<MetadataError> for ParserInstallError {
     }
 }
 
+/// Where a parser is built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParserSource {
+    /// A revision listed in nvim-treesitter's metadata (e.g., pinned by a lockfile).
+    Metadata(ParserMetadata),
+    /// A git repository given by the user (`--git`, `languages.<name>.source`).
+    Git(ParserMetadata),
+    /// A local grammar directory given by the user; built without network access.
+    Path(PathBuf),
+}
+
+impl ParserSource {
+    /// The kind of this source, as recorded in the manifest.
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            Self::Metadata(_) => SourceKind::Metadata,
+            Self::Git(_) => SourceKind::Git,
+            Self::Path(_) => SourceKind::Path,
+        }
+    }
+}
+
+/// Kind of a parser source, as recorded in the manifest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// nvim-treesitter's metadata.
+    #[default]
+    Metadata,
+    /// A git repository given by the user.
+    Git,
+    /// A local grammar directory given by the user.
+    Path,
+}
+
+impl SourceKind {
+    /// Whether the source was given by the user rather than taken from
+    /// nvim-treesitter's metadata.
+    pub fn is_custom(&self) -> bool {
+        *self != Self::Metadata
+    }
+}
+
 /// Result of installing a parser.
 pub struct ParserInstallResult {
     /// The language that was installed.
     pub language: String,
     /// Path where parser was installed.
     pub install_path: PathBuf,
-    /// Git revision that was used.
+    /// Git repository (or local directory) the parser was built from.
+    pub url: String,
+    /// Git revision that was used (`local` for a directory outside git).
     pub revision: String,
+    /// Subdirectory within the repository (for monorepos).
+    pub location: Option<String>,
+    /// Kind of source the parser was built from.
+    pub kind: SourceKind,
+    /// Queries shipped in the grammar's own `queries/` directory, installed
+    /// alongside the parser (custom sources only).
+    pub bundled_queries: Option<QueryInstallResult>,
 }
 
 /// Options for parser installation.
@@ -84,10 +155,14 @@ pub struct InstallOptions {
     pub verbose: bool,
     /// Whether to bypass the metadata cache.
     pub no_cache: bool,
+    /// Source to build from instead of the latest nvim-treesitter metadata.
+    pub source: Option<ParserSource>,
+    /// Mirrors for the metadata and grammar repositories.
+    pub sources: InstallSources,
 }
 
 /// Find the tree-sitter CLI executable.
-fn find_tree_sitter() -> Option<PathBuf> {
+pub(crate) fn find_tree_sitter() -> Option<PathBuf> {
     // Check common locations
     let paths = [
         // Cargo bin directory
@@ -110,7 +185,7 @@ fn which_tree_sitter() -> Option<PathBuf> {
 }
 
 /// Get the shared library extension for the current platform.
-fn shared_lib_extension() -> &'static str {
+pub(crate) fn shared_lib_extension() -> &'static str {
     if cfg!(target_os = "macos") {
         "dylib"
     } else if cfg!(target_os = "windows") {
@@ -148,6 +223,13 @@ pub fn install_parser(
         return Err(ParserInstallError::AlreadyExists(parser_file));
     }
 
+    // Report a missing grammar directory before anything else
+    if let Some(ParserSource::Path(grammar_dir)) = &options.source
+        && !grammar_dir.is_dir()
+    {
+        return Err(ParserInstallError::SourceNotFound(grammar_dir.clone()));
+    }
+
     // Find tree-sitter CLI
     let tree_sitter = find_tree_sitter().ok_or(ParserInstallError::TreeSitterNotFound)?;
 
@@ -155,36 +237,77 @@ pub fn install_parser(
         eprintln!("Using tree-sitter at: {}", tree_sitter.display());
     }
 
-    // Fetch metadata (with caching support)
-    if options.verbose {
-        eprintln!("Fetching metadata for '{}'...", language);
-    }
-    let fetch_options = FetchOptions {
-        data_dir: Some(&options.data_dir),
-        use_cache: !options.no_cache,
-    };
-    let metadata = fetch_parser_metadata(language, Some(&fetch_options))?;
-
-    if options.verbose {
-        eprintln!("Repository: {}", metadata.url);
-        eprintln!("Revision: {}", metadata.revision);
-    }
-
-    // Create temp directory for cloning
+    // Create temp directory for the sources
     let temp_dir = tempfile::tempdir()?;
     let clone_dir = temp_dir.path().join("parser");
 
-    // Clone the repository
-    if options.verbose {
-        eprintln!("Cloning repository...");
-    }
-    clone_repo(&metadata.url, &metadata.revision, &clone_dir)?;
+    let (source_dir, metadata, kind) = match &options.source {
+        Some(ParserSource::Path(grammar_dir)) => {
+            let grammar_dir = grammar_dir.canonicalize()?;
+            if options.verbose {
+                eprintln!("Grammar directory: {}", grammar_dir.display());
+            }
+            // Build a copy so the user's directory is left untouched
+            copy_grammar_dir(&grammar_dir, &clone_dir)?;
+            let metadata = ParserMetadata {
+                url: grammar_dir.to_string_lossy().into_owned(),
+                revision: git_head(&grammar_dir).unwrap_or_else(|| LOCAL_REVISION.to_string()),
+                location: None,
+            };
+            (clone_dir.clone(), metadata, SourceKind::Path)
+        }
+        source => {
+            let (mut metadata, kind) = match source {
+                Some(ParserSource::Metadata(metadata)) => (metadata.clone(), SourceKind::Metadata),
+                Some(ParserSource::Git(metadata)) => (metadata.clone(), SourceKind::Git),
+                _ => {
+                    // Fetch metadata (with caching support)
+                    if options.verbose {
+                        eprintln!("Fetching metadata for '{}'...", language);
+                    }
+                    let fetch_options = FetchOptions {
+                        data_dir: Some(&options.data_dir),
+                        use_cache: !options.no_cache,
+                        metadata_url: options.sources.metadata_url.as_deref(),
+                    };
+                    (
+                        fetch_parser_metadata(language, Some(&fetch_options))?,
+                        SourceKind::Metadata,
+                    )
+                }
+            };
+
+            if options.verbose {
+                eprintln!("Repository: {}", metadata.url);
+                eprintln!("Revision: {}", metadata.revision);
+            }
 
-    // Determine the source directory (handle monorepos)
-    let source_dir = if let Some(ref location) = metadata.location {
-        clone_dir.join(location)
-    } else {
-        clone_dir.clone()
+            // Clone the repository (from its mirror, if any)
+            let clone_url = options.sources.rewrite_url(&metadata.url);
+            if options.verbose {
+                if clone_url == metadata.url {
+                    eprintln!("Cloning repository...");
+                } else {
+                    eprintln!("Cloning repository from {}...", clone_url);
+                }
+            }
+            clone_repo(&clone_url, &metadata.revision, &clone_dir)?;
+
+            // Record the commit a user-given branch or tag resolved to
+            if kind == SourceKind::Git
+                && let Some(commit) = git_head(&clone_dir)
+            {
+                metadata.revision = commit;
+            }
+
+            // Determine the source directory (handle monorepos)
+            let source_dir = if let Some(ref location) = metadata.location {
+                clone_dir.join(location)
+            } else {
+                clone_dir.clone()
+            };
+            (source_dir, metadata, kind)
+        }
     };
 
     if options.verbose {
@@ -195,7 +318,28 @@ pub fn install_parser(
     build_parser(&tree_sitter, &source_dir, options.verbose)?;
 
     // Find the built library
-    let built_lib = find_built_library(&source_dir)?;
+    let mut built_lib = find_built_library(&source_dir)?;
+
+    // A grammar shipping a parser.c generated for an ABI this build cannot
+    // load is regenerated for the current ABI and built again
+    if let Some(version) = incompatible_abi(&built_lib, language) {
+        if !source_dir.join("src").join("grammar.json").exists() {
+            return Err(ParserInstallError::IncompatibleAbi(version));
+        }
+        if options.verbose {
+            eprintln!(
+                "Built parser uses ABI version {}; regenerating for ABI version {}...",
+                version, LANGUAGE_VERSION
+            );
+        }
+        fs::remove_file(&built_lib)?;
+        generate_parser(&tree_sitter, &source_dir, options.verbose)?;
+        build_parser(&tree_sitter, &source_dir, options.verbose)?;
+        built_lib = find_built_library(&source_dir)?;
+        if let Some(version) = incompatible_abi(&built_lib, language) {
+            return Err(ParserInstallError::IncompatibleAbi(version));
+        }
+    }
 
     if options.verbose {
         eprintln!("Built library: {}", built_lib.display());
@@ -209,13 +353,60 @@ pub fn install_parser(
         eprintln!("Installed to: {}", parser_file.display());
     }
 
+    // Custom grammars usually ship their own queries
+    let bundled_queries = if kind.is_custom() {
+        install_bundled_queries(language, &source_dir, &options.data_dir, &metadata.revision)?
+    } else {
+        None
+    };
+
     Ok(ParserInstallResult {
         language: language.to_string(),
         install_path: parser_file,
+        url: metadata.url,
         revision: metadata.revision,
+        location: metadata.location,
+        kind,
+        bundled_queries,
     })
 }
 
+/// Revision recorded for a local grammar directory outside git.
+const LOCAL_REVISION: &str = "local";
+
+/// Directories skipped when copying a local grammar directory.
+const SKIPPED_GRAMMAR_DIRS: &[&str] = &[".git", "node_modules", "target", "build"];
+
+/// Copy a grammar directory, skipping VCS metadata and build outputs.
+fn copy_grammar_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !SKIPPED_GRAMMAR_DIRS.contains(&name.to_string_lossy().as_ref()) {
+                copy_grammar_dir(&entry.path(), &to.join(&name))?;
+            }
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Commit checked out in `dir`, if it is a git work tree.
+fn git_head(dir: &Path) -> Option<String> {
+    Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+}
+
 /// Clone a git repository at a specific revision.
 fn clone_repo(url: &str, revision: &str, dest: &Path) -> Result<(), ParserInstallError> {
     // First, clone with depth 1 (we'll fetch the specific revision)
@@ -265,9 +456,27 @@ fn clone_repo(url: &str, revision: &str, dest: &Path) -> Result<(), ParserInstal
         )));
     }
 
+    // A pinned commit must be exactly what was checked out, whatever the
+    // remote (or a mirror standing in for it) sent
+    if is_commit_hash(revision) {
+        let head = git_head(dest).unwrap_or_default();
+        if !head.eq_ignore_ascii_case(revision) {
+            return Err(ParserInstallError::GitError(format!(
+                "Checked out {} instead of the pinned commit {}",
+                if head.is_empty() { "nothing" } else { &head },
+                revision
+            )));
+        }
+    }
+
     Ok(())
 }
 
+/// Whether `revision` is a full (SHA-1) commit hash rather than a branch or tag.
+pub(crate) fn is_commit_hash(revision: &str) -> bool {
+    revision.len() == 40 && revision.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 /// Build the parser using tree-sitter CLI.
 fn build_parser(
     tree_sitter: &Path,
@@ -299,6 +508,51 @@ fn build_parser(
     Ok(())
 }
 
+/// Regenerate the parser source from `src/grammar.json` for the ABI version
+/// this build of tree-sitter uses.
+fn generate_parser(
+    tree_sitter: &Path,
+    source_dir: &Path,
+    verbose: bool,
+) -> Result<(), ParserInstallError> {
+    let output = Command::new(tree_sitter)
+        .current_dir(source_dir)
+        .arg("generate")
+        .arg("--abi")
+        .arg(LANGUAGE_VERSION.to_string())
+        .arg(Path::new("src").join("grammar.json"))
+        .output()
+        .map_err(|e| ParserInstallError::CompileError(e.to_string()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if verbose {
+            eprintln!(
+                "Generate stdout: {}",
+                String::from_utf8_lossy(&output.stdout)
+            );
+            eprintln!("Generate stderr: {}", stderr);
+        }
+        return Err(ParserInstallError::CompileError(format!(
+            "tree-sitter generate failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// ABI version of a built parser library, if kakehashi cannot load it.
+///
+/// Libraries that fail to load for other reasons (e.g., a symbol name not
+/// following the `tree_sitter_<language>` convention) are not reported.
+fn incompatible_abi(library: &Path, language: &str) -> Option<usize> {
+    match ParserLoader::new().load_language(&library.to_string_lossy(), language) {
+        Err(ParserLoadError::IncompatibleAbi(version)) => Some(version),
+        _ => None,
+    }
+}
+
 /// Find the built shared library in the source directory.
 fn find_built_library(source_dir: &Path) -> Result<PathBuf, ParserInstallError> {
     let ext = shared_lib_extension();
@@ -340,6 +594,47 @@ mod tests {
         // Just verify it doesn't panic
     }
 
+    #[test]
+    fn test_copy_grammar_dir_skips_vcs_and_build_outputs() {
+        let temp = tempdir().expect("Failed to create temp dir");
+        let grammar = temp.path().join("tree-sitter-mylang");
+        fs::create_dir_all(grammar.join("src/tree_sitter")).unwrap();
+        fs::write(grammar.join("src/parser.c"), "").unwrap();
+        fs::write(grammar.join("src/tree_sitter/parser.h"), "").unwrap();
+        fs::create_dir_all(grammar.join(".git")).unwrap();
+        fs::create_dir_all(grammar.join("node_modules/dep")).unwrap();
+
+        let copy = temp.path().join("copy");
+        copy_grammar_dir(&grammar, &copy).unwrap();
+
+        assert!(copy.join("src/parser.c").exists());
+        assert!(copy.join("src/tree_sitter/parser.h").exists());
+        assert!(!copy.join(".git").exists());
+        assert!(!copy.join("node_modules").exists());
+    }
+
+    #[test]
+    fn test_install_parser_rejects_missing_grammar_dir() {
+        let temp = tempdir().expect("Failed to create temp dir");
+        let options = InstallOptions {
+            data_dir: temp.path().to_path_buf(),
+            force: false,
+            verbose: false,
+            no_cache: false,
+            source: Some(ParserSource::Path(temp.path().join("missing"))),
+            sources: InstallSources::default(),
+        };
+
+        // Reported before looking for tree-sitter; nothing is fetched
+        match install_parser("mylang", &options) {
+            Err(ParserInstallError::SourceNotFound(path)) => {
+                assert_eq!(path, temp.path().join("missing"))
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("install should fail"),
+        }
+    }
+
     #[test]
     fn test_parser_file_exists_returns_none_when_missing() {
         let temp = tempdir().expect("Failed to create temp dir");
@@ -392,6 +687,83 @@ mod tests {
         assert!(dest.join(".git").exists(), "Should be a git repository");
     }
 
+    /// Grammar repositories are cloned from their mirror; a `file://`
+    /// repository stands in for the internal git server.
+    #[test]
+    fn test_clone_repo_follows_url_rewrite() {
+        let temp = tempdir().expect("Failed to create temp dir");
+        let mirror = temp.path().join("mirror");
+        let upstream = mirror.join("example").join("tree-sitter-mylang");
+        fs::create_dir_all(&upstream).unwrap();
+        fs::write(upstream.join("grammar.js"), "module.exports = {};").unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&upstream)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .expect("git should run");
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "grammar.js"]);
+        git(&["commit", "--quiet", "-m", "initial"]);
+        git(&["tag", "v1.0.0"]);
+
+        let sources = InstallSources {
+            url_rewrites: vec![(
+                "https://github.com/".to_string(),
+                format!("file://{}/", mirror.display()),
+            )],
+            ..InstallSources::default()
+        };
+        let url = sources.rewrite_url("https://github.com/example/tree-sitter-mylang");
+        let dest = temp.path().join("clone");
+
+        clone_repo(&url, "v1.0.0", &dest).expect("clone from the mirror");
+        assert!(dest.join("grammar.js").exists());
+    }
+
+    #[test]
+    fn test_clone_repo_checks_out_pinned_commit() {
+        let temp = tempdir().expect("Failed to create temp dir");
+        let upstream = temp.path().join("upstream");
+        fs::create_dir_all(&upstream).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&upstream)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .expect("git should run");
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "--quiet"]);
+        fs::write(upstream.join("grammar.js"), "// v1").unwrap();
+        git(&["add", "grammar.js"]);
+        git(&["commit", "--quiet", "-m", "v1"]);
+        let pinned = git_head(&upstream).unwrap();
+        fs::write(upstream.join("grammar.js"), "// v2").unwrap();
+        git(&["commit", "--quiet", "-am", "v2"]);
+
+        let dest = temp.path().join("clone");
+        clone_repo(&format!("file://{}", upstream.display()), &pinned, &dest)
+            .expect("clone the pinned commit");
+        assert_eq!(git_head(&dest).as_deref(), Some(pinned.as_str()));
+        assert_eq!(
+            fs::read_to_string(dest.join("grammar.js")).unwrap(),
+            "// v1"
+        );
+    }
+
+    #[test]
+    fn test_is_commit_hash() {
+        assert!(is_commit_hash("0123456789abcdef0123456789ABCDEF01234567"));
+        assert!(!is_commit_hash("v1.0.0"));
+        assert!(!is_commit_hash("main"));
+        assert!(!is_commit_hash("0123456"));
+    }
+
     /// Test that clone_repo works with commit hash revisions
     #[test]
     fn test_clone_repo_with_commit_hash() {
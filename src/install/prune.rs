@@ -0,0 +1,364 @@
+//! Removal of installed languages that have not been used for a while.
+//!
+//! A language is stale when the server has not loaded it (see
+//! [`super::usage`]) and it was not installed within the maximum age. Its
+//! parser is removed, and so are its queries unless a retained language
+//! inherits them. Query directories without a parser, such as the `ecma`
+//! parent of `javascript`, are removed once stale and no longer inherited.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::manifest::Manifest;
+use super::parser::shared_lib_extension;
+use super::queries::installed_query_parents;
+use super::usage::Usage;
+use crate::language::FailedParserRegistry;
+
+/// A language with files to remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleLanguage {
+    pub language: String,
+    /// Last load recorded by the server, if any.
+    pub last_loaded: Option<u64>,
+    /// Parser library to remove.
+    pub parser: Option<PathBuf>,
+    /// Query directory to remove; `None` if there is none or it is still
+    /// inherited by a retained language.
+    pub queries: Option<PathBuf>,
+}
+
+/// What `language prune` removes from a data directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrunePlan {
+    pub languages: Vec<StaleLanguage>,
+    /// Entries of the failed parser list whose parser is not installed, or
+    /// is removed by this plan.
+    pub failed_parsers: Vec<String>,
+}
+
+impl PrunePlan {
+    /// Whether there is nothing to remove.
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty() && self.failed_parsers.is_empty()
+    }
+}
+
+/// Find the languages of `data_dir` not used within `max_age` seconds of `now`.
+pub fn plan_prune(data_dir: &Path, max_age: u64, now: u64) -> io::Result<PrunePlan> {
+    let manifest = Manifest::load(data_dir)?;
+    let usage = Usage::load(data_dir)?;
+    let parsers = installed_parsers(data_dir)?;
+    let query_dirs = installed_query_dirs(data_dir)?;
+
+    // The latest of the last load and the install, or the file's age for
+    // files installed before either was recorded
+    let is_stale = |language: &str, path: &Path| {
+        let last_active = usage
+            .last_loaded
+            .get(language)
+            .copied()
+            .into_iter()
+            .chain(manifest.languages.get(language).map(|r| r.installed_at))
+            .max()
+            .unwrap_or_else(|| modified_time(path));
+        now.saturating_sub(last_active) > max_age
+    };
+
+    let retained: Vec<&str> = parsers
+        .iter()
+        .filter(|(language, path)| !is_stale(language, path))
+        .chain(query_dirs.iter().filter(|(language, path)| {
+            !parsers.contains_key(*language) && !is_stale(language, path)
+        }))
+        .map(|(language, _)| language.as_str())
+        .collect();
+    let kept_queries = with_inherited_queries(&retained, data_dir);
+
+    let languages: BTreeSet<&String> = parsers.keys().chain(query_dirs.keys()).collect();
+    let stale: Vec<StaleLanguage> = languages
+        .into_iter()
+        .filter_map(|language| {
+            let parser = parsers
+                .get(language)
+                .filter(|path| is_stale(language, path))
+                .cloned();
+            let queries = query_dirs
+                .get(language)
+                .filter(|_| !kept_queries.contains(language))
+                .cloned();
+            (parser.is_some() || queries.is_some()).then(|| StaleLanguage {
+                language: language.clone(),
+                last_loaded: usage.last_loaded.get(language).copied(),
+                parser,
+                queries,
+            })
+        })
+        .collect();
+
+    let registry = FailedParserRegistry::new(data_dir);
+    registry.load_failed_parsers()?;
+    let removed: BTreeSet<&str> = stale
+        .iter()
+        .filter(|language| language.parser.is_some())
+        .map(|language| language.language.as_str())
+        .collect();
+    let mut failed_parsers: Vec<String> = registry
+        .failed_parsers()
+        .into_iter()
+        .filter(|language| !parsers.contains_key(language) || removed.contains(language.as_str()))
+        .collect();
+    failed_parsers.sort();
+
+    Ok(PrunePlan {
+        languages: stale,
+        failed_parsers,
+    })
+}
+
+/// Remove the files of `plan` from `data_dir` and forget the removed
+/// languages in the manifest, usage record and failed parser list.
+pub fn apply_prune(data_dir: &Path, plan: &PrunePlan) -> io::Result<()> {
+    for language in &plan.languages {
+        if let Some(parser) = &language.parser {
+            remove(fs::remove_file(parser))?;
+        }
+        if let Some(queries) = &language.queries {
+            remove(fs::remove_dir_all(queries))?;
+        }
+    }
+
+    Manifest::update(data_dir, |manifest| {
+        for stale in &plan.languages {
+            let Some(record) = manifest.languages.get_mut(&stale.language) else {
+                continue;
+            };
+            if stale.parser.is_some() {
+                record.parser = None;
+            }
+            if stale.queries.is_some() {
+                record.queries = None;
+            }
+            if record.parser.is_none() && record.queries.is_none() {
+                manifest.remove(&stale.language);
+            }
+        }
+    })?;
+
+    if Usage::path(data_dir).exists() {
+        Usage::update(data_dir, |usage| {
+            for stale in plan.languages.iter().filter(|stale| stale.parser.is_some()) {
+                usage.last_loaded.remove(&stale.language);
+            }
+        })?;
+    }
+
+    if !plan.failed_parsers.is_empty() {
+        let registry = FailedParserRegistry::new(data_dir);
+        registry.load_failed_parsers()?;
+        for language in &plan.failed_parsers {
+            registry.clear_failed(language)?;
+        }
+    }
+    Ok(())
+}
+
+/// `languages` and every language whose queries they inherit, transitively.
+fn with_inherited_queries(languages: &[&str], data_dir: &Path) -> BTreeSet<String> {
+    let mut kept = BTreeSet::new();
+    let mut pending: Vec<String> = languages.iter().map(|l| l.to_string()).collect();
+    while let Some(language) = pending.pop() {
+        if kept.insert(language.clone()) {
+            pending.extend(installed_query_parents(&language, data_dir));
+        }
+    }
+    kept
+}
+
+/// Parser libraries in `data_dir`, by language.
+fn installed_parsers(data_dir: &Path) -> io::Result<BTreeMap<String, PathBuf>> {
+    Ok(list_dir(&data_dir.join("parser"))?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == shared_lib_extension())
+        })
+        .filter_map(|path| Some((path.file_stem()?.to_string_lossy().into_owned(), path)))
+        .collect())
+}
+
+/// Query directories in `data_dir`, by language.
+fn installed_query_dirs(data_dir: &Path) -> io::Result<BTreeMap<String, PathBuf>> {
+    Ok(list_dir(&data_dir.join("queries"))?
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .filter_map(|path| Some((path.file_name()?.to_string_lossy().into_owned(), path)))
+        .collect())
+}
+
+/// Entries of a directory; a missing directory has none.
+fn list_dir(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Modification time of `path` as a Unix timestamp (0 if unknown).
+fn modified_time(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Treat an already removed file as removed.
+fn remove(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::manifest::{LanguageRecord, QueriesRecord};
+    use tempfile::TempDir;
+
+    const DAY: u64 = 24 * 60 * 60;
+    const NOW: u64 = 1000 * DAY;
+
+    /// Install a fake language, recorded as installed at `installed_at`.
+    fn install(data_dir: &Path, language: &str, parser: bool, inherits: &str, installed_at: u64) {
+        if parser {
+            let library =
+                data_dir
+                    .join("parser")
+                    .join(format!("{}.{}", language, shared_lib_extension()));
+            fs::create_dir_all(library.parent().unwrap()).unwrap();
+            fs::write(library, "parser").unwrap();
+        }
+        let queries = data_dir.join("queries").join(language);
+        fs::create_dir_all(&queries).unwrap();
+        let header = if inherits.is_empty() {
+            String::new()
+        } else {
+            format!("; inherits: {}\n", inherits)
+        };
+        fs::write(
+            queries.join("highlights.scm"),
+            header + "(comment) @comment",
+        )
+        .unwrap();
+        Manifest::update(data_dir, |manifest| {
+            manifest.languages.insert(
+                language.to_string(),
+                LanguageRecord {
+                    queries: Some(QueriesRecord {
+                        revision: "abc".to_string(),
+                        files: BTreeMap::new(),
+                    }),
+                    installed_at,
+                    ..LanguageRecord::default()
+                },
+            );
+        })
+        .unwrap();
+    }
+
+    fn summary(plan: &PrunePlan) -> Vec<(String, bool, bool)> {
+        plan.languages
+            .iter()
+            .map(|stale| {
+                (
+                    stale.language.clone(),
+                    stale.parser.is_some(),
+                    stale.queries.is_some(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_keeps_recently_used_languages_and_inherited_queries() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let old = NOW - 200 * DAY;
+        // typescript (used recently) inherits ecma, which is shared with the
+        // unused javascript; javascript also inherits the unused jsx
+        install(data_dir, "typescript", true, "ecma", old);
+        install(data_dir, "javascript", true, "ecma,jsx", old);
+        install(data_dir, "ecma", false, "", old);
+        install(data_dir, "jsx", false, "", old);
+        install(data_dir, "lua", true, "", NOW - DAY);
+        Usage::update(data_dir, |usage| {
+            usage
+                .last_loaded
+                .insert("typescript".to_string(), NOW - DAY);
+            usage.last_loaded.insert("javascript".to_string(), old);
+        })
+        .unwrap();
+
+        let plan = plan_prune(data_dir, 90 * DAY, NOW).unwrap();
+        assert_eq!(
+            summary(&plan),
+            vec![
+                ("javascript".to_string(), true, true),
+                ("jsx".to_string(), false, true),
+            ]
+        );
+        assert_eq!(plan.languages[0].last_loaded, Some(old));
+
+        // A longer maximum age keeps everything
+        assert!(plan_prune(data_dir, 365 * DAY, NOW).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_removes_files_and_records() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let old = NOW - 200 * DAY;
+        // c is unused but its queries are inherited by cpp
+        install(data_dir, "c", true, "", old);
+        install(data_dir, "cpp", true, "c", NOW);
+        install(data_dir, "yaml", true, "", old);
+        fs::write(data_dir.join("failed_parsers"), "yaml\ncpp\nremoved\n").unwrap();
+
+        let plan = plan_prune(data_dir, 90 * DAY, NOW).unwrap();
+        assert_eq!(
+            summary(&plan),
+            vec![
+                ("c".to_string(), true, false),
+                ("yaml".to_string(), true, true),
+            ]
+        );
+        assert_eq!(plan.failed_parsers, vec!["removed", "yaml"]);
+
+        apply_prune(data_dir, &plan).unwrap();
+        let ext = shared_lib_extension();
+        assert!(!data_dir.join(format!("parser/c.{ext}")).exists());
+        assert!(data_dir.join("queries/c").exists());
+        assert!(!data_dir.join(format!("parser/yaml.{ext}")).exists());
+        assert!(!data_dir.join("queries/yaml").exists());
+
+        let manifest = Manifest::load(data_dir).unwrap();
+        assert!(manifest.languages["c"].queries.is_some());
+        assert!(!manifest.languages.contains_key("yaml"));
+        assert_eq!(
+            fs::read_to_string(data_dir.join("failed_parsers"))
+                .unwrap()
+                .trim(),
+            "cpp"
+        );
+        assert!(plan_prune(data_dir, 90 * DAY, NOW).unwrap().is_empty());
+    }
+}
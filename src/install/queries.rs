@@ -1,13 +1,27 @@
-//! Query file downloading from nvim-treesitter repository.
+//! Query file downloading from nvim-treesitter repository, and installation
+//! of the queries bundled with custom grammars.
 
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
 
-/// Base URL for nvim-treesitter query files on GitHub (main branch).
-/// Note: In the main branch, queries are under runtime/queries instead of queries.
-const NVIM_TREESITTER_QUERIES_URL: &str =
-    "https://raw.githubusercontent.com/nvim-treesitter/nvim-treesitter/main/runtime/queries";
+use super::sources::InstallSources;
+
+/// Serializes query installs within this process: languages installed in
+/// parallel may inherit the same parent queries (e.g., ecma).
+static QUERY_INSTALL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Git repository that query files are taken from.
+pub const NVIM_TREESITTER_REPO_URL: &str = "https://github.com/nvim-treesitter/nvim-treesitter";
+
+/// Branch used when no query revision is pinned.
+pub const DEFAULT_QUERIES_BRANCH: &str = "main";
+
+/// Revision recorded for query files downloaded from the main branch because
+/// its commit could not be resolved.
+pub const UNRESOLVED_QUERIES_REVISION: &str = "unresolved";
 
 /// Query file types to download.
 const QUERY_FILES: &[&str] = &["highlights.scm", "locals.scm", "injections.scm"];
@@ -64,6 +78,94 @@ pub struct QueryInstallResult {
     pub install_path: PathBuf,
     /// List of files that were downloaded.
     pub files_downloaded: Vec<String>,
+    /// nvim-treesitter revision the files were downloaded from.
+    pub revision: String,
+    /// Why the commit of the main branch could not be resolved, if the files
+    /// were downloaded from the branch itself (`revision` is then
+    /// [`UNRESOLVED_QUERIES_REVISION`]).
+    pub unresolved: Option<String>,
+    /// Queries installed along with these for the languages they inherit
+    /// from (e.g., ecma and jsx for typescript).
+    pub inherited: Vec<QueryInstallResult>,
+}
+
+impl QueryInstallResult {
+    /// Record that the files come from an unresolved main branch.
+    fn with_unresolved(self, unresolved: Option<String>) -> Self {
+        match unresolved {
+            Some(reason) => Self {
+                revision: UNRESOLVED_QUERIES_REVISION.to_string(),
+                inherited: self
+                    .inherited
+                    .into_iter()
+                    .map(|parent| parent.with_unresolved(Some(reason.clone())))
+                    .collect(),
+                unresolved: Some(reason),
+                ..self
+            },
+            None => self,
+        }
+    }
+}
+
+/// Resolve the commit that nvim-treesitter's main branch currently points to.
+///
+/// Downloading from the commit rather than the branch keeps all query files
+/// of one install consistent, and lets the commit be recorded for lockfiles.
+/// The repository is looked up through the URL rewrites of `sources`.
+pub fn resolve_queries_revision(sources: &InstallSources) -> Result<String, QueryInstallError> {
+    let repository = sources.queries_repository();
+    let output = Command::new("git")
+        .args([
+            "ls-remote",
+            &repository,
+            &format!("refs/heads/{}", DEFAULT_QUERIES_BRANCH),
+        ])
+        .output()
+        .map_err(|e| QueryInstallError::HttpError(format!("git ls-remote failed: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(QueryInstallError::HttpError(format!(
+            "git ls-remote failed for {}",
+            repository
+        )));
+    }
+
+    parse_ls_remote_output(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        QueryInstallError::HttpError(format!(
+            "branch '{}' not found in {}",
+            DEFAULT_QUERIES_BRANCH, repository
+        ))
+    })
+}
+
+/// Extract the commit hash from `git ls-remote` output (`<sha>\t<ref>`).
+fn parse_ls_remote_output(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .find(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_string)
+}
+
+/// Use `revision` if pinned, otherwise the current main commit.
+///
+/// Falls back to the main branch itself when the commit cannot be resolved
+/// (e.g., git is not installed), so installs still work unpinned; the reason
+/// is returned alongside. Query sources without a revision in their URL are
+/// not resolved at all.
+fn revision_or_latest(
+    revision: Option<&str>,
+    sources: &InstallSources,
+) -> (String, Option<String>) {
+    match revision {
+        Some(revision) => (revision.to_string(), None),
+        None if !sources.queries_are_versioned() => (DEFAULT_QUERIES_BRANCH.to_string(), None),
+        None => match resolve_queries_revision(sources) {
+            Ok(revision) => (revision, None),
+            Err(e) => (DEFAULT_QUERIES_BRANCH.to_string(), Some(e.to_string())),
+        },
+    }
 }
 
 /// Download and install query files for a language.
@@ -72,6 +174,8 @@ pub struct QueryInstallResult {
 /// * `language` - The language to install queries for (e.g., "lua", "rust")
 /// * `data_dir` - The base data directory for kakehashi
 /// * `force` - Whether to overwrite existing queries
+/// * `revision` - nvim-treesitter commit to download from (latest main if `None`)
+/// * `sources` - Where query files are downloaded from
 ///
 /// # Returns
 /// * `Ok(QueryInstallResult)` - Installation succeeded
@@ -80,6 +184,8 @@ pub fn install_queries(
     language: &str,
     data_dir: &Path,
     force: bool,
+    revision: Option<&str>,
+    sources: &InstallSources,
 ) -> Result<QueryInstallResult, QueryInstallError> {
     let queries_dir = data_dir.join("queries").join(language);
 
@@ -88,6 +194,8 @@ pub fn install_queries(
         return Err(QueryInstallError::AlreadyExists(queries_dir));
     }
 
+    let (revision, unresolved) = revision_or_latest(revision, sources);
+
     // Create the queries directory
     fs::create_dir_all(&queries_dir)?;
 
@@ -96,10 +204,7 @@ pub fn install_queries(
 
     // Download each query file
     for query_file in QUERY_FILES {
-        let url = format!(
-            "{}/{}/{}",
-            NVIM_TREESITTER_QUERIES_URL, language, query_file
-        );
+        let url = sources.query_file_url(&revision, language, query_file);
 
         match download_file(&url) {
             Ok(content) => {
@@ -138,7 +243,65 @@ pub fn install_queries(
         language: language.to_string(),
         install_path: queries_dir,
         files_downloaded,
-    })
+        revision,
+        unresolved: None,
+        inherited: Vec::new(),
+    }
+    .with_unresolved(unresolved))
+}
+
+/// Install the query files shipped in a grammar's own `queries/` directory.
+///
+/// Uses `queries/<language>/` if present (nvim-treesitter layout), otherwise
+/// the `.scm` files directly in `queries/` (tree-sitter layout). Existing
+/// queries for the language are replaced. Returns `None` if the grammar ships
+/// no query files.
+pub fn install_bundled_queries(
+    language: &str,
+    grammar_dir: &Path,
+    data_dir: &Path,
+    revision: &str,
+) -> std::io::Result<Option<QueryInstallResult>> {
+    let bundled_dir = grammar_dir.join("queries");
+    let bundled_dir = if bundled_dir.join(language).is_dir() {
+        bundled_dir.join(language)
+    } else {
+        bundled_dir
+    };
+    if !bundled_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&bundled_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    files.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "scm"));
+    if files.is_empty() {
+        return Ok(None);
+    }
+    files.sort();
+
+    let queries_dir = data_dir.join("queries").join(language);
+    if queries_dir.exists() {
+        fs::remove_dir_all(&queries_dir)?;
+    }
+    fs::create_dir_all(&queries_dir)?;
+    let mut files_downloaded = Vec::new();
+    for file in files {
+        if let Some(name) = file.file_name() {
+            fs::copy(&file, queries_dir.join(name))?;
+            files_downloaded.push(name.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(Some(QueryInstallResult {
+        language: language.to_string(),
+        install_path: queries_dir,
+        files_downloaded,
+        revision: revision.to_string(),
+        unresolved: None,
+        inherited: Vec::new(),
+    }))
 }
 
 /// Parse the `; inherits: lang1,lang2` directive from query content.
@@ -155,16 +318,46 @@ fn parse_inherits_directive(content: &str) -> Vec<String> {
     }
 }
 
+/// Languages whose queries the installed queries of `language` inherit from
+/// (directly), sorted and deduplicated.
+pub fn installed_query_parents(language: &str, data_dir: &Path) -> Vec<String> {
+    let mut parents: Vec<String> = fs::read_dir(data_dir.join("queries").join(language))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "scm"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| parse_inherits_directive(&content))
+        .collect();
+    parents.sort();
+    parents.dedup();
+    parents
+}
+
 /// Download and install query files for a language, including inherited dependencies.
 ///
 /// This recursively downloads parent queries (e.g., ecma, jsx for TypeScript).
+/// Parents are downloaded from the same `revision` as the language itself.
 pub fn install_queries_with_dependencies(
     language: &str,
     data_dir: &Path,
     force: bool,
+    revision: Option<&str>,
+    sources: &InstallSources,
 ) -> Result<QueryInstallResult, QueryInstallError> {
+    let (revision, unresolved) = revision_or_latest(revision, sources);
+    let _guard = QUERY_INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut installed = std::collections::HashSet::new();
-    install_queries_recursive(language, data_dir, force, &mut installed)
+    install_queries_recursive(
+        language,
+        data_dir,
+        force,
+        &revision,
+        sources,
+        &mut installed,
+    )
+    .map(|result| result.with_unresolved(unresolved))
 }
 
 /// Internal recursive helper for installing queries with dependencies.
@@ -172,6 +365,8 @@ fn install_queries_recursive(
     language: &str,
     data_dir: &Path,
     force: bool,
+    revision: &str,
+    sources: &InstallSources,
     installed: &mut std::collections::HashSet<String>,
 ) -> Result<QueryInstallResult, QueryInstallError> {
     // Skip if already installed in this session
@@ -180,6 +375,9 @@ fn install_queries_recursive(
             language: language.to_string(),
             install_path: data_dir.join("queries").join(language),
             files_downloaded: vec![],
+            revision: revision.to_string(),
+            unresolved: None,
+            inherited: Vec::new(),
         });
     }
 
@@ -195,7 +393,9 @@ fn install_queries_recursive(
             let parents = parse_inherits_directive(&content);
             for parent in parents {
                 // Install parent dependencies (don't force, just ensure they exist)
-                let _ = install_queries_recursive(&parent, data_dir, false, installed);
+                let _ = install_queries_recursive(
+                    &parent, data_dir, false, revision, sources, installed,
+                );
             }
         }
         installed.insert(language.to_string());
@@ -211,10 +411,7 @@ fn install_queries_recursive(
 
     // Download each query file
     for query_file in QUERY_FILES {
-        let url = format!(
-            "{}/{}/{}",
-            NVIM_TREESITTER_QUERIES_URL, language, query_file
-        );
+        let url = sources.query_file_url(revision, language, query_file);
 
         match download_file(&url) {
             Ok(content) => {
@@ -257,10 +454,13 @@ fn install_queries_recursive(
     installed.insert(language.to_string());
 
     // Install parent dependencies
+    let mut inherited = Vec::new();
     for parent in parents_to_install {
         eprintln!("Installing inherited queries: {}", parent);
         // Don't fail if parent already exists
-        match install_queries_recursive(&parent, data_dir, false, installed) {
+        match install_queries_recursive(&parent, data_dir, false, revision, sources, installed) {
+            // Parents already installed in this session come back without files
+            Ok(result) if !result.files_downloaded.is_empty() => inherited.push(result),
             Ok(_) | Err(QueryInstallError::AlreadyExists(_)) => {}
             Err(e) => {
                 eprintln!(
@@ -275,11 +475,19 @@ fn install_queries_recursive(
         language: language.to_string(),
         install_path: queries_dir,
         files_downloaded,
+        revision: revision.to_string(),
+        unresolved: None,
+        inherited,
     })
 }
 
-/// Download a file from a URL.
+/// Download a file from a URL; `file://` URLs are read from disk.
 fn download_file(url: &str) -> Result<String, QueryInstallError> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return fs::read_to_string(path)
+            .map_err(|e| QueryInstallError::HttpError(format!("{} ({})", e, url)));
+    }
+
     let response =
         reqwest::blocking::get(url).map_err(|e| QueryInstallError::HttpError(e.to_string()))?;
 
@@ -307,7 +515,7 @@ mod tests {
         let data_dir = temp_dir.path().to_path_buf();
 
         // This test requires network access - skip in CI if needed
-        let result = install_queries("lua", &data_dir, false);
+        let result = install_queries("lua", &data_dir, false, None, &InstallSources::default());
 
         // The test may fail due to network issues, but structure should be correct
         if let Ok(result) = result {
@@ -326,7 +534,13 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let data_dir = temp_dir.path().to_path_buf();
 
-        let result = install_queries("nonexistent_language_xyz_123", &data_dir, false);
+        let result = install_queries(
+            "nonexistent_language_xyz_123",
+            &data_dir,
+            false,
+            None,
+            &InstallSources::default(),
+        );
 
         assert!(result.is_err());
         if let Err(QueryInstallError::LanguageNotSupported(lang)) = result {
@@ -345,10 +559,151 @@ mod tests {
         fs::write(queries_dir.join("highlights.scm"), "existing content").unwrap();
 
         // Without force, should error
-        let result = install_queries("lua", &data_dir, false);
+        let result = install_queries("lua", &data_dir, false, None, &InstallSources::default());
         assert!(matches!(result, Err(QueryInstallError::AlreadyExists(_))));
 
         // With force, should succeed (requires network)
         // Skip actual download test to avoid flaky CI
     }
+
+    #[test]
+    fn test_parse_ls_remote_output_extracts_commit() {
+        let output = "0123456789abcdef0123456789abcdef01234567\trefs/heads/main\n";
+        assert_eq!(
+            parse_ls_remote_output(output).as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(parse_ls_remote_output(""), None);
+    }
+
+    #[test]
+    fn test_query_file_url_pins_revision() {
+        assert_eq!(
+            InstallSources::default().query_file_url("abc123", "lua", "highlights.scm"),
+            "https://raw.githubusercontent.com/nvim-treesitter/nvim-treesitter/abc123/runtime/queries/lua/highlights.scm"
+        );
+    }
+
+    #[test]
+    fn test_install_queries_from_file_url_runtime_layout() {
+        let temp = TempDir::new().unwrap();
+        let runtime = temp.path().join("runtime").join("queries");
+        fs::create_dir_all(runtime.join("mylang")).unwrap();
+        fs::write(
+            runtime.join("mylang").join("highlights.scm"),
+            "(comment) @comment",
+        )
+        .unwrap();
+        let sources = InstallSources {
+            queries_url: Some(format!("file://{}", runtime.display())),
+            ..InstallSources::default()
+        };
+        let data_dir = temp.path().join("data");
+
+        // No {revision} in the URL: nothing is resolved over the network
+        let result = install_queries("mylang", &data_dir, false, None, &sources).unwrap();
+
+        assert_eq!(result.files_downloaded, vec!["highlights.scm"]);
+        assert_eq!(result.revision, DEFAULT_QUERIES_BRANCH);
+        assert_eq!(
+            fs::read_to_string(data_dir.join("queries/mylang/highlights.scm")).unwrap(),
+            "(comment) @comment"
+        );
+    }
+
+    #[test]
+    fn test_install_queries_with_dependencies_returns_inherited_results() {
+        let temp = TempDir::new().unwrap();
+        let runtime = temp.path().join("runtime").join("queries");
+        for (language, content) in [
+            ("mylang", "; inherits: base\n(comment) @comment"),
+            ("base", "; inherits: core\n(string) @string"),
+            ("core", "(number) @number"),
+        ] {
+            fs::create_dir_all(runtime.join(language)).unwrap();
+            fs::write(runtime.join(language).join("highlights.scm"), content).unwrap();
+        }
+        let sources = InstallSources {
+            queries_url: Some(format!("file://{}", runtime.display())),
+            ..InstallSources::default()
+        };
+        let data_dir = temp.path().join("data");
+
+        let result =
+            install_queries_with_dependencies("mylang", &data_dir, false, None, &sources).unwrap();
+
+        assert_eq!(result.inherited.len(), 1);
+        let base = &result.inherited[0];
+        assert_eq!(base.language, "base");
+        assert_eq!(base.install_path, data_dir.join("queries/base"));
+        assert_eq!(base.revision, result.revision);
+        assert_eq!(base.inherited.len(), 1);
+        assert_eq!(base.inherited[0].language, "core");
+    }
+
+    #[test]
+    fn test_install_queries_records_unresolved_revision() {
+        let temp = TempDir::new().unwrap();
+        let queries = temp.path().join("mirror").join(DEFAULT_QUERIES_BRANCH);
+        fs::create_dir_all(queries.join("mylang")).unwrap();
+        fs::write(queries.join("mylang/highlights.scm"), "(comment) @comment").unwrap();
+        let sources = InstallSources {
+            queries_url: Some(format!(
+                "file://{}/{{revision}}",
+                temp.path().join("mirror").display()
+            )),
+            // The commit of main cannot be looked up in a missing repository
+            url_rewrites: vec![(
+                NVIM_TREESITTER_REPO_URL.to_string(),
+                temp.path().join("missing").display().to_string(),
+            )],
+            ..InstallSources::default()
+        };
+        let data_dir = temp.path().join("data");
+
+        let result = install_queries("mylang", &data_dir, false, None, &sources).unwrap();
+
+        assert_eq!(result.files_downloaded, vec!["highlights.scm"]);
+        assert_eq!(result.revision, UNRESOLVED_QUERIES_REVISION);
+        assert!(result.unresolved.is_some());
+    }
+
+    #[test]
+    fn test_install_bundled_queries_supports_both_layouts() {
+        let temp = tempfile::tempdir().expect("Failed to create temp dir");
+        let data_dir = temp.path().join("data");
+
+        // tree-sitter layout: queries/*.scm
+        let flat = temp.path().join("tree-sitter-flat");
+        fs::create_dir_all(flat.join("queries")).unwrap();
+        fs::write(flat.join("queries/highlights.scm"), "(comment) @comment").unwrap();
+        fs::write(flat.join("queries/README.md"), "not a query").unwrap();
+        let result = install_bundled_queries("flat", &flat, &data_dir, "local")
+            .unwrap()
+            .expect("bundled queries");
+        assert_eq!(result.files_downloaded, vec!["highlights.scm"]);
+        assert_eq!(result.revision, "local");
+        assert!(data_dir.join("queries/flat/highlights.scm").exists());
+        assert!(!data_dir.join("queries/flat/README.md").exists());
+
+        // nvim-treesitter layout: queries/<language>/*.scm
+        let nested = temp.path().join("tree-sitter-nested");
+        fs::create_dir_all(nested.join("queries/nested")).unwrap();
+        fs::write(nested.join("queries/nested/locals.scm"), "").unwrap();
+        let result = install_bundled_queries("nested", &nested, &data_dir, "abc")
+            .unwrap()
+            .expect("bundled queries");
+        assert_eq!(result.files_downloaded, vec!["locals.scm"]);
+        assert!(data_dir.join("queries/nested/locals.scm").exists());
+
+        // No queries shipped
+        let bare = temp.path().join("tree-sitter-bare");
+        fs::create_dir_all(&bare).unwrap();
+        assert!(
+            install_bundled_queries("bare", &bare, &data_dir, "local")
+                .unwrap()
+                .is_none()
+        );
+        assert!(!data_dir.join("queries/bare").exists());
+    }
 }
@@ -0,0 +1,178 @@
+//! Download locations for parser metadata, query files and grammar repositories.
+//!
+//! By default everything comes from GitHub: nvim-treesitter's `parsers.lua`
+//! and query files, and the grammar repositories listed in the metadata. Each
+//! can be redirected for networks that only reach mirrors. Repository URLs are
+//! rewritten only when cloning; the manifest keeps recording the upstream URL,
+//! so lockfiles stay portable between machines using different mirrors.
+//! Auto-install can also take languages from a directory of offline bundles.
+
+use std::path::PathBuf;
+
+use crate::config::InstallSourcesConfig;
+
+use super::queries::NVIM_TREESITTER_REPO_URL;
+
+/// Raw nvim-treesitter query files on GitHub.
+pub const DEFAULT_QUERIES_URL: &str =
+    "https://raw.githubusercontent.com/nvim-treesitter/nvim-treesitter/{revision}/runtime/queries";
+
+/// Placeholder in a queries URL replaced with the query revision.
+const REVISION_PLACEHOLDER: &str = "{revision}";
+
+/// Mirrors to download from instead of the upstream locations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallSources {
+    /// URL of nvim-treesitter's parsers.lua (GitHub if `None`).
+    pub metadata_url: Option<String>,
+    /// Base URL of the query files (see [`DEFAULT_QUERIES_URL`]); `http(s)://`
+    /// or `file://`.
+    pub queries_url: Option<String>,
+    /// Git repository URL prefixes and their replacements.
+    pub url_rewrites: Vec<(String, String)>,
+    /// Directory of bundles auto-install tries before the network.
+    pub bundle_dir: Option<PathBuf>,
+}
+
+impl InstallSources {
+    /// Apply the longest matching rewrite prefix to a git repository URL.
+    ///
+    /// Among equally long prefixes, the one added last wins.
+    pub fn rewrite_url(&self, url: &str) -> String {
+        self.url_rewrites
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or_else(
+                || url.to_string(),
+                |(prefix, replacement)| format!("{}{}", replacement, &url[prefix.len()..]),
+            )
+    }
+
+    /// Repository the query revision is resolved from.
+    pub fn queries_repository(&self) -> String {
+        self.rewrite_url(NVIM_TREESITTER_REPO_URL)
+    }
+
+    /// Whether the queries URL depends on the query revision.
+    ///
+    /// A layout without `{revision}` (e.g. a Helix-style `runtime/queries`
+    /// checkout) serves a single version, so its revision cannot be pinned.
+    pub fn queries_are_versioned(&self) -> bool {
+        self.queries_url
+            .as_deref()
+            .is_none_or(|url| url.contains(REVISION_PLACEHOLDER))
+    }
+
+    /// URL of a query file at the given revision.
+    pub fn query_file_url(&self, revision: &str, language: &str, query_file: &str) -> String {
+        let base = self.queries_url.as_deref().unwrap_or(DEFAULT_QUERIES_URL);
+        format!(
+            "{}/{}/{}",
+            base.trim_end_matches('/')
+                .replace(REVISION_PLACEHOLDER, revision),
+            language,
+            query_file
+        )
+    }
+}
+
+impl From<&InstallSourcesConfig> for InstallSources {
+    fn from(config: &InstallSourcesConfig) -> Self {
+        let mut url_rewrites: Vec<(String, String)> = config
+            .url_rewrites
+            .iter()
+            .flatten()
+            .map(|(prefix, replacement)| (prefix.clone(), replacement.clone()))
+            .collect();
+        url_rewrites.sort();
+        Self {
+            metadata_url: config.metadata_url.clone(),
+            queries_url: config.queries_url.clone(),
+            url_rewrites,
+            bundle_dir: config.bundle_dir.as_ref().map(PathBuf::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrites(pairs: &[(&str, &str)]) -> InstallSources {
+        InstallSources {
+            url_rewrites: pairs
+                .iter()
+                .map(|(prefix, replacement)| (prefix.to_string(), replacement.to_string()))
+                .collect(),
+            ..InstallSources::default()
+        }
+    }
+
+    #[test]
+    fn test_rewrite_url_uses_longest_prefix() {
+        let sources = rewrites(&[
+            ("https://github.com/", "https://git.internal/mirror/"),
+            (
+                "https://github.com/tree-sitter/",
+                "https://git.internal/tree-sitter/",
+            ),
+        ]);
+
+        assert_eq!(
+            sources.rewrite_url("https://github.com/MunifTanjim/tree-sitter-lua"),
+            "https://git.internal/mirror/MunifTanjim/tree-sitter-lua"
+        );
+        assert_eq!(
+            sources.rewrite_url("https://github.com/tree-sitter/tree-sitter-rust"),
+            "https://git.internal/tree-sitter/tree-sitter-rust"
+        );
+        assert_eq!(
+            sources.rewrite_url("https://gitlab.com/x/tree-sitter-y"),
+            "https://gitlab.com/x/tree-sitter-y"
+        );
+        assert_eq!(
+            sources.queries_repository(),
+            "https://git.internal/mirror/nvim-treesitter/nvim-treesitter"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_url_prefers_later_rewrite_for_same_prefix() {
+        let sources = rewrites(&[
+            ("https://github.com/", "https://config.internal/"),
+            ("https://github.com/", "file:///srv/mirror/"),
+        ]);
+
+        assert_eq!(
+            sources.rewrite_url("https://github.com/a/b"),
+            "file:///srv/mirror/a/b"
+        );
+    }
+
+    #[test]
+    fn test_query_file_url_supports_custom_layouts() {
+        let default = InstallSources::default();
+        assert!(default.queries_are_versioned());
+
+        let helix = InstallSources {
+            queries_url: Some("file:///opt/helix/runtime/queries/".to_string()),
+            ..InstallSources::default()
+        };
+        assert!(!helix.queries_are_versioned());
+        assert_eq!(
+            helix.query_file_url("main", "lua", "highlights.scm"),
+            "file:///opt/helix/runtime/queries/lua/highlights.scm"
+        );
+
+        let proxy = InstallSources {
+            queries_url: Some("https://proxy.internal/nvim-treesitter/{revision}/q".to_string()),
+            ..InstallSources::default()
+        };
+        assert!(proxy.queries_are_versioned());
+        assert_eq!(
+            proxy.query_file_url("abc123", "lua", "locals.scm"),
+            "https://proxy.internal/nvim-treesitter/abc123/q/lua/locals.scm"
+        );
+    }
+}
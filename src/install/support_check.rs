@@ -79,6 +79,7 @@ pub async fn should_skip_unsupported_language(
 struct FetchOptionsOwned {
     data_dir: Option<PathBuf>,
     use_cache: bool,
+    metadata_url: Option<String>,
 }
 
 impl From<&FetchOptions<'_>> for FetchOptionsOwned {
@@ -86,6 +87,7 @@ impl From<&FetchOptions<'_>> for FetchOptionsOwned {
         Self {
             data_dir: options.data_dir.map(PathBuf::from),
             use_cache: options.use_cache,
+            metadata_url: options.metadata_url.map(str::to_string),
         }
     }
 }
@@ -95,6 +97,7 @@ impl FetchOptionsOwned {
         FetchOptions {
             data_dir: self.data_dir.as_deref(),
             use_cache: self.use_cache,
+            metadata_url: self.metadata_url.as_deref(),
         }
     }
 }
@@ -196,6 +199,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         let (should_skip, reason) =
@@ -234,6 +238,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         let (should_skip, reason) = should_skip_unsupported_language("lua", Some(&options)).await;
@@ -255,6 +260,7 @@ return {
         let options = FetchOptions {
             data_dir: Some(temp.path()),
             use_cache: true,
+            metadata_url: None,
         };
 
         let (should_skip, reason) = should_skip_unsupported_language("lua", Some(&options)).await;
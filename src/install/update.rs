@@ -0,0 +1,461 @@
+//! Upgrading installed languages to the current nvim-treesitter metadata.
+//!
+//! `language outdated` compares the parser source recorded in the manifest
+//! with nvim-treesitter's `parsers.lua`; `language update` rebuilds outdated
+//! languages in a staging directory inside the data directory and moves the
+//! results into place only once the parser and its queries are both ready.
+//! The files they replace are kept under `<data_dir>/rollback/`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::manifest::{Manifest, ParserRecord};
+use super::metadata::ParserMetadata;
+use super::parser::{
+    InstallOptions, ParserInstallError, ParserInstallResult, ParserSource, install_parser,
+};
+use super::queries::{QueryInstallError, QueryInstallResult, install_queries_with_dependencies};
+use super::record_in_manifest;
+use super::sources::InstallSources;
+
+/// Directory within the data directory holding the files replaced by the
+/// most recent update of each language.
+pub const ROLLBACK_DIR: &str = "rollback";
+
+/// An installed language whose parser source differs from the current metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedLanguage {
+    /// Language name.
+    pub language: String,
+    /// Source recorded at install time.
+    pub installed: ParserRecord,
+    /// Source listed in the current metadata.
+    pub latest: ParserMetadata,
+}
+
+/// Error types for language updates.
+#[derive(Debug)]
+pub enum UpdateError {
+    /// Building the new parser failed.
+    ParserError(ParserInstallError),
+    /// Downloading the new queries failed.
+    QueryError(QueryInstallError),
+    /// File system operation failed.
+    IoError(io::Error),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ParserError(e) => write!(f, "Parser build failed: {}", e),
+            Self::QueryError(e) => write!(f, "Query download failed: {}", e),
+            Self::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<ParserInstallError> for UpdateError {
+    fn from(e: ParserInstallError) -> Self {
+        Self::ParserError(e)
+    }
+}
+
+impl From<QueryInstallError> for UpdateError {
+    fn from(e: QueryInstallError) -> Self {
+        Self::QueryError(e)
+    }
+}
+
+impl From<io::Error> for UpdateError {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+/// Result of updating a language.
+pub struct UpdateResult {
+    /// The installed parser.
+    pub parser: ParserInstallResult,
+    /// The installed queries, if the language had queries installed.
+    pub queries: Option<QueryInstallResult>,
+}
+
+/// Languages in `manifest` whose parser url, revision or location differs
+/// from `metadata`, sorted by name.
+///
+/// Languages without a recorded parser, installed from a custom source, or
+/// missing from the metadata are never reported.
+pub fn outdated_languages(
+    manifest: &Manifest,
+    metadata: &HashMap<String, ParserMetadata>,
+) -> Vec<OutdatedLanguage> {
+    manifest
+        .languages
+        .iter()
+        .filter_map(|(language, record)| {
+            let installed = record.parser.as_ref().filter(|p| !p.kind.is_custom())?;
+            let latest = metadata.get(language)?;
+            (installed.metadata() != *latest).then(|| OutdatedLanguage {
+                language: language.clone(),
+                installed: installed.clone(),
+                latest: latest.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Rebuild `language` from `source` and swap it into `data_dir`.
+///
+/// The parser (and, if the language had queries installed, the latest queries
+/// with their inherited dependencies) are built in a staging directory first;
+/// nothing in `data_dir` changes unless both succeed. The replaced parser
+/// library and query directories are moved to `<data_dir>/rollback/`.
+pub fn update_language(
+    language: &str,
+    data_dir: &Path,
+    source: ParserMetadata,
+    sources: &InstallSources,
+    verbose: bool,
+) -> Result<UpdateResult, UpdateError> {
+    let with_queries = Manifest::load(data_dir)?
+        .languages
+        .get(language)
+        .is_none_or(|record| record.queries.is_some());
+
+    // Stage inside the data directory so the final moves are plain renames
+    fs::create_dir_all(data_dir)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".update-")
+        .tempdir_in(data_dir)?;
+    let options = InstallOptions {
+        data_dir: staging.path().to_path_buf(),
+        force: true,
+        verbose,
+        no_cache: true,
+        source: Some(ParserSource::Metadata(source)),
+        sources: sources.clone(),
+    };
+    let mut parser = install_parser(language, &options)?;
+    let mut queries = if with_queries {
+        Some(install_queries_with_dependencies(
+            language,
+            staging.path(),
+            true,
+            None,
+            sources,
+        )?)
+    } else {
+        None
+    };
+
+    commit_staged_files(staging.path(), data_dir, &mut parser, queries.as_mut())?;
+
+    Ok(UpdateResult { parser, queries })
+}
+
+/// Swap the staged files into `data_dir` and record them in the manifest,
+/// along with the queries of every inherited language staged with them.
+fn commit_staged_files(
+    staging: &Path,
+    data_dir: &Path,
+    parser: &mut ParserInstallResult,
+    queries: Option<&mut QueryInstallResult>,
+) -> io::Result<()> {
+    swap_staged_files(staging, data_dir)?;
+
+    if let Some(file_name) = parser.install_path.file_name() {
+        parser.install_path = data_dir.join("parser").join(file_name);
+    }
+    let queries = queries.map(|queries| {
+        relocate_queries(queries, data_dir);
+        &*queries
+    });
+    record_in_manifest(data_dir, |manifest| {
+        manifest.record_parser(parser)?;
+        if let Some(queries) = queries {
+            manifest.record_queries_with_inherited(queries)?;
+        }
+        Ok(())
+    })
+}
+
+/// Point `queries` and its inherited results at their swapped-in directories.
+fn relocate_queries(queries: &mut QueryInstallResult, data_dir: &Path) {
+    queries.install_path = data_dir.join("queries").join(&queries.language);
+    for parent in &mut queries.inherited {
+        relocate_queries(parent, data_dir);
+    }
+}
+
+/// Move every staged parser library and query directory into `data_dir`,
+/// keeping what they replace under the rollback directory.
+fn swap_staged_files(staging: &Path, data_dir: &Path) -> io::Result<()> {
+    let rollback = data_dir.join(ROLLBACK_DIR);
+    for kind in ["parser", "queries"] {
+        let staged_dir = staging.join(kind);
+        if !staged_dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&staged_dir)? {
+            let name = entry?.file_name();
+            replace_with_rollback(
+                &staged_dir.join(&name),
+                &data_dir.join(kind).join(&name),
+                &rollback.join(kind).join(&name),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Rename `staged` to `target`, first moving an existing `target` to `rollback`.
+///
+/// If the final rename fails, the previous `target` is restored.
+fn replace_with_rollback(staged: &Path, target: &Path, rollback: &Path) -> io::Result<()> {
+    let had_previous = target.exists();
+    if had_previous {
+        remove_path(rollback)?;
+        create_parent_dir(rollback)?;
+        fs::rename(target, rollback)?;
+    }
+    create_parent_dir(target)?;
+    if let Err(e) = fs::rename(staged, target) {
+        if had_previous {
+            let _ = fs::rename(rollback, target);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+/// Remove a file or directory; a missing path is not an error.
+fn remove_path(path: &Path) -> io::Result<()> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Path of the previous version of `language`'s parser library, if an update
+/// kept one.
+pub fn rollback_parser_path(language: &str, data_dir: &Path) -> Option<PathBuf> {
+    super::parser_file_exists(language, &data_dir.join(ROLLBACK_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::manifest::LanguageRecord;
+    use crate::install::parser::SourceKind;
+    use tempfile::TempDir;
+
+    fn record(url: &str, revision: &str) -> LanguageRecord {
+        LanguageRecord {
+            parser: Some(ParserRecord {
+                url: url.to_string(),
+                revision: revision.to_string(),
+                location: None,
+                sha256: "00".repeat(32),
+                kind: SourceKind::Metadata,
+            }),
+            queries: None,
+            installed_at: 1_700_000_000,
+            bundle: None,
+        }
+    }
+
+    fn metadata(url: &str, revision: &str) -> ParserMetadata {
+        ParserMetadata {
+            url: url.to_string(),
+            revision: revision.to_string(),
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_outdated_languages_reports_changed_sources_only() {
+        let mut manifest = Manifest::default();
+        manifest
+            .languages
+            .insert("lua".to_string(), record("https://example.com/lua", "v1"));
+        manifest
+            .languages
+            .insert("rust".to_string(), record("https://example.com/rust", "v1"));
+        manifest.languages.insert(
+            "custom".to_string(),
+            record("https://example.com/custom", "v1"),
+        );
+        manifest
+            .languages
+            .insert("queries_only".to_string(), LanguageRecord::default());
+        let mut private = record("https://example.com/private", "v1");
+        private.parser.as_mut().unwrap().kind = SourceKind::Git;
+        manifest.languages.insert("private".to_string(), private);
+
+        let metadata = HashMap::from([
+            ("lua".to_string(), metadata("https://example.com/lua", "v2")),
+            (
+                "rust".to_string(),
+                metadata("https://example.com/rust", "v1"),
+            ),
+            (
+                "queries_only".to_string(),
+                metadata("https://example.com/q", "v1"),
+            ),
+            // Same name upstream, but installed from a custom source
+            (
+                "private".to_string(),
+                metadata("https://example.com/upstream", "v2"),
+            ),
+        ]);
+
+        let outdated = outdated_languages(&manifest, &metadata);
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].language, "lua");
+        assert_eq!(outdated[0].installed.revision, "v1");
+        assert_eq!(outdated[0].latest.revision, "v2");
+    }
+
+    #[test]
+    fn test_outdated_languages_detects_moved_repository() {
+        let mut manifest = Manifest::default();
+        manifest
+            .languages
+            .insert("lua".to_string(), record("https://example.com/old", "v1"));
+        let metadata =
+            HashMap::from([("lua".to_string(), metadata("https://example.com/new", "v1"))]);
+
+        assert_eq!(outdated_languages(&manifest, &metadata).len(), 1);
+    }
+
+    #[test]
+    fn test_swap_staged_files_keeps_previous_version_for_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let staging = temp_dir.path().join("staging");
+        for (root, content) in [(&data_dir, "old"), (&staging, "new")] {
+            fs::create_dir_all(root.join("parser")).unwrap();
+            fs::write(root.join("parser").join("lua.so"), content).unwrap();
+            fs::create_dir_all(root.join("queries").join("lua")).unwrap();
+            fs::write(root.join("queries/lua/highlights.scm"), content).unwrap();
+        }
+        // A dependency installed only in staging
+        fs::create_dir_all(staging.join("queries").join("ecma")).unwrap();
+        fs::write(staging.join("queries/ecma/highlights.scm"), "new").unwrap();
+
+        swap_staged_files(&staging, &data_dir).unwrap();
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(data_dir.join("parser/lua.so")), "new");
+        assert_eq!(read(data_dir.join("queries/lua/highlights.scm")), "new");
+        assert_eq!(read(data_dir.join("queries/ecma/highlights.scm")), "new");
+        assert_eq!(read(data_dir.join("rollback/parser/lua.so")), "old");
+        assert_eq!(
+            read(data_dir.join("rollback/queries/lua/highlights.scm")),
+            "old"
+        );
+        assert!(!data_dir.join("rollback/queries/ecma").exists());
+    }
+
+    #[test]
+    fn test_commit_staged_files_records_inherited_queries() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let runtime = temp_dir.path().join("runtime");
+        for (language, content) in [
+            ("typescript", "; inherits: ecma\n(type_identifier) @type"),
+            ("ecma", "(comment) @comment"),
+        ] {
+            fs::create_dir_all(runtime.join(language)).unwrap();
+            fs::write(runtime.join(language).join("highlights.scm"), content).unwrap();
+        }
+        // Previously installed versions, replaced by the update
+        for language in ["typescript", "ecma"] {
+            fs::create_dir_all(data_dir.join("queries").join(language)).unwrap();
+            fs::write(
+                data_dir
+                    .join("queries")
+                    .join(language)
+                    .join("highlights.scm"),
+                "old",
+            )
+            .unwrap();
+        }
+        let sources = InstallSources {
+            queries_url: Some(format!("file://{}", runtime.display())),
+            ..InstallSources::default()
+        };
+
+        let staging = TempDir::new_in(&data_dir).unwrap();
+        let library = staging.path().join("parser").join("typescript.so");
+        fs::create_dir_all(library.parent().unwrap()).unwrap();
+        fs::write(&library, "parser").unwrap();
+        let mut parser = ParserInstallResult {
+            language: "typescript".to_string(),
+            install_path: library,
+            url: "https://example.com/typescript".to_string(),
+            revision: "v2".to_string(),
+            location: None,
+            kind: SourceKind::Metadata,
+            bundled_queries: None,
+        };
+        let mut queries =
+            install_queries_with_dependencies("typescript", staging.path(), true, None, &sources)
+                .unwrap();
+
+        commit_staged_files(staging.path(), &data_dir, &mut parser, Some(&mut queries)).unwrap();
+
+        assert_eq!(
+            queries.inherited[0].install_path,
+            data_dir.join("queries/ecma")
+        );
+        let manifest = Manifest::load(&data_dir).unwrap();
+        for language in ["typescript", "ecma"] {
+            let recorded = &manifest.languages[language].queries.as_ref().unwrap().files;
+            let path = data_dir
+                .join("queries")
+                .join(language)
+                .join("highlights.scm");
+            assert_eq!(
+                recorded["highlights.scm"],
+                crate::install::manifest::sha256_file(&path).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_replace_with_rollback_overwrites_older_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("staged"), "v3").unwrap();
+        fs::write(dir.join("target"), "v2").unwrap();
+        fs::write(dir.join("rollback"), "v1").unwrap();
+
+        replace_with_rollback(
+            &dir.join("staged"),
+            &dir.join("target"),
+            &dir.join("rollback"),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("target")).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(dir.join("rollback")).unwrap(), "v2");
+        assert!(!dir.join("staged").exists());
+    }
+}
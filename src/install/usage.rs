@@ -0,0 +1,106 @@
+//! Record of when each installed language was last loaded.
+//!
+//! The server records a timestamp per language in `<data_dir>/usage.toml`
+//! whenever it loads a parser from a data directory, so `language prune` can
+//! tell unused languages apart. It is kept out of `manifest.toml`, whose
+//! changes tell running servers that a language was reinstalled.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use super::integrity::parser_data_dir;
+use super::manifest::unix_now;
+
+/// File name of the usage record within the data directory.
+pub const USAGE_FILE: &str = "usage.toml";
+
+/// Serializes read-modify-write cycles on the usage record within this
+/// process (languages are loaded from several tasks).
+static USAGE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Last time each language of a data directory was loaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Unix timestamp of the last load, by language.
+    #[serde(default)]
+    pub last_loaded: BTreeMap<String, u64>,
+}
+
+impl Usage {
+    /// Path of the usage record in `data_dir`.
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(USAGE_FILE)
+    }
+
+    /// Load the usage record of `data_dir`; a missing record is empty.
+    pub fn load(data_dir: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(Self::path(data_dir)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the usage record to `data_dir`, replacing the previous one atomically.
+    pub fn save(&self, data_dir: &Path) -> io::Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut temp = tempfile::NamedTempFile::new_in(data_dir)?;
+        io::Write::write_all(&mut temp, content.as_bytes())?;
+        temp.persist(Self::path(data_dir)).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Load, modify and save the usage record of `data_dir`.
+    pub fn update(data_dir: &Path, modify: impl FnOnce(&mut Self)) -> io::Result<()> {
+        let _guard = USAGE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut usage = Self::load(data_dir)?;
+        modify(&mut usage);
+        usage.save(data_dir)
+    }
+}
+
+/// Record that `library` was just loaded as the parser of `language`.
+///
+/// Libraries outside a data directory (e.g., configured with
+/// `languages.<name>.parser`) are not recorded.
+pub fn record_load(language: &str, library: &Path) -> io::Result<()> {
+    let Some(data_dir) = parser_data_dir(language, library) else {
+        return Ok(());
+    };
+    Usage::update(data_dir, |usage| {
+        usage.last_loaded.insert(language.to_string(), unix_now());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_load_only_records_data_dir_parsers() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        fs::create_dir_all(data_dir.join("parser")).unwrap();
+
+        record_load("lua", &data_dir.join("parser/lua.so")).unwrap();
+        record_load("lua", &data_dir.join("elsewhere/lua.so")).unwrap();
+        record_load("luau", &data_dir.join("parser/lua.so")).unwrap();
+
+        let usage = Usage::load(data_dir).unwrap();
+        assert_eq!(
+            usage.last_loaded.keys().collect::<Vec<_>>(),
+            vec!["lua"],
+            "{:?}",
+            usage
+        );
+        assert!(usage.last_loaded["lua"] > 0);
+    }
+}
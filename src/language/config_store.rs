@@ -8,6 +8,7 @@ pub struct ConfigStore {
     language_configs: RwLock<HashMap<String, LanguageConfig>>,
     capture_mappings: RwLock<CaptureMappings>,
     search_paths: RwLock<Option<Vec<String>>>,
+    verify_parsers: RwLock<bool>,
 }
 
 impl ConfigStore {
@@ -16,6 +17,7 @@ impl ConfigStore {
             language_configs: RwLock::new(HashMap::new()),
             capture_mappings: RwLock::new(CaptureMappings::default()),
             search_paths: RwLock::new(None),
+            verify_parsers: RwLock::new(false),
         }
     }
 
@@ -34,6 +36,13 @@ impl ConfigStore {
         self.set_language_configs(settings.languages.clone());
         self.set_capture_mappings(settings.capture_mappings.clone());
         self.set_search_paths(settings.search_paths.clone());
+        self.set_verify_parsers(
+            settings
+                .install
+                .as_ref()
+                .and_then(|install| install.verify)
+                .unwrap_or(false),
+        );
     }
 
     pub fn get_language_config(&self, lang_name: &str) -> Option<LanguageConfig> {
@@ -109,6 +118,28 @@ impl ConfigStore {
         }
     }
 
+    // ========== Parser Verification ==========
+    pub fn set_verify_parsers(&self, verify: bool) {
+        match self.verify_parsers.write() {
+            Ok(mut guard) => *guard = verify,
+            Err(poisoned) => {
+                warn!(target: "kakehashi::lock_recovery", "Recovered from poisoned lock in config_store::set_verify_parsers");
+                *poisoned.into_inner() = verify;
+            }
+        }
+    }
+
+    /// Whether parsers that changed since installation must not be loaded.
+    pub fn get_verify_parsers(&self) -> bool {
+        match self.verify_parsers.read() {
+            Ok(guard) => *guard,
+            Err(poisoned) => {
+                warn!(target: "kakehashi::lock_recovery", "Recovered from poisoned lock in config_store::get_verify_parsers");
+                *poisoned.into_inner()
+            }
+        }
+    }
+
     /// Clear all configurations
     pub fn clear(&self) {
         match self.language_configs.write() {
@@ -132,6 +163,7 @@ impl ConfigStore {
                 *poisoned.into_inner() = None;
             }
         }
+        self.set_verify_parsers(false);
     }
 }
 
@@ -214,6 +246,8 @@ mod tests {
             capture_mappings: CaptureMappings::default(),
             auto_install: None,
             language_servers: None,
+            bridge_merge: None,
+            install: None,
         };
 
         store.update_from_settings(&settings);
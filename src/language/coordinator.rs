@@ -1,21 +1,28 @@
 use super::config_store::ConfigStore;
 use super::events::{LanguageEvent, LanguageLoadResult, LanguageLoadSummary, LanguageLogLevel};
 use super::filetypes::FiletypeResolver;
-use super::loader::ParserLoader;
+use super::injection::collect_all_injections;
+use super::loader::{ParserLoadError, ParserLoader};
 use super::parser_pool::{DocumentParserPool, ParserFactory};
 use super::query_loader::{ParseFailure, QueryLoader};
 use super::query_store::QueryStore;
 use super::registry::LanguageRegistry;
 use crate::config::settings::{LanguageConfig, QueryKind, infer_query_kind};
 use crate::config::{CaptureMappings, TreeSitterSettings, WorkspaceSettings};
+use crate::install::integrity::{check_parser_library, parser_data_dir};
+use crate::install::usage::record_load;
 use log::debug;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
 use std::sync::{Arc, RwLock};
-use tree_sitter::Language;
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
 
 /// Maximum length (in characters) for pattern previews in log messages.
 const MAX_PREVIEW_LEN: usize = 60;
 
+/// Maximum nesting depth searched by `injected_languages`.
+const MAX_INJECTION_DEPTH: usize = 10;
+
 /// Context for loading a query, including metadata for log messages.
 struct QueryLoadContext<'a> {
     language_id: &'a str,
@@ -68,6 +75,23 @@ impl LanguageCoordinator {
         }
     }
 
+    /// Unload a language so the next `ensure_language_loaded` reads its parser
+    /// and queries from disk again (e.g., after `kakehashi language update`).
+    ///
+    /// Visibility: Public - called by LSP layer when an install update is
+    /// detected. Returns false if the language was not loaded.
+    pub fn unload_language(&self, language_id: &str) -> bool {
+        if self.language_registry.remove(language_id).is_none() {
+            return false;
+        }
+        self.query_store.clear_language(language_id);
+        self.parser_loader
+            .write()
+            .unwrap()
+            .retire_language(language_id);
+        true
+    }
+
     /// Initialize from workspace-level settings and return coordination events.
     ///
     /// Visibility: Public - called by LSP layer during initialization and
@@ -191,6 +215,11 @@ impl LanguageCoordinator {
             ));
         };
 
+        let integrity_event = match self.check_parser_integrity(language_id, &lib_path) {
+            Ok(event) => event,
+            Err(event) => return LanguageLoadResult::failure_with(event),
+        };
+
         let language = {
             let result = self
                 .parser_loader
@@ -199,6 +228,9 @@ impl LanguageCoordinator {
                 .load_language(&lib_path, language_id);
             match result {
                 Ok(lang) => lang,
+                Err(ParserLoadError::IncompatibleAbi(version)) => {
+                    return incompatible_parser(language_id, &lib_path, version);
+                }
                 Err(err) => {
                     return LanguageLoadResult::failure_with(LanguageEvent::log(
                         LanguageLogLevel::Error,
@@ -210,8 +242,9 @@ impl LanguageCoordinator {
 
         self.language_registry
             .register_unchecked(language_id.to_string(), language.clone());
+        record_use(language_id, &lib_path);
 
-        let mut events = Vec::new();
+        let mut events: Vec<LanguageEvent> = integrity_event.into_iter().collect();
 
         // Use fault-tolerant loading for all query types
         // This handles languages like TypeScript that inherit from ecma,
@@ -675,6 +708,76 @@ impl LanguageCoordinator {
         None
     }
 
+    /// Detect the language of a document, whether or not its parser is available.
+    ///
+    /// Follows the same chain as `detect_language`, but falls back to the last
+    /// detected candidate so callers can install a missing parser.
+    ///
+    /// Visibility: Public - called by the CLI (`language install --for`).
+    pub fn detect_document_language(&self, path: &str, content: &str) -> Option<String> {
+        let (result, _, candidate) = self.detect_language_with_method(path, content, None, None);
+        result.or(candidate)
+    }
+
+    /// Languages injected into `text`, parsed as `language`.
+    ///
+    /// Injected regions whose parser can be loaded are searched for further
+    /// injections (e.g., markdown -> markdown_inline -> html), so calling this
+    /// again after installing the injected parsers finds the next level.
+    /// Injection identifiers are normalized the way auto-install does it
+    /// (e.g., "py" -> "python"). Returns an empty set if the host language or
+    /// its injection query cannot be loaded.
+    ///
+    /// Visibility: Public - called by the CLI (`language install --for`).
+    pub fn injected_languages(&self, language: &str, text: &str) -> BTreeSet<String> {
+        let mut languages = BTreeSet::new();
+        self.collect_injected_languages(language, text, 0, &mut languages);
+        languages
+    }
+
+    fn collect_injected_languages(
+        &self,
+        language: &str,
+        text: &str,
+        depth: usize,
+        languages: &mut BTreeSet<String>,
+    ) {
+        if depth >= MAX_INJECTION_DEPTH || !self.ensure_language_loaded(language).success {
+            return;
+        }
+        let Some(injection_query) = self.get_injection_query(language) else {
+            return;
+        };
+        let Some(tree) = ParserFactory::new(self.language_registry.clone())
+            .create_parser(language)
+            .and_then(|mut parser| parser.parse(text, None))
+        else {
+            return;
+        };
+
+        let regions: Vec<(String, std::ops::Range<usize>)> =
+            collect_all_injections(&tree.root_node(), text, Some(&injection_query))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|injection| {
+                    let range = injection.content_node.byte_range();
+                    let injected = if self.has_parser_available(&injection.language) {
+                        injection.language
+                    } else {
+                        super::heuristic::detect_from_token(&injection.language)
+                            .unwrap_or(injection.language)
+                    };
+                    (injected, range)
+                })
+                .collect();
+        for (injected, range) in regions {
+            languages.insert(injected.clone());
+            if let Some(content) = text.get(range) {
+                self.collect_injected_languages(&injected, content, depth + 1, languages);
+            }
+        }
+    }
+
     /// Create a document parser pool.
     ///
     /// Visibility: Public - called by LSP layer (lsp_impl) and analysis modules
@@ -743,6 +846,11 @@ impl LanguageCoordinator {
             ));
         };
 
+        let integrity_event = match self.check_parser_integrity(lang_name, &lib_path) {
+            Ok(event) => event,
+            Err(event) => return LanguageLoadResult::failure_with(event),
+        };
+
         let language = {
             let result = self
                 .parser_loader
@@ -751,6 +859,9 @@ impl LanguageCoordinator {
                 .load_language(&lib_path, lang_name);
             match result {
                 Ok(lang) => lang,
+                Err(ParserLoadError::IncompatibleAbi(version)) => {
+                    return incompatible_parser(lang_name, &lib_path, version);
+                }
                 Err(err) => {
                     return LanguageLoadResult::failure_with(LanguageEvent::log(
                         LanguageLogLevel::Error,
@@ -762,8 +873,10 @@ impl LanguageCoordinator {
 
         self.language_registry
             .register_unchecked(lang_name.to_string(), language.clone());
+        record_use(lang_name, &lib_path);
 
-        let mut events = self.load_queries_for_language(lang_name, config, search_paths, &language);
+        let mut events: Vec<LanguageEvent> = integrity_event.into_iter().collect();
+        events.extend(self.load_queries_for_language(lang_name, config, search_paths, &language));
         events.push(LanguageEvent::log(
             LanguageLogLevel::Info,
             format!("Language {lang_name} loaded."),
@@ -771,6 +884,41 @@ impl LanguageCoordinator {
         LanguageLoadResult::success_with(events)
     }
 
+    /// Check a parser library against its install record before loading it.
+    ///
+    /// A library that changed since installation yields a warning to report.
+    /// When `install.verify` is enabled the load is refused instead, as it is
+    /// for a library in a data directory that has no record or cannot be
+    /// checked; explicitly configured libraries elsewhere only warn.
+    fn check_parser_integrity(
+        &self,
+        lang_name: &str,
+        lib_path: &str,
+    ) -> Result<Option<LanguageEvent>, LanguageEvent> {
+        let verify = self.config_store.get_verify_parsers();
+        let refuse = |reason: String| {
+            Err(LanguageEvent::log(
+                LanguageLogLevel::Error,
+                format!("Refusing to load language {lang_name}: {reason}"),
+            ))
+        };
+        match check_parser_library(lang_name, Path::new(lib_path)) {
+            Ok(Some(issue)) if verify && issue.refuses_parser() => refuse(issue.to_string()),
+            Ok(Some(issue)) if issue.is_tampered() => Ok(Some(LanguageEvent::log(
+                LanguageLogLevel::Warning,
+                format!("Parser for {lang_name} failed verification: {issue}"),
+            ))),
+            Ok(_) => Ok(None),
+            Err(err) if verify && parser_data_dir(lang_name, Path::new(lib_path)).is_some() => {
+                refuse(format!("could not verify {lib_path}: {err}"))
+            }
+            Err(err) => Ok(Some(LanguageEvent::log(
+                LanguageLogLevel::Warning,
+                format!("Could not verify parser for {lang_name}: {err}"),
+            ))),
+        }
+    }
+
     fn load_queries_for_language(
         &self,
         lang_name: &str,
@@ -955,6 +1103,34 @@ impl LanguageCoordinator {
     }
 }
 
+/// Failure to load a parser built for an unsupported ABI version, asking
+/// for the parser to be rebuilt.
+fn incompatible_parser(lang_name: &str, lib_path: &str, version: usize) -> LanguageLoadResult {
+    LanguageLoadResult {
+        success: false,
+        events: vec![
+            LanguageEvent::log(
+                LanguageLogLevel::Error,
+                format!(
+                    "Parser for {lang_name} at {lib_path} was built for tree-sitter ABI version \
+                     {version}, but this version of kakehashi supports ABI versions \
+                     {MIN_COMPATIBLE_LANGUAGE_VERSION} to {LANGUAGE_VERSION}. Rebuild it with \
+                     'kakehashi language install {lang_name} --force' (done automatically when \
+                     autoInstall is enabled)."
+                ),
+            ),
+            LanguageEvent::parser_rebuild_needed(lang_name),
+        ],
+    }
+}
+
+/// Record the load of a parser for `language prune`; failing to is harmless.
+fn record_use(lang_name: &str, lib_path: &str) {
+    if let Err(err) = record_load(lang_name, Path::new(lib_path)) {
+        debug!("Could not record use of language {lang_name}: {err}");
+    }
+}
+
 /// Truncate a pattern string for display in log messages.
 ///
 /// Collapses whitespace and truncates to max_len characters, adding "..." if truncated.
@@ -1102,6 +1278,152 @@ mod tests {
         assert!(load_result.success);
     }
 
+    #[test]
+    fn test_document_languages_include_missing_host_and_injections() {
+        let coordinator = LanguageCoordinator::new();
+
+        // No parser is available, but the candidate is still reported
+        assert_eq!(
+            coordinator.detect_document_language("/tmp/script.py", ""),
+            Some("python".to_string())
+        );
+        assert!(
+            coordinator
+                .injected_languages("python", "print('hello')")
+                .is_empty()
+        );
+
+        coordinator.register_language_for_test("rust", tree_sitter_rust::LANGUAGE.into());
+        let query = tree_sitter::Query::new(
+            &tree_sitter_rust::LANGUAGE.into(),
+            r#"
+            ((line_comment) @injection.content (#set! injection.language "py"))
+            ((string_content) @injection.content (#set! injection.language "rust"))
+            "#,
+        )
+        .unwrap();
+        coordinator.register_injection_query_for_test("rust", query);
+
+        let text = "// print('hello')\nfn main() { let s = \"x\"; }\n";
+        assert_eq!(
+            coordinator.injected_languages("rust", text),
+            BTreeSet::from(["python".to_string(), "rust".to_string()])
+        );
+
+        // Injected regions are searched for injections of their own
+        let nested = "fn main() { let s = \"// print('hello')\"; }\n";
+        assert_eq!(
+            coordinator.injected_languages("rust", nested),
+            BTreeSet::from(["python".to_string(), "rust".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_incompatible_abi_asks_for_rebuild() {
+        use crate::config::WorkspaceSettings;
+        use crate::language::loader::test_helpers::build_incompatible_abi_library;
+
+        // A library whose language claims an ABI version from the future
+        let temp = tempfile::tempdir().unwrap();
+        if build_incompatible_abi_library(temp.path()).is_none() {
+            return;
+        }
+
+        let coordinator = LanguageCoordinator::new();
+        coordinator.load_settings(WorkspaceSettings {
+            search_paths: vec![temp.path().to_string_lossy().into_owned()],
+            ..Default::default()
+        });
+        let result = coordinator.ensure_language_loaded("fakeabi");
+
+        assert!(!result.success);
+        assert!(result.needs_rebuild());
+        assert!(
+            matches!(
+                &result.events[0],
+                LanguageEvent::Log { level: LanguageLogLevel::Error, message }
+                    if message.contains("built for tree-sitter ABI version 999")
+            ),
+            "{:?}",
+            result.events
+        );
+        assert!(!coordinator.has_parser_available("fakeabi"));
+    }
+
+    #[test]
+    fn test_modified_parser_is_refused_only_in_verify_mode() {
+        use crate::config::WorkspaceSettings;
+        use crate::config::settings::InstallSourcesConfig;
+        use crate::install::manifest::{LanguageRecord, Manifest, ParserRecord};
+        use crate::install::parser::shared_lib_extension;
+
+        let temp = tempfile::tempdir().unwrap();
+        let library = temp
+            .path()
+            .join("parser")
+            .join(format!("fakelang.{}", shared_lib_extension()));
+        std::fs::create_dir_all(library.parent().unwrap()).unwrap();
+        std::fs::write(&library, "tampered").unwrap();
+        Manifest::update(temp.path(), |manifest| {
+            manifest.languages.insert(
+                "fakelang".to_string(),
+                LanguageRecord {
+                    parser: Some(ParserRecord {
+                        url: "https://example.com/fakelang".to_string(),
+                        revision: "v1".to_string(),
+                        location: None,
+                        sha256: "00".repeat(32),
+                        kind: Default::default(),
+                    }),
+                    ..LanguageRecord::default()
+                },
+            );
+        })
+        .unwrap();
+
+        let load = |verify: bool| {
+            let coordinator = LanguageCoordinator::new();
+            coordinator.load_settings(WorkspaceSettings {
+                search_paths: vec![temp.path().to_string_lossy().into_owned()],
+                install: Some(InstallSourcesConfig {
+                    verify: Some(verify),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+            coordinator.ensure_language_loaded("fakelang")
+        };
+        let messages = |result: &LanguageLoadResult| -> Vec<String> {
+            result
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    LanguageEvent::Log { message, .. } => Some(message.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let refused = load(true);
+        assert!(!refused.success);
+        assert!(
+            messages(&refused)[0].starts_with("Refusing to load language fakelang"),
+            "{:?}",
+            messages(&refused)
+        );
+
+        // Without verify mode the library is loaded anyway (and fails here,
+        // since it is not a real library)
+        let attempted = load(false);
+        assert!(
+            messages(&attempted)
+                .iter()
+                .all(|message| !message.starts_with("Refusing")),
+            "{:?}",
+            messages(&attempted)
+        );
+    }
+
     #[test]
     fn test_load_settings_does_not_make_parser_available() {
         // Documents that load_settings alone does NOT make parsers available.
@@ -1173,6 +1495,22 @@ mod tests {
         // via register_language_for_test which is only available there.
     }
 
+    #[test]
+    fn test_unload_language_forgets_parser_and_queries() {
+        let coordinator = LanguageCoordinator::new();
+        let language: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
+        coordinator.register_language_for_test("rust", language.clone());
+        coordinator.register_injection_query_for_test(
+            "rust",
+            tree_sitter::Query::new(&language, "(line_comment) @injection.content").unwrap(),
+        );
+
+        assert!(coordinator.unload_language("rust"));
+        assert!(!coordinator.has_parser_available("rust"));
+        assert!(coordinator.get_injection_query("rust").is_none());
+        assert!(!coordinator.unload_language("rust"));
+    }
+
     #[test]
     fn test_heuristic_used_when_language_id_plaintext() {
         let coordinator = LanguageCoordinator::new();
@@ -8,6 +8,11 @@ pub enum LanguageEvent {
     SemanticTokensRefresh {
         language_id: String,
     },
+    /// The installed parser has an incompatible ABI version and must be
+    /// rebuilt before the language can be used.
+    ParserRebuildNeeded {
+        language_id: String,
+    },
 }
 
 impl LanguageEvent {
@@ -23,6 +28,12 @@ impl LanguageEvent {
             language_id: language_id.into(),
         }
     }
+
+    pub fn parser_rebuild_needed(language_id: impl Into<String>) -> Self {
+        Self::ParserRebuildNeeded {
+            language_id: language_id.into(),
+        }
+    }
 }
 
 /// Log levels abstracted from LSP message types
@@ -62,6 +73,13 @@ impl LanguageLoadResult {
     pub fn log(&mut self, level: LanguageLogLevel, message: impl Into<String>) {
         self.push_event(LanguageEvent::log(level, message));
     }
+
+    /// Whether the language failed to load because its parser must be rebuilt.
+    pub fn needs_rebuild(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, LanguageEvent::ParserRebuildNeeded { .. }))
+    }
 }
 
 /// Summary of applying configuration across multiple languages
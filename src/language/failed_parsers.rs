@@ -89,7 +89,10 @@ impl FailedParserRegistry {
     }
 
     /// Load the list of failed parsers from disk.
-    fn load_failed_parsers(&self) -> io::Result<()> {
+    ///
+    /// Unlike [`init`](Self::init), this does not perform crash recovery, so it
+    /// can be used to inspect the state of another process.
+    pub fn load_failed_parsers(&self) -> io::Result<()> {
         let path = self.failed_parsers_path();
         if path.exists() {
             let content = fs::read_to_string(&path)?;
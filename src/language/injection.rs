@@ -521,6 +521,28 @@ pub struct ResolvedInjection {
     pub virtual_content: String,
 }
 
+impl ResolvedInjection {
+    /// Treat the whole host document as a region of its own language.
+    ///
+    /// Used for host-document bridging: the region starts at line 0 and its
+    /// virtual content is the host text, so no coordinate translation applies.
+    pub(crate) fn whole_document(language: &str, region_id: &str, text: &str) -> Self {
+        let region = CacheableInjectionRegion {
+            language: language.to_string(),
+            byte_range: 0..text.len(),
+            line_range: 0..text.lines().count() as u32,
+            region_id: region_id.to_string(),
+            content_hash: CacheableInjectionRegion::hash_content(text),
+        };
+        Self {
+            region,
+            region_id: region_id.to_string(),
+            injection_language: language.to_string(),
+            virtual_content: text.to_string(),
+        }
+    }
+}
+
 /// Central service for resolving injection regions at LSP positions
 pub struct InjectionResolver;
 
@@ -696,6 +718,19 @@ mod tests {
     use rstest::rstest;
     use tree_sitter::Parser;
 
+    #[test]
+    fn test_whole_document_region_spans_host_text() {
+        let text = "# Title\n\nSome text\n";
+
+        let resolved = ResolvedInjection::whole_document("markdown", "host", text);
+
+        assert_eq!(resolved.injection_language, "markdown");
+        assert_eq!(resolved.region.region_id, "host");
+        assert_eq!(resolved.region.line_range, 0..3);
+        assert_eq!(resolved.region.byte_range, 0..text.len());
+        assert_eq!(resolved.virtual_content, text);
+    }
+
     #[test]
     fn test_parse_offset_directive_for_pattern() {
         // Test that the pattern-aware function correctly returns
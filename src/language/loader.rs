@@ -3,14 +3,22 @@ use path_clean::PathClean;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
-use std::path::PathBuf;
-use tree_sitter::Language;
+use std::path::{Path, PathBuf};
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+/// Directory, next to a reloaded library, holding the private copies it is
+/// loaded from.
+const RELOAD_DIR: &str = ".reload";
 
 /// A wrapper around dynamic library loading for Tree-sitter language parsers
 #[derive(Default)]
 pub struct ParserLoader {
     /// Cache of loaded libraries to prevent reloading
     loaded_libraries: HashMap<String, Library>,
+    /// Libraries replaced via `retire_language`, keyed by language name.
+    /// They are never unloaded: trees, queries and parsers created from them
+    /// may still be alive.
+    retired_libraries: HashMap<String, Vec<Library>>,
 }
 
 #[derive(Debug)]
@@ -18,6 +26,9 @@ pub enum ParserLoadError {
     LibraryLoadError(libloading::Error),
     SymbolNotFound(String),
     CacheError(String),
+    /// The parser was generated for an ABI version this build of tree-sitter
+    /// cannot use.
+    IncompatibleAbi(usize),
 }
 
 impl fmt::Display for ParserLoadError {
@@ -26,6 +37,11 @@ impl fmt::Display for ParserLoadError {
             ParserLoadError::LibraryLoadError(e) => write!(f, "Failed to load library: {e}"),
             ParserLoadError::SymbolNotFound(func) => write!(f, "Symbol not found: {func}"),
             ParserLoadError::CacheError(msg) => write!(f, "Cache error: {msg}"),
+            ParserLoadError::IncompatibleAbi(version) => write!(
+                f,
+                "Parser uses tree-sitter ABI version {version}, but this version of kakehashi \
+                 supports ABI versions {MIN_COMPATIBLE_LANGUAGE_VERSION} to {LANGUAGE_VERSION}"
+            ),
         }
     }
 }
@@ -51,7 +67,9 @@ impl ParserLoader {
     /// * `lang_name` - Name of the language (e.g., "rust", "javascript")
     ///
     /// # Returns
-    /// The loaded Language or an error
+    /// The loaded Language or an error. A library with an incompatible ABI
+    /// version is retired, so loading the language again after a rebuild
+    /// reads the new file.
     pub fn load_language(
         &mut self,
         path: &str,
@@ -65,7 +83,11 @@ impl ParserLoader {
 
         // Load the library if not already loaded
         if !self.loaded_libraries.contains_key(lang_name) {
-            let library = unsafe { Library::new(&normalized_path)? };
+            let library = if self.retired_libraries.contains_key(lang_name) {
+                Self::open_fresh_copy(&normalized_path)?
+            } else {
+                unsafe { Library::new(&normalized_path)? }
+            };
             self.loaded_libraries.insert(lang_name.to_string(), library);
         }
 
@@ -84,12 +106,101 @@ impl ParserLoader {
         // Call the function to get the Language
         let language = unsafe { language_fn() };
 
+        // Parser::set_language would reject it later, leaving the language
+        // silently without a tree
+        let version = language.abi_version();
+        if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+            self.retire_language(lang_name);
+            return Err(ParserLoadError::IncompatibleAbi(version));
+        }
+
         Ok(language)
     }
+
+    /// Forget the library of a language so the next `load_language` reads
+    /// the file again (e.g., after `kakehashi language update` replaced it).
+    ///
+    /// Returns false if the language was not loaded.
+    pub fn retire_language(&mut self, lang_name: &str) -> bool {
+        let Some(library) = self.loaded_libraries.remove(lang_name) else {
+            return false;
+        };
+        self.retired_libraries
+            .entry(lang_name.to_string())
+            .or_default()
+            .push(library);
+        true
+    }
+
+    /// Open a private copy of a library.
+    ///
+    /// The dynamic loader hands back the already-open handle when a path is
+    /// opened twice, so a replaced library must be loaded from a new path.
+    /// The copy is made in [`RELOAD_DIR`] next to the library rather than in
+    /// the system temp dir, which is often mounted `noexec`; the temp dir is
+    /// only used if the library's directory is not writable.
+    fn open_fresh_copy(path: &Path) -> Result<Library, ParserLoadError> {
+        let suffix = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("kakehashi-parser-").suffix(&suffix);
+        let reload_dir = path.parent().map(|dir| dir.join(RELOAD_DIR));
+        let copy = reload_dir
+            .and_then(|dir| {
+                std::fs::create_dir_all(&dir).ok()?;
+                builder.tempfile_in(dir).ok()
+            })
+            .map_or_else(|| builder.tempfile(), Ok)
+            .map_err(|e| ParserLoadError::CacheError(e.to_string()))?
+            .into_temp_path();
+        std::fs::copy(path, &copy).map_err(|e| ParserLoadError::CacheError(e.to_string()))?;
+        // The copy is removed when `copy` drops; the open library keeps its mapping
+        Ok(unsafe { Library::new(&*copy)? })
+    }
+}
+
+/// Test helper for building parser libraries the loader must refuse.
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use std::path::{Path, PathBuf};
+
+    use crate::install::parser::shared_lib_extension;
+
+    /// Build `<data_dir>/parser/fakeabi.<ext>`, whose language claims the
+    /// unsupported tree-sitter ABI version 999.
+    ///
+    /// Returns `None` if no C compiler is available.
+    pub(crate) fn build_incompatible_abi_library(data_dir: &Path) -> Option<PathBuf> {
+        let source = data_dir.join("fakeabi.c");
+        std::fs::write(
+            &source,
+            "static const unsigned int language[64] = {999};\n\
+             const void *tree_sitter_fakeabi(void) { return language; }\n",
+        )
+        .ok()?;
+        let library = data_dir
+            .join("parser")
+            .join(format!("fakeabi.{}", shared_lib_extension()));
+        std::fs::create_dir_all(library.parent()?).ok()?;
+        let compiled = std::process::Command::new("cc")
+            .args(["-shared", "-fPIC", "-o"])
+            .arg(&library)
+            .arg(&source)
+            .status()
+            .is_ok_and(|status| status.success());
+        if !compiled {
+            eprintln!("Skipping: no C compiler to build the test library");
+            return None;
+        }
+        Some(library)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::test_helpers::build_incompatible_abi_library;
     use super::*;
 
     #[test]
@@ -98,6 +209,34 @@ mod tests {
         assert!(loader.loaded_libraries.is_empty());
     }
 
+    #[test]
+    fn test_retire_language_without_loaded_library() {
+        let mut loader = ParserLoader::new();
+        assert!(!loader.retire_language("lua"));
+        assert!(loader.retired_libraries.is_empty());
+    }
+
+    #[test]
+    fn test_reload_copies_library_next_to_it() {
+        // A library whose language claims an ABI version from the future is
+        // retired on load, so loading it again opens a fresh copy
+        let temp = tempfile::tempdir().unwrap();
+        let Some(library) = build_incompatible_abi_library(temp.path()) else {
+            return;
+        };
+
+        let mut loader = ParserLoader::new();
+        let path = library.to_string_lossy();
+        for _ in 0..2 {
+            assert!(matches!(
+                loader.load_language(&path, "fakeabi"),
+                Err(ParserLoadError::IncompatibleAbi(999))
+            ));
+        }
+        assert!(temp.path().join("parser").join(RELOAD_DIR).is_dir());
+        assert_eq!(loader.retired_libraries["fakeabi"].len(), 2);
+    }
+
     #[test]
     fn test_error_display() {
         let err = ParserLoadError::SymbolNotFound("tree_sitter_rust".to_string());
@@ -23,6 +23,14 @@ impl ParserFactory {
             Some(parser)
         })
     }
+
+    /// Whether `parser` uses the currently registered grammar of a language.
+    fn is_current(&self, language_id: &str, parser: &Parser) -> bool {
+        match (self.language_registry.get(language_id), parser.language()) {
+            (Some(registered), Some(language)) => *language == registered,
+            _ => false,
+        }
+    }
 }
 
 /// Per-document parser pool for efficient parser reuse
@@ -57,7 +65,13 @@ impl DocumentParserPool {
     }
 
     /// Release a parser back to the pool for reuse
+    ///
+    /// Parsers checked out before their language was reloaded are dropped
+    /// instead, so later parses use the reloaded grammar.
     pub fn release(&mut self, language_id: String, parser: Parser) {
+        if !self.factory.is_current(&language_id, &parser) {
+            return;
+        }
         self.available.entry(language_id).or_default().push(parser);
     }
 
@@ -121,6 +135,23 @@ mod tests {
         assert_eq!(pool.pool_size("rust"), 0);
     }
 
+    #[test]
+    fn test_document_parser_pool_drops_parsers_of_replaced_language() {
+        let language_registry = create_test_language_registry();
+        let factory = ParserFactory::new(language_registry.clone());
+        let mut pool = DocumentParserPool::new(factory);
+
+        let parser = pool.acquire("rust").unwrap();
+        // The language is reloaded while the parser is checked out
+        language_registry.register_unchecked("rust".to_string(), tree_sitter_md::LANGUAGE.into());
+        pool.release("rust".to_string(), parser);
+        assert_eq!(pool.pool_size("rust"), 0);
+
+        let parser = pool.acquire("rust").unwrap();
+        pool.release("rust".to_string(), parser);
+        assert_eq!(pool.pool_size("rust"), 1);
+    }
+
     #[test]
     fn test_document_parser_pool_clear() {
         let language_registry = create_test_language_registry();
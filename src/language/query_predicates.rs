@@ -18,10 +18,8 @@ fn check_predicate(query: &Query, match_: &QueryMatch, capture: &QueryCapture, t
         let node_text = &text[node.start_byte()..node.end_byte()];
 
         match predicate.operator.as_ref() {
-            "lua-match?" => {
-                if !check_lua_match(predicate.args.get(1), node_text) {
-                    return false;
-                }
+            "lua-match?" if !check_lua_match(predicate.args.get(1), node_text) => {
+                return false;
             }
             "match?" => {
                 if let Some(tree_sitter::QueryPredicateArg::String(pattern_str)) =
@@ -32,15 +30,11 @@ fn check_predicate(query: &Query, match_: &QueryMatch, capture: &QueryCapture, t
                     return false;
                 }
             }
-            "eq?" => {
-                if !check_eq(predicate.args.get(1), node_text, match_, text) {
-                    return false;
-                }
+            "eq?" if !check_eq(predicate.args.get(1), node_text, match_, text) => {
+                return false;
             }
-            "not-eq?" => {
-                if !check_not_eq(predicate.args.get(1), node_text, match_, text) {
-                    return false;
-                }
+            "not-eq?" if !check_not_eq(predicate.args.get(1), node_text, match_, text) => {
+                return false;
             }
             _ => {}
         }
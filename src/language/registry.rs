@@ -40,6 +40,13 @@ impl LanguageRegistry {
             .map(|entry| entry.value().clone())
     }
 
+    /// Remove a language, returning it if it was registered
+    pub fn remove(&self, language_id: &str) -> Option<Language> {
+        self.languages
+            .remove(language_id)
+            .map(|(_, language)| language)
+    }
+
     /// Check if a language is registered
     pub fn contains(&self, language_id: &str) -> bool {
         self.languages.contains_key(language_id)
@@ -1,5 +1,6 @@
 pub(crate) mod analysis;
 pub mod config;
+pub mod doctor;
 pub mod document;
 pub mod error;
 pub mod install;
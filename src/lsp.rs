@@ -7,6 +7,7 @@ pub(crate) mod in_progress_set;
 mod settings_manager;
 mod synthetic_diagnostics;
 mod text_sync;
+mod trigger_characters;
 
 mod lsp_impl;
 mod progress;
@@ -8,17 +8,25 @@
 //! - `InstallingLanguages`: Type alias for `InProgressSet<String>` tracking concurrent installs
 //! - `InstallingLanguagesExt`: Extension trait providing domain-specific method names
 //! - `AutoInstallManager`: Isolated coordinator for installation
+//! - `InstallUpdateWatcher`: Detects languages reinstalled by another process
 //! - `get_injected_languages`: Extracts unique injected languages from a document
+//! - `configured_parser_source`: Resolves `languages.<name>.source` for installation
 
 mod manager;
+mod update_watcher;
 
 pub(crate) use manager::{AutoInstallManager, InstallEvent};
+pub(crate) use update_watcher::InstallUpdateWatcher;
 
+use crate::config::settings::LanguageSourceConfig;
 use crate::document::{DocumentStore, get_language_for_document};
+use crate::install::metadata::ParserMetadata;
+use crate::install::parser::ParserSource;
 use crate::language::LanguageCoordinator;
 use crate::language::injection::collect_all_injections;
 use crate::lsp::in_progress_set::InProgressSet;
 use std::collections::HashSet;
+use std::path::Path;
 use url::Url;
 
 /// Tracks languages currently being installed to prevent duplicate installs.
@@ -99,10 +107,63 @@ pub fn get_injected_languages(
     injections.iter().map(|i| i.language.clone()).collect()
 }
 
+/// Git revision used when `languages.<name>.source` names no `rev`.
+const DEFAULT_GIT_REVISION: &str = "HEAD";
+
+/// Resolve a configured `languages.<name>.source` to an install source.
+///
+/// Relative paths are resolved against `workspace_root` when known.
+pub(crate) fn configured_parser_source(
+    source: &LanguageSourceConfig,
+    workspace_root: Option<&Path>,
+) -> ParserSource {
+    match source {
+        LanguageSourceConfig::Path { path } => {
+            let path = Path::new(path);
+            ParserSource::Path(match workspace_root {
+                Some(root) if path.is_relative() => root.join(path),
+                _ => path.to_path_buf(),
+            })
+        }
+        LanguageSourceConfig::Git { git, rev, location } => ParserSource::Git(ParserMetadata {
+            url: git.clone(),
+            revision: rev
+                .clone()
+                .unwrap_or_else(|| DEFAULT_GIT_REVISION.to_string()),
+            location: location.clone(),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_configured_parser_source_resolves_paths_and_defaults_rev() {
+        let path = LanguageSourceConfig::Path {
+            path: "grammars/tree-sitter-mylang".to_string(),
+        };
+        assert_eq!(
+            configured_parser_source(&path, Some(Path::new("/repo"))),
+            ParserSource::Path(Path::new("/repo/grammars/tree-sitter-mylang").to_path_buf())
+        );
+
+        let git = LanguageSourceConfig::Git {
+            git: "https://example.com/tree-sitter-mylang".to_string(),
+            rev: None,
+            location: Some("grammar".to_string()),
+        };
+        assert_eq!(
+            configured_parser_source(&git, None),
+            ParserSource::Git(ParserMetadata {
+                url: "https://example.com/tree-sitter-mylang".to_string(),
+                revision: "HEAD".to_string(),
+                location: Some("grammar".to_string()),
+            })
+        );
+    }
+
     #[test]
     fn test_get_injected_languages_extracts_unique_languages() {
         // Test that get_injected_languages extracts unique languages from injection regions
@@ -20,9 +20,14 @@
 //! 3. Dispatching returned events to ClientNotifier
 //! 4. Handling post-install coordination (settings update, language reload)
 
+use dashmap::DashSet;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tower_lsp_server::ls_types::MessageType;
 
+use crate::install::bundle::find_bundle_with_language;
+use crate::install::parser::ParserSource;
+use crate::install::sources::InstallSources;
 use crate::install::support_check::should_skip_unsupported_language;
 use crate::language::FailedParserRegistry;
 
@@ -128,6 +133,9 @@ pub(crate) struct AutoInstallManager {
     installing_languages: InstallingLanguages,
     /// Tracks parsers that have crashed to prevent repeated failures
     failed_parsers: FailedParserRegistry,
+    /// Languages already rebuilt for an incompatible ABI this session, so a
+    /// rebuild producing the same ABI is not repeated on every document
+    rebuilt_languages: Arc<DashSet<String>>,
 }
 
 impl std::fmt::Debug for AutoInstallManager {
@@ -135,6 +143,7 @@ impl std::fmt::Debug for AutoInstallManager {
         f.debug_struct("AutoInstallManager")
             .field("installing_languages", &"InstallingLanguages")
             .field("failed_parsers", &"FailedParserRegistry")
+            .field("rebuilt_languages", &self.rebuilt_languages)
             .finish()
     }
 }
@@ -152,6 +161,7 @@ impl AutoInstallManager {
         Self {
             installing_languages,
             failed_parsers,
+            rebuilt_languages: Arc::new(DashSet::new()),
         }
     }
 
@@ -222,9 +232,41 @@ impl AutoInstallManager {
     /// - Call reload_language_after_install (Kakehashi handles post-install)
     ///
     /// This enables unit testing without LSP infrastructure.
-    pub async fn try_install(&self, language: &str) -> InstallResult {
+    ///
+    /// `source` is the parser source configured in `languages.<name>.source`;
+    /// languages with one are installed even if nvim-treesitter lacks them.
+    /// `sources` are the mirrors configured in `install`; a bundle in its
+    /// `bundleDir` containing the language is unpacked instead of building.
+    ///
+    /// `rebuild` replaces an installed parser with an incompatible ABI
+    /// version by building it again with the installed tree-sitter CLI
+    /// (bundles are skipped, as they would restore the same library). Each
+    /// language is rebuilt at most once per session.
+    pub async fn try_install(
+        &self,
+        language: &str,
+        source: Option<ParserSource>,
+        sources: InstallSources,
+        rebuild: bool,
+    ) -> InstallResult {
         let mut events = Vec::new();
 
+        if rebuild && !self.rebuilt_languages.insert(language.to_string()) {
+            events.push(InstallEvent::Log {
+                level: MessageType::WARNING,
+                message: format!(
+                    "Parser for '{}' was already rebuilt but still has an incompatible ABI \
+                     version. Update the tree-sitter CLI and run: \
+                     kakehashi language install {} --force",
+                    language, language
+                ),
+            });
+            return InstallResult {
+                outcome: InstallOutcome::Failed,
+                events,
+            };
+        }
+
         // Check if parser previously failed (crash protection)
         if self.failed_parsers.is_failed(language) {
             events.push(InstallEvent::Log {
@@ -241,6 +283,18 @@ impl AutoInstallManager {
             };
         }
 
+        // A local bundle takes precedence over the network
+        let bundle = match sources.bundle_dir.clone() {
+            Some(bundle_dir) if !rebuild => {
+                let lang = language.to_string();
+                tokio::task::spawn_blocking(move || find_bundle_with_language(&bundle_dir, &lang))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+            _ => None,
+        };
+
         // Check if language is supported by nvim-treesitter
         let default_data_dir = crate::install::default_data_dir();
         let fetch_options =
@@ -249,10 +303,14 @@ impl AutoInstallManager {
                 .map(|dir| crate::install::metadata::FetchOptions {
                     data_dir: Some(dir.as_path()),
                     use_cache: true,
+                    metadata_url: sources.metadata_url.as_deref(),
                 });
 
-        let (should_skip, reason) =
-            should_skip_unsupported_language(language, fetch_options.as_ref()).await;
+        let (should_skip, reason) = if source.is_some() || bundle.is_some() {
+            (false, None)
+        } else {
+            should_skip_unsupported_language(language, fetch_options.as_ref()).await
+        };
 
         if let Some(reason) = &reason {
             events.push(InstallEvent::Log {
@@ -301,7 +359,7 @@ impl AutoInstallManager {
         };
 
         // Check if parser already exists - skip installation and just signal reload
-        if crate::install::parser_file_exists(language, &data_dir).is_some() {
+        if !rebuild && crate::install::parser_file_exists(language, &data_dir).is_some() {
             events.push(InstallEvent::Log {
                 level: MessageType::INFO,
                 message: format!(
@@ -322,13 +380,31 @@ impl AutoInstallManager {
         // Log installation start
         events.push(InstallEvent::Log {
             level: MessageType::INFO,
-            message: format!("Auto-installing language '{}' in background...", language),
+            message: match &bundle {
+                None if rebuild => format!(
+                    "Rebuilding parser for '{}' for this version of tree-sitter...",
+                    language
+                ),
+                Some(bundle) => format!(
+                    "Auto-installing language '{}' from bundle {}...",
+                    language,
+                    bundle.display()
+                ),
+                None => format!("Auto-installing language '{}' in background...", language),
+            },
         });
 
         // Run the actual installation
         let lang = language.to_string();
-        let result =
-            crate::install::install_language_async(lang.clone(), data_dir.clone(), false).await;
+        let result = crate::install::install_language_async(
+            lang.clone(),
+            data_dir.clone(),
+            rebuild,
+            source,
+            sources,
+            bundle,
+        )
+        .await;
 
         // Mark installation as complete
         self.installing_languages.finish_install(&lang);
@@ -440,7 +516,9 @@ mod tests {
         manager.installing_languages.try_start_install("lua");
 
         // Try to install same language
-        let result = manager.try_install("lua").await;
+        let result = manager
+            .try_install("lua", None, InstallSources::default(), false)
+            .await;
 
         assert_eq!(result.outcome, InstallOutcome::AlreadyInstalling);
         assert!(result.events.iter().any(|e| matches!(
@@ -460,7 +538,9 @@ mod tests {
             .expect("mark_failed failed");
 
         // Try to install
-        let result = manager.try_install("bad_parser").await;
+        let result = manager
+            .try_install("bad_parser", None, InstallSources::default(), false)
+            .await;
 
         assert_eq!(result.outcome, InstallOutcome::ParserFailed);
         assert!(result.events.iter().any(|e| matches!(
@@ -469,6 +549,24 @@ mod tests {
         )));
     }
 
+    #[tokio::test]
+    async fn test_try_install_rebuilds_each_language_once() {
+        let (manager, _temp) = create_test_manager();
+
+        // A previous rebuild in this session still produced an incompatible parser
+        manager.rebuilt_languages.insert("lua".to_string());
+
+        let result = manager
+            .try_install("lua", None, InstallSources::default(), true)
+            .await;
+
+        assert_eq!(result.outcome, InstallOutcome::Failed);
+        assert!(result.events.iter().any(|e| matches!(
+            e,
+            InstallEvent::Log { level: MessageType::WARNING, message } if message.contains("already rebuilt")
+        )));
+    }
+
     #[test]
     fn test_clear_failed_removes_parser_from_failed_list() {
         let (manager, _temp) = create_test_manager();
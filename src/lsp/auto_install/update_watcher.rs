@@ -0,0 +1,178 @@
+//! InstallUpdateWatcher - Detects languages reinstalled by another process.
+//!
+//! `kakehashi language update` (or `language install --force`) run from a
+//! terminal replaces the parser and queries in a data directory and records
+//! the install in the directory's `manifest.toml`. Kakehashi polls those
+//! manifests from document notifications, at most once per `POLL_INTERVAL`,
+//! and reloads the languages whose install record changed. Records are
+//! compared as a whole, file hashes included, since two installs within the
+//! same second share their `installed_at`.
+//!
+//! Languages that appear in a manifest for the first time are not reported:
+//! they were not loaded from that directory before, so they load lazily as
+//! usual (this also keeps the server's own auto-installs from echoing back).
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::install::manifest::{LanguageRecord, Manifest};
+
+/// Minimum time between two manifest polls.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Tracks the install records of data directories between polls.
+pub(crate) struct InstallUpdateWatcher {
+    interval: Duration,
+    state: Mutex<WatcherState>,
+}
+
+#[derive(Default)]
+struct WatcherState {
+    last_poll: Option<Instant>,
+    /// Install record of each language, per data directory.
+    records: HashMap<PathBuf, BTreeMap<String, LanguageRecord>>,
+}
+
+impl InstallUpdateWatcher {
+    pub(crate) fn new() -> Self {
+        Self::with_interval(POLL_INTERVAL)
+    }
+
+    fn with_interval(interval: Duration) -> Self {
+        Self {
+            interval,
+            state: Mutex::new(WatcherState::default()),
+        }
+    }
+
+    /// Languages reinstalled in any of `data_dirs` since the previous poll,
+    /// paired with the data directory holding them.
+    ///
+    /// The first poll of a directory only records its state, and polls within
+    /// the poll interval of the previous one return nothing.
+    pub(crate) fn poll(&self, data_dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if state
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return Vec::new();
+        }
+        state.last_poll = Some(now);
+
+        let mut updated = Vec::new();
+        for data_dir in data_dirs {
+            // An unreadable manifest counts as empty; its languages are
+            // reported again only after a later reinstall.
+            let current = Manifest::load(data_dir)
+                .map(|manifest| manifest.languages)
+                .unwrap_or_default();
+            if let Some(previous) = state.records.get(data_dir) {
+                updated.extend(
+                    current
+                        .iter()
+                        .filter(|(language, record)| {
+                            previous
+                                .get(*language)
+                                .is_some_and(|before| before != *record)
+                        })
+                        .map(|(language, _)| (language.clone(), data_dir.clone())),
+                );
+            }
+            state.records.insert(data_dir.clone(), current);
+        }
+        updated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::manifest::ParserRecord;
+    use crate::install::parser::SourceKind;
+    use tempfile::TempDir;
+
+    fn record(data_dir: &std::path::Path, language: &str, installed_at: u64) {
+        Manifest::update(data_dir, |manifest| {
+            manifest.languages.insert(
+                language.to_string(),
+                LanguageRecord {
+                    installed_at,
+                    ..LanguageRecord::default()
+                },
+            );
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_poll_reports_reinstalled_languages_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let dirs = vec![data_dir.clone()];
+        record(&data_dir, "lua", 100);
+        let watcher = InstallUpdateWatcher::with_interval(Duration::ZERO);
+
+        // The first poll is the baseline
+        assert!(watcher.poll(&dirs).is_empty());
+
+        // Newly installed languages are not reported
+        record(&data_dir, "rust", 200);
+        assert!(watcher.poll(&dirs).is_empty());
+
+        record(&data_dir, "lua", 300);
+        assert_eq!(watcher.poll(&dirs), vec![("lua".to_string(), data_dir)]);
+        assert!(watcher.poll(&dirs).is_empty());
+    }
+
+    #[test]
+    fn test_poll_reports_reinstall_within_the_same_second() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let dirs = vec![data_dir.clone()];
+        let install = |sha256: &str| {
+            Manifest::update(&data_dir, |manifest| {
+                manifest.languages.insert(
+                    "lua".to_string(),
+                    LanguageRecord {
+                        parser: Some(ParserRecord {
+                            url: "https://github.com/example/tree-sitter-lua".to_string(),
+                            revision: "v1".to_string(),
+                            location: None,
+                            sha256: sha256.to_string(),
+                            kind: SourceKind::Metadata,
+                        }),
+                        installed_at: 100,
+                        ..LanguageRecord::default()
+                    },
+                );
+            })
+            .unwrap();
+        };
+        install("aaa");
+        let watcher = InstallUpdateWatcher::with_interval(Duration::ZERO);
+        assert!(watcher.poll(&dirs).is_empty());
+
+        install("bbb");
+        assert_eq!(
+            watcher.poll(&dirs),
+            vec![("lua".to_string(), data_dir.clone())]
+        );
+    }
+
+    #[test]
+    fn test_poll_is_throttled() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let dirs = vec![data_dir.clone()];
+        record(&data_dir, "lua", 100);
+        let watcher = InstallUpdateWatcher::with_interval(Duration::from_secs(3600));
+
+        assert!(watcher.poll(&dirs).is_empty());
+        record(&data_dir, "lua", 300);
+        assert!(watcher.poll(&dirs).is_empty());
+    }
+}
@@ -10,21 +10,34 @@
 //! - `coordinator` - BridgeCoordinator for unified pool + region ID tracking
 //! - `protocol` - VirtualDocumentUri, request building, and response transformation
 //! - `pool` - LanguageServerPool for server pool coordination (ADR-0016)
+//! - `server_instance` - Per-root server instances and launch variable substitution
+//! - `text_document` / `workspace` - Request handlers mirroring `lsp_impl`
 
 mod actor;
 mod connection;
 mod coordinator;
 mod pool;
 mod protocol;
+mod server_instance;
 mod text_document;
+mod workspace;
 
 // Re-export public types
 pub(crate) use actor::UpstreamNotification;
 pub(crate) use coordinator::BridgeCoordinator;
 pub(crate) use coordinator::ResolvedServerConfig;
+pub(crate) use pool::IDLE_SWEEP_INTERVAL;
 pub use pool::LanguageServerPool;
+pub(crate) use pool::ServerTriggerCharacters;
 pub(crate) use pool::UpstreamId;
+pub(crate) use protocol::BridgeResolveData;
+pub(crate) use protocol::HOST_DOCUMENT_REGION_ID;
 pub(crate) use protocol::location_link_to_location;
+pub(crate) use protocol::{
+    BRIDGE_COMMANDS, BRIDGE_RESTART_COMMAND, BRIDGE_STATUS_COMMAND, BRIDGE_STOP_COMMAND,
+    unwrap_bridge_command,
+};
+pub(crate) use text_document::region_formatting_edit;
 
 /// Integration tests for the bridge module.
 ///
@@ -59,6 +72,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let host_uri = Url::parse("file:///test/doc.md").unwrap();
@@ -104,6 +118,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let host_uri = Url::parse("file:///test/doc.md").unwrap();
@@ -156,6 +171,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let host_uri = Url::parse("file:///test/doc.md").unwrap();
@@ -207,6 +223,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let host_uri = Url::parse("file:///test/doc.md").unwrap();
@@ -254,6 +271,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let host_uri = Url::parse("file:///test/doc.md").unwrap();
@@ -37,8 +37,25 @@ pub(crate) enum UpstreamNotification {
     /// Request upstream to re-pull diagnostics.
     /// Sent when downstream server issues `workspace/diagnostic/refresh`.
     DiagnosticRefresh,
+    /// Request upstream to refresh completion / signature help trigger characters.
+    /// Sent when a downstream server finishes its handshake or (un)registers
+    /// `textDocument/completion` or `textDocument/signatureHelp` dynamically.
+    TriggerCharactersChanged,
+    /// Tell the user that a crash-looping server is no longer restarted.
+    /// Sent by the pool when a server exceeds its restart policy.
+    ServerGaveUp {
+        server_name: String,
+        restarts: usize,
+    },
 }
 
+/// Methods whose registration options carry trigger characters advertised upstream.
+const TRIGGER_CHARACTER_METHODS: &[&str] = &[
+    "textDocument/completion",
+    "textDocument/signatureHelp",
+    "textDocument/onTypeFormatting",
+];
+
 /// Liveness channel endpoints for the reader task.
 ///
 /// Groups the four liveness-related parameters that `reader_loop_with_liveness`
@@ -690,7 +707,16 @@ async fn handle_server_request(
                                 lang_prefix, reg.method, reg.id
                             );
                         }
+                        let triggers_changed = reg_params
+                            .registrations
+                            .iter()
+                            .any(|reg| TRIGGER_CHARACTER_METHODS.contains(&reg.method.as_str()));
                         deps.dynamic_capabilities.register(reg_params.registrations);
+                        if triggers_changed {
+                            let _ = deps
+                                .upstream_tx
+                                .send(UpstreamNotification::TriggerCharactersChanged);
+                        }
                         Ok(())
                     }
                     Err(e) => {
@@ -728,8 +754,16 @@ async fn handle_server_request(
                                 lang_prefix, unreg.method, unreg.id
                             );
                         }
+                        let triggers_changed = unreg_params.unregisterations.iter().any(|unreg| {
+                            TRIGGER_CHARACTER_METHODS.contains(&unreg.method.as_str())
+                        });
                         deps.dynamic_capabilities
                             .unregister(unreg_params.unregisterations);
+                        if triggers_changed {
+                            let _ = deps
+                                .upstream_tx
+                                .send(UpstreamNotification::TriggerCharactersChanged);
+                        }
                         Ok(())
                     }
                     Err(e) => {
@@ -1396,6 +1430,53 @@ mod tests {
         }
     }
 
+    /// Test that registering completion dynamically asks upstream to refresh
+    /// trigger characters, while unrelated registrations do not.
+    #[tokio::test]
+    async fn handle_message_register_completion_signals_trigger_characters_changed() {
+        let router = ResponseRouter::new();
+        let (response_tx, _response_rx) = mpsc::channel(16);
+        let dynamic_capabilities = Arc::new(DynamicCapabilityRegistry::new());
+        let (upstream_tx, mut upstream_rx) = mpsc::unbounded_channel();
+        let deps = ServerRequestDeps {
+            language: None,
+            response_tx,
+            dynamic_capabilities,
+            upstream_tx,
+        };
+
+        let unrelated = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "client/registerCapability",
+            "params": {
+                "registrations": [{ "id": "hover-1", "method": "textDocument/hover" }]
+            }
+        });
+        handle_message(unrelated, &router, "", &deps).await;
+        assert!(upstream_rx.try_recv().is_err());
+
+        let completion = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "client/registerCapability",
+            "params": {
+                "registrations": [{
+                    "id": "completion-1",
+                    "method": "textDocument/completion",
+                    "registerOptions": { "triggerCharacters": ["@"] }
+                }]
+            }
+        });
+        handle_message(completion, &router, "", &deps).await;
+        assert_eq!(
+            upstream_rx
+                .try_recv()
+                .expect("should have upstream notification"),
+            UpstreamNotification::TriggerCharactersChanged
+        );
+    }
+
     #[tokio::test]
     async fn handle_message_unknown_server_request_sends_method_not_found() {
         let router = ResponseRouter::new();
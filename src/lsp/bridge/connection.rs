@@ -1,30 +1,70 @@
-//! Async connection to downstream language server processes.
+//! Async connection to downstream language servers.
 //!
 //! This module provides the core connection type for communicating with
-//! language servers via stdio using async I/O.
+//! language servers using async I/O, either over the stdio of a spawned child
+//! process or over a socket to an already running server (TCP or Unix domain
+//! socket, see [`ConnectAddress`]).
 //!
 //! # Structure
 //!
-//! - `BridgeWriter`: Handles writing LSP messages to stdin
-//! - `BridgeReader`: Handles reading LSP messages from stdout
-//! - `AsyncBridgeConnection`: Owns the child process and coordinates I/O
+//! - `BridgeWriter`: Handles writing LSP messages to stdin (or the socket)
+//! - `BridgeReader`: Handles reading LSP messages from stdout (or the socket)
+//! - `AsyncBridgeConnection`: Owns the child process (if any) and coordinates I/O
 //!
 //! The separation of reader/writer enables future Reader Task introduction
 //! (ADR-0015) where the reader runs in a dedicated task for non-blocking
 //! response routing.
 
+use std::collections::HashMap;
 use std::io;
+use std::path::Path;
 use std::process::Stdio;
 
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+
+/// Address of an already running language server to connect to instead of
+/// spawning one.
+///
+/// Parsed from the `connect` field of a server configuration:
+/// - `unix:/path/to/socket` or an absolute path → Unix domain socket
+/// - `tcp://host:port` or `host:port` → TCP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ConnectAddress {
+    Tcp(String),
+    Unix(std::path::PathBuf),
+}
+
+impl ConnectAddress {
+    /// Parse a `connect` address.
+    pub(crate) fn parse(address: &str) -> io::Result<Self> {
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(Self::Unix(path.into()));
+        }
+        if address.starts_with('/') {
+            return Ok(Self::Unix(address.into()));
+        }
+        let host_port = address.strip_prefix("tcp://").unwrap_or(address);
+        match host_port.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(Self::Tcp(host_port.to_string()))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid connect address (expected host:port or unix socket path): {address}"
+                ),
+            )),
+        }
+    }
+}
 
 /// Writer handle for sending LSP messages to downstream language server.
 ///
-/// Wraps `ChildStdin` to provide LSP message framing (Content-Length header).
-/// This type will be used directly when single-writer loop is introduced.
+/// Wraps the server's input (`ChildStdin` or the write half of a socket) to
+/// provide LSP message framing (Content-Length header).
 pub(crate) struct BridgeWriter {
-    stdin: ChildStdin,
+    stdin: Box<dyn AsyncWrite + Send + Unpin>,
 }
 
 impl BridgeWriter {
@@ -46,18 +86,19 @@ impl BridgeWriter {
 
 /// Reader handle for receiving LSP messages from downstream language server.
 ///
-/// Wraps `BufReader<ChildStdout>` to provide LSP message parsing.
+/// Wraps the server's output (`ChildStdout` or the read half of a socket) in a
+/// `BufReader` to provide LSP message parsing.
 /// This type is used by the Reader Task (ADR-0015) for
 /// non-blocking response routing via ResponseRouter.
 pub(crate) struct BridgeReader {
-    stdout: BufReader<ChildStdout>,
+    stdout: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
 }
 
 impl BridgeReader {
-    /// Create a new BridgeReader from a ChildStdout.
-    pub(crate) fn new(stdout: ChildStdout) -> Self {
+    /// Create a new BridgeReader from the server's output stream.
+    pub(crate) fn new(stdout: impl AsyncRead + Send + Unpin + 'static) -> Self {
         Self {
-            stdout: BufReader::new(stdout),
+            stdout: BufReader::new(Box::new(stdout)),
         }
     }
 }
@@ -112,10 +153,11 @@ impl BridgeReader {
     }
 }
 
-/// Async connection to a downstream language server process.
+/// Async connection to a downstream language server.
 ///
 /// Manages the lifecycle of a child process running a language server,
-/// providing async I/O for LSP JSON-RPC communication over stdio.
+/// providing async I/O for LSP JSON-RPC communication over stdio. Connections
+/// to running servers over a socket have no child process.
 ///
 /// # Architecture (ADR-0015)
 ///
@@ -125,16 +167,17 @@ impl BridgeReader {
 ///
 /// Use `split()` to separate writer and reader after initialization.
 pub(crate) struct AsyncBridgeConnection {
-    child: Option<Child>,         // Option to support taking for split()
+    child: Option<Child>,         // None for socket connections, or after split()
     writer: Option<BridgeWriter>, // Option to support taking for split()
     reader: Option<BridgeReader>, // Option to support taking for Reader Task
 }
 
 /// Writer half of a split connection.
 ///
-/// Owns the child process and writer. Dropping this kills the child process.
+/// Owns the child process (if any) and writer. Dropping this kills the child
+/// process; a socket is closed when the writer and reader are dropped.
 pub(crate) struct SplitConnectionWriter {
-    child: Child,
+    child: Option<Child>,
     writer: BridgeWriter,
 }
 
@@ -144,8 +187,19 @@ impl SplitConnectionWriter {
         self.writer.write_message(message).await
     }
 
+    /// Whether this connection owns the server process (spawned via `cmd`).
+    ///
+    /// Servers reached over a socket are shared daemons: they are never killed
+    /// and receive no `exit` notification.
+    pub(crate) fn owns_process(&self) -> bool {
+        self.child.is_some()
+    }
+
     /// Force-kill the child process with platform-appropriate escalation.
     ///
+    /// For socket connections there is no process to kill; the socket's write
+    /// half is shut down instead, closing the connection.
+    ///
     /// # Platform-Specific Behavior
     ///
     /// **Unix (Linux, macOS)**:
@@ -158,14 +212,25 @@ impl SplitConnectionWriter {
     /// - No graceful period (Windows has no SIGTERM equivalent)
     /// - Language servers should handle cleanup via LSP shutdown/exit handshake (ADR-0017)
     pub(crate) async fn force_kill_with_escalation(&mut self) {
+        let Some(child) = self.child.as_mut() else {
+            if let Err(e) = self.writer.stdin.shutdown().await {
+                log::debug!(
+                    target: "kakehashi::bridge",
+                    "Error closing server socket: {}",
+                    e
+                );
+            }
+            return;
+        };
+
         #[cfg(unix)]
         {
-            self.force_kill_with_escalation_unix().await;
+            Self::force_kill_with_escalation_unix(child).await;
         }
 
         #[cfg(not(unix))]
         {
-            self.force_kill_with_escalation_general().await;
+            Self::force_kill_with_escalation_general(child).await;
         }
     }
 
@@ -176,14 +241,14 @@ impl SplitConnectionWriter {
     /// 2. Wait up to 2 seconds for process exit
     /// 3. Escalate to SIGKILL if still alive
     #[cfg(unix)]
-    async fn force_kill_with_escalation_unix(&mut self) {
+    async fn force_kill_with_escalation_unix(child: &mut Child) {
         use nix::sys::signal::{Signal, kill};
         use nix::unistd::Pid;
         use std::time::Duration;
 
         const SIGTERM_WAIT: Duration = Duration::from_secs(2);
 
-        let Some(pid) = self.child.id() else {
+        let Some(pid) = child.id() else {
             log::debug!(
                 target: "kakehashi::bridge",
                 "force_kill_with_escalation: child process already exited"
@@ -207,7 +272,7 @@ impl SplitConnectionWriter {
                 pid, e
             );
             // If SIGTERM fails, try SIGKILL directly via start_kill()
-            if let Err(kill_err) = self.child.start_kill() {
+            if let Err(kill_err) = child.start_kill() {
                 log::error!(
                     target: "kakehashi::bridge",
                     "Failed to send SIGTERM to process {}, and fallback SIGKILL also failed: {}",
@@ -215,7 +280,7 @@ impl SplitConnectionWriter {
                 );
             } else {
                 // Wait for process to be reaped after fallback SIGKILL
-                match self.child.wait().await {
+                match child.wait().await {
                     Ok(status) => {
                         log::debug!(
                             target: "kakehashi::bridge",
@@ -236,7 +301,7 @@ impl SplitConnectionWriter {
         }
 
         // Step 2: Wait for process to exit with timeout
-        let wait_result = tokio::time::timeout(SIGTERM_WAIT, self.child.wait()).await;
+        let wait_result = tokio::time::timeout(SIGTERM_WAIT, child.wait()).await;
 
         match wait_result {
             Ok(Ok(status)) => {
@@ -279,7 +344,7 @@ impl SplitConnectionWriter {
         }
 
         // Wait for process to be reaped after SIGKILL
-        match self.child.wait().await {
+        match child.wait().await {
             Ok(status) => {
                 log::debug!(
                     target: "kakehashi::bridge",
@@ -302,8 +367,8 @@ impl SplitConnectionWriter {
     /// On non-Unix platforms (e.g., Windows), terminates the process immediately.
     /// No graceful period is available as these platforms lack SIGTERM equivalent.
     #[cfg(not(unix))]
-    async fn force_kill_with_escalation_general(&mut self) {
-        let Some(pid) = self.child.id() else {
+    async fn force_kill_with_escalation_general(child: &mut Child) {
+        let Some(pid) = child.id() else {
             log::debug!(
                 target: "kakehashi::bridge",
                 "force_kill_with_escalation: child process already exited"
@@ -317,7 +382,7 @@ impl SplitConnectionWriter {
             pid
         );
 
-        if let Err(e) = self.child.start_kill() {
+        if let Err(e) = child.start_kill() {
             log::error!(
                 target: "kakehashi::bridge",
                 "Failed to terminate process {}: {}",
@@ -327,7 +392,7 @@ impl SplitConnectionWriter {
         }
 
         // Wait for process to be reaped
-        match self.child.wait().await {
+        match child.wait().await {
             Ok(status) => {
                 log::debug!(
                     target: "kakehashi::bridge",
@@ -349,18 +414,21 @@ impl SplitConnectionWriter {
 impl Drop for SplitConnectionWriter {
     fn drop(&mut self) {
         // Kill the child process to prevent orphans (AC3)
-        if let Err(e) = self.child.start_kill() {
-            log::warn!(
-                target: "kakehashi::bridge",
-                "Failed to kill child process: {}",
-                e
-            );
-        } else {
-            log::debug!(
-                target: "kakehashi::bridge",
-                "Killed child process {:?}",
-                self.child.id()
-            );
+        // Socket connections have no child; dropping the stream closes them
+        if let Some(ref mut child) = self.child {
+            if let Err(e) = child.start_kill() {
+                log::warn!(
+                    target: "kakehashi::bridge",
+                    "Failed to kill child process: {}",
+                    e
+                );
+            } else {
+                log::debug!(
+                    target: "kakehashi::bridge",
+                    "Killed child process {:?}",
+                    child.id()
+                );
+            }
         }
     }
 }
@@ -373,17 +441,38 @@ impl AsyncBridgeConnection {
     ///
     /// # Returns
     /// A new `AsyncBridgeConnection` with stdio pipes connected to the child process.
+    #[cfg(test)]
     pub(crate) async fn spawn(cmd: Vec<String>) -> io::Result<Self> {
+        Self::spawn_with(cmd, &HashMap::new(), None).await
+    }
+
+    /// Spawn a language server with extra environment variables and an
+    /// optional working directory.
+    ///
+    /// # Arguments
+    /// * `cmd` - Command and arguments to spawn
+    /// * `env` - Variables added to (or overriding) kakehashi's environment
+    /// * `cwd` - Working directory; kakehashi's own when `None`
+    pub(crate) async fn spawn_with(
+        cmd: Vec<String>,
+        env: &HashMap<String, String>,
+        cwd: Option<&Path>,
+    ) -> io::Result<Self> {
         let (program, args) = cmd.split_first().ok_or_else(|| {
             io::Error::new(io::ErrorKind::InvalidInput, "command must not be empty")
         })?;
 
-        let mut child = Command::new(program)
+        let mut command = Command::new(program);
+        command
             .args(args)
+            .envs(env)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()?;
+            .stderr(Stdio::null());
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
+        let mut child = command.spawn()?;
 
         let stdin = child
             .stdin
@@ -397,15 +486,62 @@ impl AsyncBridgeConnection {
 
         Ok(Self {
             child: Some(child),
-            writer: Some(BridgeWriter { stdin }),
+            writer: Some(BridgeWriter {
+                stdin: Box::new(stdin),
+            }),
             reader: Some(BridgeReader::new(stdout)),
         })
     }
 
+    /// Connect to an already running language server.
+    ///
+    /// # Arguments
+    /// * `address` - A `connect` address (see [`ConnectAddress::parse`]),
+    ///   e.g. `"127.0.0.1:6005"` or `"unix:/tmp/clangd.sock"`
+    ///
+    /// # Returns
+    /// A new `AsyncBridgeConnection` over the socket, without a child process.
+    pub(crate) async fn connect(address: &str) -> io::Result<Self> {
+        let (read_half, write_half): (
+            Box<dyn AsyncRead + Send + Unpin>,
+            Box<dyn AsyncWrite + Send + Unpin>,
+        ) = match ConnectAddress::parse(address)? {
+            ConnectAddress::Tcp(host_port) => {
+                let stream = tokio::net::TcpStream::connect(host_port).await?;
+                let (read_half, write_half) = stream.into_split();
+                (Box::new(read_half), Box::new(write_half))
+            }
+            #[cfg(unix)]
+            ConnectAddress::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                let (read_half, write_half) = stream.into_split();
+                (Box::new(read_half), Box::new(write_half))
+            }
+            #[cfg(not(unix))]
+            ConnectAddress::Unix(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unix socket connections are not supported on this platform",
+                ));
+            }
+        };
+
+        Ok(Self {
+            child: None,
+            writer: Some(BridgeWriter { stdin: write_half }),
+            reader: Some(BridgeReader::new(read_half)),
+        })
+    }
+
+    /// OS process ID of the spawned server, or `None` for socket connections.
+    pub(crate) fn process_id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(Child::id)
+    }
+
     /// Split into separate writer and reader components.
     ///
     /// This takes ownership of the internal components and returns:
-    /// - `SplitConnectionWriter`: For sending messages (holds child process)
+    /// - `SplitConnectionWriter`: For sending messages (holds child process, if any)
     /// - `BridgeReader`: For receiving messages (goes to Reader Task)
     ///
     /// # Panics
@@ -416,10 +552,8 @@ impl AsyncBridgeConnection {
             .take()
             .expect("split() called after reader was already taken");
 
-        let child = self
-            .child
-            .take()
-            .expect("split() called after child was already taken");
+        // Socket connections have no child process
+        let child = self.child.take();
 
         let writer_inner = self
             .writer
@@ -526,6 +660,89 @@ mod tests {
         assert!(parsed["result"].is_object());
     }
 
+    #[test]
+    fn connect_address_parses_tcp_and_unix_forms() {
+        assert_eq!(
+            ConnectAddress::parse("127.0.0.1:6005").unwrap(),
+            ConnectAddress::Tcp("127.0.0.1:6005".to_string())
+        );
+        assert_eq!(
+            ConnectAddress::parse("tcp://localhost:6005").unwrap(),
+            ConnectAddress::Tcp("localhost:6005".to_string())
+        );
+        assert_eq!(
+            ConnectAddress::parse("unix:/tmp/clangd.sock").unwrap(),
+            ConnectAddress::Unix("/tmp/clangd.sock".into())
+        );
+        assert_eq!(
+            ConnectAddress::parse("/tmp/clangd.sock").unwrap(),
+            ConnectAddress::Unix("/tmp/clangd.sock".into())
+        );
+        assert!(ConnectAddress::parse("localhost").is_err());
+        assert!(ConnectAddress::parse("localhost:port").is_err());
+    }
+
+    /// Echo every byte received on an accepted stream back to the peer.
+    async fn echo<S: AsyncRead + AsyncWrite + Unpin>(stream: S) {
+        let (mut read_half, mut write_half) = tokio::io::split(stream);
+        let _ = tokio::io::copy(&mut read_half, &mut write_half).await;
+    }
+
+    #[tokio::test]
+    async fn connect_over_tcp_exchanges_messages() {
+        use serde_json::json;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            echo(stream).await;
+        });
+
+        let mut conn = AsyncBridgeConnection::connect(&address)
+            .await
+            .expect("connect should succeed");
+        conn.write_message(&json!({ "jsonrpc": "2.0", "id": 1, "result": null }))
+            .await
+            .expect("write should succeed");
+
+        let parsed = conn.read_message().await.expect("read should succeed");
+        assert_eq!(parsed["id"], 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn connect_over_unix_socket_exchanges_messages() {
+        use serde_json::json;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.sock");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            echo(stream).await;
+        });
+
+        let mut conn = AsyncBridgeConnection::connect(&format!("unix:{}", path.display()))
+            .await
+            .expect("connect should succeed");
+        conn.write_message(&json!({ "jsonrpc": "2.0", "id": 2, "result": null }))
+            .await
+            .expect("write should succeed");
+
+        let parsed = conn.read_message().await.expect("read should succeed");
+        assert_eq!(parsed["id"], 2);
+    }
+
+    #[tokio::test]
+    async fn connect_to_closed_port_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        assert!(AsyncBridgeConnection::connect(&address).await.is_err());
+    }
+
     /// Integration test: Initialize lua-language-server and verify response
     #[tokio::test]
     async fn initialize_lua_language_server() {
@@ -23,6 +23,7 @@ use crate::language::region_id_tracker::{EditInfo, RegionIdTracker};
 use crate::lsp::request_id::CancelForwarder;
 
 use super::LanguageServerPool;
+use super::server_instance::{resolve_instance, resolve_instance_config};
 
 /// Resolved server configuration with server name.
 ///
@@ -153,92 +154,42 @@ impl BridgeCoordinator {
     // Config lookup (moved from Kakehashi)
     // ========================================
 
-    /// Get bridge server config for a given injection language from settings.
-    ///
-    /// Looks up the bridge.servers configuration and finds a server that handles
-    /// the specified language. Returns `ResolvedServerConfig` which includes both
-    /// the server name (for connection pooling) and the config (for spawning).
+    /// Get all bridge server configs for a given injection language from settings.
     ///
-    /// Returns None if:
-    /// - No server is configured for this injection language, OR
-    /// - The host language has a bridge filter that excludes this injection language
+    /// Returns **all** servers configured for the injection language. This
+    /// enables diagnostic fan-out to multiple servers (e.g., pyright + ruff both
+    /// handling Python) and capability-aware routing of single-server requests.
     ///
     /// Uses wildcard resolution (ADR-0011) for host language lookup:
     /// - If host language is not defined, inherits from languages._ if present
     /// - This allows setting default bridge filters for all hosts via languages._
     ///
-    /// # Arguments
-    /// * `settings` - The current workspace settings
-    /// * `host_language` - The language of the host document (e.g., "markdown")
-    /// * `injection_language` - The injection language to bridge (e.g., "rust", "python")
-    pub(crate) fn get_config_for_language(
-        &self,
-        settings: &WorkspaceSettings,
-        host_language: &str,
-        injection_language: &str,
-    ) -> Option<ResolvedServerConfig> {
-        // Use wildcard resolution for host language lookup (ADR-0011)
-        // This allows languages._ to define default bridge filters
-        if let Some(host_settings) =
-            resolve_language_settings_with_wildcard(&settings.languages, host_language)
-            && !host_settings.is_language_bridgeable(injection_language)
-        {
-            log::debug!(
-                target: "kakehashi::bridge",
-                "Bridge filter for {} blocks injection language {}",
-                host_language,
-                injection_language
-            );
-            return None;
-        }
-
-        // Check if language servers exist
-        if let Some(ref servers) = settings.language_servers {
-            // Look for a server that handles this language
-            // ADR-0011: Resolve each server with wildcard BEFORE checking languages,
-            // because languages list may be inherited from languageServers._
-            for server_name in servers.keys() {
-                // Skip wildcard entry - we use it for inheritance, not direct lookup
-                if server_name == "_" {
-                    continue;
-                }
-
-                if let Some(resolved_config) =
-                    resolve_language_server_with_wildcard(servers, server_name)
-                        .filter(|c| c.languages.iter().any(|l| l == injection_language))
-                {
-                    return Some(ResolvedServerConfig {
-                        server_name: server_name.clone(),
-                        config: resolved_config,
-                    });
-                }
-            }
-        }
-
-        None
-    }
-
-    /// Get all bridge server configs for a given injection language from settings.
-    ///
-    /// Unlike `get_config_for_language()` which returns the first matching server,
-    /// this method returns **all** servers configured for the injection language.
-    /// This enables diagnostic fan-out to multiple servers (e.g., pyright + ruff
-    /// both handling Python).
+    /// Results are sorted by `priority` (highest first), then by server name for
+    /// deterministic ordering.
     ///
-    /// Results are sorted by server name for deterministic ordering.
+    /// Each config is resolved for `host_uri`: servers with `rootMarkers` get the
+    /// instance for the host document's project root (server name
+    /// `<server>@<root>`), and `${workspaceFolder}` / `${hostDir}` are substituted.
     ///
     /// Returns an empty Vec if:
     /// - No servers are configured for this injection language, OR
     /// - The host language has a bridge filter that excludes this injection language
+    ///   (unless `injection_language` is the host language itself and host-document
+    ///   bridging is enabled for it)
     pub(crate) fn get_all_configs_for_language(
         &self,
         settings: &WorkspaceSettings,
+        host_uri: &Url,
         host_language: &str,
         injection_language: &str,
     ) -> Vec<ResolvedServerConfig> {
-        // Check bridge filter (same logic as get_config_for_language)
+        // Use wildcard resolution for host language lookup (ADR-0011)
+        // This allows languages._ to define default bridge filters.
+        // The host document itself (host-document bridging) is not subject to
+        // the injection filter; it is opted into through `host` instead.
         if let Some(host_settings) =
             resolve_language_settings_with_wildcard(&settings.languages, host_language)
+            && !(host_language == injection_language && host_settings.is_host_bridged())
             && !host_settings.is_language_bridgeable(injection_language)
         {
             log::debug!(
@@ -254,24 +205,71 @@ impl BridgeCoordinator {
             return Vec::new();
         };
 
+        let workspace_root = self.pool.root_uri();
         let mut results: Vec<ResolvedServerConfig> = servers
             .keys()
             .filter(|name| *name != "_")
             .filter_map(|server_name| {
                 resolve_language_server_with_wildcard(servers, server_name)
                     .filter(|c| c.languages.iter().any(|l| l == injection_language))
-                    .map(|config| ResolvedServerConfig {
-                        server_name: server_name.clone(),
-                        config,
+                    .map(|config| {
+                        let (instance, config) = resolve_instance(
+                            server_name,
+                            config,
+                            host_uri,
+                            workspace_root.as_deref(),
+                        );
+                        self.pool.register_instance(&instance);
+                        ResolvedServerConfig {
+                            server_name: instance.key(),
+                            config,
+                        }
                     })
             })
             .collect();
 
-        // Sort by server name for deterministic ordering
-        results.sort_by(|a, b| a.server_name.cmp(&b.server_name));
+        // Higher priority first; server name breaks ties for deterministic ordering
+        results.sort_by(|a, b| {
+            let priority = |c: &ResolvedServerConfig| c.config.priority.unwrap_or(0);
+            priority(b)
+                .cmp(&priority(a))
+                .then_with(|| a.server_name.cmp(&b.server_name))
+        });
         results
     }
 
+    /// Get the bridge server config for a server by its configured name.
+    ///
+    /// Used by resolve-style requests (e.g., `codeAction/resolve`) that must be
+    /// routed back to the server which produced the item, regardless of which
+    /// server would be picked for the language today.
+    ///
+    /// `server_name` may be a per-root instance key (`<server>@<root>`) handed
+    /// out by `get_all_configs_for_language`; the config is then resolved for
+    /// that root.
+    ///
+    /// Returns None if the server is not configured (anymore) or is the `_` wildcard.
+    pub(crate) fn get_config_for_server(
+        &self,
+        settings: &WorkspaceSettings,
+        server_name: &str,
+    ) -> Option<ResolvedServerConfig> {
+        let instance = self.pool.instance(server_name);
+        if instance.server_name == "_" {
+            return None;
+        }
+        let servers = settings
+            .language_servers
+            .as_ref()
+            .filter(|servers| servers.contains_key(&instance.server_name))?;
+        resolve_language_server_with_wildcard(servers, &instance.server_name).map(|config| {
+            ResolvedServerConfig {
+                server_name: server_name.to_string(),
+                config: resolve_instance_config(&instance, config, self.pool.root_uri().as_deref()),
+            }
+        })
+    }
+
     // ========================================
     // Region ID management (delegate to tracker)
     // ========================================
@@ -358,28 +356,35 @@ impl BridgeCoordinator {
 
     /// Eagerly spawn language servers for detected injection languages.
     ///
-    /// This method looks up each injection language in the settings, finds the
-    /// corresponding language server config, and spawns the server if not already
-    /// running. The LSP handshake runs in a background task.
+    /// This method looks up each injection language in the settings, finds
+    /// **all** language server configs for it, and spawns each server if not
+    /// already running. The LSP handshake runs in a background task.
+    ///
+    /// Spawning every server (not just the first) lets capability-aware routing
+    /// see each server's capabilities before the first request arrives.
     ///
     /// Call this after parsing a document to warm up servers for code blocks,
     /// eliminating first-request latency for hover, completion, etc.
     ///
     /// # Arguments
     /// * `settings` - Current workspace settings
+    /// * `host_uri` - URI of the host document (selects per-root instances)
     /// * `host_language` - Language of the host document (e.g., "markdown")
     /// * `injection_languages` - Set of detected injection languages (e.g., {"lua", "python"})
     pub(crate) async fn eager_spawn_servers(
         &self,
         settings: &WorkspaceSettings,
+        host_uri: &Url,
         host_language: &str,
         injection_languages: impl IntoIterator<Item = impl AsRef<str>>,
     ) {
         for lang in injection_languages {
             let lang = lang.as_ref();
 
-            // Look up server config for this injection language
-            if let Some(resolved) = self.get_config_for_language(settings, host_language, lang) {
+            // Look up all server configs for this injection language
+            for resolved in
+                self.get_all_configs_for_language(settings, host_uri, host_language, lang)
+            {
                 log::debug!(
                     target: "kakehashi::bridge",
                     "Warming up {} server for {} injection",
@@ -422,6 +427,10 @@ mod tests {
     use crate::config::settings::BridgeLanguageConfig;
     use std::collections::HashMap;
 
+    fn host_uri() -> Url {
+        Url::parse("file:///project/doc.md").unwrap()
+    }
+
     #[test]
     fn test_get_config_respects_bridge_filter() {
         let coordinator = BridgeCoordinator::new();
@@ -444,6 +453,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -456,7 +466,10 @@ mod tests {
         );
 
         // rust should be blocked by markdown's bridge filter
-        let result = coordinator.get_config_for_language(&settings, "markdown", "rust");
+        let result = coordinator
+            .get_all_configs_for_language(&settings, &host_uri(), "markdown", "rust")
+            .into_iter()
+            .next();
         assert!(
             result.is_none(),
             "rust should be blocked by markdown's bridge filter"
@@ -479,6 +492,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -491,7 +505,10 @@ mod tests {
         );
 
         // rust should be allowed (no filter)
-        let result = coordinator.get_config_for_language(&settings, "markdown", "rust");
+        let result = coordinator
+            .get_all_configs_for_language(&settings, &host_uri(), "markdown", "rust")
+            .into_iter()
+            .next();
         assert!(
             result.is_some(),
             "rust should be allowed when no filter is set"
@@ -517,6 +534,7 @@ mod tests {
                 languages: vec!["python".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
         servers.insert(
@@ -526,6 +544,7 @@ mod tests {
                 languages: vec!["python".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -537,7 +556,8 @@ mod tests {
             Some(servers),
         );
 
-        let result = coordinator.get_all_configs_for_language(&settings, "markdown", "python");
+        let result =
+            coordinator.get_all_configs_for_language(&settings, &host_uri(), "markdown", "python");
         assert_eq!(result.len(), 2, "should return both pyright and ruff");
 
         // Use HashSet for order-independent comparison (HashMap iteration is non-deterministic)
@@ -547,6 +567,40 @@ mod tests {
         assert!(names.contains("ruff"), "should contain ruff");
     }
 
+    #[test]
+    fn test_get_all_configs_orders_by_priority_then_name() {
+        let coordinator = BridgeCoordinator::new();
+
+        let server = |cmd: &str, priority: Option<i32>| BridgeServerConfig {
+            cmd: vec![cmd.to_string()],
+            languages: vec!["python".to_string()],
+            initialization_options: None,
+            workspace_type: None,
+            priority,
+            ..Default::default()
+        };
+        let mut servers = HashMap::new();
+        servers.insert("basedpyright".to_string(), server("basedpyright", None));
+        servers.insert("pyright".to_string(), server("pyright-langserver", None));
+        servers.insert("ruff".to_string(), server("ruff", Some(10)));
+        servers.insert("pylsp".to_string(), server("pylsp", Some(-1)));
+
+        let settings = WorkspaceSettings::with_language_servers(
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            false,
+            Some(servers),
+        );
+
+        let names: Vec<String> = coordinator
+            .get_all_configs_for_language(&settings, &host_uri(), "markdown", "python")
+            .into_iter()
+            .map(|r| r.server_name)
+            .collect();
+        assert_eq!(names, vec!["ruff", "basedpyright", "pyright", "pylsp"]);
+    }
+
     #[test]
     fn test_get_all_configs_returns_empty_when_blocked_by_filter() {
         let coordinator = BridgeCoordinator::new();
@@ -569,6 +623,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -581,7 +636,8 @@ mod tests {
         );
 
         // rust should be blocked by markdown's bridge filter
-        let result = coordinator.get_all_configs_for_language(&settings, "markdown", "rust");
+        let result =
+            coordinator.get_all_configs_for_language(&settings, &host_uri(), "markdown", "rust");
         assert!(
             result.is_empty(),
             "rust should be blocked by markdown's bridge filter"
@@ -604,6 +660,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -615,7 +672,8 @@ mod tests {
             Some(servers),
         );
 
-        let result = coordinator.get_all_configs_for_language(&settings, "markdown", "rust");
+        let result =
+            coordinator.get_all_configs_for_language(&settings, &host_uri(), "markdown", "rust");
         assert_eq!(result.len(), 1, "should return exactly one server");
         assert_eq!(result[0].server_name, "rust-analyzer");
     }
@@ -640,6 +698,7 @@ mod tests {
                 languages: vec!["rust".to_string()],
                 initialization_options: None,
                 workspace_type: None,
+                ..Default::default()
             },
         );
 
@@ -652,10 +711,146 @@ mod tests {
         );
 
         // "quarto" is not defined, so it inherits from wildcard which blocks all
-        let result = coordinator.get_config_for_language(&settings, "quarto", "rust");
+        let result = coordinator
+            .get_all_configs_for_language(&settings, &host_uri(), "quarto", "rust")
+            .into_iter()
+            .next();
         assert!(
             result.is_none(),
             "quarto should inherit wildcard's empty filter"
         );
     }
+
+    #[test]
+    fn test_host_document_bridging_bypasses_injection_filter() {
+        let coordinator = BridgeCoordinator::new();
+
+        // markdown bridges nothing into injections, but bridges the host document
+        let mut markdown = LanguageSettings::with_bridge(None, None, Some(HashMap::new()));
+        markdown.host = Some(BridgeLanguageConfig { enabled: true });
+        let mut languages = HashMap::new();
+        languages.insert("markdown".to_string(), markdown);
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "marksman".to_string(),
+            BridgeServerConfig {
+                cmd: vec!["marksman".to_string()],
+                languages: vec!["markdown".to_string(), "rust".to_string()],
+                initialization_options: None,
+                workspace_type: None,
+                ..Default::default()
+            },
+        );
+
+        let settings = WorkspaceSettings::with_language_servers(
+            vec![],
+            languages,
+            HashMap::new(),
+            false,
+            Some(servers),
+        );
+
+        let host = coordinator.get_all_configs_for_language(
+            &settings,
+            &host_uri(),
+            "markdown",
+            "markdown",
+        );
+        assert_eq!(host.len(), 1);
+        assert_eq!(host[0].server_name, "marksman");
+        assert!(
+            coordinator
+                .get_all_configs_for_language(&settings, &host_uri(), "markdown", "rust")
+                .is_empty(),
+            "injection filter still applies to other languages"
+        );
+    }
+
+    #[test]
+    fn test_get_config_for_server_looks_up_by_name() {
+        let coordinator = BridgeCoordinator::new();
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "rust-analyzer".to_string(),
+            BridgeServerConfig {
+                cmd: vec!["rust-analyzer".to_string()],
+                languages: vec!["rust".to_string()],
+                initialization_options: None,
+                workspace_type: None,
+                ..Default::default()
+            },
+        );
+
+        let settings = WorkspaceSettings::with_language_servers(
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            false,
+            Some(servers),
+        );
+
+        let resolved = coordinator
+            .get_config_for_server(&settings, "rust-analyzer")
+            .expect("configured server should be found by name");
+        assert_eq!(resolved.server_name, "rust-analyzer");
+        assert_eq!(resolved.config.cmd, vec!["rust-analyzer".to_string()]);
+
+        assert!(
+            coordinator
+                .get_config_for_server(&settings, "pyright")
+                .is_none()
+        );
+        assert!(coordinator.get_config_for_server(&settings, "_").is_none());
+    }
+
+    #[test]
+    fn test_get_config_for_server_resolves_instance_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path().join("app");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("package.json"), "{}").unwrap();
+        let host_uri = Url::from_file_path(project.join("README.md")).unwrap();
+
+        let coordinator = BridgeCoordinator::new();
+        let mut servers = HashMap::new();
+        // npm-scoped names contain the instance key separator themselves
+        servers.insert(
+            "@vue/language-server".to_string(),
+            BridgeServerConfig {
+                cmd: vec!["vue-language-server".to_string()],
+                languages: vec!["vue".to_string()],
+                root_markers: Some(vec!["package.json".to_string()]),
+                ..Default::default()
+            },
+        );
+        let settings = WorkspaceSettings::with_language_servers(
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            false,
+            Some(servers),
+        );
+
+        let configs =
+            coordinator.get_all_configs_for_language(&settings, &host_uri, "markdown", "vue");
+        assert_eq!(configs.len(), 1);
+        let key = format!("@vue/language-server@{}", project.display());
+        assert_eq!(configs[0].server_name, key);
+
+        let instance = coordinator
+            .get_config_for_server(&settings, &key)
+            .expect("instance key should resolve to its server");
+        assert_eq!(instance.server_name, key);
+        assert_eq!(
+            coordinator.pool().instance(&key).root_uri(),
+            Some(Url::from_directory_path(&project).unwrap().to_string())
+        );
+        assert!(
+            coordinator
+                .get_config_for_server(&settings, "@vue/language-server")
+                .is_some()
+        );
+    }
 }
@@ -18,6 +18,7 @@
 //! - [`ConnectionHandle`]: Handle to a single downstream connection (ADR-0014)
 //! - [`ConnectionState`]: State machine for connection lifecycle
 
+mod capability_routing;
 mod connection_action;
 mod connection_handle;
 mod connection_state;
@@ -27,6 +28,8 @@ mod execute;
 mod handshake;
 mod liveness_timeout;
 mod message_sender;
+mod restart_policy;
+mod server_control;
 mod shutdown;
 mod shutdown_timeout;
 #[cfg(test)]
@@ -35,8 +38,11 @@ pub(super) mod test_helpers;
 pub(crate) use connection_action::BridgeError;
 use connection_action::{ConnectionAction, decide_connection_action};
 use handshake::perform_lsp_handshake;
+use restart_policy::{RestartDecision, RestartHistory, RestartPolicy};
 
-pub(crate) use connection_handle::{ConnectionHandle, NotificationSendResult};
+pub(crate) use connection_handle::{
+    ConnectionHandle, NotificationSendResult, ServerTriggerCharacters,
+};
 pub(crate) use connection_state::ConnectionState;
 use document_tracker::DocumentOpenDecision;
 use document_tracker::DocumentTracker;
@@ -49,7 +55,7 @@ use std::collections::{HashMap, HashSet};
 use std::io;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tokio::sync::Mutex;
 use url::Url;
@@ -66,10 +72,26 @@ use super::protocol::{VirtualDocumentUri, build_didopen_notification};
 /// within this duration, the connection attempt fails with a timeout error.
 pub(crate) const INIT_TIMEOUT_SECS: u64 = 30;
 
+/// How often idle servers are checked against their `timeouts.idle`.
+pub(crate) const IDLE_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Handshake timeout for a server: `timeouts.initialize`, else [`INIT_TIMEOUT_SECS`].
+pub(crate) fn init_timeout(
+    server_config: &crate::config::settings::BridgeServerConfig,
+) -> Duration {
+    let secs = server_config
+        .timeouts
+        .as_ref()
+        .and_then(|t| t.initialize)
+        .unwrap_or(INIT_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
 use super::actor::{
     OUTBOUND_QUEUE_CAPACITY, ResponseRouter, UpstreamNotification, spawn_reader_task_for_language,
 };
 use super::connection::AsyncBridgeConnection;
+use super::server_instance::ServerInstance;
 
 /// Upstream request ID type supporting both numeric and string IDs per LSP spec.
 ///
@@ -240,6 +262,25 @@ pub struct LanguageServerPool {
     ///
     /// This prevents infinite retry loops when a server's handshake consistently panics.
     consecutive_panic_counts: std::sync::Mutex<HashMap<String, u32>>,
+    /// Restart history per server, consulted before respawning a server whose
+    /// previous connection failed (crash backoff and restart limit).
+    restart_histories: std::sync::Mutex<HashMap<String, RestartHistory>>,
+    /// Idle timeout per server, recorded when the connection is created from
+    /// the server's `timeouts.idle` setting.
+    idle_timeouts: std::sync::Mutex<HashMap<String, Duration>>,
+    /// When each server was first seen without open virtual documents.
+    ///
+    /// Maintained by `shutdown_idle_connections()`; cleared as soon as the
+    /// server has an open document again.
+    idle_since: std::sync::Mutex<HashMap<String, Instant>>,
+    /// Servers stopped with `kakehashi.bridge.stop`; not respawned until restarted.
+    stopped_servers: std::sync::Mutex<HashSet<String>>,
+    /// Configured server and project root of each per-root instance key.
+    ///
+    /// Registered when an instance is resolved for a document; keys without an
+    /// entry are plain server names. The root is sent as `rootUri` on
+    /// initialize in place of the workspace root.
+    instances: std::sync::Mutex<HashMap<String, ServerInstance>>,
     /// Workspace root URI forwarded from upstream client.
     ///
     /// Set via `set_root_uri()` after receiving the upstream initialize request.
@@ -287,6 +328,11 @@ impl LanguageServerPool {
             upstream_request_registry: std::sync::Mutex::new(HashMap::new()),
             cancel_metrics: CancelForwardingMetrics::default(),
             consecutive_panic_counts: std::sync::Mutex::new(HashMap::new()),
+            restart_histories: std::sync::Mutex::new(HashMap::new()),
+            idle_timeouts: std::sync::Mutex::new(HashMap::new()),
+            idle_since: std::sync::Mutex::new(HashMap::new()),
+            stopped_servers: std::sync::Mutex::new(HashSet::new()),
+            instances: std::sync::Mutex::new(HashMap::new()),
             root_uri: std::sync::Mutex::new(None),
             upstream_tx,
             upstream_rx: std::sync::Mutex::new(Some(upstream_rx)),
@@ -302,11 +348,35 @@ impl LanguageServerPool {
     }
 
     /// Get the workspace root URI.
-    fn root_uri(&self) -> Option<String> {
+    pub(crate) fn root_uri(&self) -> Option<String> {
         let root_uri = self.root_uri.lock().unwrap_or_else(|e| e.into_inner());
         root_uri.clone()
     }
 
+    /// Remember which server and root a per-root instance key belongs to.
+    pub(crate) fn register_instance(&self, instance: &ServerInstance) {
+        if instance.root.is_none() {
+            return;
+        }
+        self.instances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(instance.key(), instance.clone());
+    }
+
+    /// The instance a pool key stands for.
+    ///
+    /// Keys that were never registered as per-root instances are taken to be
+    /// plain server names.
+    pub(crate) fn instance(&self, key: &str) -> ServerInstance {
+        self.instances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| ServerInstance::single(key))
+    }
+
     /// Take the upstream notification receiver for forwarding to the editor.
     ///
     /// Returns `Some(receiver)` on first call, `None` on subsequent calls.
@@ -333,6 +403,23 @@ impl LanguageServerPool {
         self.connections.lock().await
     }
 
+    /// Trigger characters of every Ready downstream server, keyed by server name.
+    ///
+    /// Servers still initializing (or failed) are skipped; they are picked up
+    /// once their handshake completes and `TriggerCharactersChanged` is sent.
+    pub(crate) async fn ready_server_trigger_characters(
+        &self,
+    ) -> Vec<(String, ServerTriggerCharacters)> {
+        let connections = self.connections.lock().await;
+        let mut triggers: Vec<_> = connections
+            .iter()
+            .filter(|(_, handle)| handle.state() == ConnectionState::Ready)
+            .map(|(name, handle)| (name.clone(), handle.trigger_characters()))
+            .collect();
+        triggers.sort_by(|a, b| a.0.cmp(&b.0));
+        triggers
+    }
+
     // ========================================
     // DocumentTracker delegation methods
     // ========================================
@@ -539,7 +626,7 @@ impl LanguageServerPool {
         self.get_or_create_connection_with_timeout(
             server_name,
             server_config,
-            Duration::from_secs(INIT_TIMEOUT_SECS),
+            init_timeout(server_config),
         )
         .await
     }
@@ -572,7 +659,7 @@ impl LanguageServerPool {
             .get_or_create_connection_with_timeout(
                 server_name,
                 server_config,
-                Duration::from_secs(INIT_TIMEOUT_SECS),
+                init_timeout(server_config),
             )
             .await;
     }
@@ -706,6 +793,14 @@ impl LanguageServerPool {
                 return Err(err.into());
             }
             ConnectionAction::SpawnNew => {
+                if self.is_stopped(server_name) {
+                    return Err(BridgeError::Stopped.into());
+                }
+
+                // A previous connection that failed (or could not be spawned)
+                // makes this a restart, subject to the server's restart policy
+                self.check_restart_allowed(server_name, server_config, existing_state)?;
+
                 // Remove stale connection if present (Failed or Closed state)
                 if existing_state.is_some() {
                     connections.remove(server_name);
@@ -713,8 +808,31 @@ impl LanguageServerPool {
             }
         }
 
-        // Spawn new connection (while holding lock to prevent concurrent spawns)
-        let mut conn = AsyncBridgeConnection::spawn(server_config.cmd.clone()).await?;
+        // Spawn new connection (while holding lock to prevent concurrent spawns),
+        // or connect to the already running server when `connect` is configured
+        let conn = match &server_config.connect {
+            Some(address) => AsyncBridgeConnection::connect(address).await,
+            None => {
+                AsyncBridgeConnection::spawn_with(
+                    server_config.cmd.clone(),
+                    &server_config.env.clone().unwrap_or_default(),
+                    server_config.cwd.as_deref().map(std::path::Path::new),
+                )
+                .await
+            }
+        };
+        {
+            let mut histories = self
+                .restart_histories
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            histories
+                .entry(server_name.to_string())
+                .or_default()
+                .record_spawn_result(conn.is_ok());
+        }
+        let mut conn = conn?;
+        let process_id = conn.process_id();
 
         // Split connection immediately
         let (writer, reader) = conn.split();
@@ -736,9 +854,9 @@ impl LanguageServerPool {
         let dynamic_capabilities = Arc::new(DynamicCapabilityRegistry::new());
 
         // Now spawn reader task with liveness timeout - it can route the initialize response immediately
-        // Liveness timeout is configured via LivenessTimeout::default() (60s per ADR-0018 Tier 2)
+        // Liveness timeout comes from `timeouts.liveness` (default 60s per ADR-0018 Tier 2)
         // Server name is passed for structured logging (observability improvement)
-        let liveness_timeout = liveness_timeout::LivenessTimeout::default();
+        let liveness_timeout = Self::liveness_timeout(server_name, server_config);
         let reader_handle = spawn_reader_task_for_language(
             reader,
             Arc::clone(&router),
@@ -760,6 +878,14 @@ impl LanguageServerPool {
             dynamic_capabilities,
         ));
 
+        if let Some(pid) = process_id {
+            handle.set_process_id(pid);
+        }
+        if let Some(secs) = server_config.timeouts.as_ref().and_then(|t| t.request) {
+            handle.set_request_timeout(Duration::from_secs(secs));
+        }
+        self.record_idle_timeout(server_name, server_config);
+
         // Insert into pool immediately so concurrent requests see Initializing state
         connections.insert(server_name.to_string(), Arc::clone(&handle));
 
@@ -776,9 +902,13 @@ impl LanguageServerPool {
         // - If this function's caller is cancelled, only the JoinHandle await is dropped
         // - The spawned handshake task continues to completion
         let init_options = server_config.initialization_options.clone();
-        let root_uri = self.root_uri();
+        let root_uri = self
+            .instance(server_name)
+            .root_uri()
+            .or_else(|| self.root_uri());
         let handle_for_handshake = Arc::clone(&handle);
         let server_name_for_log = server_name.to_string();
+        let upstream_tx = self.upstream_tx.clone();
         let handshake_task = tokio::spawn(async move {
             let init_result = tokio::time::timeout(
                 timeout,
@@ -803,6 +933,8 @@ impl LanguageServerPool {
                     );
                     handle_for_handshake.set_server_capabilities(capabilities);
                     handle_for_handshake.set_state(ConnectionState::Ready);
+                    // New (or restarted) server: its trigger characters may differ
+                    let _ = upstream_tx.send(UpstreamNotification::TriggerCharactersChanged);
                     Ok(())
                 }
                 Ok(Err(e)) => {
@@ -892,6 +1024,105 @@ impl LanguageServerPool {
         }
     }
 
+    /// Apply the server's restart policy before respawning it.
+    ///
+    /// Spawning counts as a restart when the previous connection is still in
+    /// the pool (Failed or Closed) or the previous spawn attempt failed. The
+    /// first restart in the window is immediate; later ones back off
+    /// exponentially. Once the restart limit is exceeded the user is notified
+    /// and the server is not restarted again.
+    fn check_restart_allowed(
+        &self,
+        server_name: &str,
+        server_config: &crate::config::settings::BridgeServerConfig,
+        existing_state: Option<ConnectionState>,
+    ) -> io::Result<()> {
+        let policy = RestartPolicy::from_config(server_config.restart.as_ref());
+        let mut histories = self
+            .restart_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let history = histories.entry(server_name.to_string()).or_default();
+        if existing_state.is_none() && !history.spawn_failed() {
+            return Ok(());
+        }
+
+        match history.try_restart(&policy, Instant::now()) {
+            RestartDecision::Allow => {
+                log::info!(
+                    target: "kakehashi::bridge::connection",
+                    "[{}] Restarting server (restart {} of {} within {:?})",
+                    server_name,
+                    history.recent_restarts(),
+                    policy.max_restarts,
+                    policy.window
+                );
+                Ok(())
+            }
+            RestartDecision::BackOff(delay) => {
+                log::debug!(
+                    target: "kakehashi::bridge::connection",
+                    "[{}] Restart delayed for another {:?}",
+                    server_name,
+                    delay
+                );
+                Err(BridgeError::BackingOff.into())
+            }
+            RestartDecision::GiveUp => {
+                let restarts = history.recent_restarts();
+                log::error!(
+                    target: "kakehashi::bridge::connection",
+                    "[{}] Server failed after {} restarts within {:?}; giving up",
+                    server_name,
+                    restarts,
+                    policy.window
+                );
+                let _ = self.upstream_tx.send(UpstreamNotification::ServerGaveUp {
+                    server_name: server_name.to_string(),
+                    restarts,
+                });
+                Err(BridgeError::RestartLimitReached.into())
+            }
+            RestartDecision::GivenUp => Err(BridgeError::RestartLimitReached.into()),
+        }
+    }
+
+    /// Liveness timeout for a server: `timeouts.liveness` when valid, else the default.
+    fn liveness_timeout(
+        server_name: &str,
+        server_config: &crate::config::settings::BridgeServerConfig,
+    ) -> liveness_timeout::LivenessTimeout {
+        let Some(secs) = server_config.timeouts.as_ref().and_then(|t| t.liveness) else {
+            return liveness_timeout::LivenessTimeout::default();
+        };
+        liveness_timeout::LivenessTimeout::new(Duration::from_secs(secs)).unwrap_or_else(|e| {
+            log::warn!(
+                target: "kakehashi::bridge::connection",
+                "[{}] Ignoring timeouts.liveness: {}",
+                server_name,
+                e
+            );
+            liveness_timeout::LivenessTimeout::default()
+        })
+    }
+
+    /// Remember the server's `timeouts.idle` for the idle sweeper.
+    fn record_idle_timeout(
+        &self,
+        server_name: &str,
+        server_config: &crate::config::settings::BridgeServerConfig,
+    ) {
+        let mut idle_timeouts = self.idle_timeouts.lock().unwrap_or_else(|e| e.into_inner());
+        match server_config.timeouts.as_ref().and_then(|t| t.idle) {
+            Some(secs) => {
+                idle_timeouts.insert(server_name.to_string(), Duration::from_secs(secs));
+            }
+            None => {
+                idle_timeouts.remove(server_name);
+            }
+        }
+    }
+
     /// Forward a $/cancelRequest notification to a downstream language server.
     ///
     /// Translates the upstream (client) request ID to the downstream (language server)
@@ -1568,6 +1799,7 @@ mod tests {
             languages: vec!["lua".to_string()],
             initialization_options: None,
             workspace_type: None,
+            ..Default::default()
         };
 
         let result = pool
@@ -3430,4 +3662,155 @@ mod tests {
             "should have forwarded cancel to both servers"
         );
     }
+
+    // ========================================
+    // Restart policy and idle shutdown tests
+    // ========================================
+
+    /// Config for a server that never answers initialize, with the given restart policy.
+    fn crash_looping_config(
+        restart: crate::config::settings::BridgeRestartPolicy,
+    ) -> crate::config::settings::BridgeServerConfig {
+        crate::config::settings::BridgeServerConfig {
+            restart: Some(restart),
+            ..devnull_config()
+        }
+    }
+
+    /// Restarts after the first one wait for the crash backoff.
+    #[tokio::test]
+    async fn failed_server_restart_is_delayed_by_backoff() {
+        let pool = LanguageServerPool::new();
+        let config = crash_looping_config(crate::config::settings::BridgeRestartPolicy {
+            max_restarts: Some(5),
+            window_secs: None,
+            backoff_ms: Some(60_000),
+            max_backoff_ms: Some(60_000),
+        });
+        pool.connections.lock().await.insert(
+            "lua".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+
+        // First restart is immediate (handshake then times out -> Failed)
+        let first = pool
+            .get_or_create_connection_with_timeout("lua", &config, Duration::from_millis(50))
+            .await;
+        assert_eq!(
+            first.err().expect("restart should fail").kind(),
+            io::ErrorKind::TimedOut
+        );
+
+        // Second restart falls inside the backoff
+        let second = pool
+            .get_or_create_connection_with_timeout("lua", &config, Duration::from_millis(50))
+            .await;
+        assert_eq!(
+            second.err().expect("restart should fail").to_string(),
+            BridgeError::BackingOff.to_string()
+        );
+        assert_eq!(
+            pool.connections.lock().await["lua"].state(),
+            ConnectionState::Failed,
+            "failed connection stays in the pool until the restart is allowed"
+        );
+    }
+
+    /// Exceeding the restart limit notifies the user once and stops restarting.
+    #[tokio::test]
+    async fn crash_looping_server_is_given_up_with_notification() {
+        let pool = LanguageServerPool::new();
+        let mut upstream_rx = pool.take_upstream_rx().unwrap();
+        let config = crash_looping_config(crate::config::settings::BridgeRestartPolicy {
+            max_restarts: Some(1),
+            window_secs: None,
+            backoff_ms: Some(0),
+            max_backoff_ms: None,
+        });
+        pool.connections.lock().await.insert(
+            "lua".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+
+        let restarted = pool
+            .get_or_create_connection_with_timeout("lua", &config, Duration::from_millis(50))
+            .await;
+        assert_eq!(
+            restarted.err().expect("restart should fail").kind(),
+            io::ErrorKind::TimedOut
+        );
+
+        for _ in 0..2 {
+            let result = pool
+                .get_or_create_connection_with_timeout("lua", &config, Duration::from_millis(50))
+                .await;
+            assert_eq!(
+                result.err().expect("restart should fail").to_string(),
+                BridgeError::RestartLimitReached.to_string()
+            );
+        }
+
+        assert_eq!(
+            upstream_rx.try_recv().unwrap(),
+            UpstreamNotification::ServerGaveUp {
+                server_name: "lua".to_string(),
+                restarts: 1,
+            }
+        );
+        assert!(
+            upstream_rx.try_recv().is_err(),
+            "user is notified only once"
+        );
+    }
+
+    /// A server without open documents is shut down once its idle timeout expires.
+    #[tokio::test]
+    async fn idle_server_is_shut_down_and_removed() {
+        let pool = LanguageServerPool::new();
+        let handle = create_handle_with_state(ConnectionState::Ready).await;
+        pool.connections
+            .lock()
+            .await
+            .insert("lua".to_string(), Arc::clone(&handle));
+        pool.idle_timeouts
+            .lock()
+            .unwrap()
+            .insert("lua".to_string(), Duration::ZERO);
+
+        pool.shutdown_idle_connections().await;
+
+        assert!(!pool.connections.lock().await.contains_key("lua"));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handle.state() == ConnectionState::Ready {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("idle server should begin shutting down");
+    }
+
+    /// A server with an open virtual document is never considered idle.
+    #[tokio::test]
+    async fn server_with_open_documents_is_not_idle() {
+        let pool = LanguageServerPool::new();
+        let handle = create_handle_with_state(ConnectionState::Ready).await;
+        pool.connections
+            .lock()
+            .await
+            .insert("lua".to_string(), Arc::clone(&handle));
+        pool.idle_timeouts
+            .lock()
+            .unwrap()
+            .insert("lua".to_string(), Duration::ZERO);
+        let host_uri = test_host_uri("doc");
+        let virtual_uri = VirtualDocumentUri::new(&url_to_uri(&host_uri), "lua", TEST_ULID_LUA_0);
+        pool.document_tracker
+            .should_send_didopen(&host_uri, &virtual_uri, "lua")
+            .await;
+
+        pool.shutdown_idle_connections().await;
+
+        assert!(pool.connections.lock().await.contains_key("lua"));
+        assert_eq!(handle.state(), ConnectionState::Ready);
+    }
 }
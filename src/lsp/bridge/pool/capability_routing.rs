@@ -0,0 +1,170 @@
+//! Capability-aware server selection.
+//!
+//! Several servers may be configured for one injection language (e.g., pyright
+//! and ruff for Python), each implementing only part of the protocol. Requests
+//! for a single-server feature should go to the first server that actually
+//! supports it, instead of waiting for an error or timeout from one that does not.
+//!
+//! Capabilities are only known once a server's handshake has completed, so the
+//! selection distinguishes three cases per candidate:
+//! - Ready and supports the method: selected immediately
+//! - Ready without support: skipped
+//! - Capabilities unknown (not spawned yet, or still initializing): remembered
+//!   as a fallback, used only if no Ready server supports the method
+//!
+//! Features whose results are merged across servers instead ask every candidate
+//! that is not known to lack the method (see `capable_servers_for_method`).
+
+use super::{ConnectionState, LanguageServerPool};
+use crate::lsp::bridge::ResolvedServerConfig;
+
+/// What is known about a candidate server's support for a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CapabilitySupport {
+    /// Server is Ready and supports the method.
+    Supported,
+    /// Server is Ready and does not support the method.
+    Unsupported,
+    /// Server has not completed its handshake yet.
+    Unknown,
+}
+
+/// Pick the candidate to route a request to.
+///
+/// Returns the index of the first `Supported` candidate; otherwise the first
+/// `Unknown` candidate; otherwise `None` (no server can handle the method).
+pub(super) fn select_capable_candidate(support: &[CapabilitySupport]) -> Option<usize> {
+    support
+        .iter()
+        .position(|s| *s == CapabilitySupport::Supported)
+        .or_else(|| {
+            support
+                .iter()
+                .position(|s| *s == CapabilitySupport::Unknown)
+        })
+}
+
+/// Indices of the candidates to fan a request out to.
+///
+/// Every candidate except those known to lack the method, in candidate order.
+pub(super) fn fan_out_candidates(support: &[CapabilitySupport]) -> Vec<usize> {
+    support
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s != CapabilitySupport::Unsupported)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+impl LanguageServerPool {
+    /// Classify each candidate's support for `method` from existing connections.
+    async fn candidate_support(
+        &self,
+        method: &str,
+        candidates: &[ResolvedServerConfig],
+    ) -> Vec<CapabilitySupport> {
+        let connections = self.connections().await;
+        candidates
+            .iter()
+            .map(|candidate| match connections.get(&candidate.server_name) {
+                Some(handle) if handle.state() == ConnectionState::Ready => {
+                    if handle.has_capability(method) {
+                        CapabilitySupport::Supported
+                    } else {
+                        CapabilitySupport::Unsupported
+                    }
+                }
+                _ => CapabilitySupport::Unknown,
+            })
+            .collect()
+    }
+
+    /// Select the first candidate server that supports `method`.
+    ///
+    /// Candidates are checked in order against the capabilities of existing
+    /// connections; no server is spawned here. Servers whose capabilities are
+    /// not known yet are only used as a fallback (see module docs).
+    ///
+    /// # Arguments
+    /// * `method` - The LSP method to route (e.g., "textDocument/hover")
+    /// * `candidates` - Servers configured for the injection language, in priority order
+    pub(crate) async fn select_server_for_method(
+        &self,
+        method: &str,
+        mut candidates: Vec<ResolvedServerConfig>,
+    ) -> Option<ResolvedServerConfig> {
+        let support = self.candidate_support(method, &candidates).await;
+
+        let Some(index) = select_capable_candidate(&support) else {
+            log::debug!(
+                target: "kakehashi::bridge",
+                "No bridged server supports {} ({} candidates)",
+                method,
+                candidates.len()
+            );
+            return None;
+        };
+        Some(candidates.swap_remove(index))
+    }
+
+    /// Filter candidates down to the servers a merged request should be sent to.
+    ///
+    /// Keeps candidate (priority) order and drops only Ready servers that do not
+    /// support `method`; servers whose capabilities are unknown are kept.
+    pub(crate) async fn capable_servers_for_method(
+        &self,
+        method: &str,
+        candidates: Vec<ResolvedServerConfig>,
+    ) -> Vec<ResolvedServerConfig> {
+        let support = self.candidate_support(method, &candidates).await;
+        let keep = fan_out_candidates(&support);
+        candidates
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| keep.contains(index))
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CapabilitySupport::{Supported, Unknown, Unsupported};
+    use super::*;
+
+    #[test]
+    fn first_supporting_candidate_wins() {
+        assert_eq!(
+            select_capable_candidate(&[Unsupported, Supported, Supported]),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn ready_supporting_candidate_beats_earlier_unknown() {
+        assert_eq!(select_capable_candidate(&[Unknown, Supported]), Some(1));
+    }
+
+    #[test]
+    fn unknown_candidate_is_fallback() {
+        assert_eq!(
+            select_capable_candidate(&[Unsupported, Unknown, Unknown]),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn no_candidate_when_all_ready_servers_lack_support() {
+        assert_eq!(select_capable_candidate(&[Unsupported, Unsupported]), None);
+        assert_eq!(select_capable_candidate(&[]), None);
+    }
+
+    #[test]
+    fn fan_out_skips_only_unsupported_candidates() {
+        assert_eq!(
+            fan_out_candidates(&[Supported, Unsupported, Unknown, Supported]),
+            vec![0, 2, 3]
+        );
+        assert!(fan_out_candidates(&[Unsupported]).is_empty());
+    }
+}
@@ -26,6 +26,12 @@ pub(crate) enum BridgeError {
     Closing,
     /// Server disabled after repeated handshake failures.
     Disabled,
+    /// Server crashed recently; its restart is delayed by the crash backoff.
+    BackingOff,
+    /// Server exceeded its restart limit and is no longer restarted.
+    RestartLimitReached,
+    /// Server was stopped with `kakehashi.bridge.stop` and stays down until restarted.
+    Stopped,
     // === ADR-0015 Single-Writer Loop variants ===
     /// Request queue is full; request rejected with REQUEST_FAILED.
     ///
@@ -75,6 +81,13 @@ impl std::fmt::Display for BridgeError {
                     "bridge: server disabled after repeated handshake failures"
                 )
             }
+            BridgeError::BackingOff => {
+                write!(f, "bridge: server restart delayed after repeated crashes")
+            }
+            BridgeError::RestartLimitReached => {
+                write!(f, "bridge: server stopped after too many restarts")
+            }
+            BridgeError::Stopped => write!(f, "bridge: server stopped"),
             BridgeError::QueueFull => write!(f, "bridge: request queue full"),
             BridgeError::ChannelClosed => write!(f, "bridge: writer channel closed"),
         }
@@ -21,7 +21,13 @@ use std::time::Duration;
 
 use log::warn;
 use tokio::sync::mpsc;
-use tower_lsp_server::ls_types::ServerCapabilities;
+use tower_lsp_server::ls_types::{
+    CallHierarchyServerCapability, CodeActionProviderCapability, ColorProviderCapability,
+    DeclarationCapability, FoldingRangeProviderCapability, HoverProviderCapability,
+    ImplementationProviderCapability, LinkedEditingRangeServerCapabilities, OneOf,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, ServerCapabilities, TypeDefinitionProviderCapability,
+};
 
 use super::connection_action::BridgeError;
 use super::dynamic_capability_registry::DynamicCapabilityRegistry;
@@ -113,8 +119,18 @@ pub(crate) struct ConnectionHandle {
     ///
     /// Updated by the reader task, queried by request handlers via `has_capability()`.
     dynamic_capabilities: Arc<DynamicCapabilityRegistry>,
+    /// Per-request timeout (ADR-0018 Tier 1) from the server's `timeouts.request`.
+    ///
+    /// Set once when the connection is created; `DEFAULT_REQUEST_TIMEOUT` applies
+    /// when unset.
+    request_timeout: OnceLock<Duration>,
+    /// OS process ID of the spawned server (unset for socket connections).
+    process_id: OnceLock<u32>,
 }
 
+/// Default per-request timeout (ADR-0018 Tier 1).
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl ConnectionHandle {
     /// Create a new ConnectionHandle in Ready state (test helper).
     ///
@@ -182,9 +198,28 @@ impl ConnectionHandle {
             next_request_id: AtomicI64::new(2),
             server_capabilities: OnceLock::new(),
             dynamic_capabilities,
+            request_timeout: OnceLock::new(),
+            process_id: OnceLock::new(),
         }
     }
 
+    /// Record the OS process ID of the spawned server.
+    pub(super) fn set_process_id(&self, pid: u32) {
+        let _ = self.process_id.set(pid);
+    }
+
+    /// OS process ID of the spawned server, if it was spawned by kakehashi.
+    pub(crate) fn process_id(&self) -> Option<u32> {
+        self.process_id.get().copied()
+    }
+
+    /// Set the per-request timeout used by `wait_for_response()`.
+    ///
+    /// Only the first call has an effect.
+    pub(super) fn set_request_timeout(&self, timeout: Duration) {
+        let _ = self.request_timeout.set(timeout);
+    }
+
     /// Generate a unique downstream request ID.
     ///
     /// Each call returns the next ID in the sequence (2, 3, 4, ...).
@@ -422,8 +457,16 @@ impl ConnectionHandle {
     /// "textDocument/hover" => caps.hover_provider.is_some(),
     /// ```
     pub(crate) fn has_capability(&self, method: &str) -> bool {
-        // Check dynamic registrations first (may arrive after initialize)
-        if self.dynamic_capabilities().has_registration(method) {
+        // Check dynamic registrations first (may arrive after initialize).
+        // Semantic tokens are registered once for all of their request methods.
+        let registration_method = match method {
+            "textDocument/semanticTokens/full" => "textDocument/semanticTokens",
+            other => other,
+        };
+        if self
+            .dynamic_capabilities()
+            .has_registration(registration_method)
+        {
             return true;
         }
         // Fall back to static capabilities from initialize response
@@ -432,10 +475,200 @@ impl ConnectionHandle {
         };
         match method {
             "textDocument/diagnostic" => caps.diagnostic_provider.is_some(),
+            "textDocument/formatting" => is_enabled(&caps.document_formatting_provider),
+            "textDocument/rangeFormatting" => is_enabled(&caps.document_range_formatting_provider),
+            "textDocument/hover" => matches!(
+                caps.hover_provider,
+                Some(HoverProviderCapability::Simple(true) | HoverProviderCapability::Options(_))
+            ),
+            "textDocument/completion" => caps.completion_provider.is_some(),
+            "completionItem/resolve" => caps
+                .completion_provider
+                .as_ref()
+                .is_some_and(|c| c.resolve_provider == Some(true)),
+            "textDocument/signatureHelp" => caps.signature_help_provider.is_some(),
+            "textDocument/definition" => is_enabled(&caps.definition_provider),
+            "textDocument/typeDefinition" => matches!(
+                caps.type_definition_provider,
+                Some(
+                    TypeDefinitionProviderCapability::Simple(true)
+                        | TypeDefinitionProviderCapability::Options(_)
+                )
+            ),
+            "textDocument/implementation" => matches!(
+                caps.implementation_provider,
+                Some(
+                    ImplementationProviderCapability::Simple(true)
+                        | ImplementationProviderCapability::Options(_)
+                )
+            ),
+            "textDocument/declaration" => matches!(
+                caps.declaration_provider,
+                Some(
+                    DeclarationCapability::Simple(true)
+                        | DeclarationCapability::RegistrationOptions(_)
+                        | DeclarationCapability::Options(_)
+                )
+            ),
+            "textDocument/references" => is_enabled(&caps.references_provider),
+            "textDocument/documentHighlight" => is_enabled(&caps.document_highlight_provider),
+            "textDocument/documentSymbol" => is_enabled(&caps.document_symbol_provider),
+            "textDocument/documentLink" => caps.document_link_provider.is_some(),
+            "textDocument/documentColor" | "textDocument/colorPresentation" => matches!(
+                caps.color_provider,
+                Some(
+                    ColorProviderCapability::Simple(true)
+                        | ColorProviderCapability::ColorProvider(_)
+                        | ColorProviderCapability::Options(_)
+                )
+            ),
+            "textDocument/rename" => is_enabled(&caps.rename_provider),
+            "textDocument/moniker" => is_enabled(&caps.moniker_provider),
+            "textDocument/prepareCallHierarchy" => matches!(
+                caps.call_hierarchy_provider,
+                Some(
+                    CallHierarchyServerCapability::Simple(true)
+                        | CallHierarchyServerCapability::Options(_)
+                )
+            ),
+            "textDocument/inlayHint" => is_enabled(&caps.inlay_hint_provider),
+            "textDocument/foldingRange" => matches!(
+                caps.folding_range_provider,
+                Some(
+                    FoldingRangeProviderCapability::Simple(true)
+                        | FoldingRangeProviderCapability::FoldingProvider(_)
+                        | FoldingRangeProviderCapability::Options(_)
+                )
+            ),
+            "textDocument/linkedEditingRange" => matches!(
+                caps.linked_editing_range_provider,
+                Some(
+                    LinkedEditingRangeServerCapabilities::Simple(true)
+                        | LinkedEditingRangeServerCapabilities::Options(_)
+                        | LinkedEditingRangeServerCapabilities::RegistrationOptions(_)
+                )
+            ),
+            "textDocument/onTypeFormatting" => caps.document_on_type_formatting_provider.is_some(),
+            "textDocument/codeAction" => matches!(
+                caps.code_action_provider,
+                Some(
+                    CodeActionProviderCapability::Simple(true)
+                        | CodeActionProviderCapability::Options(_)
+                )
+            ),
+            "codeAction/resolve" => matches!(
+                &caps.code_action_provider,
+                Some(CodeActionProviderCapability::Options(options))
+                    if options.resolve_provider == Some(true)
+            ),
+            "textDocument/codeLens" => caps.code_lens_provider.is_some(),
+            "codeLens/resolve" => caps
+                .code_lens_provider
+                .as_ref()
+                .is_some_and(|c| c.resolve_provider == Some(true)),
+            "workspace/executeCommand" => caps.execute_command_provider.is_some(),
+            "textDocument/semanticTokens/full" => caps
+                .semantic_tokens_provider
+                .as_ref()
+                .map(semantic_tokens_options)
+                .is_some_and(|options| {
+                    !matches!(
+                        options.full,
+                        None | Some(SemanticTokensFullOptions::Bool(false))
+                    )
+                }),
             _ => false,
         }
     }
 
+    /// Legend the downstream server uses to encode semantic tokens.
+    ///
+    /// A dynamic registration takes precedence over the initialize response.
+    pub(crate) fn semantic_tokens_legend(&self) -> Option<SemanticTokensLegend> {
+        let dynamic = self
+            .dynamic_capabilities()
+            .registration_options("textDocument/semanticTokens")
+            .into_iter()
+            .find_map(|options| serde_json::from_value(options.get("legend")?.clone()).ok());
+        dynamic.or_else(|| {
+            self.server_capabilities()?
+                .semantic_tokens_provider
+                .as_ref()
+                .map(|provider| semantic_tokens_options(provider).legend.clone())
+        })
+    }
+
+    /// Trigger characters the downstream server advertises for completion,
+    /// signature help and on-type formatting, from both its initialize response
+    /// and dynamic registrations.
+    ///
+    /// Characters keep their first-seen order and are deduplicated.
+    pub(crate) fn trigger_characters(&self) -> ServerTriggerCharacters {
+        let mut triggers = ServerTriggerCharacters::default();
+
+        if let Some(caps) = self.server_capabilities() {
+            if let Some(completion) = &caps.completion_provider {
+                push_unique(
+                    &mut triggers.completion,
+                    completion.trigger_characters.iter().flatten(),
+                );
+            }
+            if let Some(signature_help) = &caps.signature_help_provider {
+                push_unique(
+                    &mut triggers.signature_help,
+                    signature_help.trigger_characters.iter().flatten(),
+                );
+                push_unique(
+                    &mut triggers.signature_help_retrigger,
+                    signature_help.retrigger_characters.iter().flatten(),
+                );
+            }
+            if let Some(on_type) = &caps.document_on_type_formatting_provider {
+                push_unique(
+                    &mut triggers.on_type_formatting,
+                    std::iter::once(&on_type.first_trigger_character)
+                        .chain(on_type.more_trigger_character.iter().flatten()),
+                );
+            }
+        }
+
+        let strings = |options: &serde_json::Value, key: &str| -> Vec<String> {
+            options
+                .get(key)
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default()
+        };
+        let dynamic = self.dynamic_capabilities();
+        for options in dynamic.registration_options("textDocument/completion") {
+            push_unique(
+                &mut triggers.completion,
+                &strings(&options, "triggerCharacters"),
+            );
+        }
+        for options in dynamic.registration_options("textDocument/signatureHelp") {
+            push_unique(
+                &mut triggers.signature_help,
+                &strings(&options, "triggerCharacters"),
+            );
+            push_unique(
+                &mut triggers.signature_help_retrigger,
+                &strings(&options, "retriggerCharacters"),
+            );
+        }
+        for options in dynamic.registration_options("textDocument/onTypeFormatting") {
+            let first: Option<String> = options
+                .get("firstTriggerCharacter")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+            push_unique(&mut triggers.on_type_formatting, first.iter());
+            push_unique(
+                &mut triggers.on_type_formatting,
+                &strings(&options, "moreTriggerCharacter"),
+            );
+        }
+
+        triggers
+    }
+
     /// Begin graceful shutdown of the connection.
     ///
     /// Transitions the connection to Closing state, which:
@@ -471,8 +704,9 @@ impl ConnectionHandle {
     /// 1. Transition to Closing state (new operations rejected)
     /// 2. Stop writer task and reclaim the writer via 3-phase protocol
     /// 3. Send LSP "shutdown" request directly and wait for response
-    /// 4. Send LSP "exit" notification directly
-    /// 5. Force kill process (Unix: SIGTERM→SIGKILL escalation)
+    /// 4. Send LSP "exit" notification directly (spawned servers only)
+    /// 5. Force kill process (Unix: SIGTERM→SIGKILL escalation), or close the
+    ///    socket of a server reached via `connect`
     /// 6. Transition to Closed state
     ///
     /// # Writer Task Synchronization (ADR-0015)
@@ -558,9 +792,13 @@ impl ConnectionHandle {
             }
 
             // 4. Send exit notification directly (no response expected)
-            let exit_notification = build_exit_notification();
-            // Best effort - if this fails, process will be killed anyway
-            let _ = writer.write_message(&exit_notification).await;
+            // Shared servers reached over a socket must keep running, so they
+            // only get the shutdown request before the socket is closed
+            if writer.owns_process() {
+                let exit_notification = build_exit_notification();
+                // Best effort - if this fails, process will be killed anyway
+                let _ = writer.write_message(&exit_notification).await;
+            }
 
             Ok(())
         }
@@ -572,6 +810,7 @@ impl ConnectionHandle {
         //
         // Unix: SIGTERM->SIGKILL escalation with 2s grace period
         // Windows: TerminateProcess directly (no grace period)
+        // Socket connections: the socket is closed instead
         if let Some(ref mut writer) = maybe_writer {
             writer.force_kill_with_escalation().await;
         }
@@ -651,8 +890,9 @@ impl ConnectionHandle {
 
     /// Wait for a response with timeout, cleaning up on timeout.
     ///
-    /// Takes the oneshot receiver and request ID, waits for response with
-    /// 30-second timeout. On timeout, removes the pending entry from router.
+    /// Takes the oneshot receiver and request ID, waits for response with the
+    /// configured request timeout (30 seconds by default). On timeout, removes
+    /// the pending entry from router.
     ///
     /// Also checks for liveness timeout failure and transitions to Failed state
     /// if the reader task signaled a liveness timeout (ADR-0014 Phase 3).
@@ -663,9 +903,13 @@ impl ConnectionHandle {
     ) -> io::Result<serde_json::Value> {
         use tokio::time::timeout;
 
-        const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+        let request_timeout = self
+            .request_timeout
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
 
-        match timeout(REQUEST_TIMEOUT, response_rx).await {
+        match timeout(request_timeout, response_rx).await {
             Ok(Ok(response)) => {
                 // Check if this was an error response from liveness timeout
                 // If so, transition to Failed state (ADR-0014 Phase 3)
@@ -694,6 +938,43 @@ impl ConnectionHandle {
     }
 }
 
+/// Options shared by both forms of the semantic tokens capability.
+fn semantic_tokens_options(provider: &SemanticTokensServerCapabilities) -> &SemanticTokensOptions {
+    match provider {
+        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+        SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            &options.semantic_tokens_options
+        }
+    }
+}
+
+/// Whether a `boolean | Options` capability is enabled.
+///
+/// `Some(false)` is how servers explicitly opt out, so presence alone is not enough.
+fn is_enabled<T>(capability: &Option<OneOf<bool, T>>) -> bool {
+    matches!(capability, Some(OneOf::Left(true) | OneOf::Right(_)))
+}
+
+/// Completion, signature help and on-type formatting trigger characters of one
+/// downstream server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ServerTriggerCharacters {
+    pub(crate) completion: Vec<String>,
+    pub(crate) signature_help: Vec<String>,
+    pub(crate) signature_help_retrigger: Vec<String>,
+    /// `firstTriggerCharacter` followed by `moreTriggerCharacter`.
+    pub(crate) on_type_formatting: Vec<String>,
+}
+
+/// Append `chars` to `target`, skipping ones already present.
+fn push_unique<'a>(target: &mut Vec<String>, chars: impl IntoIterator<Item = &'a String>) {
+    for c in chars {
+        if !target.contains(c) {
+            target.push(c.clone());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -743,6 +1024,55 @@ mod tests {
         assert_eq!(id3, 4, "Third user request ID should be 4");
     }
 
+    /// Test that graceful shutdown of a server reached via `connect` sends only
+    /// the shutdown request (no exit, so the shared server keeps running) and
+    /// then closes the socket.
+    #[tokio::test]
+    async fn graceful_shutdown_over_socket_skips_exit_and_closes_socket() {
+        use crate::lsp::bridge::connection::BridgeReader;
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read_half, mut write_half) = stream.into_split();
+            let mut reader = BridgeReader::new(read_half);
+            let mut methods = Vec::new();
+            // read_message fails once the client closes the socket
+            while let Ok(message) = reader.read_message().await {
+                let method = message["method"].as_str().unwrap_or_default().to_string();
+                if method == "shutdown" {
+                    let body = serde_json::json!({
+                        "jsonrpc": "2.0", "id": message["id"], "result": null
+                    })
+                    .to_string();
+                    let framed = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+                    write_half.write_all(framed.as_bytes()).await.unwrap();
+                }
+                methods.push(method);
+            }
+            methods
+        });
+
+        let mut conn = AsyncBridgeConnection::connect(&address)
+            .await
+            .expect("should connect to server");
+        let (writer, reader) = conn.split();
+        let router = Arc::new(ResponseRouter::new());
+        let reader_handle = spawn_reader_task(reader, Arc::clone(&router));
+        let handle = ConnectionHandle::new(writer, router, reader_handle);
+
+        handle.graceful_shutdown().await.unwrap();
+
+        let methods = tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .expect("socket should be closed after shutdown")
+            .unwrap();
+        assert_eq!(methods, vec!["shutdown".to_string()]);
+        assert_eq!(handle.state(), ConnectionState::Closed);
+    }
+
     /// Test that ConnectionHandle wraps connection with state (ADR-0015).
     /// State should start as Ready (since constructor is called after init handshake),
     /// and can transition via set_state().
@@ -1407,6 +1737,24 @@ mod tests {
         ConnectionHandle::new(writer, router, reader_handle)
     }
 
+    /// A configured request timeout bounds `wait_for_response()` and cleans up the entry.
+    #[tokio::test]
+    async fn wait_for_response_uses_configured_request_timeout() {
+        let handle = spawn_sink_handle().await;
+        handle.set_request_timeout(Duration::from_millis(50));
+
+        let (request_id, response_rx) = handle.register_request().unwrap();
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle.wait_for_response(request_id, response_rx),
+        )
+        .await
+        .expect("configured timeout should fire well before 5s");
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(handle.router().pending_count(), 0);
+    }
+
     /// Test that server_capabilities returns None before set_server_capabilities is called.
     #[tokio::test]
     async fn server_capabilities_returns_none_before_init() {
@@ -1457,6 +1805,36 @@ mod tests {
         assert!(caps.completion_provider.is_none());
     }
 
+    /// Test that trigger characters merge static and dynamic sources without duplicates.
+    #[tokio::test]
+    async fn trigger_characters_merge_static_and_dynamic() {
+        use tower_lsp_server::ls_types::{CompletionOptions, Registration, SignatureHelpOptions};
+
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec![".".to_string(), "<".to_string()]),
+                ..Default::default()
+            }),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_string()]),
+                retrigger_characters: Some(vec![",".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        handle.dynamic_capabilities().register(vec![Registration {
+            id: "completion-1".to_string(),
+            method: "textDocument/completion".to_string(),
+            register_options: Some(serde_json::json!({"triggerCharacters": ["<", "@"]})),
+        }]);
+
+        let triggers = handle.trigger_characters();
+        assert_eq!(triggers.completion, vec![".", "<", "@"]);
+        assert_eq!(triggers.signature_help, vec!["("]);
+        assert_eq!(triggers.signature_help_retrigger, vec![","]);
+    }
+
     // ========================================
     // Unified Capability Check Tests
     // ========================================
@@ -1532,4 +1910,157 @@ mod tests {
         // This method has no static capability mapping
         assert!(!handle.has_capability("textDocument/someUnknownMethod"));
     }
+
+    /// Test has_capability honours explicit `false` for boolean-or-options capabilities.
+    #[tokio::test]
+    async fn has_capability_formatting_respects_explicit_false() {
+        use tower_lsp_server::ls_types::{DocumentRangeFormattingOptions, WorkDoneProgressOptions};
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            document_formatting_provider: Some(OneOf::Left(false)),
+            document_range_formatting_provider: Some(OneOf::Right(
+                DocumentRangeFormattingOptions {
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                },
+            )),
+            ..Default::default()
+        });
+        assert!(!handle.has_capability("textDocument/formatting"));
+        assert!(handle.has_capability("textDocument/rangeFormatting"));
+    }
+
+    /// Test has_capability for a server implementing only part of the protocol
+    /// (like ruff: hover and code actions, but no completion or definition).
+    #[tokio::test]
+    async fn has_capability_partial_protocol_server() {
+        use tower_lsp_server::ls_types::CodeActionOptions;
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                resolve_provider: Some(true),
+                ..Default::default()
+            })),
+            definition_provider: Some(OneOf::Left(false)),
+            ..Default::default()
+        });
+        assert!(handle.has_capability("textDocument/hover"));
+        assert!(handle.has_capability("textDocument/codeAction"));
+        assert!(handle.has_capability("codeAction/resolve"));
+        assert!(!handle.has_capability("textDocument/completion"));
+        assert!(!handle.has_capability("textDocument/definition"));
+        assert!(!handle.has_capability("textDocument/references"));
+    }
+
+    /// Call hierarchy is detected from the static `callHierarchyProvider`.
+    #[tokio::test]
+    async fn call_hierarchy_capability() {
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            ..Default::default()
+        });
+        assert!(handle.has_capability("textDocument/prepareCallHierarchy"));
+
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(false)),
+            ..Default::default()
+        });
+        assert!(!handle.has_capability("textDocument/prepareCallHierarchy"));
+    }
+
+    /// Folding, linked editing and on-type formatting are detected from static
+    /// capabilities, and on-type trigger characters merge both sources.
+    #[tokio::test]
+    async fn folding_linked_editing_and_on_type_capabilities() {
+        use tower_lsp_server::ls_types::{DocumentOnTypeFormattingOptions, Registration};
+
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(
+                false,
+            )),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: "}".to_string(),
+                more_trigger_character: Some(vec![";".to_string()]),
+            }),
+            ..Default::default()
+        });
+        handle.dynamic_capabilities().register(vec![Registration {
+            id: "on-type-1".to_string(),
+            method: "textDocument/onTypeFormatting".to_string(),
+            register_options: Some(serde_json::json!({
+                "firstTriggerCharacter": "\n",
+                "moreTriggerCharacter": [";"]
+            })),
+        }]);
+
+        assert!(handle.has_capability("textDocument/foldingRange"));
+        assert!(!handle.has_capability("textDocument/linkedEditingRange"));
+        assert!(handle.has_capability("textDocument/onTypeFormatting"));
+        assert_eq!(
+            handle.trigger_characters().on_type_formatting,
+            vec!["}", ";", "\n"]
+        );
+    }
+
+    /// Code lens resolve requires `resolveProvider`.
+    #[tokio::test]
+    async fn code_lens_capability() {
+        use tower_lsp_server::ls_types::CodeLensOptions;
+
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
+            ..Default::default()
+        });
+        assert!(handle.has_capability("textDocument/codeLens"));
+        assert!(!handle.has_capability("codeLens/resolve"));
+        assert!(!handle.has_capability("workspace/executeCommand"));
+    }
+
+    /// Semantic tokens need `full` support, and a dynamic registration supplies
+    /// both the capability and the legend.
+    #[tokio::test]
+    async fn semantic_tokens_capability_and_legend() {
+        use tower_lsp_server::ls_types::{Registration, SemanticTokenType};
+
+        let handle = spawn_sink_handle().await;
+        handle.set_server_capabilities(ServerCapabilities {
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    legend: SemanticTokensLegend {
+                        token_types: vec![SemanticTokenType::STRUCT],
+                        token_modifiers: vec![],
+                    },
+                    range: Some(true),
+                    full: None,
+                    ..Default::default()
+                }),
+            ),
+            ..Default::default()
+        });
+        assert!(!handle.has_capability("textDocument/semanticTokens/full"));
+        assert_eq!(
+            handle.semantic_tokens_legend().unwrap().token_types,
+            vec![SemanticTokenType::STRUCT]
+        );
+
+        handle.dynamic_capabilities().register(vec![Registration {
+            id: "semantic-tokens-1".to_string(),
+            method: "textDocument/semanticTokens".to_string(),
+            register_options: Some(serde_json::json!({
+                "legend": { "tokenTypes": ["builtinType"], "tokenModifiers": ["mutable"] },
+                "full": true
+            })),
+        }]);
+        assert!(handle.has_capability("textDocument/semanticTokens/full"));
+        let legend = handle.semantic_tokens_legend().unwrap();
+        assert_eq!(legend.token_types[0].as_str(), "builtinType");
+        assert_eq!(legend.token_modifiers[0].as_str(), "mutable");
+    }
 }
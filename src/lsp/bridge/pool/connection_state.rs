@@ -19,7 +19,8 @@
 /// - Closing -> Closed (on completion/timeout)
 /// - Failed -> Closed (direct, no LSP handshake - stdin unavailable)
 /// - Failed connections are removed from pool, next request spawns fresh server
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum ConnectionState {
     /// Server spawned, initialize request sent, awaiting response
     Initializing,
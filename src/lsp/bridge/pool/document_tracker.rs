@@ -322,6 +322,65 @@ impl DocumentTracker {
         }
     }
 
+    /// Number of virtual documents open on the given server.
+    ///
+    /// Used by the idle sweeper and the bridge status command.
+    pub(super) async fn open_document_count(&self, server_name: &str) -> usize {
+        let versions = self.document_versions.lock().await;
+        versions.get(server_name).map_or(0, HashMap::len)
+    }
+
+    /// Forget every virtual document opened on the given server.
+    ///
+    /// Used when a server is stopped or restarted: the new process knows none
+    /// of the documents, so each must be opened again. Returns the removed
+    /// documents with their host URIs. A document URI stays marked as opened while another server
+    /// still tracks it.
+    ///
+    /// # Lock Ordering
+    ///
+    /// Acquires `document_versions` first, then `host_to_virtual`.
+    pub(super) async fn remove_server_documents(
+        &self,
+        server_name: &str,
+    ) -> Vec<(Url, OpenedVirtualDoc)> {
+        let mut versions = self.document_versions.lock().await;
+        versions.remove(server_name);
+
+        let mut removed = Vec::new();
+        let mut host_map = self.host_to_virtual.lock().await;
+        for (host_uri, docs) in host_map.iter_mut() {
+            docs.retain(|doc| {
+                if doc.server_name == server_name {
+                    removed.push((host_uri.clone(), doc.clone()));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        host_map.retain(|_, docs| !docs.is_empty());
+
+        let mut opened = match self.opened_documents.write() {
+            Ok(opened) => opened,
+            Err(poisoned) => {
+                warn!(
+                    target: "kakehashi::lock_recovery",
+                    "Recovered from poisoned opened_documents lock in remove_server_documents()"
+                );
+                poisoned.into_inner()
+            }
+        };
+        for (_, doc) in &removed {
+            let uri_string = doc.virtual_uri.to_uri_string();
+            if !versions.values().any(|docs| docs.contains_key(&uri_string)) {
+                opened.remove(&uri_string);
+            }
+        }
+
+        removed
+    }
+
     /// Remove and return all virtual documents for a host URI.
     ///
     /// Used by did_close module for cleanup.
@@ -1084,4 +1143,41 @@ mod tests {
             Some("tsgo".to_string())
         );
     }
+
+    #[tokio::test]
+    async fn remove_server_documents_forgets_only_that_server() {
+        let tracker = DocumentTracker::new();
+        let host_uri = Url::parse("file:///test/doc.md").unwrap();
+        let lua_uri = VirtualDocumentUri::new(&url_to_uri(&host_uri), "lua", TEST_ULID_LUA_0);
+        let python_uri = VirtualDocumentUri::new(&url_to_uri(&host_uri), "python", TEST_ULID_LUA_1);
+
+        // lua is open on two servers, python on one
+        for (uri, server) in [
+            (&lua_uri, "lua-ls"),
+            (&lua_uri, "stylua"),
+            (&python_uri, "lua-ls"),
+        ] {
+            tracker.should_send_didopen(&host_uri, uri, server).await;
+            tracker.mark_document_opened(uri);
+        }
+        assert_eq!(tracker.open_document_count("lua-ls").await, 2);
+
+        let removed = tracker.remove_server_documents("lua-ls").await;
+
+        assert_eq!(removed.len(), 2);
+        assert!(
+            removed
+                .iter()
+                .all(|(host, doc)| host == &host_uri && doc.server_name == "lua-ls")
+        );
+        assert_eq!(tracker.open_document_count("lua-ls").await, 0);
+        assert_eq!(tracker.open_document_count("stylua").await, 1);
+        // Still open on stylua
+        assert!(tracker.is_document_opened(&lua_uri));
+        assert!(!tracker.is_document_opened(&python_uri));
+        assert_eq!(
+            tracker.get_server_for_virtual_uri(&lua_uri).await,
+            Some("stylua".to_string())
+        );
+    }
 }
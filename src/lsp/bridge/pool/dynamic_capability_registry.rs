@@ -70,6 +70,27 @@ impl DynamicCapabilityRegistry {
         };
         guard.values().any(|r| r.method == method)
     }
+
+    /// Registration options of every registration for `method`.
+    ///
+    /// Registrations without options are skipped.
+    pub(crate) fn registration_options(&self, method: &str) -> Vec<serde_json::Value> {
+        let guard = match self.registrations.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                warn!(
+                    target: "kakehashi::lock_recovery",
+                    "Recovered from poisoned lock in DynamicCapabilityRegistry::registration_options()"
+                );
+                poisoned.into_inner()
+            }
+        };
+        guard
+            .values()
+            .filter(|r| r.method == method)
+            .filter_map(|r| r.register_options.clone())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -178,6 +199,29 @@ mod tests {
         assert!(registry.has_registration("textDocument/completion"));
     }
 
+    #[test]
+    fn registration_options_collects_options_for_method() {
+        let registry = DynamicCapabilityRegistry::new();
+        let mut with_options = make_registration("1", "textDocument/completion");
+        with_options.register_options = Some(serde_json::json!({"triggerCharacters": ["@"]}));
+
+        registry.register(vec![
+            with_options,
+            make_registration("2", "textDocument/completion"),
+            make_registration("3", "textDocument/hover"),
+        ]);
+
+        assert_eq!(
+            registry.registration_options("textDocument/completion"),
+            vec![serde_json::json!({"triggerCharacters": ["@"]})]
+        );
+        assert!(
+            registry
+                .registration_options("textDocument/hover")
+                .is_empty()
+        );
+    }
+
     #[test]
     fn poison_recovery_on_write() {
         let registry = Arc::new(DynamicCapabilityRegistry::new());
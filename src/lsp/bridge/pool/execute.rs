@@ -6,6 +6,8 @@
 //!
 //! - `execute_bridge_request`: Full lifecycle including connection lookup
 //! - `execute_bridge_request_with_handle`: Lifecycle with pre-fetched connection handle
+//! - `execute_server_request`: Lifecycle for requests that are not tied to a
+//!   virtual document (e.g., `codeAction/resolve`), skipping steps 2, 3 and 7
 //!
 //! The lifecycle steps are:
 //! 1. Get or create a connection
@@ -215,6 +217,59 @@ impl LanguageServerPool {
         )
         .await
     }
+
+    /// Execute a request that is not bound to a virtual document.
+    ///
+    /// Resolve-style requests (`codeAction/resolve`, ...) carry everything the
+    /// downstream server needs in the item itself, so there is no virtual URI
+    /// to build and no didOpen to send. The virtual document the item came from
+    /// is still open on the server because it was opened by the original request.
+    ///
+    /// # Arguments
+    ///
+    /// * `server_name` - The server name from config
+    /// * `server_config` - The server configuration containing command and options
+    /// * `upstream_request_id` - The original request ID from the upstream client
+    /// * `build_request` - Closure to build the JSON-RPC request from the allocated request ID
+    /// * `transform_response` - Closure to transform the raw JSON-RPC response
+    pub(crate) async fn execute_server_request<T>(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        upstream_request_id: UpstreamId,
+        build_request: impl FnOnce(RequestId) -> serde_json::Value,
+        transform_response: impl FnOnce(serde_json::Value) -> T,
+    ) -> io::Result<T> {
+        let handle = self
+            .get_or_create_connection(server_name, server_config)
+            .await?;
+
+        self.register_upstream_request(upstream_request_id.clone(), server_name);
+
+        let (request_id, response_rx) =
+            match handle.register_request_with_upstream(Some(upstream_request_id.clone())) {
+                Ok(result) => result,
+                Err(e) => {
+                    self.unregister_upstream_request(&upstream_request_id, server_name);
+                    return Err(e);
+                }
+            };
+
+        let request = build_request(request_id);
+
+        // Queue the request via single-writer loop (ADR-0015)
+        if let Err(e) = handle.send_request(request, request_id) {
+            handle.router().remove(request_id);
+            self.unregister_upstream_request(&upstream_request_id, server_name);
+            return Err(e.into());
+        }
+
+        let response = handle.wait_for_response(request_id, response_rx).await;
+
+        self.unregister_upstream_request(&upstream_request_id, server_name);
+
+        Ok(transform_response(response?))
+    }
 }
 
 #[cfg(test)]
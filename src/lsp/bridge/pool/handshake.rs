@@ -14,7 +14,7 @@
 
 use std::io;
 
-use tower_lsp_server::ls_types::ServerCapabilities;
+use tower_lsp_server::ls_types::{Registration, ServerCapabilities};
 
 use super::ConnectionHandle;
 use super::connection_handle::NotificationSendResult;
@@ -66,6 +66,7 @@ pub(super) async fn perform_lsp_handshake(
         .and_then(|r| r.get("capabilities"))
         .cloned()
         .unwrap_or_default();
+    record_static_type_hierarchy(handle, &caps_value);
     let capabilities = serde_json::from_value::<ServerCapabilities>(caps_value).unwrap_or_default();
 
     // 4. Send initialized notification via the single-writer loop
@@ -88,3 +89,46 @@ pub(super) async fn perform_lsp_handshake(
 
     Ok(capabilities)
 }
+
+/// Registration ID for a type hierarchy provider declared in the initialize response.
+const STATIC_TYPE_HIERARCHY_REGISTRATION_ID: &str = "kakehashi-static-type-hierarchy";
+
+/// Record a static `typeHierarchyProvider` as a registration.
+///
+/// ls_types' `ServerCapabilities` has no `typeHierarchyProvider` field (LSP 3.17),
+/// so the capability would be lost when the response is parsed. Recording it in
+/// the dynamic registry lets `has_capability()` see it; the server never
+/// unregisters an ID it did not choose, so the entry lives as long as the connection.
+fn record_static_type_hierarchy(handle: &ConnectionHandle, caps_value: &serde_json::Value) {
+    let enabled = match caps_value.get("typeHierarchyProvider") {
+        None | Some(serde_json::Value::Null) | Some(serde_json::Value::Bool(false)) => false,
+        Some(_) => true,
+    };
+    if enabled {
+        handle.dynamic_capabilities().register(vec![Registration {
+            id: STATIC_TYPE_HIERARCHY_REGISTRATION_ID.to_string(),
+            method: "textDocument/prepareTypeHierarchy".to_string(),
+            register_options: None,
+        }]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::bridge::pool::ConnectionState;
+    use crate::lsp::bridge::pool::test_helpers::create_handle_with_state;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn static_type_hierarchy_provider_is_recorded() {
+        let handle = create_handle_with_state(ConnectionState::Ready).await;
+        record_static_type_hierarchy(&handle, &json!({ "typeHierarchyProvider": true }));
+        assert!(handle.has_capability("textDocument/prepareTypeHierarchy"));
+
+        let handle = create_handle_with_state(ConnectionState::Ready).await;
+        record_static_type_hierarchy(&handle, &json!({ "typeHierarchyProvider": false }));
+        record_static_type_hierarchy(&handle, &json!({ "hoverProvider": true }));
+        assert!(!handle.has_capability("textDocument/prepareTypeHierarchy"));
+    }
+}
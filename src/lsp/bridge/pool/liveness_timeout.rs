@@ -16,19 +16,9 @@ use std::time::Duration;
 ///
 /// # Valid Range
 ///
-/// The ADR recommends 30-120 seconds for Tier 2 timeouts, but this type
-/// currently does not enforce this range at construction time.
-///
-/// # Future Work
-///
-/// TODO: When adding user-configurable liveness timeout, introduce both
-/// validation and configuration together:
-/// - Add a `new(duration: Duration) -> Result<Self, Error>` constructor
-///   that validates the duration is within the 30-120s range
-/// - Add configuration support in workspace settings (e.g., `bridge.livenessTimeoutSecs`)
-/// - Consider per-language-server timeout configuration
-/// - Keep validation and configuration changes in the same PR to ensure
-///   users can only configure valid values
+/// The ADR recommends 30-120 seconds for Tier 2 timeouts. [`LivenessTimeout::new`]
+/// rejects values outside this range; it is used for the per-server
+/// `timeouts.liveness` setting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct LivenessTimeout(Duration);
 
@@ -36,6 +26,34 @@ impl LivenessTimeout {
     /// Default timeout: 60 seconds (middle of ADR-0018 recommended 30-120s range)
     const DEFAULT_SECS: u64 = 60;
 
+    /// Minimum valid timeout: 30 seconds
+    const MIN_SECS: u64 = 30;
+
+    /// Maximum valid timeout: 120 seconds
+    const MAX_SECS: u64 = 120;
+
+    /// Create a new LivenessTimeout with validation.
+    ///
+    /// # Returns
+    /// - `Ok(LivenessTimeout)` if duration is within 30-120s (inclusive)
+    /// - `Err(io::Error)` with InvalidInput kind if duration is out of range
+    pub(crate) fn new(duration: Duration) -> std::io::Result<Self> {
+        let min = Duration::from_secs(Self::MIN_SECS);
+        let max = Duration::from_secs(Self::MAX_SECS);
+        if duration < min || duration > max {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Liveness timeout must be between {}s and {}s, got {:?}",
+                    Self::MIN_SECS,
+                    Self::MAX_SECS,
+                    duration
+                ),
+            ));
+        }
+        Ok(Self(duration))
+    }
+
     /// Get the inner Duration value.
     pub(crate) fn as_duration(&self) -> Duration {
         self.0
@@ -47,3 +65,22 @@ impl Default for LivenessTimeout {
         Self(Duration::from_secs(Self::DEFAULT_SECS))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ADR-0018 specifies the liveness timeout should be 30-120s.
+    #[test]
+    fn liveness_timeout_validates_range() {
+        assert!(LivenessTimeout::new(Duration::from_secs(29)).is_err());
+        assert!(LivenessTimeout::new(Duration::from_secs(121)).is_err());
+        assert_eq!(
+            LivenessTimeout::new(Duration::from_secs(30))
+                .unwrap()
+                .as_duration(),
+            Duration::from_secs(30)
+        );
+        assert!(LivenessTimeout::new(Duration::from_secs(120)).is_ok());
+    }
+}
@@ -0,0 +1,244 @@
+//! Restart policy and crash backoff for downstream language servers.
+//!
+//! A server whose previous connection Failed (crash, handshake error, liveness
+//! timeout) is respawned on the next request. Without limits, a crash-looping
+//! server would be respawned for every request. [`RestartHistory`] records the
+//! restarts inside a sliding window and decides whether the next restart may
+//! proceed now, must wait (exponential backoff), or is refused for good.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::settings::BridgeRestartPolicy;
+
+/// Resolved restart policy with defaults applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RestartPolicy {
+    /// Restarts allowed within `window` before giving up
+    pub(super) max_restarts: u32,
+    /// Sliding window over which restarts are counted
+    pub(super) window: Duration,
+    /// Delay before the second restart in the window
+    pub(super) backoff: Duration,
+    /// Upper bound for the backoff delay
+    pub(super) max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    const DEFAULT_MAX_RESTARTS: u32 = 5;
+    const DEFAULT_WINDOW_SECS: u64 = 180;
+    const DEFAULT_BACKOFF_MS: u64 = 1_000;
+    const DEFAULT_MAX_BACKOFF_MS: u64 = 30_000;
+
+    /// Resolve the policy from the server's `restart` setting.
+    pub(super) fn from_config(config: Option<&BridgeRestartPolicy>) -> Self {
+        let config = config.cloned().unwrap_or_default();
+        Self {
+            max_restarts: config.max_restarts.unwrap_or(Self::DEFAULT_MAX_RESTARTS),
+            window: Duration::from_secs(config.window_secs.unwrap_or(Self::DEFAULT_WINDOW_SECS)),
+            backoff: Duration::from_millis(config.backoff_ms.unwrap_or(Self::DEFAULT_BACKOFF_MS)),
+            max_backoff: Duration::from_millis(
+                config
+                    .max_backoff_ms
+                    .unwrap_or(Self::DEFAULT_MAX_BACKOFF_MS),
+            ),
+        }
+    }
+
+    /// Delay required after the most recent restart, given `recent` restarts
+    /// already in the window.
+    ///
+    /// The first restart is immediate; afterwards the delay doubles with each
+    /// restart, capped at `max_backoff`.
+    fn backoff_after(&self, recent: usize) -> Duration {
+        if recent == 0 {
+            return Duration::ZERO;
+        }
+        let exponent = u32::try_from(recent - 1).unwrap_or(u32::MAX).min(31);
+        self.backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_backoff)
+    }
+}
+
+/// Outcome of asking whether a server may be restarted now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RestartDecision {
+    /// Restart now; the attempt has been recorded
+    Allow,
+    /// Too soon after the previous restart; retry after the given delay
+    BackOff(Duration),
+    /// The restart limit was just exceeded; the caller should notify the user
+    GiveUp,
+    /// The server was already given up on
+    GivenUp,
+}
+
+/// Restart history of a single server.
+#[derive(Debug, Default)]
+pub(super) struct RestartHistory {
+    /// Times of the restarts inside the current window (oldest first)
+    restarts: VecDeque<Instant>,
+    /// Set once the restart limit is exceeded
+    gave_up: bool,
+    /// Set when spawning (or connecting to) the server failed, so the next
+    /// attempt counts as a restart even though no Failed connection remains
+    spawn_failed: bool,
+}
+
+impl RestartHistory {
+    /// Whether the next spawn is a restart after a failure.
+    pub(super) fn spawn_failed(&self) -> bool {
+        self.spawn_failed
+    }
+
+    /// Record the outcome of spawning the server process or socket connection.
+    pub(super) fn record_spawn_result(&mut self, ok: bool) {
+        self.spawn_failed = !ok;
+    }
+
+    /// Number of restarts recorded in the current window.
+    pub(super) fn recent_restarts(&self) -> usize {
+        self.restarts.len()
+    }
+
+    /// Decide whether a restart may proceed at `now`, recording it if so.
+    pub(super) fn try_restart(&mut self, policy: &RestartPolicy, now: Instant) -> RestartDecision {
+        if self.gave_up {
+            return RestartDecision::GivenUp;
+        }
+
+        while let Some(&oldest) = self.restarts.front() {
+            if now.saturating_duration_since(oldest) >= policy.window {
+                self.restarts.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.restarts.len() >= policy.max_restarts as usize {
+            self.gave_up = true;
+            return RestartDecision::GiveUp;
+        }
+
+        if let Some(&last) = self.restarts.back() {
+            let ready_at = last + policy.backoff_after(self.restarts.len());
+            if now < ready_at {
+                return RestartDecision::BackOff(ready_at - now);
+            }
+        }
+
+        self.restarts.push_back(now);
+        RestartDecision::Allow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts,
+            window: Duration::from_secs(60),
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+
+    #[test]
+    fn from_config_applies_defaults_and_overrides() {
+        let defaults = RestartPolicy::from_config(None);
+        assert_eq!(defaults.max_restarts, 5);
+        assert_eq!(defaults.window, Duration::from_secs(180));
+        assert_eq!(defaults.backoff, Duration::from_secs(1));
+        assert_eq!(defaults.max_backoff, Duration::from_secs(30));
+
+        let custom = RestartPolicy::from_config(Some(&BridgeRestartPolicy {
+            max_restarts: Some(2),
+            window_secs: None,
+            backoff_ms: Some(250),
+            max_backoff_ms: None,
+        }));
+        assert_eq!(custom.max_restarts, 2);
+        assert_eq!(custom.window, Duration::from_secs(180));
+        assert_eq!(custom.backoff, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn backoff_doubles_and_is_capped() {
+        let policy = policy(10);
+        assert_eq!(policy.backoff_after(0), Duration::ZERO);
+        assert_eq!(policy.backoff_after(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff_after(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff_after(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff_after(4), Duration::from_secs(4));
+        assert_eq!(policy.backoff_after(usize::MAX), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn first_restart_is_immediate_and_second_backs_off() {
+        let policy = policy(5);
+        let mut history = RestartHistory::default();
+        let start = Instant::now();
+
+        assert_eq!(history.try_restart(&policy, start), RestartDecision::Allow);
+        assert_eq!(
+            history.try_restart(&policy, start + Duration::from_millis(200)),
+            RestartDecision::BackOff(Duration::from_millis(800))
+        );
+        assert_eq!(
+            history.try_restart(&policy, start + Duration::from_secs(1)),
+            RestartDecision::Allow
+        );
+        assert_eq!(history.recent_restarts(), 2);
+    }
+
+    #[test]
+    fn exceeding_max_restarts_gives_up_once() {
+        let policy = policy(2);
+        let mut history = RestartHistory::default();
+        let start = Instant::now();
+
+        assert_eq!(history.try_restart(&policy, start), RestartDecision::Allow);
+        let later = start + Duration::from_secs(1);
+        assert_eq!(history.try_restart(&policy, later), RestartDecision::Allow);
+        let later = later + Duration::from_secs(10);
+        assert_eq!(history.try_restart(&policy, later), RestartDecision::GiveUp);
+        // Even after the window has passed, a given-up server stays down
+        let much_later = later + Duration::from_secs(600);
+        assert_eq!(
+            history.try_restart(&policy, much_later),
+            RestartDecision::GivenUp
+        );
+    }
+
+    #[test]
+    fn restarts_outside_window_are_forgotten() {
+        let policy = policy(2);
+        let mut history = RestartHistory::default();
+        let start = Instant::now();
+
+        assert_eq!(history.try_restart(&policy, start), RestartDecision::Allow);
+        let later = start + Duration::from_secs(2);
+        assert_eq!(history.try_restart(&policy, later), RestartDecision::Allow);
+
+        // Both restarts fall out of the 60s window: the next one is immediate
+        let after_window = later + Duration::from_secs(60);
+        assert_eq!(
+            history.try_restart(&policy, after_window),
+            RestartDecision::Allow
+        );
+        assert_eq!(history.recent_restarts(), 1);
+    }
+
+    #[test]
+    fn spawn_failure_is_remembered_until_next_success() {
+        let mut history = RestartHistory::default();
+        assert!(!history.spawn_failed());
+        history.record_spawn_result(false);
+        assert!(history.spawn_failed());
+        history.record_spawn_result(true);
+        assert!(!history.spawn_failed());
+    }
+}
@@ -0,0 +1,431 @@
+//! Inspection and manual control of downstream language servers.
+//!
+//! Backs the `kakehashi.bridge.status`, `kakehashi.bridge.stop` and
+//! `kakehashi.bridge.restart` commands, which let users see what is running
+//! and recover a wedged server without restarting the whole LSP session.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use url::Url;
+
+use super::{
+    ConnectionHandle, ConnectionHandleSender, ConnectionState, GlobalShutdownTimeout,
+    LanguageServerPool, OpenedVirtualDoc, init_timeout,
+};
+use crate::config::settings::BridgeServerConfig;
+
+/// Snapshot of one downstream server, as reported by `kakehashi.bridge.status`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerStatus {
+    /// Server name from config
+    pub(crate) name: String,
+    /// Connection state; stopped servers are reported as `closed`
+    pub(crate) state: ConnectionState,
+    /// Whether the server was stopped with `kakehashi.bridge.stop`
+    pub(crate) stopped: bool,
+    /// OS process ID (`None` for socket connections and stopped servers)
+    pub(crate) pid: Option<u32>,
+    /// Virtual documents currently open on the server
+    pub(crate) open_documents: usize,
+    /// Requests awaiting a response from the server
+    pub(crate) pending_requests: usize,
+}
+
+impl LanguageServerPool {
+    /// Status of every known server, sorted by name.
+    pub(crate) async fn server_statuses(&self) -> Vec<ServerStatus> {
+        let handles: Vec<(String, Arc<ConnectionHandle>)> = {
+            let connections = self.connections.lock().await;
+            connections
+                .iter()
+                .map(|(name, handle)| (name.clone(), Arc::clone(handle)))
+                .collect()
+        };
+
+        let mut statuses = Vec::with_capacity(handles.len());
+        for (name, handle) in handles {
+            statuses.push(ServerStatus {
+                open_documents: self.document_tracker.open_document_count(&name).await,
+                state: handle.state(),
+                stopped: false,
+                pid: handle.process_id(),
+                pending_requests: handle.router().pending_count(),
+                name,
+            });
+        }
+        for name in self.stopped_server_names() {
+            if !statuses.iter().any(|status| status.name == name) {
+                statuses.push(ServerStatus {
+                    name,
+                    state: ConnectionState::Closed,
+                    stopped: true,
+                    pid: None,
+                    open_documents: 0,
+                    pending_requests: 0,
+                });
+            }
+        }
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    /// Names of all servers with a connection in the pool, sorted.
+    pub(crate) async fn server_names(&self) -> Vec<String> {
+        let connections = self.connections.lock().await;
+        let mut names: Vec<String> = connections.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Stop a server and keep it down until it is restarted.
+    ///
+    /// The stop is recorded for the configured server, so no instance of it
+    /// is spawned for another project root either. Returns `false` if the
+    /// server had no connection.
+    pub(crate) async fn stop_server(&self, server_name: &str) -> bool {
+        self.stopped_servers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.instance(server_name).server_name);
+        let (had_connection, _) = self.take_server(server_name).await;
+        had_connection
+    }
+
+    /// Restart a server and re-open its virtual documents on the new process.
+    ///
+    /// The restart clears the server's stopped flag and restart history, so a
+    /// server that was stopped or given up on (restart policy) comes back.
+    /// `contents` returns the current (language, region_id, content) tuples of a
+    /// host document; documents whose region no longer exists are not re-opened.
+    ///
+    /// A server without a connection (e.g., one that was stopped) is not
+    /// spawned here: a server name may stand for per-root instances, which come
+    /// back on the next request for the root they serve.
+    ///
+    /// Returns the number of re-opened virtual documents.
+    pub(crate) async fn restart_server(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        contents: impl Fn(&Url) -> Vec<(String, String, String)>,
+    ) -> io::Result<usize> {
+        let (had_connection, docs) = self.take_server(server_name).await;
+
+        self.stopped_servers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.instance(server_name).server_name);
+        self.restart_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(server_name);
+        self.consecutive_panic_counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(server_name);
+        if !had_connection {
+            return Ok(0);
+        }
+
+        let handle = self
+            .get_or_create_connection_wait_ready(
+                server_name,
+                server_config,
+                init_timeout(server_config),
+            )
+            .await?;
+
+        let mut docs_by_host: HashMap<Url, Vec<OpenedVirtualDoc>> = HashMap::new();
+        for (host_uri, doc) in docs {
+            docs_by_host.entry(host_uri).or_default().push(doc);
+        }
+
+        let mut reopened = 0;
+        for (host_uri, docs) in docs_by_host {
+            let regions = contents(&host_uri);
+            for doc in docs {
+                let Some((_, _, content)) = regions.iter().find(|(language, region_id, _)| {
+                    language == doc.virtual_uri.language()
+                        && region_id == doc.virtual_uri.region_id()
+                }) else {
+                    continue;
+                };
+                self.ensure_document_opened(
+                    &mut ConnectionHandleSender(&handle),
+                    &host_uri,
+                    &doc.virtual_uri,
+                    content,
+                    server_name,
+                )
+                .await?;
+                reopened += 1;
+            }
+        }
+        Ok(reopened)
+    }
+
+    /// Whether the server (any instance of it) was stopped with
+    /// `kakehashi.bridge.stop`.
+    pub(super) fn is_stopped(&self, server_name: &str) -> bool {
+        self.stopped_servers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&self.instance(server_name).server_name)
+    }
+
+    fn stopped_server_names(&self) -> Vec<String> {
+        self.stopped_servers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Remove a server from the pool, forget its documents and shut it down.
+    ///
+    /// Returns whether a connection existed, and the forgotten documents.
+    async fn take_server(&self, server_name: &str) -> (bool, Vec<(Url, OpenedVirtualDoc)>) {
+        let handle = self.connections.lock().await.remove(server_name);
+        let docs = self
+            .document_tracker
+            .remove_server_documents(server_name)
+            .await;
+        self.idle_since
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(server_name);
+
+        let Some(handle) = handle else {
+            return (false, docs);
+        };
+
+        match handle.state() {
+            ConnectionState::Ready | ConnectionState::Initializing => {
+                log::info!(
+                    target: "kakehashi::bridge",
+                    "Shutting down {} connection on request",
+                    server_name
+                );
+                let shutdown = tokio::time::timeout(
+                    GlobalShutdownTimeout::default().as_duration(),
+                    handle.graceful_shutdown(),
+                )
+                .await;
+                if !matches!(shutdown, Ok(Ok(()))) {
+                    log::warn!(
+                        target: "kakehashi::bridge",
+                        "Graceful shutdown of {} did not complete",
+                        server_name
+                    );
+                    handle.complete_shutdown();
+                }
+            }
+            ConnectionState::Failed => handle.complete_shutdown(),
+            ConnectionState::Closing | ConnectionState::Closed => {}
+        }
+        (true, docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::bridge::pool::test_helpers::*;
+    use crate::lsp::bridge::protocol::VirtualDocumentUri;
+    use crate::lsp::bridge::server_instance::ServerInstance;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn status_reports_state_documents_and_stopped_servers() {
+        let pool = LanguageServerPool::new();
+        pool.connections.lock().await.insert(
+            "lua".to_string(),
+            create_handle_with_state(ConnectionState::Ready).await,
+        );
+        let host_uri = test_host_uri("doc");
+        let virtual_uri = VirtualDocumentUri::new(&url_to_uri(&host_uri), "lua", TEST_ULID_LUA_0);
+        pool.document_tracker
+            .should_send_didopen(&host_uri, &virtual_uri, "lua")
+            .await;
+        pool.stopped_servers
+            .lock()
+            .unwrap()
+            .insert("pyright".to_string());
+
+        let statuses = pool.server_statuses().await;
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].name, "lua");
+        assert_eq!(statuses[0].state, ConnectionState::Ready);
+        assert!(!statuses[0].stopped);
+        assert_eq!(statuses[0].open_documents, 1);
+        assert_eq!(statuses[0].pending_requests, 0);
+        assert_eq!(statuses[1].name, "pyright");
+        assert!(statuses[1].stopped);
+        assert_eq!(
+            serde_json::to_value(&statuses[0]).unwrap()["openDocuments"],
+            1
+        );
+        assert_eq!(
+            serde_json::to_value(&statuses[0]).unwrap()["state"],
+            "ready"
+        );
+    }
+
+    #[tokio::test]
+    async fn stopped_server_is_not_respawned_until_restarted() {
+        let pool = LanguageServerPool::new();
+        let config = devnull_config();
+        pool.connections.lock().await.insert(
+            "lua".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+
+        assert!(pool.stop_server("lua").await);
+        assert!(pool.connections.lock().await.is_empty());
+
+        let result = pool.get_or_create_connection("lua", &config).await;
+        assert_eq!(
+            result
+                .err()
+                .expect("stopped server must not spawn")
+                .to_string(),
+            "bridge: server stopped"
+        );
+        assert!(!pool.stop_server("lua").await, "already stopped");
+    }
+
+    fn register_instances(pool: &LanguageServerPool, server_name: &str, roots: &[&str]) {
+        for root in roots {
+            pool.register_instance(&ServerInstance {
+                server_name: server_name.to_string(),
+                root: Some(PathBuf::from(root)),
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_applies_to_instances_for_every_root() {
+        let pool = LanguageServerPool::new();
+        let config = devnull_config();
+        register_instances(&pool, "rust-analyzer", &["/project-a", "/project-b"]);
+        register_instances(&pool, "pyright", &["/project-a"]);
+        pool.connections.lock().await.insert(
+            "rust-analyzer@/project-a".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+
+        assert!(pool.stop_server("rust-analyzer@/project-a").await);
+        for key in ["rust-analyzer@/project-b", "rust-analyzer"] {
+            let result = pool.get_or_create_connection(key, &config).await;
+            assert_eq!(
+                result
+                    .err()
+                    .expect("stopped server must not spawn")
+                    .to_string(),
+                "bridge: server stopped"
+            );
+        }
+
+        // Stopping before any instance exists covers later instances too
+        assert!(!pool.stop_server("pyright").await);
+        assert!(pool.is_stopped("pyright@/project-a"));
+    }
+
+    #[tokio::test]
+    async fn stop_handles_server_names_containing_the_separator() {
+        let pool = LanguageServerPool::new();
+        register_instances(&pool, "@vue/language-server", &["/app"]);
+        pool.connections.lock().await.insert(
+            "@vue/language-server@/app".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+
+        assert!(pool.stop_server("@vue/language-server@/app").await);
+        assert!(pool.is_stopped("@vue/language-server"));
+        assert!(!pool.is_stopped("vue"));
+        assert_eq!(
+            pool.stopped_server_names(),
+            vec!["@vue/language-server".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn restart_after_stop_does_not_spawn_rootless_instance() {
+        let pool = LanguageServerPool::new();
+        register_instances(&pool, "rust-analyzer", &["/project-a"]);
+        pool.connections.lock().await.insert(
+            "rust-analyzer@/project-a".to_string(),
+            create_handle_with_state(ConnectionState::Failed).await,
+        );
+        assert!(pool.stop_server("rust-analyzer@/project-a").await);
+
+        let reopened = pool
+            .restart_server("rust-analyzer", &devnull_config(), |_| Vec::new())
+            .await
+            .expect("restart of a stopped server should succeed");
+
+        assert_eq!(reopened, 0);
+        assert!(
+            pool.connections.lock().await.is_empty(),
+            "no instance without a project root may be spawned"
+        );
+        assert!(!pool.is_stopped("rust-analyzer@/project-a"));
+        assert!(pool.stopped_server_names().is_empty());
+    }
+
+    #[tokio::test]
+    async fn restart_reopens_tracked_documents_on_new_connection() {
+        if !lua_ls_available() {
+            return;
+        }
+
+        let pool = LanguageServerPool::new();
+        let config = lua_ls_config();
+        let host_uri = test_host_uri("doc");
+        let kept = VirtualDocumentUri::new(&url_to_uri(&host_uri), "lua", TEST_ULID_LUA_0);
+        let gone = VirtualDocumentUri::new(&url_to_uri(&host_uri), "lua", TEST_ULID_LUA_1);
+
+        let old_handle = pool
+            .get_or_create_connection_wait_ready("lua", &config, init_timeout(&config))
+            .await
+            .expect("lua-language-server should start");
+        for uri in [&kept, &gone] {
+            pool.ensure_document_opened(
+                &mut ConnectionHandleSender(&old_handle),
+                &host_uri,
+                uri,
+                "print('hello')",
+                "lua",
+            )
+            .await
+            .unwrap();
+        }
+
+        let reopened = pool
+            .restart_server("lua", &config, |_| {
+                vec![(
+                    "lua".to_string(),
+                    TEST_ULID_LUA_0.to_string(),
+                    "print('again')".to_string(),
+                )]
+            })
+            .await
+            .expect("restart should succeed");
+
+        assert_eq!(reopened, 1);
+        assert_eq!(old_handle.state(), ConnectionState::Closed);
+        let new_handle = Arc::clone(&pool.connections.lock().await["lua"]);
+        assert!(!Arc::ptr_eq(&old_handle, &new_handle));
+        assert_eq!(new_handle.state(), ConnectionState::Ready);
+        assert!(pool.is_document_opened(&kept));
+        assert!(!pool.is_document_opened(&gone));
+        assert_eq!(pool.document_tracker.open_document_count("lua").await, 1);
+
+        pool.shutdown_all().await;
+    }
+}
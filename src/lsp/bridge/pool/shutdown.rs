@@ -4,6 +4,7 @@
 //! implementing graceful and forced shutdown per ADR-0017 (Graceful Shutdown).
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use super::{ConnectionState, GlobalShutdownTimeout, LanguageServerPool};
 
@@ -161,6 +162,104 @@ impl LanguageServerPool {
         }
     }
 
+    /// Shut down servers that have been idle for longer than their `timeouts.idle`.
+    ///
+    /// A Ready server is idle while it has no open virtual documents. Called
+    /// periodically (every `IDLE_SWEEP_INTERVAL`); the first sweep that finds a
+    /// server idle starts its idle clock. Expired servers are removed from the
+    /// pool before the LSP shutdown handshake (which runs in a background
+    /// task), so the next request spawns a fresh server without counting as a
+    /// restart.
+    pub(crate) async fn shutdown_idle_connections(&self) {
+        let idle_timeouts = self
+            .idle_timeouts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if idle_timeouts.is_empty() {
+            return;
+        }
+
+        let candidates: Vec<(String, Arc<super::ConnectionHandle>)> = {
+            let connections = self.connections.lock().await;
+            connections
+                .iter()
+                .filter(|(name, handle)| {
+                    idle_timeouts.contains_key(*name) && handle.state() == ConnectionState::Ready
+                })
+                .map(|(name, handle)| (name.clone(), Arc::clone(handle)))
+                .collect()
+        };
+
+        let now = Instant::now();
+        let mut expired = Vec::new();
+        for (server_name, handle) in candidates {
+            let is_idle = self
+                .document_tracker
+                .open_document_count(&server_name)
+                .await
+                == 0;
+            let mut idle_since = self.idle_since.lock().unwrap_or_else(|e| e.into_inner());
+            if !is_idle {
+                idle_since.remove(&server_name);
+                continue;
+            }
+            let since = *idle_since.entry(server_name.clone()).or_insert(now);
+            if now.duration_since(since) >= idle_timeouts[&server_name] {
+                idle_since.remove(&server_name);
+                expired.push((server_name, handle));
+            }
+        }
+        self.idle_since
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|name, _| idle_timeouts.contains_key(name));
+
+        for (server_name, handle) in expired {
+            {
+                let mut connections = self.connections.lock().await;
+                match connections.get(&server_name) {
+                    Some(current) if Arc::ptr_eq(current, &handle) => {
+                        connections.remove(&server_name);
+                    }
+                    // Replaced concurrently; the new connection is not idle
+                    _ => continue,
+                }
+            }
+
+            log::info!(
+                target: "kakehashi::bridge",
+                "Shutting down idle {} connection",
+                server_name
+            );
+            // Shut down in the background so a hung server does not stall the sweep
+            tokio::spawn(async move {
+                let shutdown = tokio::time::timeout(
+                    GlobalShutdownTimeout::default().as_duration(),
+                    handle.graceful_shutdown(),
+                )
+                .await;
+                match shutdown {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => log::warn!(
+                        target: "kakehashi::bridge",
+                        "Graceful shutdown failed for idle {}: {}",
+                        server_name,
+                        e
+                    ),
+                    Err(_) => {
+                        log::warn!(
+                            target: "kakehashi::bridge",
+                            "Shutdown of idle {} timed out",
+                            server_name
+                        );
+                        handle.complete_shutdown();
+                    }
+                }
+            });
+        }
+    }
+
     /// Force-kill all connections with platform-appropriate escalation.
     ///
     /// This is the fallback when global shutdown timeout expires.
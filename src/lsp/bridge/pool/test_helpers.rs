@@ -48,6 +48,7 @@ pub(super) fn lua_ls_config() -> BridgeServerConfig {
         languages: vec!["lua".to_string()],
         initialization_options: None,
         workspace_type: None,
+        ..Default::default()
     }
 }
 
@@ -68,6 +69,7 @@ pub(super) fn devnull_config_for_language(language: &str) -> BridgeServerConfig
         languages: vec![language.to_string()],
         initialization_options: None,
         workspace_type: None,
+        ..Default::default()
     }
 }
 
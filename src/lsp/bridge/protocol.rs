@@ -9,16 +9,25 @@
 //! - `virtual_uri` - VirtualDocumentUri type for encoding injection region references
 //! - `request` - Request builders for downstream language servers
 //! - `response` - Response transformers for coordinate translation
+//! - `resolve_data` - Routing envelope for resolve-style requests
+//! - `hierarchy_item` - Translation of call and type hierarchy items
+//! - `bridge_command` - Routing of downstream commands through `workspace/executeCommand`
 
+mod bridge_command;
+mod hierarchy_item;
 mod lifecycle;
 mod request;
 mod request_id;
+mod resolve_data;
 mod response;
 mod virtual_uri;
 
 // Re-export all public items for external use
+pub(crate) use bridge_command::*;
+pub(crate) use hierarchy_item::*;
 pub(crate) use lifecycle::*;
 pub(crate) use request::*;
 pub(crate) use request_id::RequestId;
+pub(crate) use resolve_data::BridgeResolveData;
 pub(crate) use response::*;
-pub(crate) use virtual_uri::VirtualDocumentUri;
+pub(crate) use virtual_uri::{HOST_DOCUMENT_REGION_ID, VirtualDocumentUri};
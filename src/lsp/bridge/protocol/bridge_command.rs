@@ -0,0 +1,131 @@
+//! Routing of downstream commands through `workspace/executeCommand`.
+//!
+//! Commands attached to bridged items (e.g. code lenses) name commands of a
+//! downstream server, which the upstream client knows nothing about. The
+//! bridge therefore replaces each such command with [`BRIDGE_EXECUTE_COMMAND`],
+//! whose single argument is a [`BridgeResolveData`] envelope holding the
+//! original command:
+//!
+//! ```json
+//! { "title": "▶ Run", "command": "kakehashi.bridge.executeCommand",
+//!   "arguments": [{ "kakehashiBridge": { "serverName": "rust-analyzer", ...,
+//!     "data": { "title": "▶ Run", "command": "rust-analyzer.runSingle", "arguments": [...] } } }] }
+//! ```
+//!
+//! When the client executes it, the envelope tells which server to send the
+//! original command to. The original arguments are forwarded untouched, so
+//! they still reference the virtual document the server knows.
+
+use tower_lsp_server::ls_types::Command;
+
+use super::resolve_data::BridgeResolveData;
+
+/// Command identifier advertised upstream for all bridged commands.
+pub(crate) const BRIDGE_EXECUTE_COMMAND: &str = "kakehashi.bridge.executeCommand";
+
+/// Report every downstream server's state, PID and document/request counts.
+pub(crate) const BRIDGE_STATUS_COMMAND: &str = "kakehashi.bridge.status";
+
+/// Restart a downstream server (argument: server name; all servers if omitted).
+pub(crate) const BRIDGE_RESTART_COMMAND: &str = "kakehashi.bridge.restart";
+
+/// Stop a downstream server until it is restarted (argument: server name;
+/// all servers if omitted).
+pub(crate) const BRIDGE_STOP_COMMAND: &str = "kakehashi.bridge.stop";
+
+/// Every command kakehashi advertises through `executeCommandProvider`.
+pub(crate) const BRIDGE_COMMANDS: &[&str] = &[
+    BRIDGE_EXECUTE_COMMAND,
+    BRIDGE_STATUS_COMMAND,
+    BRIDGE_RESTART_COMMAND,
+    BRIDGE_STOP_COMMAND,
+];
+
+/// Wrap a downstream command so that executing it is routed back to `origin`.
+///
+/// `origin.data` is ignored; the original command takes its place.
+pub(crate) fn wrap_bridge_command(command: Command, origin: &BridgeResolveData) -> Command {
+    let title = command.title.clone();
+    let envelope = BridgeResolveData {
+        data: serde_json::to_value(command).ok(),
+        ..origin.clone()
+    };
+    Command {
+        title,
+        command: BRIDGE_EXECUTE_COMMAND.to_string(),
+        arguments: Some(vec![envelope.into_data()]),
+    }
+}
+
+/// Recover the origin and the original command from a wrapped command.
+///
+/// Returns `None` if `command` is not [`BRIDGE_EXECUTE_COMMAND`] or its
+/// argument is not a bridge envelope holding a command.
+pub(crate) fn unwrap_bridge_command(
+    command: &str,
+    arguments: &[serde_json::Value],
+) -> Option<(BridgeResolveData, Command)> {
+    if command != BRIDGE_EXECUTE_COMMAND {
+        return None;
+    }
+    let origin = BridgeResolveData::from_data(arguments.first())?;
+    let original: Command = serde_json::from_value(origin.data.clone()?).ok()?;
+    Some((origin, original))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn origin() -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "rust-analyzer".to_string(),
+            host_uri: "file:///project/doc.md".parse().unwrap(),
+            injection_language: "rust".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line: 3,
+            data: Some(json!({"stale": true})),
+        }
+    }
+
+    fn run_command() -> Command {
+        Command {
+            title: "▶ Run".to_string(),
+            command: "rust-analyzer.runSingle".to_string(),
+            arguments: Some(vec![json!({"label": "test it_works"})]),
+        }
+    }
+
+    #[test]
+    fn wrapped_command_keeps_title_and_routes_through_bridge() {
+        let wrapped = wrap_bridge_command(run_command(), &origin());
+
+        assert_eq!(wrapped.title, "▶ Run");
+        assert_eq!(wrapped.command, BRIDGE_EXECUTE_COMMAND);
+        let arguments = wrapped.arguments.unwrap();
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(
+            arguments[0]["kakehashiBridge"]["data"]["command"],
+            "rust-analyzer.runSingle"
+        );
+    }
+
+    #[test]
+    fn unwrap_restores_original_command() {
+        let wrapped = wrap_bridge_command(run_command(), &origin());
+
+        let (unwrapped_origin, original) =
+            unwrap_bridge_command(&wrapped.command, &wrapped.arguments.unwrap()).unwrap();
+
+        assert_eq!(unwrapped_origin.server_name, "rust-analyzer");
+        assert_eq!(unwrapped_origin.region_id, "region-0");
+        assert_eq!(original, run_command());
+    }
+
+    #[test]
+    fn foreign_commands_are_not_unwrapped() {
+        assert!(unwrap_bridge_command("rust-analyzer.runSingle", &[]).is_none());
+        assert!(unwrap_bridge_command(BRIDGE_EXECUTE_COMMAND, &[json!({"id": 1})]).is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Coordinate and routing translation for call and type hierarchy items.
+//!
+//! `textDocument/prepareCallHierarchy` and `textDocument/prepareTypeHierarchy`
+//! return items that the client later sends back in follow-up requests
+//! (`callHierarchy/incomingCalls`, `typeHierarchy/supertypes`, ...). Like
+//! resolvable items, their `data` is wrapped in a [`BridgeResolveData`]
+//! envelope so the follow-up reaches the server that produced the item.
+//!
+//! Item locations follow the goto URI filtering logic:
+//! - Real file URIs → keep as-is
+//! - Same virtual URI as request → host URI and host coordinates
+//! - Different virtual URI → filter out (cross-region, can't transform safely)
+
+use std::str::FromStr;
+
+use tower_lsp_server::ls_types::{CallHierarchyItem, Range, TypeHierarchyItem, Uri};
+
+use super::resolve_data::BridgeResolveData;
+use super::virtual_uri::VirtualDocumentUri;
+
+/// Common shape of `CallHierarchyItem` and `TypeHierarchyItem`.
+pub(crate) trait HierarchyItem {
+    /// The URI of the document containing the item.
+    fn uri(&self) -> &Uri;
+    /// The item's URI, full range and selection range.
+    fn location_mut(&mut self) -> (&mut Uri, &mut Range, &mut Range);
+    /// The item's `data` field.
+    fn data_mut(&mut self) -> &mut Option<serde_json::Value>;
+}
+
+impl HierarchyItem for CallHierarchyItem {
+    fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    fn location_mut(&mut self) -> (&mut Uri, &mut Range, &mut Range) {
+        (&mut self.uri, &mut self.range, &mut self.selection_range)
+    }
+
+    fn data_mut(&mut self) -> &mut Option<serde_json::Value> {
+        &mut self.data
+    }
+}
+
+impl HierarchyItem for TypeHierarchyItem {
+    fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    fn location_mut(&mut self) -> (&mut Uri, &mut Range, &mut Range) {
+        (&mut self.uri, &mut self.range, &mut self.selection_range)
+    }
+
+    fn data_mut(&mut self) -> &mut Option<serde_json::Value> {
+        &mut self.data
+    }
+}
+
+/// Transform a hierarchy item from a downstream server to host coordinates.
+///
+/// Returns `None` if the item lies in a different virtual document. The item's
+/// `data` is wrapped in `origin` so follow-up requests can be routed back.
+///
+/// # Arguments
+/// * `item` - The item as returned by the downstream server
+/// * `request_virtual_uri` - The virtual URI the request was sent for
+/// * `origin` - Envelope describing the server and region (its `data` is ignored)
+pub(crate) fn transform_hierarchy_item_to_host<T: HierarchyItem>(
+    mut item: T,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) -> Option<T> {
+    let (uri, range, selection_range) = item.location_mut();
+    if VirtualDocumentUri::is_virtual_uri(uri.as_str()) {
+        if uri.as_str() != request_virtual_uri {
+            return None;
+        }
+        *uri = origin.host_uri.clone();
+        for range in [range, selection_range] {
+            range.start.line = range.start.line.saturating_add(origin.region_start_line);
+            range.end.line = range.end.line.saturating_add(origin.region_start_line);
+        }
+    }
+
+    let data = item.data_mut();
+    *data = Some(
+        BridgeResolveData {
+            data: data.take(),
+            ..origin.clone()
+        }
+        .into_data(),
+    );
+    Some(item)
+}
+
+/// Transform a hierarchy item sent back by the client to the downstream server's view.
+///
+/// Inverse of [`transform_hierarchy_item_to_host`]: the envelope is replaced with
+/// the server's original `data`, and an item located in the host document is
+/// moved back into `origin`'s virtual document using the region start line
+/// recorded in the envelope. Items in real files keep their location.
+pub(crate) fn transform_hierarchy_item_to_virtual<T: HierarchyItem>(
+    mut item: T,
+    origin: &BridgeResolveData,
+) -> T {
+    *item.data_mut() = origin.data.clone();
+
+    let (uri, range, selection_range) = item.location_mut();
+    if *uri == origin.host_uri
+        && let Ok(virtual_uri) = Uri::from_str(&origin.virtual_uri_string())
+    {
+        *uri = virtual_uri;
+        // saturating_sub like other host→virtual translations
+        for range in [range, selection_range] {
+            range.start.line = range.start.line.saturating_sub(origin.region_start_line);
+            range.end.line = range.end.line.saturating_sub(origin.region_start_line);
+        }
+    }
+    item
+}
+
+/// Whether a hierarchy item sent back by the client lies in `origin`'s region.
+pub(crate) fn is_hierarchy_item_in_region<T: HierarchyItem>(
+    item: &T,
+    origin: &BridgeResolveData,
+) -> bool {
+    *item.uri() == origin.host_uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::{Position, SymbolKind};
+
+    fn host_uri() -> Uri {
+        let url = url::Url::parse("file:///project/doc.md").unwrap();
+        crate::lsp::lsp_impl::url_to_uri(&url).expect("test URL should convert to URI")
+    }
+
+    fn origin(data: Option<serde_json::Value>) -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "rust-analyzer".to_string(),
+            host_uri: host_uri(),
+            injection_language: "rust".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line: 10,
+            data,
+        }
+    }
+
+    fn item(uri: &str, line: u32) -> CallHierarchyItem {
+        CallHierarchyItem {
+            name: "main".to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: Uri::from_str(uri).unwrap(),
+            range: Range::new(Position::new(line, 0), Position::new(line + 2, 1)),
+            selection_range: Range::new(Position::new(line, 3), Position::new(line, 7)),
+            data: Some(json!({"id": 7})),
+        }
+    }
+
+    #[test]
+    fn item_in_request_region_is_moved_to_host() {
+        let origin = origin(None);
+        let virtual_uri = origin.virtual_uri_string();
+
+        let transformed =
+            transform_hierarchy_item_to_host(item(&virtual_uri, 1), &virtual_uri, &origin).unwrap();
+
+        assert_eq!(transformed.uri, host_uri());
+        assert_eq!(transformed.range.start.line, 11);
+        assert_eq!(transformed.range.end.line, 13);
+        assert_eq!(transformed.selection_range.start.line, 11);
+        let envelope = BridgeResolveData::from_data(transformed.data.as_ref()).unwrap();
+        assert_eq!(envelope.server_name, "rust-analyzer");
+        assert_eq!(envelope.data, Some(json!({"id": 7})));
+    }
+
+    #[test]
+    fn item_in_real_file_keeps_location() {
+        let origin = origin(None);
+        let transformed = transform_hierarchy_item_to_host(
+            item("file:///project/src/lib.rs", 1),
+            &origin.virtual_uri_string(),
+            &origin,
+        )
+        .unwrap();
+
+        assert_eq!(transformed.uri.as_str(), "file:///project/src/lib.rs");
+        assert_eq!(transformed.range.start.line, 1);
+        assert!(BridgeResolveData::from_data(transformed.data.as_ref()).is_some());
+    }
+
+    #[test]
+    fn item_in_other_region_is_filtered_out() {
+        let origin = origin(None);
+        let other_region = BridgeResolveData {
+            region_id: "region-1".to_string(),
+            ..origin.clone()
+        }
+        .virtual_uri_string();
+
+        assert!(
+            transform_hierarchy_item_to_host(
+                item(&other_region, 1),
+                &origin.virtual_uri_string(),
+                &origin
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn round_trip_restores_server_view() {
+        let origin = origin(None);
+        let virtual_uri = origin.virtual_uri_string();
+        let original = item(&virtual_uri, 1);
+
+        let host_item =
+            transform_hierarchy_item_to_host(original.clone(), &virtual_uri, &origin).unwrap();
+        let envelope = BridgeResolveData::from_data(host_item.data.as_ref()).unwrap();
+        let restored = transform_hierarchy_item_to_virtual(host_item, &envelope);
+
+        assert_eq!(restored, original);
+    }
+
+    #[test]
+    fn real_file_item_is_sent_back_unchanged() {
+        let origin = origin(Some(json!({"id": 7})));
+        let mut host_item = item("file:///project/src/lib.rs", 4);
+        host_item.data = Some(origin.clone().into_data());
+
+        assert!(!is_hierarchy_item_in_region(&host_item, &origin));
+        let restored = transform_hierarchy_item_to_virtual(host_item, &origin);
+
+        assert_eq!(restored, item("file:///project/src/lib.rs", 4));
+    }
+}
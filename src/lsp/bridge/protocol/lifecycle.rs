@@ -13,11 +13,15 @@ use super::request_id::RequestId;
 /// Uses typed `ClientCapabilities` from `ls_types` for compile-time field validation.
 fn build_bridge_client_capabilities() -> serde_json::Value {
     use tower_lsp_server::ls_types::{
-        ClientCapabilities, CompletionClientCapabilities, CompletionItemCapability,
-        DiagnosticClientCapabilities, DocumentLinkClientCapabilities,
-        DocumentSymbolClientCapabilities, DynamicRegistrationClientCapabilities, GotoCapability,
+        ClientCapabilities, CodeActionCapabilityResolveSupport, CodeActionClientCapabilities,
+        CodeActionKindLiteralSupport, CodeActionLiteralSupport, CodeLensClientCapabilities,
+        CompletionClientCapabilities, CompletionItemCapability,
+        CompletionItemCapabilityResolveSupport, DiagnosticClientCapabilities,
+        DocumentLinkClientCapabilities, DocumentSymbolClientCapabilities,
+        DynamicRegistrationClientCapabilities, FoldingRangeClientCapabilities, GotoCapability,
         HoverClientCapabilities, InlayHintClientCapabilities, MarkupKind,
         SignatureHelpClientCapabilities, TextDocumentClientCapabilities,
+        WorkspaceClientCapabilities,
     };
 
     let goto_link = Some(GotoCapability {
@@ -36,6 +40,14 @@ fn build_bridge_client_capabilities() -> serde_json::Value {
             completion_item: Some(CompletionItemCapability {
                 snippet_support: Some(true),
                 insert_replace_support: Some(true),
+                // Resolved lazily via completionItem/resolve (routed by the resolve envelope)
+                resolve_support: Some(CompletionItemCapabilityResolveSupport {
+                    properties: vec![
+                        "documentation".to_string(),
+                        "detail".to_string(),
+                        "additionalTextEdits".to_string(),
+                    ],
+                }),
                 ..Default::default()
             }),
             ..Default::default()
@@ -74,6 +86,53 @@ fn build_bridge_client_capabilities() -> serde_json::Value {
         moniker: Some(DynamicRegistrationClientCapabilities {
             dynamic_registration: Some(false),
         }),
+        code_lens: Some(CodeLensClientCapabilities {
+            dynamic_registration: Some(false),
+        }),
+        folding_range: Some(FoldingRangeClientCapabilities {
+            dynamic_registration: Some(false),
+            ..Default::default()
+        }),
+        linked_editing_range: Some(DynamicRegistrationClientCapabilities {
+            dynamic_registration: Some(false),
+        }),
+        // Dynamic so servers may re-register their trigger characters
+        on_type_formatting: Some(DynamicRegistrationClientCapabilities {
+            dynamic_registration: Some(true),
+        }),
+        call_hierarchy: Some(DynamicRegistrationClientCapabilities {
+            dynamic_registration: Some(false),
+        }),
+        // Dynamic so servers may register it; the static provider is read from
+        // the raw initialize response (see handshake.rs)
+        type_hierarchy: Some(DynamicRegistrationClientCapabilities {
+            dynamic_registration: Some(true),
+        }),
+        // Code action kinds are passed through verbatim, so any kind is accepted.
+        // `data` is preserved across resolve by the bridge's resolve envelope.
+        code_action: Some(CodeActionClientCapabilities {
+            dynamic_registration: Some(false),
+            code_action_literal_support: Some(CodeActionLiteralSupport {
+                code_action_kind: CodeActionKindLiteralSupport {
+                    value_set: vec![
+                        String::new(),
+                        "quickfix".to_string(),
+                        "refactor".to_string(),
+                        "refactor.extract".to_string(),
+                        "refactor.inline".to_string(),
+                        "refactor.rewrite".to_string(),
+                        "source".to_string(),
+                        "source.organizeImports".to_string(),
+                        "source.fixAll".to_string(),
+                    ],
+                },
+            }),
+            data_support: Some(true),
+            resolve_support: Some(CodeActionCapabilityResolveSupport {
+                properties: vec!["edit".to_string(), "command".to_string()],
+            }),
+            ..Default::default()
+        }),
         ..Default::default()
     };
 
@@ -84,8 +143,17 @@ fn build_bridge_client_capabilities() -> serde_json::Value {
         });
     }
 
+    // Commands of bridged code lenses are forwarded via workspace/executeCommand
+    let workspace = WorkspaceClientCapabilities {
+        execute_command: Some(DynamicRegistrationClientCapabilities {
+            dynamic_registration: Some(false),
+        }),
+        ..Default::default()
+    };
+
     let capabilities = ClientCapabilities {
         text_document: Some(text_document),
+        workspace: Some(workspace),
         ..Default::default()
     };
 
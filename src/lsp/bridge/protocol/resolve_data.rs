@@ -0,0 +1,120 @@
+//! Routing envelope for resolve-style requests.
+//!
+//! Items such as code actions are returned to the upstream client and may come
+//! back later in a `*/resolve` request. By then the bridge no longer knows which
+//! downstream server produced the item, nor which injection region it belongs to.
+//!
+//! To route the resolve request back, the bridge replaces the item's `data`
+//! field with an envelope that records the origin and keeps the server's own
+//! `data` inside it:
+//!
+//! ```json
+//! { "kakehashiBridge": { "serverName": "lua_ls", "hostUri": "file:///doc.md",
+//!   "injectionLanguage": "lua", "regionId": "01ARZ...", "regionStartLine": 3,
+//!   "data": <original data> } }
+//! ```
+//!
+//! The envelope is unwrapped before the item is sent to the downstream server,
+//! so servers always see exactly the `data` they produced.
+
+use serde::{Deserialize, Serialize};
+
+use super::virtual_uri::VirtualDocumentUri;
+use tower_lsp_server::ls_types::Uri;
+
+/// Key under which the envelope is stored in an item's `data` field.
+const ENVELOPE_KEY: &str = "kakehashiBridge";
+
+/// Origin of a bridged item, stored in its `data` field while it is upstream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeResolveData {
+    /// Name of the downstream server that produced the item.
+    pub(crate) server_name: String,
+    /// Host document the injection region belongs to.
+    pub(crate) host_uri: Uri,
+    /// Injection language of the region.
+    pub(crate) injection_language: String,
+    /// Region ID used to build the virtual document URI.
+    pub(crate) region_id: String,
+    /// Starting line of the region in the host document when the item was produced.
+    pub(crate) region_start_line: u32,
+    /// The downstream server's original `data`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data: Option<serde_json::Value>,
+}
+
+impl BridgeResolveData {
+    /// Wrap this envelope into a JSON value suitable for an item's `data` field.
+    pub(crate) fn into_data(self) -> serde_json::Value {
+        let envelope = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        serde_json::json!({ ENVELOPE_KEY: envelope })
+    }
+
+    /// Extract an envelope from an item's `data` field.
+    ///
+    /// Returns `None` if the data was not produced by the bridge.
+    pub(crate) fn from_data(data: Option<&serde_json::Value>) -> Option<Self> {
+        let envelope = data?.get(ENVELOPE_KEY)?;
+        serde_json::from_value(envelope.clone()).ok()
+    }
+
+    /// The virtual document URI string the item originated from.
+    pub(crate) fn virtual_uri_string(&self) -> String {
+        VirtualDocumentUri::new(&self.host_uri, &self.injection_language, &self.region_id)
+            .to_uri_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "lua_ls".to_string(),
+            host_uri: "file:///project/doc.md".parse().unwrap(),
+            injection_language: "lua".to_string(),
+            region_id: "01JPMQ8ZYYQA".to_string(),
+            region_start_line: 3,
+            data: Some(json!({"id": 42})),
+        }
+    }
+
+    #[test]
+    fn envelope_round_trips_through_data_field() {
+        let envelope = sample();
+        let data = envelope.clone().into_data();
+
+        assert_eq!(data["kakehashiBridge"]["serverName"], "lua_ls");
+        assert_eq!(data["kakehashiBridge"]["data"]["id"], 42);
+        assert_eq!(BridgeResolveData::from_data(Some(&data)), Some(envelope));
+    }
+
+    #[test]
+    fn envelope_without_inner_data_omits_field() {
+        let envelope = BridgeResolveData {
+            data: None,
+            ..sample()
+        };
+        let data = envelope.clone().into_data();
+
+        assert!(data["kakehashiBridge"].get("data").is_none());
+        assert_eq!(BridgeResolveData::from_data(Some(&data)), Some(envelope));
+    }
+
+    #[test]
+    fn foreign_data_is_not_an_envelope() {
+        assert_eq!(BridgeResolveData::from_data(None), None);
+        assert_eq!(BridgeResolveData::from_data(Some(&json!({"id": 42}))), None);
+    }
+
+    #[test]
+    fn virtual_uri_matches_original_request() {
+        let envelope = sample();
+        let expected = VirtualDocumentUri::new(&envelope.host_uri, "lua", "01JPMQ8ZYYQA");
+
+        assert_eq!(envelope.virtual_uri_string(), expected.to_uri_string());
+    }
+}
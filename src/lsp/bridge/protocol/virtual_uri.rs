@@ -7,12 +7,21 @@
 //! For most URIs (file://, https://, etc.), the virtual URI preserves the
 //! original scheme and directory. For "cannot-be-a-base" URIs (untitled:,
 //! mailto:, data:), a kakehashi:// scheme fallback is used.
+//!
+//! When host-document bridging is enabled, the host document itself is tracked
+//! as a region with [`HOST_DOCUMENT_REGION_ID`]; its URI is the host URI.
 
 /// Prefix used for virtual document filenames.
 ///
 /// This distinctive prefix identifies virtual URIs and prevents collisions with real files.
 const VIRTUAL_URI_PREFIX: &str = "kakehashi-virtual-uri-";
 
+/// Region ID standing for the whole host document (host-document bridging).
+///
+/// Injection region IDs are ULIDs (26 uppercase alphanumerics), so this cannot
+/// collide with them.
+pub(crate) const HOST_DOCUMENT_REGION_ID: &str = "host";
+
 /// Virtual document URI for injection regions.
 ///
 /// Encodes host URI + injection language + region ID into a URI that
@@ -78,6 +87,12 @@ impl VirtualDocumentUri {
         &self.language
     }
 
+    /// Check if this URI stands for the host document itself rather than an
+    /// injection region (see [`HOST_DOCUMENT_REGION_ID`]).
+    pub(crate) fn is_host_document(&self) -> bool {
+        self.region_id == HOST_DOCUMENT_REGION_ID
+    }
+
     /// Check if a URI string represents a virtual document.
     ///
     /// Virtual document URIs have the filename pattern `kakehashi-virtual-uri-{region_id}.{ext}`.
@@ -126,7 +141,14 @@ impl VirtualDocumentUri {
     ///
     /// The region_id is percent-encoded by the url crate to ensure URI-safe characters.
     /// While ULIDs only contain alphanumeric characters, this provides defense-in-depth.
+    ///
+    /// The host document region maps to the host URI unchanged, so the host server
+    /// sees the real file and its responses need no URI rewriting.
     pub(crate) fn to_uri_string(&self) -> String {
+        if self.is_host_document() {
+            return self.host_uri.as_str().to_string();
+        }
+
         let extension = Self::language_to_extension(&self.language);
         let virtual_filename = format!("{VIRTUAL_URI_PREFIX}{}.{extension}", self.region_id);
 
@@ -201,6 +223,22 @@ mod tests {
     // to_uri_string tests
     // ==========================================================================
 
+    #[test]
+    fn to_uri_string_for_host_document_is_host_uri() {
+        let host_uri = Url::parse("file:///project/docs/README.md").unwrap();
+        let virtual_uri =
+            VirtualDocumentUri::new(&url_to_uri(&host_uri), "markdown", HOST_DOCUMENT_REGION_ID);
+
+        assert!(virtual_uri.is_host_document());
+        assert_eq!(
+            virtual_uri.to_uri_string(),
+            "file:///project/docs/README.md"
+        );
+        assert!(!VirtualDocumentUri::is_virtual_uri(
+            &virtual_uri.to_uri_string()
+        ));
+    }
+
     #[test]
     fn to_uri_string_uses_host_directory() {
         let host_uri = Url::parse("file:///project/docs/README.md").unwrap();
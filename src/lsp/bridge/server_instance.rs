@@ -0,0 +1,316 @@
+//! Per-root server instances and launch variable substitution.
+//!
+//! A server configured with `rootMarkers` runs one instance per project root:
+//! the root is found by walking up from the host document to the nearest
+//! directory containing a marker (e.g., `Cargo.toml`), and the instance is
+//! pooled under the key `<server>@<root>`. Servers without markers keep a
+//! single instance keyed by their name, rooted at the workspace folder.
+//!
+//! `${workspaceFolder}` and `${hostDir}` in `cmd`, `env` and `cwd` are
+//! substituted when the config is resolved for a document.
+
+use std::path::{Path, PathBuf};
+
+use url::Url;
+
+use crate::config::settings::BridgeServerConfig;
+
+/// Separator between server name and project root in an instance key.
+const INSTANCE_SEPARATOR: char = '@';
+
+/// A configured server bound to the project root it serves.
+///
+/// Server names are arbitrary config keys and may contain the separator
+/// themselves (e.g., `@vue/language-server`), so the pool keeps this next to
+/// each instance key instead of parsing the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServerInstance {
+    /// Configured server name (the `languageServers` key)
+    pub(crate) server_name: String,
+    /// Project root of a per-root instance; `None` for a single instance
+    pub(crate) root: Option<PathBuf>,
+}
+
+impl ServerInstance {
+    /// Instance of a server that is not split by project root.
+    pub(crate) fn single(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            root: None,
+        }
+    }
+
+    /// Root URI sent to the server on initialize, if the instance has a root.
+    pub(crate) fn root_uri(&self) -> Option<String> {
+        self.root
+            .as_deref()
+            .and_then(|root| Url::from_directory_path(root).ok())
+            .map(String::from)
+    }
+
+    /// Pool key of the instance: `<server>@<root>`, or the server name.
+    pub(crate) fn key(&self) -> String {
+        match &self.root {
+            Some(root) => format!("{}{INSTANCE_SEPARATOR}{}", self.server_name, root.display()),
+            None => self.server_name.clone(),
+        }
+    }
+}
+
+/// Resolve the server instance that serves `host_uri`.
+///
+/// Returns the instance and its config with variables substituted.
+pub(crate) fn resolve_instance(
+    server_name: &str,
+    config: BridgeServerConfig,
+    host_uri: &Url,
+    workspace_root: Option<&str>,
+) -> (ServerInstance, BridgeServerConfig) {
+    let host_dir = host_uri
+        .to_file_path()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+    let root = match (&config.root_markers, &host_dir) {
+        (Some(markers), Some(dir)) if !markers.is_empty() => find_project_root(dir, markers),
+        _ => None,
+    };
+    let config = apply_instance(config, root.as_deref(), host_dir.as_deref(), workspace_root);
+    let instance = ServerInstance {
+        server_name: server_name.to_string(),
+        root,
+    };
+    (instance, config)
+}
+
+/// Resolve the config of an existing instance.
+///
+/// Used when no host document is at hand (resolve requests, commands). For a
+/// per-root instance, `${hostDir}` falls back to the instance root.
+pub(crate) fn resolve_instance_config(
+    instance: &ServerInstance,
+    config: BridgeServerConfig,
+    workspace_root: Option<&str>,
+) -> BridgeServerConfig {
+    let root = instance.root.as_deref();
+    apply_instance(config, root, root, workspace_root)
+}
+
+fn apply_instance(
+    mut config: BridgeServerConfig,
+    root: Option<&Path>,
+    host_dir: Option<&Path>,
+    workspace_root: Option<&str>,
+) -> BridgeServerConfig {
+    let workspace_folder = workspace_root
+        .and_then(|uri| Url::parse(uri).ok())
+        .and_then(|uri| uri.to_file_path().ok())
+        // Directory URIs end in `/`; drop it so `${workspaceFolder}/x` stays clean
+        .map(|path| path.components().collect::<PathBuf>());
+    // Without a workspace folder, the instance root (or the host directory)
+    // is the best stand-in for it.
+    let workspace_folder = workspace_folder
+        .or_else(|| root.map(Path::to_path_buf))
+        .or_else(|| host_dir.map(Path::to_path_buf));
+    let variables = LaunchVariables {
+        workspace_folder: workspace_folder.as_deref(),
+        host_dir,
+    };
+
+    config.cmd = config
+        .cmd
+        .iter()
+        .map(|arg| variables.substitute(arg))
+        .collect();
+    if let Some(env) = config.env.as_mut() {
+        for value in env.values_mut() {
+            *value = variables.substitute(value);
+        }
+    }
+    if let Some(cwd) = config.cwd.take() {
+        let cwd = PathBuf::from(variables.substitute(&cwd));
+        // A relative cwd is relative to the instance root or workspace folder
+        let cwd = match root.or(variables.workspace_folder) {
+            Some(base) if cwd.is_relative() => base.join(cwd),
+            _ => cwd,
+        };
+        config.cwd = Some(cwd.to_string_lossy().into_owned());
+    }
+    config
+}
+
+/// Values for the variables allowed in `cmd`, `env` and `cwd`.
+struct LaunchVariables<'a> {
+    workspace_folder: Option<&'a Path>,
+    host_dir: Option<&'a Path>,
+}
+
+impl LaunchVariables<'_> {
+    /// Replace `${workspaceFolder}` and `${hostDir}`; unknown values are left as-is.
+    fn substitute(&self, value: &str) -> String {
+        let mut result = value.to_string();
+        for (name, path) in [
+            ("${workspaceFolder}", self.workspace_folder),
+            ("${hostDir}", self.host_dir),
+        ] {
+            if let Some(path) = path {
+                result = result.replace(name, &path.to_string_lossy());
+            }
+        }
+        result
+    }
+}
+
+/// Find the nearest ancestor of `start` (inclusive) containing one of `markers`.
+pub(crate) fn find_project_root(start: &Path, markers: &[String]) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| markers.iter().any(|marker| contains_marker(dir, marker)))
+        .map(Path::to_path_buf)
+}
+
+fn contains_marker(dir: &Path, marker: &str) -> bool {
+    if !marker.contains('*') {
+        return dir.join(marker).exists();
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries
+        .flatten()
+        .any(|entry| wildcard_match(marker, &entry.file_name().to_string_lossy()))
+}
+
+/// Match `name` against `pattern`, where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all: exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(cmd: &[&str]) -> BridgeServerConfig {
+        serde_json::from_value(serde_json::json!({
+            "cmd": cmd,
+            "languages": ["rust"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn wildcard_match_supports_prefix_suffix_and_infix() {
+        assert!(wildcard_match("*.cabal", "project.cabal"));
+        assert!(wildcard_match("requirements*.txt", "requirements-dev.txt"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("Cargo.toml", "Cargo.toml"));
+        assert!(!wildcard_match("*.cabal", "cabal.project"));
+        assert!(!wildcard_match("a*b*c", "ab"));
+    }
+
+    #[test]
+    fn find_project_root_walks_up_to_nearest_marker() {
+        let temp = tempfile::tempdir().unwrap();
+        let crate_dir = temp.path().join("workspace/crates/core");
+        std::fs::create_dir_all(crate_dir.join("src/docs")).unwrap();
+        std::fs::write(temp.path().join("workspace/Cargo.toml"), "").unwrap();
+        std::fs::write(crate_dir.join("Cargo.toml"), "").unwrap();
+        std::fs::write(temp.path().join("workspace/app.cabal"), "").unwrap();
+
+        let markers = vec!["Cargo.toml".to_string()];
+        assert_eq!(
+            find_project_root(&crate_dir.join("src/docs"), &markers),
+            Some(crate_dir.clone())
+        );
+        let markers = vec!["*.cabal".to_string()];
+        assert_eq!(
+            find_project_root(&crate_dir, &markers),
+            Some(temp.path().join("workspace"))
+        );
+        let markers = vec!["pyproject.toml".to_string()];
+        assert_eq!(find_project_root(&crate_dir, &markers), None);
+    }
+
+    #[test]
+    fn resolve_instance_keys_by_root_and_substitutes_variables() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path().join("project");
+        std::fs::create_dir_all(project.join("docs")).unwrap();
+        std::fs::write(project.join("pyproject.toml"), "").unwrap();
+
+        let mut config = config(&["pyright", "--root", "${workspaceFolder}", "${hostDir}"]);
+        config.root_markers = Some(vec!["pyproject.toml".to_string()]);
+        config.env = Some(HashMap::from([(
+            "VIRTUAL_ENV".to_string(),
+            "${workspaceFolder}/.venv".to_string(),
+        )]));
+        config.cwd = Some(".".to_string());
+
+        let host_uri = Url::from_file_path(project.join("docs/README.md")).unwrap();
+        let workspace = Url::from_directory_path(temp.path()).unwrap();
+        let (instance, resolved) = resolve_instance(
+            "pyright",
+            config.clone(),
+            &host_uri,
+            Some(workspace.as_str()),
+        );
+
+        assert_eq!(instance.key(), format!("pyright@{}", project.display()));
+        assert_eq!(instance.server_name, "pyright");
+        let workspace_dir = temp.path().to_string_lossy().into_owned();
+        let host_dir = project.join("docs").to_string_lossy().into_owned();
+        assert_eq!(
+            resolved.cmd,
+            vec![
+                "pyright".to_string(),
+                "--root".to_string(),
+                workspace_dir.clone(),
+                host_dir
+            ]
+        );
+        assert_eq!(
+            resolved.env.as_ref().unwrap()["VIRTUAL_ENV"],
+            format!("{workspace_dir}/.venv")
+        );
+        assert_eq!(
+            resolved.cwd.as_deref(),
+            Some(project.join(".").to_string_lossy().as_ref())
+        );
+        assert_eq!(instance.root.as_deref(), Some(project.as_path()));
+        assert_eq!(
+            instance.root_uri(),
+            Some(Url::from_directory_path(&project).unwrap().to_string())
+        );
+
+        // Resolving the instance again yields the same launch config
+        let again = resolve_instance_config(&instance, config, Some(workspace.as_str()));
+        assert_eq!(again.cwd, resolved.cwd);
+    }
+
+    #[test]
+    fn servers_without_markers_keep_a_single_instance() {
+        let host_uri = Url::parse("file:///repo/docs/README.md").unwrap();
+        let (instance, resolved) =
+            resolve_instance("lua_ls", config(&["lua-language-server"]), &host_uri, None);
+        assert_eq!(instance, ServerInstance::single("lua_ls"));
+        assert_eq!(instance.key(), "lua_ls");
+        assert_eq!(instance.root_uri(), None);
+        assert_eq!(resolved.cmd, vec!["lua-language-server".to_string()]);
+    }
+}
@@ -5,6 +5,9 @@
 //!
 //! The structure mirrors `lsp_impl/text_document/` for consistency.
 
+mod call_hierarchy;
+mod code_action;
+mod code_lens;
 #[cfg(feature = "experimental")]
 mod color_presentation;
 mod completion;
@@ -18,11 +21,19 @@ mod document_color;
 mod document_highlight;
 mod document_link;
 mod document_symbol;
+mod folding_range;
+mod formatting;
 mod hover;
 mod implementation;
 mod inlay_hint;
+mod linked_editing_range;
 mod moniker;
+mod on_type_formatting;
 mod references;
 mod rename;
+mod semantic_tokens;
 mod signature_help;
 mod type_definition;
+mod type_hierarchy;
+
+pub(crate) use formatting::region_formatting_edit;
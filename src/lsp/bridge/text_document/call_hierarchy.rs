@@ -0,0 +1,513 @@
+//! Call hierarchy request handling for bridge connections.
+//!
+//! This module provides `textDocument/prepareCallHierarchy`,
+//! `callHierarchy/incomingCalls` and `callHierarchy/outgoingCalls` for downstream
+//! language servers, handling the coordinate transformation between host and
+//! virtual documents.
+//!
+//! Prepared items carry a [`BridgeResolveData`] envelope in their `data` field
+//! (see `protocol::hierarchy_item`), which routes the follow-up requests back
+//! to the server and injection region that produced the item.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position, Range, Uri,
+};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{
+    BridgeResolveData, RequestId, VirtualDocumentUri, build_position_based_request,
+    is_hierarchy_item_in_region, transform_hierarchy_item_to_host,
+    transform_hierarchy_item_to_virtual,
+};
+
+impl LanguageServerPool {
+    /// Send a prepareCallHierarchy request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle. Returned items are in host coordinates and carry the
+    /// routing envelope for follow-up requests.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_prepare_call_hierarchy_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_position: Position,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<CallHierarchyItem>>> {
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_prepare_call_hierarchy_request(
+                    virtual_uri,
+                    host_position,
+                    region_start_line,
+                    request_id,
+                )
+            },
+            |response, ctx| {
+                let origin = hierarchy_origin(
+                    server_name,
+                    ctx.host_uri_lsp,
+                    injection_language,
+                    region_id,
+                    ctx.region_start_line,
+                );
+                transform_prepare_call_hierarchy_response_to_host(
+                    response,
+                    &ctx.virtual_uri_string,
+                    &origin,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Send a callHierarchy/incomingCalls request for an item produced by
+    /// [`send_prepare_call_hierarchy_request`](Self::send_prepare_call_hierarchy_request).
+    ///
+    /// `origin` is the item's envelope; `region_start_line` and `virtual_content`
+    /// describe the origin region as it is now, which may have moved since the
+    /// item was prepared.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_incoming_calls_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        item: CallHierarchyItem,
+        origin: BridgeResolveData,
+        host_uri: &Url,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        self.execute_bridge_request(
+            &origin.server_name,
+            server_config,
+            host_uri,
+            &origin.injection_language,
+            &origin.region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |_virtual_uri, request_id| {
+                build_call_hierarchy_item_request(
+                    item,
+                    &origin,
+                    request_id,
+                    "callHierarchy/incomingCalls",
+                )
+            },
+            |response, ctx| {
+                let current = hierarchy_origin(
+                    &origin.server_name,
+                    ctx.host_uri_lsp,
+                    &origin.injection_language,
+                    &origin.region_id,
+                    ctx.region_start_line,
+                );
+                transform_incoming_calls_response_to_host(
+                    response,
+                    &ctx.virtual_uri_string,
+                    &current,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Send a callHierarchy/outgoingCalls request for an item produced by
+    /// [`send_prepare_call_hierarchy_request`](Self::send_prepare_call_hierarchy_request).
+    ///
+    /// Arguments are the same as for
+    /// [`send_incoming_calls_request`](Self::send_incoming_calls_request).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_outgoing_calls_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        item: CallHierarchyItem,
+        origin: BridgeResolveData,
+        host_uri: &Url,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        // fromRanges are relative to the requested item, so they only need
+        // translating when that item lives in the injection region
+        let item_in_region = is_hierarchy_item_in_region(&item, &origin);
+        self.execute_bridge_request(
+            &origin.server_name,
+            server_config,
+            host_uri,
+            &origin.injection_language,
+            &origin.region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |_virtual_uri, request_id| {
+                build_call_hierarchy_item_request(
+                    item,
+                    &origin,
+                    request_id,
+                    "callHierarchy/outgoingCalls",
+                )
+            },
+            |response, ctx| {
+                let current = hierarchy_origin(
+                    &origin.server_name,
+                    ctx.host_uri_lsp,
+                    &origin.injection_language,
+                    &origin.region_id,
+                    ctx.region_start_line,
+                );
+                transform_outgoing_calls_response_to_host(
+                    response,
+                    &ctx.virtual_uri_string,
+                    &current,
+                    item_in_region,
+                )
+            },
+        )
+        .await
+    }
+}
+
+/// Build the envelope recorded in items returned from an injection region.
+pub(super) fn hierarchy_origin(
+    server_name: &str,
+    host_uri: &Uri,
+    injection_language: &str,
+    region_id: &str,
+    region_start_line: u32,
+) -> BridgeResolveData {
+    BridgeResolveData {
+        server_name: server_name.to_string(),
+        host_uri: host_uri.clone(),
+        injection_language: injection_language.to_string(),
+        region_id: region_id.to_string(),
+        region_start_line,
+        data: None,
+    }
+}
+
+/// Build a JSON-RPC prepareCallHierarchy request for a downstream language server.
+fn build_prepare_call_hierarchy_request(
+    virtual_uri: &VirtualDocumentUri,
+    host_position: Position,
+    region_start_line: u32,
+    request_id: RequestId,
+) -> serde_json::Value {
+    build_position_based_request(
+        virtual_uri,
+        host_position,
+        region_start_line,
+        request_id,
+        "textDocument/prepareCallHierarchy",
+    )
+}
+
+/// Build an incomingCalls/outgoingCalls request for the item the client sent back.
+fn build_call_hierarchy_item_request(
+    item: CallHierarchyItem,
+    origin: &BridgeResolveData,
+    request_id: RequestId,
+    method: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": method,
+        "params": {
+            "item": transform_hierarchy_item_to_virtual(item, origin)
+        }
+    })
+}
+
+/// Extract the `result` of a call hierarchy response, logging errors.
+///
+/// Returns `None` for errors, null results and payloads that don't match `T`.
+pub(super) fn parse_hierarchy_result<T: serde::de::DeserializeOwned>(
+    mut response: serde_json::Value,
+    method: &str,
+) -> Option<T> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for {}: {}", method, error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+    if result.is_null() {
+        return None;
+    }
+    serde_json::from_value(result).ok()
+}
+
+/// Transform a prepareCallHierarchy response to host coordinates.
+///
+/// Items in other injection regions are dropped; an empty array after filtering
+/// is preserved.
+fn transform_prepare_call_hierarchy_response_to_host(
+    response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) -> Option<Vec<CallHierarchyItem>> {
+    let items: Vec<CallHierarchyItem> =
+        parse_hierarchy_result(response, "textDocument/prepareCallHierarchy")?;
+    Some(
+        items
+            .into_iter()
+            .filter_map(|item| transform_hierarchy_item_to_host(item, request_virtual_uri, origin))
+            .collect(),
+    )
+}
+
+/// Transform an incomingCalls response to host coordinates.
+///
+/// `fromRanges` are relative to the caller (`from`), so they are translated
+/// together with it. Callers in other injection regions are dropped.
+fn transform_incoming_calls_response_to_host(
+    response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let calls: Vec<CallHierarchyIncomingCall> =
+        parse_hierarchy_result(response, "callHierarchy/incomingCalls")?;
+    Some(
+        calls
+            .into_iter()
+            .filter_map(|mut call| {
+                let caller_in_region = call.from.uri.as_str() == request_virtual_uri;
+                call.from =
+                    transform_hierarchy_item_to_host(call.from, request_virtual_uri, origin)?;
+                if caller_in_region {
+                    translate_ranges(&mut call.from_ranges, origin.region_start_line);
+                }
+                Some(call)
+            })
+            .collect(),
+    )
+}
+
+/// Transform an outgoingCalls response to host coordinates.
+///
+/// `fromRanges` are relative to the requested item and are translated only if
+/// `item_in_region`. Callees in other injection regions are dropped.
+fn transform_outgoing_calls_response_to_host(
+    response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+    item_in_region: bool,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let calls: Vec<CallHierarchyOutgoingCall> =
+        parse_hierarchy_result(response, "callHierarchy/outgoingCalls")?;
+    Some(
+        calls
+            .into_iter()
+            .filter_map(|mut call| {
+                call.to = transform_hierarchy_item_to_host(call.to, request_virtual_uri, origin)?;
+                if item_in_region {
+                    translate_ranges(&mut call.from_ranges, origin.region_start_line);
+                }
+                Some(call)
+            })
+            .collect(),
+    )
+}
+
+fn translate_ranges(ranges: &mut [Range], region_start_line: u32) {
+    for range in ranges {
+        range.start.line = range.start.line.saturating_add(region_start_line);
+        range.end.line = range.end.line.saturating_add(region_start_line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn test_host_uri() -> Uri {
+        let url = Url::parse("file:///project/doc.md").unwrap();
+        crate::lsp::lsp_impl::url_to_uri(&url).expect("test URL should convert to URI")
+    }
+
+    fn test_origin(region_start_line: u32) -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "rust-analyzer".to_string(),
+            host_uri: test_host_uri(),
+            injection_language: "rust".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line,
+            data: None,
+        }
+    }
+
+    fn item_json(uri: &str, line: u32) -> serde_json::Value {
+        json!({
+            "name": "helper",
+            "kind": 12,
+            "uri": uri,
+            "range": { "start": { "line": line, "character": 0 }, "end": { "line": line + 1, "character": 1 } },
+            "selectionRange": { "start": { "line": line, "character": 3 }, "end": { "line": line, "character": 9 } },
+            "data": { "id": line }
+        })
+    }
+
+    #[test]
+    fn prepare_request_uses_virtual_coordinates() {
+        let virtual_uri = VirtualDocumentUri::new(&test_host_uri(), "rust", "region-0");
+        let request = build_prepare_call_hierarchy_request(
+            &virtual_uri,
+            Position::new(7, 4),
+            5,
+            RequestId::new(42),
+        );
+
+        assert_eq!(request["method"], "textDocument/prepareCallHierarchy");
+        assert_eq!(request["params"]["position"]["line"], 2);
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            virtual_uri.to_uri_string()
+        );
+    }
+
+    #[test]
+    fn prepare_response_is_translated_and_enveloped() {
+        let origin = test_origin(5);
+        let virtual_uri = origin.virtual_uri_string();
+        let response =
+            json!({ "jsonrpc": "2.0", "id": 42, "result": [item_json(&virtual_uri, 1)] });
+
+        let items =
+            transform_prepare_call_hierarchy_response_to_host(response, &virtual_uri, &origin)
+                .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].uri, test_host_uri());
+        assert_eq!(items[0].range.start.line, 6);
+        let envelope = BridgeResolveData::from_data(items[0].data.as_ref()).unwrap();
+        assert_eq!(envelope.region_start_line, 5);
+        assert_eq!(envelope.data, Some(json!({"id": 1})));
+    }
+
+    #[test]
+    fn prepare_response_null_and_error_return_none() {
+        let origin = test_origin(5);
+        let virtual_uri = origin.virtual_uri_string();
+        assert!(
+            transform_prepare_call_hierarchy_response_to_host(
+                json!({ "jsonrpc": "2.0", "id": 42, "result": null }),
+                &virtual_uri,
+                &origin
+            )
+            .is_none()
+        );
+        assert!(
+            transform_prepare_call_hierarchy_response_to_host(
+                json!({ "jsonrpc": "2.0", "id": 42, "error": { "code": -32601, "message": "nope" } }),
+                &virtual_uri,
+                &origin
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn item_request_restores_server_data_and_coordinates() {
+        let origin = test_origin(5);
+        let virtual_uri = origin.virtual_uri_string();
+        let response =
+            json!({ "jsonrpc": "2.0", "id": 42, "result": [item_json(&virtual_uri, 1)] });
+        let item =
+            transform_prepare_call_hierarchy_response_to_host(response, &virtual_uri, &origin)
+                .unwrap()
+                .remove(0);
+        let envelope = BridgeResolveData::from_data(item.data.as_ref()).unwrap();
+
+        let request = build_call_hierarchy_item_request(
+            item,
+            &envelope,
+            RequestId::new(43),
+            "callHierarchy/incomingCalls",
+        );
+
+        assert_eq!(request["method"], "callHierarchy/incomingCalls");
+        assert_eq!(request["params"]["item"], item_json(&virtual_uri, 1));
+    }
+
+    #[test]
+    fn incoming_calls_translate_callers_in_region_and_drop_other_regions() {
+        let origin = test_origin(5);
+        let virtual_uri = origin.virtual_uri_string();
+        let other_region = BridgeResolveData {
+            region_id: "region-1".to_string(),
+            ..origin.clone()
+        }
+        .virtual_uri_string();
+        let from_range = json!([{ "start": { "line": 2, "character": 4 }, "end": { "line": 2, "character": 10 } }]);
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "result": [
+                { "from": item_json(&virtual_uri, 0), "fromRanges": from_range },
+                { "from": item_json("file:///project/src/main.rs", 8), "fromRanges": from_range },
+                { "from": item_json(&other_region, 0), "fromRanges": from_range }
+            ]
+        });
+
+        let calls =
+            transform_incoming_calls_response_to_host(response, &virtual_uri, &origin).unwrap();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].from.uri, test_host_uri());
+        assert_eq!(calls[0].from_ranges[0].start.line, 7);
+        assert_eq!(calls[1].from.uri.as_str(), "file:///project/src/main.rs");
+        assert_eq!(calls[1].from_ranges[0].start.line, 2);
+    }
+
+    #[test]
+    fn outgoing_call_ranges_follow_requested_item() {
+        let origin = test_origin(5);
+        let virtual_uri = origin.virtual_uri_string();
+        let response = || {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 42,
+                "result": [{
+                    "to": item_json("file:///project/src/lib.rs", 20),
+                    "fromRanges": [{ "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 6 } }]
+                }]
+            })
+        };
+
+        let in_region =
+            transform_outgoing_calls_response_to_host(response(), &virtual_uri, &origin, true)
+                .unwrap();
+        assert_eq!(in_region[0].from_ranges[0].start.line, 6);
+        assert_eq!(in_region[0].to.range.start.line, 20);
+
+        let in_file =
+            transform_outgoing_calls_response_to_host(response(), &virtual_uri, &origin, false)
+                .unwrap();
+        assert_eq!(in_file[0].from_ranges[0].start.line, 1);
+    }
+}
@@ -0,0 +1,656 @@
+//! Code action request handling for bridge connections.
+//!
+//! This module provides code action and code action resolve functionality for
+//! downstream language servers, handling the bidirectional coordinate
+//! transformation between host and virtual documents.
+//!
+//! Like inlay hints, code actions use a range parameter in the request. The
+//! request context also carries diagnostics, whose ranges are translated to
+//! virtual coordinates as well. WorkspaceEdits and diagnostics in responses are
+//! translated back to the host document.
+//!
+//! Code actions returned upstream carry a [`BridgeResolveData`] envelope in their
+//! `data` field so that a later `codeAction/resolve` can be routed back to the
+//! server that produced them. Their commands (and bare `Command` items) are
+//! wrapped like code lens commands, so executing them is routed back as well;
+//! the arguments keep referencing the virtual document the server knows.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionContext, CodeActionOrCommand, Diagnostic, Position, Range,
+};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{
+    BridgeResolveData, RequestId, VirtualDocumentUri, transform_workspace_edit_to_host,
+    unwrap_bridge_command, wrap_bridge_command,
+};
+
+impl LanguageServerPool {
+    /// Send a code action request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle, providing code-action-specific request building and response
+    /// transformation.
+    ///
+    /// `context.diagnostics` are expected in host coordinates and should already be
+    /// limited to the injection region; they are translated like the range.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_code_action_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_range: Range,
+        context: &CodeActionContext,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<CodeActionOrCommand>>> {
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_code_action_request(
+                    virtual_uri,
+                    host_range,
+                    context,
+                    region_start_line,
+                    request_id,
+                )
+            },
+            |response, ctx| {
+                let origin = BridgeResolveData {
+                    server_name: server_name.to_string(),
+                    host_uri: ctx.host_uri_lsp.clone(),
+                    injection_language: injection_language.to_string(),
+                    region_id: region_id.to_string(),
+                    region_start_line: ctx.region_start_line,
+                    data: None,
+                };
+                transform_code_action_response_to_host(response, &ctx.virtual_uri_string, &origin)
+            },
+        )
+        .await
+    }
+
+    /// Send a codeAction/resolve request to the server that produced the action.
+    ///
+    /// The action's `data` must still carry the envelope added by
+    /// [`send_code_action_request`](Self::send_code_action_request); `origin` is
+    /// that envelope. The server sees its original `data`, and the resolved action
+    /// is returned in host coordinates with the envelope restored.
+    ///
+    /// Returns `Ok(None)` if the server returned an error or an unparsable result.
+    pub(crate) async fn send_code_action_resolve_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        action: CodeAction,
+        origin: BridgeResolveData,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<CodeAction>> {
+        let virtual_uri_string = origin.virtual_uri_string();
+        self.execute_server_request(
+            &origin.server_name,
+            server_config,
+            upstream_request_id,
+            |request_id| build_code_action_resolve_request(action, &origin, request_id),
+            |response| {
+                transform_code_action_resolve_response_to_host(
+                    response,
+                    &virtual_uri_string,
+                    &origin,
+                )
+            },
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC code action request for a downstream language server.
+///
+/// Both the request range and the ranges of `context.diagnostics` are translated
+/// from host to virtual coordinates.
+///
+/// # Defensive Arithmetic
+///
+/// Uses `saturating_sub` for line translation to prevent panic on underflow during
+/// race conditions when document edits invalidate region data.
+fn build_code_action_request(
+    virtual_uri: &VirtualDocumentUri,
+    host_range: Range,
+    context: &CodeActionContext,
+    region_start_line: u32,
+    request_id: RequestId,
+) -> serde_json::Value {
+    let mut virtual_context = context.clone();
+    for diagnostic in &mut virtual_context.diagnostics {
+        range_to_virtual(&mut diagnostic.range, region_start_line);
+    }
+
+    let mut virtual_range = host_range;
+    range_to_virtual(&mut virtual_range, region_start_line);
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "textDocument/codeAction",
+        "params": {
+            "textDocument": {
+                "uri": virtual_uri.to_uri_string()
+            },
+            "range": virtual_range,
+            "context": virtual_context
+        }
+    })
+}
+
+/// Build a JSON-RPC codeAction/resolve request for a downstream language server.
+///
+/// Replaces the envelope in `data` with the server's original data, restores
+/// the original command (if the action already had one) and translates the
+/// action's diagnostics back to virtual coordinates.
+fn build_code_action_resolve_request(
+    mut action: CodeAction,
+    origin: &BridgeResolveData,
+    request_id: RequestId,
+) -> serde_json::Value {
+    action.data = origin.data.clone();
+    if let Some(command) = &action.command
+        && let Some((_, original)) = unwrap_bridge_command(
+            &command.command,
+            command.arguments.as_deref().unwrap_or(&[]),
+        )
+    {
+        action.command = Some(original);
+    }
+    if let Some(diagnostics) = &mut action.diagnostics {
+        for diagnostic in diagnostics {
+            range_to_virtual(&mut diagnostic.range, origin.region_start_line);
+        }
+    }
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "codeAction/resolve",
+        "params": action
+    })
+}
+
+/// Transform a code action response from virtual to host document coordinates.
+///
+/// Each item is either a `Command` (wrapped for `workspace/executeCommand`) or
+/// a `CodeAction` (edit and diagnostics translated, command wrapped, `data`
+/// wrapped in the origin envelope for later resolution).
+///
+/// # Arguments
+/// * `response` - The JSON-RPC response from the downstream language server
+/// * `request_virtual_uri` - The virtual URI from the request
+/// * `origin` - The origin envelope (without data) for the region that was queried
+fn transform_code_action_response_to_host(
+    mut response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) -> Option<Vec<CodeActionOrCommand>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/codeAction: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut items: Vec<CodeActionOrCommand> = serde_json::from_value(result).ok()?;
+
+    for item in &mut items {
+        match item {
+            CodeActionOrCommand::Command(command) => {
+                *command = wrap_bridge_command(command.clone(), origin);
+            }
+            CodeActionOrCommand::CodeAction(action) => {
+                transform_code_action_to_host(action, request_virtual_uri, origin);
+            }
+        }
+    }
+
+    Some(items)
+}
+
+/// Transform a codeAction/resolve response from virtual to host document coordinates.
+fn transform_code_action_resolve_response_to_host(
+    mut response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) -> Option<CodeAction> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for codeAction/resolve: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut action: CodeAction = serde_json::from_value(result).ok()?;
+    transform_code_action_to_host(&mut action, request_virtual_uri, origin);
+    Some(action)
+}
+
+/// Transform a single CodeAction to host coordinates and wrap its command and data.
+fn transform_code_action_to_host(
+    action: &mut CodeAction,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+) {
+    let host_uri = &origin.host_uri;
+    let region_start_line = origin.region_start_line;
+
+    if let Some(edit) = &mut action.edit {
+        transform_workspace_edit_to_host(edit, request_virtual_uri, host_uri, region_start_line);
+    }
+    if let Some(diagnostics) = &mut action.diagnostics {
+        for diagnostic in diagnostics.iter_mut() {
+            transform_diagnostic_to_host(diagnostic, region_start_line);
+        }
+    }
+    if let Some(command) = action.command.take() {
+        action.command = Some(wrap_bridge_command(command, origin));
+    }
+
+    action.data = Some(
+        BridgeResolveData {
+            data: action.data.take(),
+            ..origin.clone()
+        }
+        .into_data(),
+    );
+}
+
+/// Transform a diagnostic attached to a code action back to host coordinates.
+fn transform_diagnostic_to_host(diagnostic: &mut Diagnostic, region_start_line: u32) {
+    diagnostic.range.start.line = diagnostic
+        .range
+        .start
+        .line
+        .saturating_add(region_start_line);
+    diagnostic.range.end.line = diagnostic.range.end.line.saturating_add(region_start_line);
+}
+
+/// Translate a host range to virtual coordinates (lines only).
+fn range_to_virtual(range: &mut Range, region_start_line: u32) {
+    range.start = Position {
+        line: range.start.line.saturating_sub(region_start_line),
+        character: range.start.character,
+    };
+    range.end = Position {
+        line: range.end.line.saturating_sub(region_start_line),
+        character: range.end.character,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::{Command, Uri};
+
+    fn make_host_uri() -> Uri {
+        use url::Url;
+        crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///test.md").unwrap()).unwrap()
+    }
+
+    fn make_virtual_uri_string() -> String {
+        let host_uri = make_host_uri();
+        VirtualDocumentUri::new(&host_uri, "lua", "region-0").to_uri_string()
+    }
+
+    fn make_origin(region_start_line: u32) -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "lua_ls".to_string(),
+            host_uri: make_host_uri(),
+            injection_language: "lua".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line,
+            data: None,
+        }
+    }
+
+    fn range(start_line: u32, start_char: u32, end_line: u32, end_char: u32) -> Range {
+        Range {
+            start: Position {
+                line: start_line,
+                character: start_char,
+            },
+            end: Position {
+                line: end_line,
+                character: end_char,
+            },
+        }
+    }
+
+    // ==========================================================================
+    // Code action request builder tests
+    // ==========================================================================
+
+    #[test]
+    fn code_action_request_translates_range_and_diagnostics() {
+        let host_uri = make_host_uri();
+        let virtual_uri = VirtualDocumentUri::new(&host_uri, "lua", "region-0");
+        let context = CodeActionContext {
+            diagnostics: vec![Diagnostic {
+                range: range(12, 4, 12, 9),
+                message: "unused variable".to_string(),
+                ..Default::default()
+            }],
+            only: None,
+            trigger_kind: None,
+        };
+
+        let request = build_code_action_request(
+            &virtual_uri,
+            range(10, 5, 14, 0),
+            &context,
+            8,
+            RequestId::new(42),
+        );
+
+        assert_eq!(request["id"], 42);
+        assert_eq!(request["method"], "textDocument/codeAction");
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            virtual_uri.to_uri_string()
+        );
+        assert_eq!(request["params"]["range"]["start"]["line"], 2);
+        assert_eq!(request["params"]["range"]["start"]["character"], 5);
+        assert_eq!(request["params"]["range"]["end"]["line"], 6);
+        let diagnostic = &request["params"]["context"]["diagnostics"][0];
+        assert_eq!(diagnostic["range"]["start"]["line"], 4);
+        assert_eq!(diagnostic["range"]["end"]["character"], 9);
+        assert_eq!(diagnostic["message"], "unused variable");
+    }
+
+    #[test]
+    fn code_action_request_range_saturates_at_zero() {
+        let host_uri = make_host_uri();
+        let virtual_uri = VirtualDocumentUri::new(&host_uri, "lua", "region-0");
+
+        let request = build_code_action_request(
+            &virtual_uri,
+            range(2, 0, 5, 0),
+            &CodeActionContext::default(),
+            10,
+            RequestId::new(1),
+        );
+
+        assert_eq!(request["params"]["range"]["start"]["line"], 0);
+        assert_eq!(request["params"]["range"]["end"]["line"], 0);
+    }
+
+    #[test]
+    fn code_action_resolve_request_unwraps_data_and_translates_diagnostics() {
+        let origin = BridgeResolveData {
+            data: Some(json!({"id": 7})),
+            ..make_origin(5)
+        };
+        let action = CodeAction {
+            title: "Remove unused".to_string(),
+            diagnostics: Some(vec![Diagnostic {
+                range: range(6, 0, 6, 3),
+                ..Default::default()
+            }]),
+            data: Some(origin.clone().into_data()),
+            ..Default::default()
+        };
+
+        let request = build_code_action_resolve_request(action, &origin, RequestId::new(3));
+
+        assert_eq!(request["method"], "codeAction/resolve");
+        assert_eq!(request["params"]["data"], json!({"id": 7}));
+        assert_eq!(
+            request["params"]["diagnostics"][0]["range"]["start"]["line"],
+            1
+        );
+    }
+
+    // ==========================================================================
+    // Code action response transformation tests
+    // ==========================================================================
+
+    #[test]
+    fn code_action_response_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 42, "result": null });
+
+        let result = transform_code_action_response_to_host(
+            response,
+            &make_virtual_uri_string(),
+            &make_origin(5),
+        );
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn code_action_response_transforms_edit_and_wraps_data() {
+        let virtual_uri = make_virtual_uri_string();
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "result": [{
+                "title": "Rename to snake_case",
+                "kind": "quickfix",
+                "diagnostics": [{
+                    "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 3 } },
+                    "message": "bad name"
+                }],
+                "edit": {
+                    "changes": {
+                        virtual_uri.clone(): [{
+                            "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 3 } },
+                            "newText": "foo_bar"
+                        }]
+                    }
+                },
+                "data": { "id": 7 }
+            }]
+        });
+
+        let items = transform_code_action_response_to_host(response, &virtual_uri, &make_origin(5))
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &items[0] else {
+            panic!("Expected CodeAction");
+        };
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let edits = changes
+            .get(&make_host_uri())
+            .expect("edits re-keyed to host");
+        assert_eq!(edits[0].range.start.line, 6);
+        assert_eq!(edits[0].new_text, "foo_bar");
+        assert_eq!(action.diagnostics.as_ref().unwrap()[0].range.start.line, 6);
+
+        let envelope = BridgeResolveData::from_data(action.data.as_ref()).unwrap();
+        assert_eq!(envelope.server_name, "lua_ls");
+        assert_eq!(envelope.region_start_line, 5);
+        assert_eq!(envelope.data, Some(json!({"id": 7})));
+    }
+
+    #[test]
+    fn code_action_response_cross_region_edits_filtered_out() {
+        let other_virtual_uri =
+            VirtualDocumentUri::new(&make_host_uri(), "lua", "region-1").to_uri_string();
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "result": [{
+                "title": "Fix",
+                "edit": {
+                    "changes": {
+                        other_virtual_uri: [{
+                            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 1 } },
+                            "newText": "x"
+                        }]
+                    }
+                }
+            }]
+        });
+
+        let items = transform_code_action_response_to_host(
+            response,
+            &make_virtual_uri_string(),
+            &make_origin(5),
+        )
+        .unwrap();
+
+        let CodeActionOrCommand::CodeAction(action) = &items[0] else {
+            panic!("Expected CodeAction");
+        };
+        assert!(
+            action
+                .edit
+                .as_ref()
+                .unwrap()
+                .changes
+                .as_ref()
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn code_action_response_wraps_commands_with_original_arguments() {
+        let virtual_uri = make_virtual_uri_string();
+        let arguments = json!([
+            virtual_uri.clone(),
+            {
+                "uri": virtual_uri.clone(),
+                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 3, "character": 0 } }
+            }
+        ]);
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "result": [
+                { "title": "Disable diagnostic", "command": "lua.disable", "arguments": arguments.clone() },
+                {
+                    "title": "Run",
+                    "command": { "title": "Run", "command": "lua.run", "arguments": arguments.clone() }
+                }
+            ]
+        });
+
+        let items = transform_code_action_response_to_host(response, &virtual_uri, &make_origin(5))
+            .unwrap();
+
+        let CodeActionOrCommand::Command(command) = &items[0] else {
+            panic!("Expected Command");
+        };
+        let (origin, original) =
+            unwrap_bridge_command(&command.command, command.arguments.as_ref().unwrap())
+                .expect("command is routed through the bridge");
+        assert_eq!(origin.server_name, "lua_ls");
+        assert_eq!(original.command, "lua.disable");
+        assert_eq!(json!(original.arguments), arguments);
+
+        let CodeActionOrCommand::CodeAction(action) = &items[1] else {
+            panic!("Expected CodeAction");
+        };
+        let command = action.command.as_ref().unwrap();
+        let (_, original) =
+            unwrap_bridge_command(&command.command, command.arguments.as_ref().unwrap())
+                .expect("action command is routed through the bridge");
+        assert_eq!(original.command, "lua.run");
+        assert_eq!(json!(original.arguments), arguments);
+    }
+
+    #[test]
+    fn code_action_resolve_request_restores_original_command() {
+        let origin = make_origin(5);
+        let original = Command {
+            title: "Run".to_string(),
+            command: "lua.run".to_string(),
+            arguments: Some(vec![json!({"id": 1})]),
+        };
+        let action = CodeAction {
+            title: "Run".to_string(),
+            command: Some(wrap_bridge_command(original, &origin)),
+            data: Some(origin.clone().into_data()),
+            ..Default::default()
+        };
+
+        let request = build_code_action_resolve_request(action, &origin, RequestId::new(3));
+
+        assert_eq!(request["params"]["command"]["command"], "lua.run");
+        assert_eq!(
+            request["params"]["command"]["arguments"],
+            json!([{"id": 1}])
+        );
+    }
+
+    #[test]
+    fn code_action_resolve_response_restores_envelope_with_new_data() {
+        let virtual_uri = make_virtual_uri_string();
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "result": {
+                "title": "Remove unused",
+                "edit": {
+                    "changes": {
+                        virtual_uri.clone(): [{
+                            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 1, "character": 0 } },
+                            "newText": ""
+                        }]
+                    }
+                },
+                "data": { "id": 8 }
+            }
+        });
+
+        let action =
+            transform_code_action_resolve_response_to_host(response, &virtual_uri, &make_origin(5))
+                .unwrap();
+
+        let edits = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        assert_eq!(edits[&make_host_uri()][0].range.end.line, 6);
+        let envelope = BridgeResolveData::from_data(action.data.as_ref()).unwrap();
+        assert_eq!(envelope.data, Some(json!({"id": 8})));
+    }
+
+    #[test]
+    fn code_action_resolve_error_response_returns_none() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "error": { "code": -32601, "message": "Method not found" }
+        });
+
+        let result = transform_code_action_resolve_response_to_host(
+            response,
+            &make_virtual_uri_string(),
+            &make_origin(5),
+        );
+
+        assert!(result.is_none());
+    }
+}
@@ -0,0 +1,311 @@
+//! Code lens request handling for bridge connections.
+//!
+//! This module provides code lens and code lens resolve functionality for
+//! downstream language servers, handling the coordinate transformation between
+//! host and virtual documents.
+//!
+//! Like document symbol, code lens requests operate on the entire document.
+//! Lenses returned upstream carry a [`BridgeResolveData`] envelope in their
+//! `data` field so that a later `codeLens/resolve` reaches the server that
+//! produced them, and their commands are wrapped (see `protocol::bridge_command`)
+//! so that `workspace/executeCommand` is dispatched to the same server.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::CodeLens;
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{
+    BridgeResolveData, RequestId, VirtualDocumentUri, build_whole_document_request,
+    unwrap_bridge_command, wrap_bridge_command,
+};
+
+impl LanguageServerPool {
+    /// Send a code lens request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle, providing code-lens-specific request building and response
+    /// transformation.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_code_lens_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<CodeLens>>> {
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            build_code_lens_request,
+            |response, ctx| {
+                let origin = BridgeResolveData {
+                    server_name: server_name.to_string(),
+                    host_uri: ctx.host_uri_lsp.clone(),
+                    injection_language: injection_language.to_string(),
+                    region_id: region_id.to_string(),
+                    region_start_line: ctx.region_start_line,
+                    data: None,
+                };
+                transform_code_lens_response_to_host(response, &origin)
+            },
+        )
+        .await
+    }
+
+    /// Send a codeLens/resolve request to the server that produced the lens.
+    ///
+    /// `origin` is the envelope added by
+    /// [`send_code_lens_request`](Self::send_code_lens_request). The server sees
+    /// the lens as it produced it, and the resolved lens is returned in host
+    /// coordinates with the envelope restored.
+    ///
+    /// Returns `Ok(None)` if the server returned an error or an unparsable result.
+    pub(crate) async fn send_code_lens_resolve_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        lens: CodeLens,
+        origin: BridgeResolveData,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<CodeLens>> {
+        self.execute_server_request(
+            &origin.server_name,
+            server_config,
+            upstream_request_id,
+            |request_id| build_code_lens_resolve_request(lens, &origin, request_id),
+            |response| transform_code_lens_resolve_response_to_host(response, &origin),
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC code lens request for a downstream language server.
+fn build_code_lens_request(
+    virtual_uri: &VirtualDocumentUri,
+    request_id: RequestId,
+) -> serde_json::Value {
+    build_whole_document_request(virtual_uri, request_id, "textDocument/codeLens")
+}
+
+/// Build a JSON-RPC codeLens/resolve request for a downstream language server.
+///
+/// Restores the lens the server produced: original `data`, original command
+/// (if the lens was already resolved) and virtual coordinates.
+///
+/// # Defensive Arithmetic
+///
+/// Uses `saturating_sub` for line translation to prevent panic on underflow during
+/// race conditions when document edits invalidate region data.
+fn build_code_lens_resolve_request(
+    mut lens: CodeLens,
+    origin: &BridgeResolveData,
+    request_id: RequestId,
+) -> serde_json::Value {
+    lens.data = origin.data.clone();
+    lens.range.start.line = lens
+        .range
+        .start
+        .line
+        .saturating_sub(origin.region_start_line);
+    lens.range.end.line = lens.range.end.line.saturating_sub(origin.region_start_line);
+    if let Some(command) = &lens.command
+        && let Some((_, original)) = unwrap_bridge_command(
+            &command.command,
+            command.arguments.as_deref().unwrap_or(&[]),
+        )
+    {
+        lens.command = Some(original);
+    }
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "codeLens/resolve",
+        "params": lens
+    })
+}
+
+/// Transform a code lens response from virtual to host document coordinates.
+///
+/// # Arguments
+/// * `response` - The JSON-RPC response from the downstream language server
+/// * `origin` - The origin envelope (without data) for the region that was queried
+fn transform_code_lens_response_to_host(
+    mut response: serde_json::Value,
+    origin: &BridgeResolveData,
+) -> Option<Vec<CodeLens>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/codeLens: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut lenses: Vec<CodeLens> = serde_json::from_value(result).ok()?;
+    for lens in &mut lenses {
+        transform_code_lens_to_host(lens, origin);
+    }
+
+    Some(lenses)
+}
+
+/// Transform a codeLens/resolve response from virtual to host document coordinates.
+fn transform_code_lens_resolve_response_to_host(
+    mut response: serde_json::Value,
+    origin: &BridgeResolveData,
+) -> Option<CodeLens> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for codeLens/resolve: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut lens: CodeLens = serde_json::from_value(result).ok()?;
+    transform_code_lens_to_host(&mut lens, origin);
+    Some(lens)
+}
+
+/// Transform a single CodeLens to host coordinates and wrap its command and data.
+fn transform_code_lens_to_host(lens: &mut CodeLens, origin: &BridgeResolveData) {
+    lens.range.start.line = lens
+        .range
+        .start
+        .line
+        .saturating_add(origin.region_start_line);
+    lens.range.end.line = lens.range.end.line.saturating_add(origin.region_start_line);
+
+    if let Some(command) = lens.command.take() {
+        lens.command = Some(wrap_bridge_command(command, origin));
+    }
+
+    lens.data = Some(
+        BridgeResolveData {
+            data: lens.data.take(),
+            ..origin.clone()
+        }
+        .into_data(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::bridge::protocol::BRIDGE_EXECUTE_COMMAND;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::Uri;
+
+    fn host_uri() -> Uri {
+        crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap()).unwrap()
+    }
+
+    fn origin() -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "rust-analyzer".to_string(),
+            host_uri: host_uri(),
+            injection_language: "rust".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line: 5,
+            data: None,
+        }
+    }
+
+    fn lens_json() -> serde_json::Value {
+        json!({
+            "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 8 } },
+            "command": {
+                "title": "▶ Run",
+                "command": "rust-analyzer.runSingle",
+                "arguments": [{ "label": "test it_works" }]
+            },
+            "data": { "kind": "runnable" }
+        })
+    }
+
+    #[test]
+    fn code_lens_request_uses_virtual_uri() {
+        let virtual_uri = VirtualDocumentUri::new(&host_uri(), "rust", "region-0");
+
+        let request = build_code_lens_request(&virtual_uri, RequestId::new(4));
+
+        assert_eq!(request["method"], "textDocument/codeLens");
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            virtual_uri.to_uri_string()
+        );
+    }
+
+    #[test]
+    fn code_lens_response_translates_range_and_wraps_command_and_data() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": [lens_json()] });
+
+        let lenses = transform_code_lens_response_to_host(response, &origin()).unwrap();
+
+        assert_eq!(lenses.len(), 1);
+        assert_eq!(lenses[0].range.start.line, 6);
+        assert_eq!(lenses[0].range.end.line, 6);
+        let command = lenses[0].command.as_ref().unwrap();
+        assert_eq!(command.title, "▶ Run");
+        assert_eq!(command.command, BRIDGE_EXECUTE_COMMAND);
+        let envelope = BridgeResolveData::from_data(lenses[0].data.as_ref()).unwrap();
+        assert_eq!(envelope.server_name, "rust-analyzer");
+        assert_eq!(envelope.data, Some(json!({ "kind": "runnable" })));
+    }
+
+    #[test]
+    fn code_lens_resolve_round_trips_server_view() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": [lens_json()] });
+        let lens = transform_code_lens_response_to_host(response, &origin())
+            .unwrap()
+            .remove(0);
+        let envelope = BridgeResolveData::from_data(lens.data.as_ref()).unwrap();
+
+        let request = build_code_lens_resolve_request(lens, &envelope, RequestId::new(2));
+
+        assert_eq!(request["method"], "codeLens/resolve");
+        assert_eq!(request["params"], lens_json());
+    }
+
+    #[test]
+    fn code_lens_resolve_response_is_translated() {
+        let mut resolved = lens_json();
+        resolved["command"]["title"] = json!("3 references");
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": resolved });
+
+        let lens = transform_code_lens_resolve_response_to_host(response, &origin()).unwrap();
+
+        assert_eq!(lens.range.start.line, 6);
+        assert_eq!(lens.command.unwrap().title, "3 references");
+        assert!(BridgeResolveData::from_data(lens.data.as_ref()).is_some());
+    }
+
+    #[test]
+    fn code_lens_response_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+
+        assert!(transform_code_lens_response_to_host(response, &origin()).is_none());
+    }
+}
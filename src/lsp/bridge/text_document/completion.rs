@@ -1,7 +1,12 @@
 //! Completion request handling for bridge connections.
 //!
-//! This module provides completion request functionality for downstream language servers,
-//! handling the coordinate transformation between host and virtual documents.
+//! This module provides completion and completion item resolve functionality for
+//! downstream language servers, handling the coordinate transformation between host
+//! and virtual documents.
+//!
+//! Items returned upstream carry a [`BridgeResolveData`] envelope in their `data`
+//! field so that `completionItem/resolve` can be routed back to the server that
+//! produced them (lazily computed documentation, auto-import edits, ...).
 //!
 //! # Single-Writer Loop (ADR-0015)
 //!
@@ -17,7 +22,9 @@ use tower_lsp_server::ls_types::{CompletionItem, CompletionList, Position, Range
 use url::Url;
 
 use super::super::pool::{LanguageServerPool, UpstreamId};
-use super::super::protocol::{RequestId, VirtualDocumentUri, build_position_based_request};
+use super::super::protocol::{
+    BridgeResolveData, RequestId, VirtualDocumentUri, build_position_based_request,
+};
 
 impl LanguageServerPool {
     /// Send a completion request and wait for the response.
@@ -54,7 +61,46 @@ impl LanguageServerPool {
             |virtual_uri, request_id| {
                 build_completion_request(virtual_uri, host_position, region_start_line, request_id)
             },
-            |response, ctx| transform_completion_response_to_host(response, ctx.region_start_line),
+            |response, ctx| {
+                let mut list =
+                    transform_completion_response_to_host(response, ctx.region_start_line)?;
+                let origin = BridgeResolveData {
+                    server_name: server_name.to_string(),
+                    host_uri: ctx.host_uri_lsp.clone(),
+                    injection_language: injection_language.to_string(),
+                    region_id: region_id.to_string(),
+                    region_start_line: ctx.region_start_line,
+                    data: None,
+                };
+                attach_resolve_data(&mut list, &origin);
+                Some(list)
+            },
+        )
+        .await
+    }
+
+    /// Send a completionItem/resolve request to the server that produced the item.
+    ///
+    /// The item's `data` must still carry the envelope added by
+    /// [`send_completion_request`](Self::send_completion_request); `origin` is that
+    /// envelope. The server sees its original `data` and virtual coordinates, and the
+    /// resolved item (including `additionalTextEdits`) is returned in host
+    /// coordinates with the envelope restored.
+    ///
+    /// Returns `Ok(None)` if the server returned an error or an unparsable result.
+    pub(crate) async fn send_completion_resolve_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        item: CompletionItem,
+        origin: BridgeResolveData,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<CompletionItem>> {
+        self.execute_server_request(
+            &origin.server_name,
+            server_config,
+            upstream_request_id,
+            |request_id| build_completion_resolve_request(item, &origin, request_id),
+            |response| transform_completion_resolve_response_to_host(response, &origin),
         )
         .await
     }
@@ -76,6 +122,26 @@ fn build_completion_request(
     )
 }
 
+/// Build a JSON-RPC completionItem/resolve request for a downstream language server.
+///
+/// Replaces the envelope in `data` with the server's original data and
+/// translates the item's edits back to virtual coordinates.
+fn build_completion_resolve_request(
+    mut item: CompletionItem,
+    origin: &BridgeResolveData,
+    request_id: RequestId,
+) -> serde_json::Value {
+    item.data = origin.data.clone();
+    transform_completion_item_to_virtual(&mut item, origin.region_start_line);
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "completionItem/resolve",
+        "params": item
+    })
+}
+
 /// Parse a JSON-RPC completion response and transform coordinates to host document space.
 ///
 /// Normalizes all responses to `CompletionList` format. If the server returns an array,
@@ -124,6 +190,47 @@ fn transform_completion_response_to_host(
     Some(list)
 }
 
+/// Wrap every item's `data` in the origin envelope for later resolution.
+fn attach_resolve_data(list: &mut CompletionList, origin: &BridgeResolveData) {
+    for item in &mut list.items {
+        item.data = Some(
+            BridgeResolveData {
+                data: item.data.take(),
+                ..origin.clone()
+            }
+            .into_data(),
+        );
+    }
+}
+
+/// Parse a completionItem/resolve response and transform it to host document space.
+///
+/// The resolved item's `data` is wrapped in the origin envelope again, so the item
+/// can be resolved repeatedly.
+fn transform_completion_resolve_response_to_host(
+    mut response: serde_json::Value,
+    origin: &BridgeResolveData,
+) -> Option<CompletionItem> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for completionItem/resolve: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+    if result.is_null() {
+        return None;
+    }
+
+    let mut item: CompletionItem = serde_json::from_value(result).ok()?;
+    transform_completion_item(&mut item, origin.region_start_line);
+    item.data = Some(
+        BridgeResolveData {
+            data: item.data.take(),
+            ..origin.clone()
+        }
+        .into_data(),
+    );
+    Some(item)
+}
+
 /// Transform textEdit range in a single completion item to host coordinates.
 ///
 /// Handles both TextEdit format and InsertReplaceEdit format. Also transforms
@@ -160,6 +267,36 @@ fn transform_range(range: &mut Range, region_start_line: u32) {
     range.end.line = range.end.line.saturating_add(region_start_line);
 }
 
+/// Transform a completion item's edits from host back to virtual coordinates.
+///
+/// Inverse of [`transform_completion_item`], used when the item is sent back
+/// downstream for resolution. Uses saturating_sub like other host→virtual
+/// translations.
+fn transform_completion_item_to_virtual(item: &mut CompletionItem, region_start_line: u32) {
+    let to_virtual = |range: &mut Range| {
+        range.start.line = range.start.line.saturating_sub(region_start_line);
+        range.end.line = range.end.line.saturating_sub(region_start_line);
+    };
+
+    if let Some(ref mut text_edit) = item.text_edit {
+        match text_edit {
+            tower_lsp_server::ls_types::CompletionTextEdit::Edit(edit) => {
+                to_virtual(&mut edit.range);
+            }
+            tower_lsp_server::ls_types::CompletionTextEdit::InsertAndReplace(edit) => {
+                to_virtual(&mut edit.insert);
+                to_virtual(&mut edit.replace);
+            }
+        }
+    }
+
+    if let Some(ref mut additional_edits) = item.additional_text_edits {
+        for edit in additional_edits {
+            to_virtual(&mut edit.range);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -494,4 +631,112 @@ mod tests {
             panic!("Expected TextEdit");
         }
     }
+
+    // ==========================================================================
+    // Completion resolve tests
+    // ==========================================================================
+
+    fn test_origin(data: Option<serde_json::Value>) -> BridgeResolveData {
+        BridgeResolveData {
+            server_name: "rust-analyzer".to_string(),
+            host_uri: test_host_uri(),
+            injection_language: "rust".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line: 3,
+            data,
+        }
+    }
+
+    #[test]
+    fn completion_items_get_resolve_envelope() {
+        let mut list = CompletionList {
+            is_incomplete: false,
+            items: vec![
+                CompletionItem {
+                    label: "HashMap".to_string(),
+                    data: Some(json!({"imports": ["std::collections::HashMap"]})),
+                    ..Default::default()
+                },
+                CompletionItem {
+                    label: "Vec".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        attach_resolve_data(&mut list, &test_origin(None));
+
+        let first = BridgeResolveData::from_data(list.items[0].data.as_ref()).unwrap();
+        assert_eq!(first.server_name, "rust-analyzer");
+        assert_eq!(
+            first.data,
+            Some(json!({"imports": ["std::collections::HashMap"]}))
+        );
+        let second = BridgeResolveData::from_data(list.items[1].data.as_ref()).unwrap();
+        assert_eq!(second.data, None);
+    }
+
+    #[test]
+    fn completion_resolve_request_unwraps_data_and_translates_edits() {
+        let origin = test_origin(Some(json!({"id": 1})));
+        let item = CompletionItem {
+            label: "print".to_string(),
+            text_edit: Some(tower_lsp_server::ls_types::CompletionTextEdit::Edit(
+                tower_lsp_server::ls_types::TextEdit {
+                    range: Range {
+                        start: Position::new(5, 0),
+                        end: Position::new(5, 3),
+                    },
+                    new_text: "print".to_string(),
+                },
+            )),
+            data: Some(origin.clone().into_data()),
+            ..Default::default()
+        };
+
+        let request = build_completion_resolve_request(item, &origin, test_request_id());
+
+        assert_eq!(request["method"], "completionItem/resolve");
+        assert_eq!(request["id"], 42);
+        assert_eq!(request["params"]["data"], json!({"id": 1}));
+        assert_eq!(request["params"]["textEdit"]["range"]["start"]["line"], 2);
+    }
+
+    #[test]
+    fn completion_resolve_response_translates_additional_text_edits() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "result": {
+                "label": "HashMap",
+                "documentation": "A hash map",
+                "additionalTextEdits": [{
+                    "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                    "newText": "use std::collections::HashMap;\n"
+                }],
+                "data": { "id": 2 }
+            }
+        });
+
+        let item =
+            transform_completion_resolve_response_to_host(response, &test_origin(None)).unwrap();
+
+        let edits = item.additional_text_edits.as_ref().unwrap();
+        assert_eq!(edits[0].range.start.line, 3);
+        let envelope = BridgeResolveData::from_data(item.data.as_ref()).unwrap();
+        assert_eq!(envelope.data, Some(json!({"id": 2})));
+    }
+
+    #[test]
+    fn completion_resolve_error_response_returns_none() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 42,
+            "error": { "code": -32601, "message": "Method not found" }
+        });
+
+        assert!(
+            transform_completion_resolve_response_to_host(response, &test_origin(None)).is_none()
+        );
+    }
 }
@@ -14,7 +14,6 @@
 //! writer task, ensuring FIFO ordering with other messages.
 
 use std::io;
-use std::time::Duration;
 
 use log::warn;
 
@@ -22,7 +21,7 @@ use crate::config::settings::BridgeServerConfig;
 use tower_lsp_server::ls_types::Diagnostic;
 use url::Url;
 
-use super::super::pool::{INIT_TIMEOUT_SECS, LanguageServerPool, UpstreamId};
+use super::super::pool::{LanguageServerPool, UpstreamId, init_timeout};
 use super::super::protocol::{RequestId, VirtualDocumentUri};
 
 impl LanguageServerPool {
@@ -56,7 +55,7 @@ impl LanguageServerPool {
             .get_or_create_connection_wait_ready(
                 server_name,
                 server_config,
-                Duration::from_secs(INIT_TIMEOUT_SECS),
+                init_timeout(server_config),
             )
             .await?;
 
@@ -0,0 +1,202 @@
+//! Folding range request handling for bridge connections.
+//!
+//! This module provides folding range request functionality for downstream language
+//! servers, handling the coordinate transformation between host and virtual documents.
+//!
+//! Like document symbol, folding range requests operate on the entire document -
+//! they don't take a position parameter. Returned ranges are clipped to the
+//! injection region so a server cannot fold host lines past the end of the block.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::FoldingRange;
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{RequestId, VirtualDocumentUri, build_whole_document_request};
+
+impl LanguageServerPool {
+    /// Send a folding range request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle, providing folding-range-specific request building and response
+    /// transformation.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_folding_range_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<FoldingRange>>> {
+        let region_line_count = virtual_content.lines().count() as u32;
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            build_folding_range_request,
+            |response, ctx| {
+                transform_folding_range_response_to_host(
+                    response,
+                    ctx.region_start_line,
+                    region_line_count,
+                )
+            },
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC folding range request for a downstream language server.
+fn build_folding_range_request(
+    virtual_uri: &VirtualDocumentUri,
+    request_id: RequestId,
+) -> serde_json::Value {
+    build_whole_document_request(virtual_uri, request_id, "textDocument/foldingRange")
+}
+
+/// Transform a folding range response from virtual to host document coordinates.
+///
+/// Ranges starting past the last line of the region are dropped. Ranges ending
+/// past it are clipped to the region's last line; their `endCharacter` no longer
+/// applies and is cleared. The remaining lines are shifted by `region_start_line`.
+///
+/// # Arguments
+/// * `response` - The JSON-RPC response from the downstream language server
+/// * `region_start_line` - The starting line of the injection region in the host document
+/// * `region_line_count` - The number of lines in the virtual document
+fn transform_folding_range_response_to_host(
+    mut response: serde_json::Value,
+    region_start_line: u32,
+    region_line_count: u32,
+) -> Option<Vec<FoldingRange>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/foldingRange: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut ranges: Vec<FoldingRange> = serde_json::from_value(result).ok()?;
+    let last_line = region_line_count.checked_sub(1)?;
+
+    ranges.retain_mut(|range| {
+        if range.start_line > last_line || range.end_line < range.start_line {
+            return false;
+        }
+        if range.end_line > last_line {
+            range.end_line = last_line;
+            range.end_character = None;
+        }
+        range.start_line = range.start_line.saturating_add(region_start_line);
+        range.end_line = range.end_line.saturating_add(region_start_line);
+        true
+    });
+
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::{FoldingRangeKind, Uri};
+
+    fn make_virtual_uri() -> VirtualDocumentUri {
+        let host_uri: Uri =
+            crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap())
+                .unwrap();
+        VirtualDocumentUri::new(&host_uri, "rust", "region-0")
+    }
+
+    #[test]
+    fn folding_range_request_uses_virtual_uri() {
+        let request = build_folding_range_request(&make_virtual_uri(), RequestId::new(3));
+
+        assert_eq!(request["id"], 3);
+        assert_eq!(request["method"], "textDocument/foldingRange");
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            make_virtual_uri().to_uri_string()
+        );
+    }
+
+    #[test]
+    fn folding_range_response_shifts_lines_to_host() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                { "startLine": 0, "startCharacter": 11, "endLine": 3, "endCharacter": 1 },
+                { "startLine": 1, "endLine": 2, "kind": "comment" }
+            ]
+        });
+
+        let ranges = transform_folding_range_response_to_host(response, 5, 4).unwrap();
+
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].start_line, 5);
+        assert_eq!(ranges[0].start_character, Some(11));
+        assert_eq!(ranges[0].end_line, 8);
+        assert_eq!(ranges[0].end_character, Some(1));
+        assert_eq!(ranges[1].start_line, 6);
+        assert_eq!(ranges[1].end_line, 7);
+        assert_eq!(ranges[1].kind, Some(FoldingRangeKind::Comment));
+    }
+
+    #[test]
+    fn folding_range_response_clips_ranges_to_region() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                { "startLine": 1, "endLine": 9, "endCharacter": 4 },
+                { "startLine": 4, "endLine": 6 }
+            ]
+        });
+
+        let ranges = transform_folding_range_response_to_host(response, 10, 4).unwrap();
+
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].start_line, 11);
+        assert_eq!(ranges[0].end_line, 13);
+        assert_eq!(ranges[0].end_character, None);
+    }
+
+    #[test]
+    fn folding_range_response_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+
+        assert!(transform_folding_range_response_to_host(response, 5, 4).is_none());
+    }
+
+    #[test]
+    fn folding_range_error_response_returns_none() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32603, "message": "internal error" }
+        });
+
+        assert!(transform_folding_range_response_to_host(response, 5, 4).is_none());
+    }
+}
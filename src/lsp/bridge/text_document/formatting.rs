@@ -0,0 +1,559 @@
+//! Formatting request handling for bridge connections.
+//!
+//! This module provides document and range formatting functionality for
+//! downstream language servers, handling the coordinate transformation between
+//! host and virtual documents.
+//!
+//! Like diagnostics, formatting fans out over every injection region of the host
+//! document, so requests wait for the server to become Ready and skip servers
+//! that do not advertise the formatting capability.
+//!
+//! Downstream edits are merged into a single replacement per region by
+//! [`region_formatting_edit`], which also re-indents the formatted code to the
+//! block's original indentation (the fence's indentation for Markdown).
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+use std::ops::Range as ByteRange;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use crate::text::PositionMapper;
+use tower_lsp_server::ls_types::{FormattingOptions, Position, Range, TextEdit};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId, init_timeout};
+use super::super::protocol::{RequestId, VirtualDocumentUri};
+
+impl LanguageServerPool {
+    /// Send a formatting request for a whole injection region.
+    ///
+    /// Waits for the server to become Ready (like diagnostics) so that its
+    /// capabilities are known, and returns `Ok(None)` without sending anything
+    /// if the server does not support `textDocument/formatting`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_formatting_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        options: &FormattingOptions,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<TextEdit>>> {
+        const METHOD: &str = "textDocument/formatting";
+
+        let handle = self
+            .get_or_create_connection_wait_ready(
+                server_name,
+                server_config,
+                init_timeout(server_config),
+            )
+            .await?;
+
+        if !handle.has_capability(METHOD) {
+            log::debug!(
+                target: "kakehashi::bridge",
+                "[{}] Server does not support {}, skipping",
+                server_name,
+                METHOD
+            );
+            return Ok(None);
+        }
+
+        self.execute_bridge_request_with_handle(
+            handle,
+            server_name,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| build_formatting_request(virtual_uri, options, request_id),
+            |response, ctx| {
+                transform_formatting_response_to_host(response, METHOD, ctx.region_start_line)
+            },
+        )
+        .await
+    }
+
+    /// Send a range formatting request for part of an injection region.
+    ///
+    /// `host_range` should already be clamped to the region. Returns `Ok(None)`
+    /// without sending anything if the server does not support
+    /// `textDocument/rangeFormatting`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_range_formatting_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_range: Range,
+        options: &FormattingOptions,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<TextEdit>>> {
+        const METHOD: &str = "textDocument/rangeFormatting";
+
+        let handle = self
+            .get_or_create_connection_wait_ready(
+                server_name,
+                server_config,
+                init_timeout(server_config),
+            )
+            .await?;
+
+        if !handle.has_capability(METHOD) {
+            log::debug!(
+                target: "kakehashi::bridge",
+                "[{}] Server does not support {}, skipping",
+                server_name,
+                METHOD
+            );
+            return Ok(None);
+        }
+
+        self.execute_bridge_request_with_handle(
+            handle,
+            server_name,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_range_formatting_request(
+                    virtual_uri,
+                    host_range,
+                    options,
+                    region_start_line,
+                    request_id,
+                )
+            },
+            |response, ctx| {
+                transform_formatting_response_to_host(response, METHOD, ctx.region_start_line)
+            },
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC formatting request for a downstream language server.
+///
+/// Formatting options are forwarded verbatim.
+fn build_formatting_request(
+    virtual_uri: &VirtualDocumentUri,
+    options: &FormattingOptions,
+    request_id: RequestId,
+) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "textDocument/formatting",
+        "params": {
+            "textDocument": {
+                "uri": virtual_uri.to_uri_string()
+            },
+            "options": options
+        }
+    })
+}
+
+/// Build a JSON-RPC range formatting request for a downstream language server.
+///
+/// # Defensive Arithmetic
+///
+/// Uses `saturating_sub` for line translation to prevent panic on underflow during
+/// race conditions when document edits invalidate region data.
+fn build_range_formatting_request(
+    virtual_uri: &VirtualDocumentUri,
+    host_range: Range,
+    options: &FormattingOptions,
+    region_start_line: u32,
+    request_id: RequestId,
+) -> serde_json::Value {
+    let virtual_range = Range {
+        start: Position {
+            line: host_range.start.line.saturating_sub(region_start_line),
+            character: host_range.start.character,
+        },
+        end: Position {
+            line: host_range.end.line.saturating_sub(region_start_line),
+            character: host_range.end.character,
+        },
+    };
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "textDocument/rangeFormatting",
+        "params": {
+            "textDocument": {
+                "uri": virtual_uri.to_uri_string()
+            },
+            "range": virtual_range,
+            "options": options
+        }
+    })
+}
+
+/// Transform a formatting response from virtual to host document coordinates.
+///
+/// Formatting responses are `TextEdit[] | null`; every edit targets the
+/// requested document, so only line numbers need translating.
+fn transform_formatting_response_to_host(
+    mut response: serde_json::Value,
+    method: &str,
+    region_start_line: u32,
+) -> Option<Vec<TextEdit>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for {}: {}", method, error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut edits: Vec<TextEdit> = serde_json::from_value(result).ok()?;
+    for edit in &mut edits {
+        edit.range.start.line = edit.range.start.line.saturating_add(region_start_line);
+        edit.range.end.line = edit.range.end.line.saturating_add(region_start_line);
+    }
+
+    Some(edits)
+}
+
+/// Merge a region's formatting edits into a single host edit.
+///
+/// The edits (in host coordinates) are applied to the region's text, and the
+/// result is re-indented to the indentation shared by the region's original
+/// lines. Formatters see the block without knowing where it lives in the host,
+/// so their output typically starts at column 0; re-indenting keeps blocks in
+/// Markdown list items (and other indented injections) in place.
+///
+/// Returns `None` if the edits are unusable (outside the region, overlapping)
+/// or if the formatted text equals the original.
+pub(crate) fn region_formatting_edit(
+    host_text: &str,
+    region_byte_range: ByteRange<usize>,
+    edits: &[TextEdit],
+) -> Option<TextEdit> {
+    let original = host_text.get(region_byte_range.clone())?;
+    let mapper = PositionMapper::new(host_text);
+
+    // Convert to region-relative byte offsets, then apply back to front
+    let mut byte_edits = edits
+        .iter()
+        .map(|edit| {
+            let start = mapper.position_to_byte(edit.range.start)?;
+            let end = mapper.position_to_byte(edit.range.end)?;
+            if start < region_byte_range.start || end > region_byte_range.end || start > end {
+                return None;
+            }
+            Some((
+                start - region_byte_range.start,
+                end - region_byte_range.start,
+                edit.new_text.as_str(),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    byte_edits.sort_by_key(|(start, end, _)| (*start, *end));
+    if byte_edits.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return None;
+    }
+
+    let mut formatted = original.to_string();
+    for (start, end, new_text) in byte_edits.into_iter().rev() {
+        formatted.replace_range(start..end, new_text);
+    }
+
+    // A region that starts mid-line (e.g., after a template string delimiter)
+    // has no indentation of its own on the first line.
+    let starts_at_line_start =
+        region_byte_range.start == 0 || host_text.as_bytes()[region_byte_range.start - 1] == b'\n';
+    let indent = if starts_at_line_start {
+        common_indent(original)
+    } else {
+        ""
+    };
+    let mut reindented = reindent(&formatted, indent, starts_at_line_start);
+
+    // Keep the region's trailing newline state (the closing fence follows it)
+    match (original.ends_with('\n'), reindented.ends_with('\n')) {
+        (true, false) => reindented.push('\n'),
+        (false, true) => {
+            reindented.pop();
+        }
+        _ => {}
+    }
+
+    if reindented == original {
+        return None;
+    }
+
+    Some(TextEdit {
+        range: mapper.byte_range_to_range(region_byte_range.start, region_byte_range.end)?,
+        new_text: reindented,
+    })
+}
+
+/// Longest whitespace prefix shared by all non-blank lines.
+fn common_indent(text: &str) -> &str {
+    let mut indent: Option<&str> = None;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let leading = &line[..line.len() - line.trim_start().len()];
+        indent = Some(match indent {
+            None => leading,
+            Some(current) => {
+                let shared = current
+                    .char_indices()
+                    .zip(leading.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(current.len().min(leading.len()), |((i, _), _)| i);
+                &current[..shared]
+            }
+        });
+    }
+    indent.unwrap_or("")
+}
+
+/// Replace the common indentation of `text` with `indent`.
+///
+/// Blank lines are left empty. If `indent_first_line` is false, the first line
+/// is kept as-is (it continues a host line).
+fn reindent(text: &str, indent: &str, indent_first_line: bool) -> String {
+    let current = common_indent(text);
+    let mut result = String::with_capacity(text.len());
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.strip_prefix(current).unwrap_or(line);
+        let is_blank = content.trim().is_empty();
+        if i == 0 && !indent_first_line {
+            result.push_str(line);
+        } else if is_blank {
+            result.push_str(if content.ends_with('\n') { "\n" } else { "" });
+        } else {
+            result.push_str(indent);
+            result.push_str(content);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::Uri;
+
+    fn make_virtual_uri() -> VirtualDocumentUri {
+        use url::Url;
+        let host_uri: Uri =
+            crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap())
+                .unwrap();
+        VirtualDocumentUri::new(&host_uri, "rust", "region-0")
+    }
+
+    fn edit(start: (u32, u32), end: (u32, u32), new_text: &str) -> TextEdit {
+        TextEdit {
+            range: Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            },
+            new_text: new_text.to_string(),
+        }
+    }
+
+    fn region_of(host: &str, content: &str) -> ByteRange<usize> {
+        let start = host.find(content).unwrap();
+        start..start + content.len()
+    }
+
+    // ==========================================================================
+    // Formatting request builder tests
+    // ==========================================================================
+
+    #[test]
+    fn formatting_request_forwards_options() {
+        let options = FormattingOptions {
+            tab_size: 2,
+            insert_spaces: true,
+            ..Default::default()
+        };
+
+        let request = build_formatting_request(&make_virtual_uri(), &options, RequestId::new(7));
+
+        assert_eq!(request["id"], 7);
+        assert_eq!(request["method"], "textDocument/formatting");
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            make_virtual_uri().to_uri_string()
+        );
+        assert_eq!(request["params"]["options"]["tabSize"], 2);
+        assert_eq!(request["params"]["options"]["insertSpaces"], true);
+    }
+
+    #[test]
+    fn range_formatting_request_translates_range() {
+        let host_range = Range {
+            start: Position::new(12, 0),
+            end: Position::new(15, 4),
+        };
+
+        let request = build_range_formatting_request(
+            &make_virtual_uri(),
+            host_range,
+            &FormattingOptions::default(),
+            10,
+            RequestId::new(1),
+        );
+
+        assert_eq!(request["method"], "textDocument/rangeFormatting");
+        assert_eq!(request["params"]["range"]["start"]["line"], 2);
+        assert_eq!(request["params"]["range"]["end"]["line"], 5);
+        assert_eq!(request["params"]["range"]["end"]["character"], 4);
+    }
+
+    // ==========================================================================
+    // Formatting response transformation tests
+    // ==========================================================================
+
+    #[test]
+    fn formatting_response_translates_lines() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [{
+                "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 1, "character": 0 } },
+                "newText": "fn main() {}\n"
+            }]
+        });
+
+        let edits =
+            transform_formatting_response_to_host(response, "textDocument/formatting", 4).unwrap();
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start.line, 4);
+        assert_eq!(edits[0].range.end.line, 5);
+    }
+
+    #[test]
+    fn formatting_response_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+
+        assert!(
+            transform_formatting_response_to_host(response, "textDocument/formatting", 4).is_none()
+        );
+    }
+
+    #[test]
+    fn formatting_error_response_returns_none() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32603, "message": "rustfmt failed" }
+        });
+
+        assert!(
+            transform_formatting_response_to_host(response, "textDocument/formatting", 4).is_none()
+        );
+    }
+
+    // ==========================================================================
+    // Region merge / re-indent tests
+    // ==========================================================================
+
+    #[test]
+    fn region_edit_replaces_block_with_formatted_text() {
+        let host = "# Title\n\n```rust\nfn main(){}\n```\n";
+        let region = region_of(host, "fn main(){}\n");
+        let edits = [edit((3, 0), (4, 0), "fn main() {}\n")];
+
+        let result = region_formatting_edit(host, region, &edits).unwrap();
+
+        assert_eq!(result.range.start, Position::new(3, 0));
+        assert_eq!(result.range.end, Position::new(4, 0));
+        assert_eq!(result.new_text, "fn main() {}\n");
+    }
+
+    #[test]
+    fn region_edit_reindents_to_fence_indentation() {
+        let host = "- item\n\n   ```rust\n   fn main(){\n   let x=1;}\n   ```\n";
+        let content = "   fn main(){\n   let x=1;}\n";
+        let region = region_of(host, content);
+        // Formatter output starts at column 0
+        let edits = [edit((3, 0), (5, 0), "fn main() {\n    let x = 1;\n}\n")];
+
+        let result = region_formatting_edit(host, region, &edits).unwrap();
+
+        assert_eq!(result.new_text, "   fn main() {\n       let x = 1;\n   }\n");
+    }
+
+    #[test]
+    fn region_edit_keeps_blank_lines_empty() {
+        let host = "  ```py\n  a=1\n\n  b=2\n  ```\n";
+        let region = region_of(host, "  a=1\n\n  b=2\n");
+        let edits = [edit((1, 0), (4, 0), "a = 1\n\nb = 2\n")];
+
+        let result = region_formatting_edit(host, region, &edits).unwrap();
+
+        assert_eq!(result.new_text, "  a = 1\n\n  b = 2\n");
+    }
+
+    #[test]
+    fn region_edit_is_none_when_already_formatted() {
+        let host = "```rust\nfn main() {}\n```\n";
+        let region = region_of(host, "fn main() {}\n");
+        let edits = [edit((1, 0), (2, 0), "fn main() {}\n")];
+
+        assert!(region_formatting_edit(host, region, &edits).is_none());
+    }
+
+    #[test]
+    fn region_edit_applies_multiple_fine_grained_edits() {
+        let host = "```json\n{\"a\":1,\"b\":2}\n```\n";
+        let region = region_of(host, "{\"a\":1,\"b\":2}\n");
+        let edits = [
+            edit((1, 5), (1, 5), " "),
+            edit((1, 11), (1, 11), " "),
+            edit((1, 1), (1, 1), " "),
+        ];
+
+        let result = region_formatting_edit(host, region, &edits).unwrap();
+
+        assert_eq!(result.new_text, "{ \"a\": 1,\"b\": 2}\n");
+    }
+
+    #[test]
+    fn region_edit_rejects_edits_outside_region() {
+        let host = "# Title\n```rust\nfn main(){}\n```\n";
+        let region = region_of(host, "fn main(){}\n");
+        let edits = [edit((0, 0), (0, 1), "")];
+
+        assert!(region_formatting_edit(host, region, &edits).is_none());
+    }
+
+    #[test]
+    fn region_edit_preserves_trailing_newline_state() {
+        let host = "const q = sql`select 1`;";
+        let region = region_of(host, "select 1");
+        let edits = [edit((0, 14), (0, 22), "SELECT 1\n")];
+
+        let result = region_formatting_edit(host, region, &edits).unwrap();
+
+        assert_eq!(result.new_text, "SELECT 1");
+    }
+}
@@ -0,0 +1,207 @@
+//! Linked editing range request handling for bridge connections.
+//!
+//! This module provides linked editing range request functionality for downstream
+//! language servers, handling the coordinate transformation between host and virtual
+//! documents.
+//!
+//! Linked edits must stay inside the injection region they were requested for: if
+//! any returned range falls outside the virtual document, the whole result is
+//! discarded rather than letting the client edit host text in lockstep.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{LinkedEditingRanges, Position};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{RequestId, VirtualDocumentUri, build_position_based_request};
+
+impl LanguageServerPool {
+    /// Send a linked editing range request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle, providing linked-editing-specific request building and response
+    /// transformation.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_linked_editing_range_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_position: Position,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<LinkedEditingRanges>> {
+        let region_line_count = virtual_content.lines().count() as u32;
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_linked_editing_range_request(
+                    virtual_uri,
+                    host_position,
+                    region_start_line,
+                    request_id,
+                )
+            },
+            |response, ctx| {
+                transform_linked_editing_range_response_to_host(
+                    response,
+                    ctx.region_start_line,
+                    region_line_count,
+                )
+            },
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC linked editing range request for a downstream language server.
+///
+/// This is a thin wrapper around `build_position_based_request` with the method
+/// name "textDocument/linkedEditingRange".
+fn build_linked_editing_range_request(
+    virtual_uri: &VirtualDocumentUri,
+    host_position: Position,
+    region_start_line: u32,
+    request_id: RequestId,
+) -> serde_json::Value {
+    build_position_based_request(
+        virtual_uri,
+        host_position,
+        region_start_line,
+        request_id,
+        "textDocument/linkedEditingRange",
+    )
+}
+
+/// Transform a linked editing range response from virtual to host document coordinates.
+///
+/// Returns `None` if any range extends past the last line of the region, since
+/// the ranges would then not all belong to the same injection region.
+///
+/// # Arguments
+/// * `response` - The JSON-RPC response from the downstream language server
+/// * `region_start_line` - The starting line of the injection region in the host document
+/// * `region_line_count` - The number of lines in the virtual document
+fn transform_linked_editing_range_response_to_host(
+    mut response: serde_json::Value,
+    region_start_line: u32,
+    region_line_count: u32,
+) -> Option<LinkedEditingRanges> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/linkedEditingRange: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut linked: LinkedEditingRanges = serde_json::from_value(result).ok()?;
+    if linked
+        .ranges
+        .iter()
+        .any(|range| range.end.line >= region_line_count)
+    {
+        return None;
+    }
+
+    for range in &mut linked.ranges {
+        range.start.line = range.start.line.saturating_add(region_start_line);
+        range.end.line = range.end.line.saturating_add(region_start_line);
+    }
+
+    Some(linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::Uri;
+
+    fn make_virtual_uri() -> VirtualDocumentUri {
+        let host_uri: Uri =
+            crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap())
+                .unwrap();
+        VirtualDocumentUri::new(&host_uri, "html", "region-0")
+    }
+
+    #[test]
+    fn linked_editing_range_request_translates_position() {
+        let request = build_linked_editing_range_request(
+            &make_virtual_uri(),
+            Position::new(7, 3),
+            5,
+            RequestId::new(1),
+        );
+
+        assert_eq!(request["method"], "textDocument/linkedEditingRange");
+        assert_eq!(request["params"]["position"]["line"], 2);
+        assert_eq!(request["params"]["position"]["character"], 3);
+    }
+
+    #[test]
+    fn linked_editing_range_response_shifts_ranges_to_host() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "ranges": [
+                    { "start": { "line": 0, "character": 1 }, "end": { "line": 0, "character": 4 } },
+                    { "start": { "line": 2, "character": 2 }, "end": { "line": 2, "character": 5 } }
+                ],
+                "wordPattern": "[a-z]+"
+            }
+        });
+
+        let linked = transform_linked_editing_range_response_to_host(response, 5, 3).unwrap();
+
+        assert_eq!(linked.ranges.len(), 2);
+        assert_eq!(linked.ranges[0].start.line, 5);
+        assert_eq!(linked.ranges[0].start.character, 1);
+        assert_eq!(linked.ranges[1].end.line, 7);
+        assert_eq!(linked.word_pattern.as_deref(), Some("[a-z]+"));
+    }
+
+    #[test]
+    fn linked_editing_range_outside_region_returns_none() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "ranges": [
+                    { "start": { "line": 0, "character": 1 }, "end": { "line": 0, "character": 4 } },
+                    { "start": { "line": 3, "character": 2 }, "end": { "line": 3, "character": 5 } }
+                ]
+            }
+        });
+
+        assert!(transform_linked_editing_range_response_to_host(response, 5, 3).is_none());
+    }
+
+    #[test]
+    fn linked_editing_range_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+
+        assert!(transform_linked_editing_range_response_to_host(response, 5, 3).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! On-type formatting request handling for bridge connections.
+//!
+//! This module provides on-type formatting request functionality for downstream
+//! language servers, handling the coordinate transformation between host and
+//! virtual documents.
+//!
+//! The trigger characters the client sends this request for come from the
+//! downstream servers' `documentOnTypeFormattingProvider` capabilities and are
+//! registered with the client dynamically (see `lsp::trigger_characters`).
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{FormattingOptions, Position, TextEdit};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{RequestId, VirtualDocumentUri, build_position_based_request};
+
+impl LanguageServerPool {
+    /// Send an on-type formatting request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle, providing on-type-formatting-specific request building and
+    /// response transformation.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_on_type_formatting_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_position: Position,
+        ch: &str,
+        options: &FormattingOptions,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<TextEdit>>> {
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_on_type_formatting_request(
+                    virtual_uri,
+                    host_position,
+                    ch,
+                    options,
+                    region_start_line,
+                    request_id,
+                )
+            },
+            |response, ctx| {
+                transform_on_type_formatting_response_to_host(response, ctx.region_start_line)
+            },
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC on-type formatting request for a downstream language server.
+///
+/// Extends the position-based request with the typed character and the
+/// formatting options, which are forwarded verbatim.
+fn build_on_type_formatting_request(
+    virtual_uri: &VirtualDocumentUri,
+    host_position: Position,
+    ch: &str,
+    options: &FormattingOptions,
+    region_start_line: u32,
+    request_id: RequestId,
+) -> serde_json::Value {
+    let mut request = build_position_based_request(
+        virtual_uri,
+        host_position,
+        region_start_line,
+        request_id,
+        "textDocument/onTypeFormatting",
+    );
+    request["params"]["ch"] = serde_json::json!(ch);
+    request["params"]["options"] = serde_json::json!(options);
+    request
+}
+
+/// Transform an on-type formatting response from virtual to host document coordinates.
+///
+/// Like formatting responses, on-type formatting responses are `TextEdit[] | null`
+/// targeting the requested document, so only line numbers need translating.
+fn transform_on_type_formatting_response_to_host(
+    mut response: serde_json::Value,
+    region_start_line: u32,
+) -> Option<Vec<TextEdit>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/onTypeFormatting: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+
+    if result.is_null() {
+        return None;
+    }
+
+    let mut edits: Vec<TextEdit> = serde_json::from_value(result).ok()?;
+    for edit in &mut edits {
+        edit.range.start.line = edit.range.start.line.saturating_add(region_start_line);
+        edit.range.end.line = edit.range.end.line.saturating_add(region_start_line);
+    }
+
+    Some(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::Uri;
+
+    fn make_virtual_uri() -> VirtualDocumentUri {
+        let host_uri: Uri =
+            crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap())
+                .unwrap();
+        VirtualDocumentUri::new(&host_uri, "rust", "region-0")
+    }
+
+    #[test]
+    fn on_type_formatting_request_forwards_character_and_options() {
+        let options = FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        };
+
+        let request = build_on_type_formatting_request(
+            &make_virtual_uri(),
+            Position::new(12, 8),
+            "}",
+            &options,
+            10,
+            RequestId::new(2),
+        );
+
+        assert_eq!(request["method"], "textDocument/onTypeFormatting");
+        assert_eq!(request["params"]["position"]["line"], 2);
+        assert_eq!(request["params"]["position"]["character"], 8);
+        assert_eq!(request["params"]["ch"], "}");
+        assert_eq!(request["params"]["options"]["tabSize"], 4);
+    }
+
+    #[test]
+    fn on_type_formatting_response_translates_lines() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [{
+                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 8 } },
+                "newText": "    "
+            }]
+        });
+
+        let edits = transform_on_type_formatting_response_to_host(response, 10).unwrap();
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start.line, 12);
+        assert_eq!(edits[0].range.end.line, 12);
+        assert_eq!(edits[0].range.end.character, 8);
+    }
+
+    #[test]
+    fn on_type_formatting_response_with_null_result_returns_none() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+
+        assert!(transform_on_type_formatting_response_to_host(response, 10).is_none());
+    }
+}
@@ -11,17 +11,15 @@
 use std::io;
 
 use log::warn;
-use std::collections::HashMap;
 
 use crate::config::settings::BridgeServerConfig;
-use tower_lsp_server::ls_types::{
-    DocumentChangeOperation, DocumentChanges, OneOf, Position, TextDocumentEdit, TextEdit, Uri,
-    WorkspaceEdit,
-};
+use tower_lsp_server::ls_types::{Position, Uri, WorkspaceEdit};
 use url::Url;
 
 use super::super::pool::{LanguageServerPool, UpstreamId};
-use super::super::protocol::{RequestId, VirtualDocumentUri, build_position_based_request};
+use super::super::protocol::{
+    RequestId, VirtualDocumentUri, build_position_based_request, transform_workspace_edit_to_host,
+};
 
 impl LanguageServerPool {
     /// Send a rename request and wait for the response.
@@ -139,133 +137,16 @@ fn transform_workspace_edit_response_to_host(
 
     // Parse into typed WorkspaceEdit
     let mut edit: WorkspaceEdit = serde_json::from_value(result).ok()?;
-
-    // Transform changes map: { [uri: string]: TextEdit[] }
-    if let Some(changes) = &mut edit.changes {
-        transform_changes_map(changes, request_virtual_uri, host_uri, region_start_line);
-    }
-
-    // Transform documentChanges array
-    if let Some(doc_changes) = &mut edit.document_changes {
-        transform_document_changes(
-            doc_changes,
-            request_virtual_uri,
-            host_uri,
-            region_start_line,
-        );
-    }
+    transform_workspace_edit_to_host(&mut edit, request_virtual_uri, host_uri, region_start_line);
 
     Some(edit)
 }
 
-/// Transform the `changes` map in a WorkspaceEdit.
-///
-/// Re-keys virtual URIs to host URI and transforms TextEdit ranges.
-/// Cross-region virtual URIs are removed entirely.
-fn transform_changes_map(
-    changes: &mut HashMap<Uri, Vec<TextEdit>>,
-    request_virtual_uri: &str,
-    host_uri: &Uri,
-    region_start_line: u32,
-) {
-    // Collect keys to process (can't modify HashMap keys in-place)
-    let keys: Vec<Uri> = changes.keys().cloned().collect();
-
-    for key in keys {
-        let uri_str = key.as_str();
-
-        // Case 1: Real file URI → keep as-is
-        if !VirtualDocumentUri::is_virtual_uri(uri_str) {
-            continue;
-        }
-
-        // Case 2: Same virtual URI → transform ranges, re-key to host URI
-        if uri_str == request_virtual_uri {
-            if let Some(mut edits) = changes.remove(&key) {
-                for edit in &mut edits {
-                    edit.range.start.line = edit.range.start.line.saturating_add(region_start_line);
-                    edit.range.end.line = edit.range.end.line.saturating_add(region_start_line);
-                }
-                changes.entry(host_uri.clone()).or_default().extend(edits);
-            }
-            continue;
-        }
-
-        // Case 3: Different virtual URI (cross-region) → filter out
-        changes.remove(&key);
-    }
-}
-
-/// Transform the `documentChanges` array in a WorkspaceEdit.
-///
-/// Handles both `Edits(Vec<TextDocumentEdit>)` and
-/// `Operations(Vec<DocumentChangeOperation>)` variants.
-/// File operations (CreateFile, RenameFile, DeleteFile) are preserved as-is.
-fn transform_document_changes(
-    doc_changes: &mut DocumentChanges,
-    request_virtual_uri: &str,
-    host_uri: &Uri,
-    region_start_line: u32,
-) {
-    match doc_changes {
-        DocumentChanges::Edits(edits) => {
-            edits.retain_mut(|edit| {
-                transform_text_document_edit(edit, request_virtual_uri, host_uri, region_start_line)
-            });
-        }
-        DocumentChanges::Operations(ops) => {
-            ops.retain_mut(|op| match op {
-                DocumentChangeOperation::Edit(edit) => transform_text_document_edit(
-                    edit,
-                    request_virtual_uri,
-                    host_uri,
-                    region_start_line,
-                ),
-                DocumentChangeOperation::Op(_) => true, // File operations preserved
-            });
-        }
-    }
-}
-
-/// Transform a single TextDocumentEdit's URI and edit ranges.
-///
-/// Returns `true` if the edit should be kept, `false` if it should be filtered out.
-fn transform_text_document_edit(
-    edit: &mut TextDocumentEdit,
-    request_virtual_uri: &str,
-    host_uri: &Uri,
-    region_start_line: u32,
-) -> bool {
-    let uri_str = edit.text_document.uri.as_str();
-
-    // Case 1: Real file URI → keep as-is
-    if !VirtualDocumentUri::is_virtual_uri(uri_str) {
-        return true;
-    }
-
-    // Case 2: Same virtual URI → transform
-    if uri_str == request_virtual_uri {
-        edit.text_document.uri = host_uri.clone();
-        for one_of in &mut edit.edits {
-            let text_edit = match one_of {
-                OneOf::Left(text_edit) => text_edit,
-                OneOf::Right(annotated_edit) => &mut annotated_edit.text_edit,
-            };
-            text_edit.range.start.line =
-                text_edit.range.start.line.saturating_add(region_start_line);
-            text_edit.range.end.line = text_edit.range.end.line.saturating_add(region_start_line);
-        }
-        return true;
-    }
-
-    // Case 3: Cross-region → filter out
-    false
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
+    use tower_lsp_server::ls_types::{DocumentChanges, OneOf};
 
     // ==========================================================================
     // Rename request builder tests
@@ -0,0 +1,286 @@
+//! Semantic tokens request handling for bridge connections.
+//!
+//! This module requests `textDocument/semanticTokens/full` for an injection region
+//! and decodes the response against the downstream server's own legend, so the
+//! tokens can be overlaid on tree-sitter tokens (see `analysis::semantic::overlay`).
+//!
+//! Like document symbol, semantic tokens requests operate on the entire virtual
+//! document - they don't take a position parameter.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::analysis::BridgedSemanticToken;
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{SemanticToken, SemanticTokens, SemanticTokensLegend};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{RequestId, VirtualDocumentUri, build_whole_document_request};
+
+impl LanguageServerPool {
+    /// Send a semantic tokens request and decode the response into host coordinates.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle. The response is decoded with the legend the server advertised
+    /// (statically or via dynamic registration); without a legend nothing can be
+    /// decoded and `Ok(None)` is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_semantic_tokens_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<BridgedSemanticToken>>> {
+        let data = self
+            .execute_bridge_request(
+                server_name,
+                server_config,
+                host_uri,
+                injection_language,
+                region_id,
+                region_start_line,
+                virtual_content,
+                upstream_request_id,
+                build_semantic_tokens_request,
+                |response, _ctx| parse_semantic_tokens_response(response),
+            )
+            .await?;
+        let Some(data) = data else {
+            return Ok(None);
+        };
+
+        // The request above only completes once the server is Ready, so its
+        // capabilities (and legend) are known at this point
+        let legend = self
+            .connections()
+            .await
+            .get(server_name)
+            .and_then(|handle| handle.semantic_tokens_legend());
+        let Some(legend) = legend else {
+            warn!(
+                target: "kakehashi::bridge",
+                "{} returned semantic tokens without advertising a legend",
+                server_name
+            );
+            return Ok(None);
+        };
+
+        let line_count = virtual_content.lines().count() as u32;
+        Ok(Some(decode_semantic_tokens(
+            &data,
+            &legend,
+            region_start_line,
+            line_count,
+        )))
+    }
+}
+
+/// Build a JSON-RPC semantic tokens request for a downstream language server.
+fn build_semantic_tokens_request(
+    virtual_uri: &VirtualDocumentUri,
+    request_id: RequestId,
+) -> serde_json::Value {
+    build_whole_document_request(virtual_uri, request_id, "textDocument/semanticTokens/full")
+}
+
+/// Extract the encoded token data from a semantic tokens response.
+///
+/// Returns `None` for errors, null results and malformed payloads.
+fn parse_semantic_tokens_response(mut response: serde_json::Value) -> Option<Vec<SemanticToken>> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for textDocument/semanticTokens/full: {}", error);
+    }
+    let result = response.get_mut("result").map(serde_json::Value::take)?;
+    if result.is_null() {
+        return None;
+    }
+    serde_json::from_value::<SemanticTokens>(result)
+        .ok()
+        .map(|tokens| tokens.data)
+}
+
+/// Decode relative semantic tokens into absolute host-document tokens.
+///
+/// Type and modifier indices are resolved against the server's `legend`; tokens
+/// with an unknown type index are skipped. Tokens beyond the virtual document's
+/// `line_count` cannot belong to the region and are dropped.
+fn decode_semantic_tokens(
+    data: &[SemanticToken],
+    legend: &SemanticTokensLegend,
+    region_start_line: u32,
+    line_count: u32,
+) -> Vec<BridgedSemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut line = 0u32;
+    let mut start = 0u32;
+    for token in data {
+        if token.delta_line > 0 {
+            line += token.delta_line;
+            start = token.delta_start;
+        } else {
+            start += token.delta_start;
+        }
+        if line >= line_count {
+            break;
+        }
+        let Some(token_type) = legend.token_types.get(token.token_type as usize) else {
+            continue;
+        };
+        let modifiers = legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| *bit < 32 && token.token_modifiers_bitset & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier.as_str().to_string())
+            .collect();
+        tokens.push(BridgedSemanticToken {
+            line: line + region_start_line,
+            start,
+            length: token.length,
+            token_type: token_type.as_str().to_string(),
+            modifiers,
+        });
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp_server::ls_types::{SemanticTokenModifier, SemanticTokenType};
+
+    fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![
+                SemanticTokenType::new("struct"),
+                SemanticTokenType::new("builtinType"),
+            ],
+            token_modifiers: vec![
+                SemanticTokenModifier::new("declaration"),
+                SemanticTokenModifier::new("mutable"),
+            ],
+        }
+    }
+
+    #[test]
+    fn semantic_tokens_request_targets_virtual_document() {
+        let host_uri =
+            crate::lsp::lsp_impl::url_to_uri(&Url::parse("file:///project/doc.md").unwrap())
+                .unwrap();
+        let virtual_uri = VirtualDocumentUri::new(&host_uri, "rust", "region-0");
+        let request = build_semantic_tokens_request(&virtual_uri, RequestId::new(7));
+
+        assert_eq!(request["method"], "textDocument/semanticTokens/full");
+        assert_eq!(request["id"], 7);
+        assert_eq!(
+            request["params"]["textDocument"]["uri"],
+            virtual_uri.to_uri_string()
+        );
+    }
+
+    #[test]
+    fn parse_response_extracts_token_data() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "resultId": "1", "data": [0, 7, 5, 0, 1] }
+        });
+        let data = parse_semantic_tokens_response(response).unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].delta_start, 7);
+        assert_eq!(data[0].token_modifiers_bitset, 1);
+    }
+
+    #[test]
+    fn parse_response_handles_null_and_error() {
+        assert!(
+            parse_semantic_tokens_response(json!({"jsonrpc": "2.0", "id": 1, "result": null}))
+                .is_none()
+        );
+        assert!(
+            parse_semantic_tokens_response(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32601, "message": "Method not found" }
+            }))
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn decode_translates_to_host_lines_and_resolves_legend() {
+        let data = vec![
+            // line 0, col 7, len 5: struct + declaration
+            SemanticToken {
+                delta_line: 0,
+                delta_start: 7,
+                length: 5,
+                token_type: 0,
+                token_modifiers_bitset: 0b01,
+            },
+            // line 2, col 4, len 3: builtinType + mutable
+            SemanticToken {
+                delta_line: 2,
+                delta_start: 4,
+                length: 3,
+                token_type: 1,
+                token_modifiers_bitset: 0b10,
+            },
+            // same line, col 9: unknown type index is skipped
+            SemanticToken {
+                delta_line: 0,
+                delta_start: 5,
+                length: 1,
+                token_type: 9,
+                token_modifiers_bitset: 0,
+            },
+        ];
+
+        let tokens = decode_semantic_tokens(&data, &legend(), 10, 3);
+
+        assert_eq!(
+            tokens,
+            vec![
+                BridgedSemanticToken {
+                    line: 10,
+                    start: 7,
+                    length: 5,
+                    token_type: "struct".to_string(),
+                    modifiers: vec!["declaration".to_string()],
+                },
+                BridgedSemanticToken {
+                    line: 12,
+                    start: 4,
+                    length: 3,
+                    token_type: "builtinType".to_string(),
+                    modifiers: vec!["mutable".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_drops_tokens_beyond_virtual_document() {
+        let data = vec![SemanticToken {
+            delta_line: 5,
+            delta_start: 0,
+            length: 3,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }];
+        assert!(decode_semantic_tokens(&data, &legend(), 0, 5).is_empty());
+    }
+}
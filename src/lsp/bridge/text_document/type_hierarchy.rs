@@ -0,0 +1,255 @@
+//! Type hierarchy request handling for bridge connections.
+//!
+//! This module provides `textDocument/prepareTypeHierarchy`,
+//! `typeHierarchy/supertypes` and `typeHierarchy/subtypes` for downstream
+//! language servers. Items are translated and routed exactly like call
+//! hierarchy items (see `call_hierarchy`).
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::{Position, TypeHierarchyItem};
+use url::Url;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::{
+    BridgeResolveData, RequestId, build_position_based_request, transform_hierarchy_item_to_host,
+    transform_hierarchy_item_to_virtual,
+};
+use super::call_hierarchy::{hierarchy_origin, parse_hierarchy_result};
+
+impl LanguageServerPool {
+    /// Send a prepareTypeHierarchy request and wait for the response.
+    ///
+    /// Delegates to [`execute_bridge_request`](Self::execute_bridge_request) for the
+    /// full lifecycle. Returned items are in host coordinates and carry the
+    /// routing envelope for follow-up requests.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_prepare_type_hierarchy_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        host_uri: &Url,
+        host_position: Position,
+        injection_language: &str,
+        region_id: &str,
+        region_start_line: u32,
+        virtual_content: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<TypeHierarchyItem>>> {
+        self.execute_bridge_request(
+            server_name,
+            server_config,
+            host_uri,
+            injection_language,
+            region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |virtual_uri, request_id| {
+                build_position_based_request(
+                    virtual_uri,
+                    host_position,
+                    region_start_line,
+                    request_id,
+                    "textDocument/prepareTypeHierarchy",
+                )
+            },
+            |response, ctx| {
+                let origin = hierarchy_origin(
+                    server_name,
+                    ctx.host_uri_lsp,
+                    injection_language,
+                    region_id,
+                    ctx.region_start_line,
+                );
+                transform_type_hierarchy_response_to_host(
+                    response,
+                    &ctx.virtual_uri_string,
+                    &origin,
+                    "textDocument/prepareTypeHierarchy",
+                )
+            },
+        )
+        .await
+    }
+
+    /// Send a typeHierarchy/supertypes or typeHierarchy/subtypes request for an
+    /// item produced by
+    /// [`send_prepare_type_hierarchy_request`](Self::send_prepare_type_hierarchy_request).
+    ///
+    /// `origin` is the item's envelope; `region_start_line` and `virtual_content`
+    /// describe the origin region as it is now, which may have moved since the
+    /// item was prepared.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_type_hierarchy_item_request(
+        &self,
+        server_config: &BridgeServerConfig,
+        item: TypeHierarchyItem,
+        origin: BridgeResolveData,
+        host_uri: &Url,
+        region_start_line: u32,
+        virtual_content: &str,
+        method: &str,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<Vec<TypeHierarchyItem>>> {
+        self.execute_bridge_request(
+            &origin.server_name,
+            server_config,
+            host_uri,
+            &origin.injection_language,
+            &origin.region_id,
+            region_start_line,
+            virtual_content,
+            upstream_request_id,
+            |_virtual_uri, request_id| {
+                build_type_hierarchy_item_request(item, &origin, request_id, method)
+            },
+            |response, ctx| {
+                let current = hierarchy_origin(
+                    &origin.server_name,
+                    ctx.host_uri_lsp,
+                    &origin.injection_language,
+                    &origin.region_id,
+                    ctx.region_start_line,
+                );
+                transform_type_hierarchy_response_to_host(
+                    response,
+                    &ctx.virtual_uri_string,
+                    &current,
+                    method,
+                )
+            },
+        )
+        .await
+    }
+}
+
+/// Build a supertypes/subtypes request for the item the client sent back.
+fn build_type_hierarchy_item_request(
+    item: TypeHierarchyItem,
+    origin: &BridgeResolveData,
+    request_id: RequestId,
+    method: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": method,
+        "params": {
+            "item": transform_hierarchy_item_to_virtual(item, origin)
+        }
+    })
+}
+
+/// Transform a `TypeHierarchyItem[] | null` response to host coordinates.
+///
+/// Items in other injection regions are dropped; an empty array after filtering
+/// is preserved.
+fn transform_type_hierarchy_response_to_host(
+    response: serde_json::Value,
+    request_virtual_uri: &str,
+    origin: &BridgeResolveData,
+    method: &str,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let items: Vec<TypeHierarchyItem> = parse_hierarchy_result(response, method)?;
+    Some(
+        items
+            .into_iter()
+            .filter_map(|item| transform_hierarchy_item_to_host(item, request_virtual_uri, origin))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn test_origin() -> BridgeResolveData {
+        let url = Url::parse("file:///project/doc.md").unwrap();
+        BridgeResolveData {
+            server_name: "tsgo".to_string(),
+            host_uri: crate::lsp::lsp_impl::url_to_uri(&url).unwrap(),
+            injection_language: "typescript".to_string(),
+            region_id: "region-0".to_string(),
+            region_start_line: 3,
+            data: None,
+        }
+    }
+
+    fn item_json(uri: &str, line: u32) -> serde_json::Value {
+        json!({
+            "name": "Shape",
+            "kind": 11,
+            "uri": uri,
+            "range": { "start": { "line": line, "character": 0 }, "end": { "line": line + 2, "character": 1 } },
+            "selectionRange": { "start": { "line": line, "character": 10 }, "end": { "line": line, "character": 15 } },
+            "data": "opaque"
+        })
+    }
+
+    #[test]
+    fn supertypes_keep_real_files_and_drop_other_regions() {
+        let origin = test_origin();
+        let virtual_uri = origin.virtual_uri_string();
+        let other_region = BridgeResolveData {
+            region_id: "region-1".to_string(),
+            ..origin.clone()
+        }
+        .virtual_uri_string();
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                item_json(&virtual_uri, 0),
+                item_json("file:///project/src/shape.ts", 12),
+                item_json(&other_region, 0)
+            ]
+        });
+
+        let items = transform_type_hierarchy_response_to_host(
+            response,
+            &virtual_uri,
+            &origin,
+            "typeHierarchy/supertypes",
+        )
+        .unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].uri, origin.host_uri);
+        assert_eq!(items[0].range.start.line, 3);
+        assert_eq!(items[1].range.start.line, 12);
+    }
+
+    #[test]
+    fn item_request_round_trips_server_data() {
+        let origin = test_origin();
+        let virtual_uri = origin.virtual_uri_string();
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": [item_json(&virtual_uri, 4)] });
+        let item = transform_type_hierarchy_response_to_host(
+            response,
+            &virtual_uri,
+            &origin,
+            "textDocument/prepareTypeHierarchy",
+        )
+        .unwrap()
+        .remove(0);
+        let envelope = BridgeResolveData::from_data(item.data.as_ref()).unwrap();
+
+        let request = build_type_hierarchy_item_request(
+            item,
+            &envelope,
+            RequestId::new(2),
+            "typeHierarchy/subtypes",
+        );
+
+        assert_eq!(request["method"], "typeHierarchy/subtypes");
+        assert_eq!(request["params"]["item"], item_json(&virtual_uri, 4));
+    }
+}
@@ -0,0 +1,8 @@
+//! Workspace request handlers for bridge connections.
+//!
+//! This module provides LSP workspace request functionality (executeCommand)
+//! for downstream language servers via the bridge architecture.
+//!
+//! The structure mirrors `lsp_impl/workspace/` for consistency.
+
+mod execute_command;
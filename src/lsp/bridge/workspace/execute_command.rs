@@ -0,0 +1,113 @@
+//! Execute command request handling for bridge connections.
+//!
+//! Commands reach this module after being unwrapped from the bridge envelope
+//! (see `protocol::bridge_command`), so the command and its arguments are
+//! exactly what the downstream server produced and are forwarded verbatim.
+//!
+//! # Single-Writer Loop (ADR-0015)
+//!
+//! This handler uses `send_request()` to queue requests via the channel-based
+//! writer task, ensuring FIFO ordering with other messages.
+
+use std::io;
+
+use log::warn;
+
+use crate::config::settings::BridgeServerConfig;
+use tower_lsp_server::ls_types::Command;
+
+use super::super::pool::{LanguageServerPool, UpstreamId};
+use super::super::protocol::RequestId;
+
+impl LanguageServerPool {
+    /// Send a workspace/executeCommand request and wait for the response.
+    ///
+    /// The result is opaque and returned as-is; `Ok(None)` means the server
+    /// returned `null` or an error.
+    pub(crate) async fn send_execute_command_request(
+        &self,
+        server_name: &str,
+        server_config: &BridgeServerConfig,
+        command: Command,
+        upstream_request_id: UpstreamId,
+    ) -> io::Result<Option<serde_json::Value>> {
+        self.execute_server_request(
+            server_name,
+            server_config,
+            upstream_request_id,
+            |request_id| build_execute_command_request(command, request_id),
+            transform_execute_command_response,
+        )
+        .await
+    }
+}
+
+/// Build a JSON-RPC workspace/executeCommand request for a downstream language server.
+fn build_execute_command_request(command: Command, request_id: RequestId) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id.as_i64(),
+        "method": "workspace/executeCommand",
+        "params": {
+            "command": command.command,
+            "arguments": command.arguments.unwrap_or_default()
+        }
+    })
+}
+
+/// Extract the result of a workspace/executeCommand response.
+fn transform_execute_command_response(
+    mut response: serde_json::Value,
+) -> Option<serde_json::Value> {
+    if let Some(error) = response.get("error") {
+        warn!(target: "kakehashi::bridge", "Downstream server returned error for workspace/executeCommand: {}", error);
+    }
+    response
+        .get_mut("result")
+        .map(serde_json::Value::take)
+        .filter(|result| !result.is_null())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn execute_command_request_forwards_command_verbatim() {
+        let command = Command {
+            title: "▶ Run".to_string(),
+            command: "rust-analyzer.runSingle".to_string(),
+            arguments: Some(vec![json!({ "label": "test it_works" })]),
+        };
+
+        let request = build_execute_command_request(command, RequestId::new(9));
+
+        assert_eq!(request["id"], 9);
+        assert_eq!(request["method"], "workspace/executeCommand");
+        assert_eq!(request["params"]["command"], "rust-analyzer.runSingle");
+        assert_eq!(
+            request["params"]["arguments"],
+            json!([{ "label": "test it_works" }])
+        );
+    }
+
+    #[test]
+    fn execute_command_response_passes_result_through() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": { "ok": true } });
+        assert_eq!(
+            transform_execute_command_response(response),
+            Some(json!({ "ok": true }))
+        );
+
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+        assert_eq!(transform_execute_command_response(response), None);
+
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32601, "message": "unknown command" }
+        });
+        assert_eq!(transform_execute_command_response(response), None);
+    }
+}
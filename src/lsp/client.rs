@@ -131,6 +131,8 @@ impl<'a> ClientNotifier<'a> {
     /// - `Log` events are sent as log messages to the client
     /// - `SemanticTokensRefresh` events trigger workspace/semanticTokens/refresh
     ///   (only if client declared support via capabilities)
+    /// - `ParserRebuildNeeded` events are ignored; the preceding `Log` event
+    ///   explains the failure
     pub(crate) async fn log_language_events(&self, events: &[LanguageEvent]) {
         for event in events {
             match event {
@@ -142,6 +144,8 @@ impl<'a> ClientNotifier<'a> {
                     };
                     self.client.log_message(message_type, message.clone()).await;
                 }
+                // Acted on by the callers that decide whether to auto-install
+                LanguageEvent::ParserRebuildNeeded { .. } => {}
                 LanguageEvent::SemanticTokensRefresh { language_id } => {
                     // Only send refresh if client supports it (LSP @since 3.16.0 compliance).
                     // Check MUST be before tokio::spawn - can't `continue` from async block.
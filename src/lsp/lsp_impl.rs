@@ -1,5 +1,7 @@
 mod bridge_context;
+mod bridge_merge;
 pub(crate) mod text_document;
+mod workspace;
 
 use std::collections::HashSet;
 
@@ -8,31 +10,40 @@ use tower_lsp_server::ls_types::request::{
     GotoDeclarationParams, GotoDeclarationResponse, GotoImplementationParams,
     GotoImplementationResponse, GotoTypeDefinitionParams, GotoTypeDefinitionResponse,
 };
-#[cfg(feature = "experimental")]
-use tower_lsp_server::ls_types::{
-    ColorInformation, ColorPresentation, ColorPresentationParams, ColorProviderCapability,
-    DocumentColorParams,
-};
 use tower_lsp_server::ls_types::{
-    CompletionOptions, CompletionParams, CompletionResponse, DeclarationCapability,
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CallHierarchyServerCapability, CodeAction, CodeActionOptions, CodeActionParams,
+    CodeActionProviderCapability, CodeActionResponse, CodeLens, CodeLensOptions, CodeLensParams,
+    CompletionItem, CompletionOptions, CompletionParams, CompletionResponse, DeclarationCapability,
     DiagnosticOptions, DiagnosticServerCapabilities, DidChangeConfigurationParams,
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
     DidSaveTextDocumentParams, DocumentDiagnosticParams, DocumentDiagnosticReportResult,
-    DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkOptions,
-    DocumentLinkParams, DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams,
-    GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability,
-    ImplementationProviderCapability, InitializeParams, InitializeResult, InitializedParams,
-    InlayHint, InlayHintParams, Location, Moniker, MonikerParams, OneOf, ReferenceParams,
-    RenameParams, SaveOptions, SelectionRange, SelectionRangeParams,
-    SelectionRangeProviderCapability, SemanticTokenModifier, SemanticTokenType,
-    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams, SemanticTokensRangeParams,
-    SemanticTokensRangeResult, SemanticTokensResult, SemanticTokensServerCapabilities,
-    ServerCapabilities, ServerInfo, SignatureHelp, SignatureHelpOptions, SignatureHelpParams,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TextDocumentSyncSaveOptions, TypeDefinitionProviderCapability, Uri, WorkDoneProgressOptions,
+    DocumentFormattingParams, DocumentHighlight, DocumentHighlightParams, DocumentLink,
+    DocumentLinkOptions, DocumentLinkParams, DocumentOnTypeFormattingParams,
+    DocumentRangeFormattingParams, DocumentSymbolParams, DocumentSymbolResponse,
+    ExecuteCommandOptions, ExecuteCommandParams, FoldingRange, FoldingRangeParams,
+    FoldingRangeProviderCapability, GotoDefinitionParams, GotoDefinitionResponse, Hover,
+    HoverParams, HoverProviderCapability, ImplementationProviderCapability, InitializeParams,
+    InitializeResult, InitializedParams, InlayHint, InlayHintParams, LSPAny,
+    LinkedEditingRangeParams, LinkedEditingRangeServerCapabilities, LinkedEditingRanges, Location,
+    MessageType, Moniker, MonikerParams, OneOf, ReferenceParams, RenameParams, SaveOptions,
+    SelectionRange, SelectionRangeParams, SelectionRangeProviderCapability, SemanticTokenModifier,
+    SemanticTokenType, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult,
+    SemanticTokensServerCapabilities, ServerCapabilities, ServerInfo, SignatureHelp,
+    SignatureHelpOptions, SignatureHelpParams, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions, TextEdit,
+    TypeDefinitionProviderCapability, TypeHierarchyItem, TypeHierarchyPrepareParams,
+    TypeHierarchySubtypesParams, TypeHierarchySupertypesParams, Uri, WorkDoneProgressOptions,
     WorkspaceEdit,
 };
+#[cfg(feature = "experimental")]
+use tower_lsp_server::ls_types::{
+    ColorInformation, ColorPresentation, ColorPresentationParams, ColorProviderCapability,
+    DocumentColorParams,
+};
 use tower_lsp_server::{Client, LanguageServer};
 use tree_sitter::InputEdit;
 use url::Url;
@@ -40,20 +51,23 @@ use url::Url;
 use crate::analysis::{LEGEND_MODIFIERS, LEGEND_TYPES};
 use crate::config::WorkspaceSettings;
 use crate::document::DocumentStore;
+use crate::install::sources::InstallSources;
 use crate::language::LanguageEvent;
 use crate::language::injection::{InjectionResolver, collect_all_injections};
 use crate::language::region_id_tracker::EditInfo;
 use crate::language::{DocumentParserPool, LanguageCoordinator};
-use crate::lsp::bridge::BridgeCoordinator;
+use crate::lsp::bridge::{BRIDGE_COMMANDS, BridgeCoordinator};
 use crate::lsp::client::{ClientNotifier, check_semantic_tokens_refresh_support};
 use crate::lsp::settings_manager::SettingsManager;
+use crate::lsp::trigger_characters::TriggerCharacterRegistrar;
 use crate::lsp::{SettingsSource, load_settings};
 use tokio::sync::Mutex;
 
 use super::text_sync::apply_content_changes_with_edits;
 
 use super::auto_install::{
-    AutoInstallManager, InstallEvent, InstallingLanguages, get_injected_languages,
+    AutoInstallManager, InstallEvent, InstallUpdateWatcher, InstallingLanguages,
+    configured_parser_source, get_injected_languages,
 };
 use super::cache::CacheCoordinator;
 use super::debounced_diagnostics::DebouncedDiagnosticsManager;
@@ -117,6 +131,8 @@ pub struct Kakehashi {
     settings_manager: SettingsManager,
     /// Isolated coordinator for parser auto-installation
     auto_install: AutoInstallManager,
+    /// Detects languages reinstalled by `kakehashi language update`
+    install_watcher: InstallUpdateWatcher,
     /// Bridge coordinator for downstream LS pool and region ID tracking
     bridge: BridgeCoordinator,
     /// Manager for synthetic (background) diagnostic push tasks (ADR-0020 Phase 2).
@@ -130,6 +146,9 @@ pub struct Kakehashi {
     /// dropped. Cancelling the token gives deterministic shutdown: `shutdown()`
     /// cancels → task exits immediately → no waiting for channel drainage.
     shutdown_token: tokio_util::sync::CancellationToken,
+    /// Dynamic completion / signature help registrations with downstream trigger characters.
+    /// Wrapped in Arc for sharing with the upstream forwarding task.
+    trigger_registrar: std::sync::Arc<TriggerCharacterRegistrar>,
 }
 
 impl std::fmt::Debug for Kakehashi {
@@ -142,10 +161,12 @@ impl std::fmt::Debug for Kakehashi {
             .field("cache", &"CacheCoordinator")
             .field("settings_manager", &"SettingsManager")
             .field("auto_install", &"AutoInstallManager")
+            .field("install_watcher", &"InstallUpdateWatcher")
             .field("bridge", &"BridgeCoordinator")
             .field("synthetic_diagnostics", &"SyntheticDiagnosticsManager")
             .field("debounced_diagnostics", &"DebouncedDiagnosticsManager")
             .field("shutdown_token", &"CancellationToken")
+            .field("trigger_registrar", &"TriggerCharacterRegistrar")
             .finish_non_exhaustive()
     }
 }
@@ -159,6 +180,12 @@ impl Kakehashi {
         let failed_parsers = AutoInstallManager::init_failed_parser_registry();
         let auto_install = AutoInstallManager::new(InstallingLanguages::new(), failed_parsers);
 
+        let bridge = BridgeCoordinator::new();
+        let trigger_registrar = std::sync::Arc::new(TriggerCharacterRegistrar::new(
+            client.clone(),
+            bridge.pool_arc(),
+        ));
+
         Self {
             client,
             language,
@@ -167,10 +194,12 @@ impl Kakehashi {
             cache: CacheCoordinator::new(),
             settings_manager: SettingsManager::new(),
             auto_install,
-            bridge: BridgeCoordinator::new(),
+            install_watcher: InstallUpdateWatcher::new(),
+            bridge,
             synthetic_diagnostics: std::sync::Arc::new(SyntheticDiagnosticsManager::new()),
             debounced_diagnostics: DebouncedDiagnosticsManager::new(),
             shutdown_token: tokio_util::sync::CancellationToken::new(),
+            trigger_registrar,
         }
     }
 
@@ -193,6 +222,11 @@ impl Kakehashi {
         let failed_parsers = AutoInstallManager::init_failed_parser_registry();
         let auto_install = AutoInstallManager::new(InstallingLanguages::new(), failed_parsers);
 
+        let trigger_registrar = std::sync::Arc::new(TriggerCharacterRegistrar::new(
+            client.clone(),
+            std::sync::Arc::clone(&pool),
+        ));
+
         Self {
             client,
             language,
@@ -201,10 +235,12 @@ impl Kakehashi {
             cache: CacheCoordinator::new(),
             settings_manager: SettingsManager::new(),
             auto_install,
+            install_watcher: InstallUpdateWatcher::new(),
             bridge: BridgeCoordinator::with_cancel_forwarder(pool, cancel_forwarder),
             synthetic_diagnostics: std::sync::Arc::new(SyntheticDiagnosticsManager::new()),
             debounced_diagnostics: DebouncedDiagnosticsManager::new(),
             shutdown_token: tokio_util::sync::CancellationToken::new(),
+            trigger_registrar,
         }
     }
 
@@ -227,6 +263,14 @@ impl Kakehashi {
         self.settings_manager.is_auto_install_enabled()
     }
 
+    /// Check if the client allows `textDocument/prepareTypeHierarchy` to be
+    /// registered dynamically.
+    ///
+    /// Delegates to SettingsManager for capability checking.
+    fn supports_dynamic_type_hierarchy(&self) -> bool {
+        self.settings_manager.supports_dynamic_type_hierarchy()
+    }
+
     /// Check if the client supports multiline semantic tokens.
     ///
     /// Delegates to SettingsManager for capability checking.
@@ -523,41 +567,102 @@ impl Kakehashi {
         crate::document::get_language_for_document(uri, &self.language, &self.documents)
     }
 
-    /// Get bridge server config for a given injection language from settings.
-    ///
-    /// Convenience wrapper that loads settings and delegates to the bridge coordinator.
-    /// Returns `ResolvedServerConfig` which includes both the server name (for connection
-    /// pooling) and the config (for spawning).
+    /// Select the bridge server config to route a request to.
     ///
-    /// This method stays in Kakehashi for backward compatibility with handlers.
+    /// Considers **all** servers configured for the injection language (see
+    /// `get_all_bridge_configs_for_language()`) and picks the first one whose
+    /// capabilities include `method`, so setups where each server implements
+    /// only part of the protocol (e.g., pyright + ruff) route every request to
+    /// a server that can answer it. Servers whose handshake has not completed
+    /// yet are used only as a fallback.
     ///
     /// # Arguments
+    /// * `host_uri` - The host document (selects per-root server instances)
     /// * `host_language` - The language of the host document (e.g., "markdown")
     /// * `injection_language` - The injection language to bridge (e.g., "rust", "python")
-    fn get_bridge_config_for_language(
+    /// * `method` - The LSP method to be sent (e.g., "textDocument/hover")
+    async fn select_bridge_config(
         &self,
+        host_uri: &Url,
         host_language: &str,
         injection_language: &str,
+        method: &str,
     ) -> Option<crate::lsp::bridge::ResolvedServerConfig> {
-        let settings = self.settings_manager.load_settings();
+        let candidates =
+            self.get_all_bridge_configs_for_language(host_uri, host_language, injection_language);
+        if candidates.is_empty() {
+            return None;
+        }
         self.bridge
-            .get_config_for_language(&settings, host_language, injection_language)
+            .pool()
+            .select_server_for_method(method, candidates)
+            .await
+    }
+
+    /// Get every bridge server config that may support `method`, with the
+    /// configured merge strategy for it.
+    ///
+    /// Servers are in priority order; Ready servers known to lack `method`
+    /// are dropped.
+    async fn select_bridge_configs_for_merge(
+        &self,
+        host_uri: &Url,
+        host_language: &str,
+        injection_language: &str,
+        method: &str,
+    ) -> (
+        Vec<crate::lsp::bridge::ResolvedServerConfig>,
+        crate::config::BridgeMergeStrategy,
+    ) {
+        let settings = self.settings_manager.load_settings();
+        let strategy =
+            crate::config::BridgeMergeConfig::strategy_for(settings.bridge_merge.as_ref(), method)
+                .unwrap_or(crate::config::BridgeMergeStrategy::First);
+        let candidates = self.bridge.get_all_configs_for_language(
+            &settings,
+            host_uri,
+            host_language,
+            injection_language,
+        );
+        let configs = self
+            .bridge
+            .pool()
+            .capable_servers_for_method(method, candidates)
+            .await;
+        (configs, strategy)
+    }
+
+    /// Get bridge server config by server name.
+    ///
+    /// Delegates to BridgeCoordinator. Used to route resolve requests back to
+    /// the server that produced the item.
+    fn get_bridge_config_for_server(
+        &self,
+        server_name: &str,
+    ) -> Option<crate::lsp::bridge::ResolvedServerConfig> {
+        let settings = self.settings_manager.load_settings();
+        self.bridge.get_config_for_server(&settings, server_name)
     }
 
     /// Get all bridge server configs for a given injection language from settings.
     ///
-    /// Unlike `get_bridge_config_for_language()` which returns the first match,
-    /// this returns **all** servers configured for the injection language.
+    /// Returns **all** servers configured for the injection language, sorted by name.
     /// Used by diagnostic fan-out to support multiple servers per language
-    /// (e.g., pyright + ruff both handling Python).
+    /// (e.g., pyright + ruff both handling Python), and by `select_bridge_config()`
+    /// as the candidate list for capability-aware routing.
     fn get_all_bridge_configs_for_language(
         &self,
+        host_uri: &Url,
         host_language: &str,
         injection_language: &str,
     ) -> Vec<crate::lsp::bridge::ResolvedServerConfig> {
         let settings = self.settings_manager.load_settings();
-        self.bridge
-            .get_all_configs_for_language(&settings, host_language, injection_language)
+        self.bridge.get_all_configs_for_language(
+            &settings,
+            host_uri,
+            host_language,
+            injection_language,
+        )
     }
 
     async fn apply_settings(&self, settings: WorkspaceSettings) {
@@ -586,6 +691,8 @@ impl Kakehashi {
     /// * `uri` - The document URI that triggered the install
     /// * `text` - The document text
     /// * `is_injection` - True if this is an injection language (not the document's main language)
+    /// * `rebuild` - True if the installed parser has an incompatible ABI version
+    ///   and must be built again
     ///
     /// # Returns
     /// `true` if installation was triggered (caller should skip parse_document),
@@ -596,9 +703,29 @@ impl Kakehashi {
         uri: Url,
         text: String,
         is_injection: bool,
+        rebuild: bool,
     ) -> bool {
+        // A parser source configured in `languages.<name>.source` replaces the
+        // nvim-treesitter metadata
+        let settings = self.settings_manager.load_settings();
+        let source = settings
+            .languages
+            .get(language)
+            .and_then(|settings| settings.source.as_ref())
+            .map(|source| {
+                configured_parser_source(source, self.settings_manager.root_path().as_deref())
+            });
+        let sources = settings
+            .install
+            .as_ref()
+            .map(InstallSources::from)
+            .unwrap_or_default();
+
         // Delegate to AutoInstallManager (isolated, returns events)
-        let result = self.auto_install.try_install(language).await;
+        let result = self
+            .auto_install
+            .try_install(language, source, sources, rebuild)
+            .await;
 
         // Dispatch events to ClientNotifier
         self.dispatch_install_events(language, &result.events).await;
@@ -652,13 +779,17 @@ impl Kakehashi {
         }
 
         // Create updated settings
-        let updated_settings = WorkspaceSettings::with_language_servers(
-            new_search_paths,
-            current_settings.languages.clone(),
-            current_settings.capture_mappings.clone(),
-            current_settings.auto_install,
-            current_settings.language_servers.clone(),
-        );
+        let updated_settings = WorkspaceSettings {
+            bridge_merge: current_settings.bridge_merge.clone(),
+            install: current_settings.install.clone(),
+            ..WorkspaceSettings::with_language_servers(
+                new_search_paths,
+                current_settings.languages.clone(),
+                current_settings.capture_mappings.clone(),
+                current_settings.auto_install,
+                current_settings.language_servers.clone(),
+            )
+        };
 
         // Apply the updated settings
         self.apply_settings(updated_settings).await;
@@ -683,25 +814,108 @@ impl Kakehashi {
         }
     }
 
+    /// Reload languages reinstalled by another process (`kakehashi language
+    /// update`, `language install --force`) and re-parse their documents.
+    ///
+    /// Cheap to call on every notification: the install manifests are polled
+    /// at most every few seconds.
+    async fn reload_updated_languages(&self) {
+        let settings = self.settings_manager.load_settings();
+        let mut data_dirs: Vec<std::path::PathBuf> = settings
+            .search_paths
+            .iter()
+            .map(std::path::PathBuf::from)
+            .collect();
+        if let Some(default_dir) = crate::install::default_data_dir()
+            && !data_dirs.contains(&default_dir)
+        {
+            data_dirs.push(default_dir);
+        }
+
+        for (language, data_dir) in self.install_watcher.poll(&data_dirs) {
+            // Languages that were never loaded pick up the new files lazily
+            if !self.language.unload_language(&language) {
+                continue;
+            }
+            self.parser_pool.lock().await.clear();
+            log::info!(
+                target: "kakehashi::install",
+                "Reloading '{}' updated in {}",
+                language,
+                data_dir.display()
+            );
+
+            let documents = self.documents.documents_with_language(&language);
+            if documents.is_empty() {
+                // Only used as an injection: reload it and refresh highlighting
+                let load_result = self.language.ensure_language_loaded(&language);
+                self.handle_language_events(&load_result.events).await;
+            }
+            for (uri, text) in documents {
+                // The stored tree was built by the old grammar and must not seed
+                // an incremental parse with the new one
+                self.documents
+                    .update_document(uri.clone(), text.clone(), None);
+                self.reload_language_after_install(&language, &data_dir, uri, text, false)
+                    .await;
+            }
+        }
+    }
+
     /// Forward didChange notifications to opened virtual documents in bridges.
     ///
-    /// This method collects all injection regions from the parsed document and
-    /// forwards didChange notifications to downstream language servers for any
-    /// virtual documents that have been opened (via didOpen during hover/completion).
+    /// This method collects all injection regions from the parsed document (plus
+    /// the host document when host-document bridging is enabled) and forwards
+    /// didChange notifications to downstream language servers for any virtual
+    /// documents that have been opened (via didOpen during hover/completion).
     ///
     /// Called after parse_document() in did_change() to propagate host document
     /// changes to downstream language servers.
     async fn forward_didchange_to_bridges(&self, uri: &Url, text: &str) {
-        // Get the host language for this document
-        let host_language = match self.get_language_for_document(uri) {
-            Some(lang) => lang,
-            None => return, // No language detected, nothing to forward
+        let injections = self.collect_bridge_contents(uri, text);
+        if injections.is_empty() {
+            return;
+        }
+
+        // Forward didChange to opened virtual documents
+        self.bridge
+            .forward_didchange_to_opened_docs(uri, &injections)
+            .await;
+    }
+
+    /// Build (language, region_id, content) tuples for every bridged region of
+    /// a host document: its injection regions, plus the document itself when
+    /// host-document bridging is enabled.
+    fn collect_bridge_contents(&self, uri: &Url, text: &str) -> Vec<(String, String, String)> {
+        let Some(host_language) = self.get_language_for_document(uri) else {
+            return Vec::new(); // No language detected, nothing to bridge
         };
 
+        let mut contents = self.collect_injection_contents(uri, &host_language, text);
+        if let Some(host) = self.host_document_region(&host_language, text) {
+            contents.push((
+                host.injection_language,
+                host.region_id,
+                host.virtual_content,
+            ));
+        }
+        contents
+    }
+
+    /// Build (language, region_id, content) tuples for each injection region.
+    ///
+    /// Returns an empty Vec if the language has no injection query, the document
+    /// has no parse tree, or there are no injections.
+    fn collect_injection_contents(
+        &self,
+        uri: &Url,
+        host_language: &str,
+        text: &str,
+    ) -> Vec<(String, String, String)> {
         // Get the injection query for this language
-        let injection_query = match self.language.get_injection_query(&host_language) {
+        let injection_query = match self.language.get_injection_query(host_language) {
             Some(q) => q,
-            None => return, // No injection query = no injections
+            None => return Vec::new(), // No injection query = no injections
         };
 
         // Extract tree from document with minimal lock duration
@@ -709,12 +923,12 @@ impl Kakehashi {
         let tree = {
             let doc = match self.documents.get(uri) {
                 Some(d) => d,
-                None => return, // Document not found
+                None => return Vec::new(), // Document not found
             };
 
             match doc.tree() {
                 Some(t) => t.clone(),
-                None => return, // No parse tree
+                None => return Vec::new(), // No parse tree
             }
             // Document lock released here when `doc` guard drops
         };
@@ -723,17 +937,12 @@ impl Kakehashi {
         let regions =
             match collect_all_injections(&tree.root_node(), text, Some(injection_query.as_ref())) {
                 Some(r) => r,
-                None => return, // No injections
+                None => return Vec::new(), // No injections
             };
 
-        if regions.is_empty() {
-            return;
-        }
-
-        // Build (language, region_id, content) tuples for each injection
         // ADR-0019: Use RegionIdTracker with position-based keys
         // No document lock held here - safe to access region_id_tracker
-        let injections: Vec<(String, String, String)> = regions
+        regions
             .iter()
             .map(|region| {
                 let region_id = InjectionResolver::calculate_region_id(
@@ -748,12 +957,7 @@ impl Kakehashi {
                     content.to_string(),
                 )
             })
-            .collect();
-
-        // Forward didChange to opened virtual documents
-        self.bridge
-            .forward_didchange_to_opened_docs(uri, &injections)
-            .await;
+            .collect()
     }
 
     /// Process injected languages: auto-install missing parsers and spawn bridge servers.
@@ -765,16 +969,26 @@ impl Kakehashi {
     /// This must be called AFTER parse_document so we have access to the AST.
     async fn process_injected_languages(&self, uri: &Url) {
         // Get unique injected languages from the document (computed once)
-        let languages = get_injected_languages(uri, &self.language, &self.documents);
+        let mut languages = get_injected_languages(uri, &self.language, &self.documents);
+
+        if !languages.is_empty() {
+            // Check for missing parsers and trigger auto-install
+            self.check_injected_languages_auto_install(uri, &languages)
+                .await;
+        }
+
+        // The host language's own servers are warmed up as well when
+        // host-document bridging is enabled
+        if let Some(host_language) = self.get_language_for_document(uri)
+            && self.is_host_bridged(&host_language)
+        {
+            languages.insert(host_language);
+        }
 
         if languages.is_empty() {
             return;
         }
 
-        // Check for missing parsers and trigger auto-install
-        self.check_injected_languages_auto_install(uri, &languages)
-            .await;
-
         // Eagerly spawn bridge servers for detected injection languages
         self.eager_spawn_bridge_servers(uri, languages).await;
     }
@@ -824,9 +1038,13 @@ impl Kakehashi {
                                 uri.clone(),
                                 text.clone(),
                                 true,
+                                load_result.needs_rebuild(),
                             )
                             .await;
                     }
+                } else if load_result.needs_rebuild() {
+                    // The parser exists; explain why it cannot be used
+                    self.handle_language_events(&load_result.events).await;
                 } else {
                     // Notify user that parser is missing and needs manual installation
                     self.notify_parser_missing(&resolved_lang).await;
@@ -849,9 +1067,24 @@ impl Kakehashi {
         // Get current settings for server config lookup
         let settings = self.settings_manager.load_settings();
 
+        // Remember which servers this host language bridges, so the trigger
+        // character registrations cover documents of this language.
+        let server_names: Vec<String> = languages
+            .iter()
+            .flat_map(|lang| self.get_all_bridge_configs_for_language(uri, &host_language, lang))
+            .map(|resolved| resolved.server_name)
+            .collect();
+        if self
+            .trigger_registrar
+            .record_host_language(&host_language, server_names.iter().map(String::as_str))
+        {
+            let registrar = std::sync::Arc::clone(&self.trigger_registrar);
+            tokio::spawn(async move { registrar.refresh().await });
+        }
+
         // Spawn servers for each detected injection language
         self.bridge
-            .eager_spawn_servers(&settings, &host_language, languages)
+            .eager_spawn_servers(&settings, uri, &host_language, languages)
             .await;
     }
 
@@ -885,6 +1118,9 @@ impl Kakehashi {
 /// Currently handles:
 /// - `DiagnosticRefresh`: forwards `workspace/diagnostic/refresh` to trigger a
 ///   fresh diagnostic pull from the editor.
+/// - `TriggerCharactersChanged`: refreshes the dynamic completion / signature
+///   help registrations (in a separate task, as it round-trips to the client).
+/// - `ServerGaveUp`: shows a warning that a crash-looping server was stopped.
 ///
 /// Exits when:
 /// - The channel is closed (all senders dropped), OR
@@ -892,6 +1128,7 @@ impl Kakehashi {
 async fn upstream_forwarding_loop(
     mut upstream_rx: tokio::sync::mpsc::UnboundedReceiver<super::bridge::UpstreamNotification>,
     client: Client,
+    trigger_registrar: std::sync::Arc<TriggerCharacterRegistrar>,
     cancel_token: tokio_util::sync::CancellationToken,
 ) {
     use super::bridge::UpstreamNotification;
@@ -918,6 +1155,21 @@ async fn upstream_forwarding_loop(
                             );
                         }
                     }
+                    Some(UpstreamNotification::TriggerCharactersChanged) => {
+                        let registrar = std::sync::Arc::clone(&trigger_registrar);
+                        tokio::spawn(async move { registrar.refresh().await });
+                    }
+                    Some(UpstreamNotification::ServerGaveUp { server_name, restarts }) => {
+                        client
+                            .show_message(
+                                MessageType::WARNING,
+                                format!(
+                                    "kakehashi: language server '{}' crashed {} times in a row and will not be restarted",
+                                    server_name, restarts
+                                ),
+                            )
+                            .await;
+                    }
                     None => break, // Channel closed
                 }
             }
@@ -925,6 +1177,25 @@ async fn upstream_forwarding_loop(
     }
 }
 
+/// Periodically shut down downstream servers idle past their `timeouts.idle`.
+///
+/// Exits when the `cancel_token` is cancelled (deterministic shutdown).
+async fn idle_sweep_loop(
+    pool: std::sync::Arc<super::bridge::LanguageServerPool>,
+    cancel_token: tokio_util::sync::CancellationToken,
+) {
+    let mut interval = tokio::time::interval(super::bridge::IDLE_SWEEP_INTERVAL);
+    loop {
+        tokio::select! {
+            biased;
+
+            _ = cancel_token.cancelled() => break,
+
+            _ = interval.tick() => pool.shutdown_idle_connections().await,
+        }
+    }
+}
+
 /// Cancellable upstream forwarding loop without a Client (for testing).
 ///
 /// Drains notifications from the channel and exits when the token is cancelled
@@ -1033,6 +1304,8 @@ impl LanguageServer for Kakehashi {
         });
         self.apply_settings(settings).await;
 
+        let dynamic_triggers = self.trigger_registrar.init(&params.capabilities);
+
         self.notifier().log_info("server initialized!").await;
         Ok(InitializeResult {
             server_info: Some(ServerInfo {
@@ -1070,14 +1343,19 @@ impl LanguageServer for Kakehashi {
                 type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
                 implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
-                completion_provider: Some(CompletionOptions {
+                // Registered dynamically with downstream trigger characters
+                // when the client supports it (see trigger_characters.rs)
+                completion_provider: (!dynamic_triggers.completion).then(|| CompletionOptions {
                     trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+                    resolve_provider: Some(true),
                     ..Default::default()
                 }),
-                signature_help_provider: Some(SignatureHelpOptions {
-                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
-                    retrigger_characters: Some(vec![",".to_string()]),
-                    ..Default::default()
+                signature_help_provider: (!dynamic_triggers.signature_help).then(|| {
+                    SignatureHelpOptions {
+                        trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                        retrigger_characters: Some(vec![",".to_string()]),
+                        ..Default::default()
+                    }
                 }),
                 references_provider: Some(OneOf::Left(true)),
                 document_highlight_provider: Some(OneOf::Left(true)),
@@ -1086,13 +1364,39 @@ impl LanguageServer for Kakehashi {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 rename_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                // Registered dynamically with downstream trigger characters
+                // (see trigger_characters.rs)
+                document_on_type_formatting_provider: None,
+                linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(
+                    true,
+                )),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        resolve_provider: Some(true),
+                        ..Default::default()
+                    },
+                )),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                // Downstream commands are wrapped into a single command
+                // (see bridge/protocol/bridge_command.rs), next to the
+                // server status/restart/stop commands
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: BRIDGE_COMMANDS.iter().map(|c| c.to_string()).collect(),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 inlay_hint_provider: Some(OneOf::Left(true)),
                 #[cfg(feature = "experimental")]
                 color_provider: Some(ColorProviderCapability::Simple(true)),
                 #[cfg(not(feature = "experimental"))]
                 color_provider: None,
                 moniker_provider: Some(OneOf::Left(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
                 // ADR-0020: Pull-first diagnostic forwarding
                 diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
                     DiagnosticOptions {
@@ -1116,8 +1420,45 @@ impl LanguageServer for Kakehashi {
         // triggers a fresh textDocument/diagnostic pull.
         if let Some(upstream_rx) = self.bridge.take_upstream_rx() {
             let client = self.client.clone();
+            let registrar = std::sync::Arc::clone(&self.trigger_registrar);
             let token = self.shutdown_token.clone();
-            tokio::spawn(upstream_forwarding_loop(upstream_rx, client, token));
+            tokio::spawn(upstream_forwarding_loop(
+                upstream_rx,
+                client,
+                registrar,
+                token,
+            ));
+        }
+
+        // Shut down downstream servers that stay without open documents
+        // longer than their configured idle timeout.
+        tokio::spawn(idle_sweep_loop(
+            self.bridge.pool_arc(),
+            self.shutdown_token.clone(),
+        ));
+
+        // Register completion / signature help triggers dynamically (if the client
+        // supports it). Servers becoming Ready refresh these registrations later.
+        let registrar = std::sync::Arc::clone(&self.trigger_registrar);
+        tokio::spawn(async move { registrar.refresh().await });
+
+        // ls_types' ServerCapabilities has no typeHierarchyProvider field, so
+        // type hierarchy can only be offered through dynamic registration
+        if self.supports_dynamic_type_hierarchy() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let registration = tower_lsp_server::ls_types::Registration {
+                    id: "kakehashi-type-hierarchy".to_string(),
+                    method: "textDocument/prepareTypeHierarchy".to_string(),
+                    register_options: None,
+                };
+                if let Err(e) = client.register_capability(vec![registration]).await {
+                    log::warn!(
+                        "Failed to register textDocument/prepareTypeHierarchy: {}",
+                        e
+                    );
+                }
+            });
         }
     }
 
@@ -1161,12 +1502,25 @@ impl LanguageServer for Kakehashi {
             return;
         };
 
+        self.reload_updated_languages().await;
+
         // Try to determine the language
         let language_name = self
             .language
             .get_language_for_path(uri.path())
             .or_else(|| Some(language_id.clone()));
 
+        // Register trigger characters under the client's languageId, which
+        // need not match the language name used here
+        if let Some(lang) = &language_name
+            && self
+                .trigger_registrar
+                .record_language_id(lang, &language_id)
+        {
+            let registrar = std::sync::Arc::clone(&self.trigger_registrar);
+            tokio::spawn(async move { registrar.refresh().await });
+        }
+
         // Insert document immediately (without tree) so concurrent requests can find it.
         // This handles race conditions where semanticTokens/full arrives before
         // parse_document completes. The tree will be updated by parse_document.
@@ -1201,10 +1555,17 @@ impl LanguageServer for Kakehashi {
                     // is_injection=false: This is the document's main language
                     // If install is triggered, skip parse_document here - reload_language_after_install will handle it
                     skip_parse = self
-                        .maybe_auto_install_language(lang, uri.clone(), text.clone(), false)
+                        .maybe_auto_install_language(
+                            lang,
+                            uri.clone(),
+                            text.clone(),
+                            false,
+                            load_result.needs_rebuild(),
+                        )
                         .await;
-                } else {
+                } else if !load_result.needs_rebuild() {
                     // Notify user that parser is missing and needs manual installation
+                    // (an incompatible parser was explained by the events logged above)
                     self.notify_parser_missing(lang).await;
                 }
             }
@@ -1298,6 +1659,8 @@ impl LanguageServer for Kakehashi {
             .log_trace(format!("[DID_CHANGE] START uri={}", uri))
             .await;
 
+        self.reload_updated_languages().await;
+
         // Retrieve the stored document info
         let (language_id, old_text) = {
             let doc = self.documents.get(&uri);
@@ -1480,6 +1843,10 @@ impl LanguageServer for Kakehashi {
         self.completion_impl(params).await
     }
 
+    async fn completion_resolve(&self, params: CompletionItem) -> Result<CompletionItem> {
+        self.completion_resolve_impl(params).await
+    }
+
     async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
         self.signature_help_impl(params).await
     }
@@ -1506,10 +1873,59 @@ impl LanguageServer for Kakehashi {
         self.document_symbol_impl(params).await
     }
 
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        self.folding_range_impl(params).await
+    }
+
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
         self.rename_impl(params).await
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        self.code_action_impl(params).await
+    }
+
+    async fn code_action_resolve(&self, params: CodeAction) -> Result<CodeAction> {
+        self.code_action_resolve_impl(params).await
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        self.code_lens_impl(params).await
+    }
+
+    async fn code_lens_resolve(&self, params: CodeLens) -> Result<CodeLens> {
+        self.code_lens_resolve_impl(params).await
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<LSPAny>> {
+        self.execute_command_impl(params).await
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        self.formatting_impl(params).await
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.range_formatting_impl(params).await
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.on_type_formatting_impl(params).await
+    }
+
+    async fn linked_editing_range(
+        &self,
+        params: LinkedEditingRangeParams,
+    ) -> Result<Option<LinkedEditingRanges>> {
+        self.linked_editing_range_impl(params).await
+    }
+
     async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
         self.inlay_hint_impl(params).await
     }
@@ -1531,6 +1947,48 @@ impl LanguageServer for Kakehashi {
         self.moniker_impl(params).await
     }
 
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        self.prepare_call_hierarchy_impl(params).await
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        self.incoming_calls_impl(params).await
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        self.outgoing_calls_impl(params).await
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.prepare_type_hierarchy_impl(params).await
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.supertypes_impl(params).await
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.subtypes_impl(params).await
+    }
+
     async fn diagnostic(
         &self,
         params: DocumentDiagnosticParams,
@@ -3,13 +3,30 @@
 //! All bridge endpoints (definition, type_definition, implementation, declaration,
 //! references) follow the same pattern of resolving injection context before sending
 //! requests. This module extracts that shared preamble into a single method.
+//!
+//! Follow-up requests on items produced by a bridge server (call and type
+//! hierarchy) locate the item's region again from its routing envelope.
+//!
+//! Positions outside every injection region resolve to the host document
+//! itself when host-document bridging is enabled for the host language.
+//!
+//! Endpoints whose results can be merged across servers (hover, completion,
+//! signature help, references, document highlight, definition) use the fan-out
+//! variant, which keeps every capable server instead of selecting one.
+
+use std::future::Future;
+use std::io;
 
 use tower_lsp_server::jsonrpc::Id;
 use tower_lsp_server::ls_types::{MessageType, Position, Uri};
 use url::Url;
 
+use crate::config::BridgeMergeStrategy;
+use crate::document::model::DocumentSnapshot;
 use crate::language::injection::ResolvedInjection;
-use crate::lsp::bridge::{ResolvedServerConfig, UpstreamId};
+use crate::lsp::bridge::{
+    BridgeResolveData, HOST_DOCUMENT_REGION_ID, ResolvedServerConfig, UpstreamId,
+};
 use crate::lsp::get_current_request_id;
 use crate::text::PositionMapper;
 
@@ -33,6 +50,52 @@ pub(crate) struct BridgeRequestContext {
     pub(crate) upstream_request_id: UpstreamId,
 }
 
+/// All resolved context needed to send a request to several bridge servers.
+///
+/// Produced by `Kakehashi::resolve_bridge_fan_out_context` and consumed by
+/// `Kakehashi::fan_out_bridge_request`.
+pub(crate) struct BridgeFanOutContext {
+    /// The parsed document URL (url::Url).
+    pub(crate) uri: Url,
+    /// The cursor position within the document.
+    pub(crate) position: Position,
+    /// The resolved injection region with virtual content and region metadata.
+    pub(crate) resolved: ResolvedInjection,
+    /// Servers that may support the method, in priority order.
+    pub(crate) configs: Vec<ResolvedServerConfig>,
+    /// How responses from `configs` are combined.
+    pub(crate) strategy: BridgeMergeStrategy,
+    /// The upstream JSON-RPC request ID for cancel forwarding.
+    pub(crate) upstream_request_id: UpstreamId,
+}
+
+/// All resolved context needed to send a follow-up request for a bridged item.
+///
+/// Produced by `Kakehashi::resolve_bridge_item_context` from the item's
+/// routing envelope (e.g., call hierarchy items sent back by the client).
+pub(crate) struct BridgeItemContext {
+    /// The host document URL (url::Url).
+    pub(crate) uri: Url,
+    /// The item's routing envelope.
+    pub(crate) origin: BridgeResolveData,
+    /// The item's injection region as it is now.
+    pub(crate) resolved: ResolvedInjection,
+    /// The config of the server that produced the item.
+    pub(crate) resolved_config: ResolvedServerConfig,
+    /// The upstream JSON-RPC request ID for cancel forwarding.
+    pub(crate) upstream_request_id: UpstreamId,
+}
+
+/// Get upstream request ID from task-local storage (set by RequestIdCapture middleware).
+pub(crate) fn current_upstream_request_id() -> UpstreamId {
+    match get_current_request_id() {
+        Some(Id::Number(n)) => UpstreamId::Number(n),
+        Some(Id::String(s)) => UpstreamId::String(s),
+        // For notifications without ID or null ID, use Null to avoid collision with ID 0
+        None | Some(Id::Null) => UpstreamId::Null,
+    }
+}
+
 impl Kakehashi {
     /// Resolve injection context for a bridge endpoint request.
     ///
@@ -44,8 +107,9 @@ impl Kakehashi {
     /// 3. Gets document snapshot
     /// 4. Detects document language
     /// 5. Gets injection query
-    /// 6. Resolves injection region at position
-    /// 7. Looks up bridge server config
+    /// 6. Resolves injection region at position (or the host document when
+    ///    host-document bridging is enabled and no injection contains it)
+    /// 7. Selects the first bridge server supporting `lsp_method`
     /// 8. Extracts upstream request ID from task-local storage
     ///
     /// Returns `None` for any early-exit condition (invalid URI, no document,
@@ -55,12 +119,244 @@ impl Kakehashi {
     /// * `lsp_uri` - The document URI from the LSP params
     /// * `position` - The cursor position
     /// * `method_name` - Name for log messages (e.g., "goto_definition", "references")
+    /// * `lsp_method` - LSP method used for capability-aware server selection
+    ///   (e.g., "textDocument/definition")
     pub(crate) async fn resolve_bridge_context(
         &self,
         lsp_uri: &Uri,
         position: Position,
         method_name: &str,
+        lsp_method: &str,
     ) -> Option<BridgeRequestContext> {
+        let (uri, language_name, resolved) = self
+            .resolve_injection_context(lsp_uri, position, method_name)
+            .await?;
+
+        // Get bridge server config for this language, skipping servers lacking the method
+        let Some(resolved_config) = self
+            .select_bridge_config(
+                &uri,
+                &language_name,
+                &resolved.injection_language,
+                lsp_method,
+            )
+            .await
+        else {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured for language: {} (host: {})",
+                        resolved.injection_language, language_name
+                    ),
+                )
+                .await;
+            return None;
+        };
+
+        Some(BridgeRequestContext {
+            uri,
+            position,
+            resolved,
+            resolved_config,
+            upstream_request_id: current_upstream_request_id(),
+        })
+    }
+
+    /// Resolve injection context for a request that may be answered by several
+    /// bridge servers.
+    ///
+    /// Same preamble as `resolve_bridge_context`, but keeps every configured
+    /// server that may support `lsp_method` (in priority order) together with
+    /// the `bridgeMerge` strategy for the method.
+    pub(crate) async fn resolve_bridge_fan_out_context(
+        &self,
+        lsp_uri: &Uri,
+        position: Position,
+        method_name: &str,
+        lsp_method: &str,
+    ) -> Option<BridgeFanOutContext> {
+        let (uri, language_name, resolved) = self
+            .resolve_injection_context(lsp_uri, position, method_name)
+            .await?;
+
+        // Get all bridge server configs for this language, skipping servers lacking the method
+        let (configs, strategy) = self
+            .select_bridge_configs_for_merge(
+                &uri,
+                &language_name,
+                &resolved.injection_language,
+                lsp_method,
+            )
+            .await;
+        if configs.is_empty() {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured for language: {} (host: {})",
+                        resolved.injection_language, language_name
+                    ),
+                )
+                .await;
+            return None;
+        }
+
+        Some(BridgeFanOutContext {
+            uri,
+            position,
+            resolved,
+            configs,
+            strategy,
+            upstream_request_id: current_upstream_request_id(),
+        })
+    }
+
+    /// Send a request to the servers of `ctx` according to its merge strategy.
+    ///
+    /// - `Merge`: all servers are asked concurrently and every non-empty
+    ///   response is kept
+    /// - `First`: servers are asked one at a time in priority order until one
+    ///   returns a non-empty response
+    ///
+    /// Responses are returned in server priority order. Failed requests are
+    /// logged and skipped so one misbehaving server does not hide the others.
+    pub(crate) async fn fan_out_bridge_request<'a, T, F, Fut>(
+        &self,
+        ctx: &'a BridgeFanOutContext,
+        feature: &str,
+        is_empty: impl Fn(&T) -> bool,
+        send: F,
+    ) -> Vec<T>
+    where
+        F: Fn(&'a ResolvedServerConfig) -> Fut,
+        Fut: Future<Output = io::Result<Option<T>>>,
+    {
+        let mut responses = Vec::new();
+        match ctx.strategy {
+            BridgeMergeStrategy::Merge => {
+                let results = futures::future::join_all(ctx.configs.iter().map(&send)).await;
+                for (config, result) in ctx.configs.iter().zip(results) {
+                    if let Some(response) =
+                        self.accept_bridge_response(feature, config, result).await
+                        && !is_empty(&response)
+                    {
+                        responses.push(response);
+                    }
+                }
+            }
+            BridgeMergeStrategy::First => {
+                for config in &ctx.configs {
+                    let result = send(config).await;
+                    if let Some(response) =
+                        self.accept_bridge_response(feature, config, result).await
+                        && !is_empty(&response)
+                    {
+                        responses.push(response);
+                        break;
+                    }
+                }
+            }
+        }
+        responses
+    }
+
+    /// Resolve the context for a follow-up request on an item produced by a
+    /// bridge server.
+    ///
+    /// Unlike resolve requests, follow-ups (e.g., `callHierarchy/incomingCalls`)
+    /// still operate on the item's virtual document, so its injection region is
+    /// looked up again by region ID. The region may have moved since the item
+    /// was produced; its current position and content are used.
+    ///
+    /// Returns `None` if `data` carries no envelope, the server is no longer
+    /// configured, or the region no longer exists.
+    pub(crate) async fn resolve_bridge_item_context(
+        &self,
+        data: Option<&serde_json::Value>,
+        method_name: &str,
+    ) -> Option<BridgeItemContext> {
+        // Items without a bridge envelope were not produced by a downstream server
+        let origin = BridgeResolveData::from_data(data)?;
+
+        let Some(resolved_config) = self.get_bridge_config_for_server(&origin.server_name) else {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured with name: {}",
+                        origin.server_name
+                    ),
+                )
+                .await;
+            return None;
+        };
+
+        let Ok(uri) = uri_to_url(&origin.host_uri) else {
+            log::warn!(
+                "Invalid host URI in {}: {}",
+                method_name,
+                origin.host_uri.as_str()
+            );
+            return None;
+        };
+
+        let snapshot = self.documents.get(&uri)?.snapshot()?;
+        let language_name = self.get_language_for_document(&uri)?;
+
+        let Some(resolved) = self
+            .resolve_bridge_regions(&uri, &language_name, &snapshot)
+            .into_iter()
+            .find(|resolved| resolved.region.region_id == origin.region_id)
+        else {
+            log::debug!(
+                "kakehashi::{}: injection region {} no longer exists",
+                method_name,
+                origin.region_id
+            );
+            return None;
+        };
+
+        Some(BridgeItemContext {
+            uri,
+            origin,
+            resolved,
+            resolved_config,
+            upstream_request_id: current_upstream_request_id(),
+        })
+    }
+
+    async fn accept_bridge_response<T>(
+        &self,
+        feature: &str,
+        config: &ResolvedServerConfig,
+        result: io::Result<Option<T>>,
+    ) -> Option<T> {
+        match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!(
+                            "Bridge {} request to {} failed: {}",
+                            feature, config.server_name, e
+                        ),
+                    )
+                    .await;
+                None
+            }
+        }
+    }
+
+    /// Steps 1-6 of `resolve_bridge_context`: locate the injection region at
+    /// `position`, returning the document URL, host language and resolved region.
+    async fn resolve_injection_context(
+        &self,
+        lsp_uri: &Uri,
+        position: Position,
+        method_name: &str,
+    ) -> Option<(Url, String, ResolvedInjection)> {
         // Convert ls_types::Uri to url::Url for internal use
         let Ok(uri) = uri_to_url(lsp_uri) else {
             log::warn!("Invalid URI in {}: {}", method_name, lsp_uri.as_str());
@@ -103,56 +399,78 @@ impl Kakehashi {
             return None;
         };
 
-        // Get injection query to detect injection regions
-        let injection_query = self.language.get_injection_query(&language_name)?;
-
         // Resolve injection region at position
         let mapper = PositionMapper::new(snapshot.text());
         let byte_offset = mapper.position_to_byte(position)?;
 
-        let Some(resolved) = crate::language::InjectionResolver::resolve_at_byte_offset(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            &uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-            byte_offset,
-        ) else {
-            // Not in an injection region - return None
-            return None;
-        };
+        let in_injection =
+            self.language
+                .get_injection_query(&language_name)
+                .and_then(|injection_query| {
+                    crate::language::InjectionResolver::resolve_at_byte_offset(
+                        &self.language,
+                        self.bridge.region_id_tracker(),
+                        &uri,
+                        snapshot.tree(),
+                        snapshot.text(),
+                        injection_query.as_ref(),
+                        byte_offset,
+                    )
+                });
 
-        // Get bridge server config for this language
-        let Some(resolved_config) =
-            self.get_bridge_config_for_language(&language_name, &resolved.injection_language)
-        else {
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!(
-                        "No bridge server configured for language: {} (host: {})",
-                        resolved.injection_language, language_name
-                    ),
-                )
-                .await;
-            return None;
+        // Outside any injection region, fall back to the host document itself
+        // when host-document bridging is enabled; otherwise there is nothing to bridge
+        let resolved = match in_injection {
+            Some(resolved) => resolved,
+            None => self.host_document_region(&language_name, snapshot.text())?,
         };
 
-        // Get upstream request ID from task-local storage (set by RequestIdCapture middleware)
-        let upstream_request_id = match get_current_request_id() {
-            Some(Id::Number(n)) => UpstreamId::Number(n),
-            Some(Id::String(s)) => UpstreamId::String(s),
-            // For notifications without ID or null ID, use Null to avoid collision with ID 0
-            None | Some(Id::Null) => UpstreamId::Null,
-        };
+        Some((uri, language_name, resolved))
+    }
 
-        Some(BridgeRequestContext {
-            uri,
-            position,
-            resolved,
-            resolved_config,
-            upstream_request_id,
+    /// The host document as a bridge region, if host-document bridging is
+    /// enabled for `language_name` (`languages.<name>.host.enabled`).
+    pub(crate) fn host_document_region(
+        &self,
+        language_name: &str,
+        text: &str,
+    ) -> Option<ResolvedInjection> {
+        self.is_host_bridged(language_name).then(|| {
+            ResolvedInjection::whole_document(language_name, HOST_DOCUMENT_REGION_ID, text)
         })
     }
+
+    /// Check if host-document bridging is enabled for `language_name`.
+    pub(crate) fn is_host_bridged(&self, language_name: &str) -> bool {
+        let settings = self.settings_manager.load_settings();
+        crate::config::resolve_language_settings_with_wildcard(&settings.languages, language_name)
+            .is_some_and(|host_settings| host_settings.is_host_bridged())
+    }
+
+    /// Resolve every region a whole-document bridge request is sent to: all
+    /// injection regions, followed by the host document itself when
+    /// host-document bridging is enabled for `language_name`.
+    pub(crate) fn resolve_bridge_regions(
+        &self,
+        uri: &Url,
+        language_name: &str,
+        snapshot: &DocumentSnapshot,
+    ) -> Vec<ResolvedInjection> {
+        let mut regions = self
+            .language
+            .get_injection_query(language_name)
+            .map(|injection_query| {
+                crate::language::InjectionResolver::resolve_all(
+                    &self.language,
+                    self.bridge.region_id_tracker(),
+                    uri,
+                    snapshot.tree(),
+                    snapshot.text(),
+                    injection_query.as_ref(),
+                )
+            })
+            .unwrap_or_default();
+        regions.extend(self.host_document_region(language_name, snapshot.text()));
+        regions
+    }
 }
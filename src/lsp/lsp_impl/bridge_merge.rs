@@ -0,0 +1,297 @@
+//! Combining responses from multiple bridge servers for one injection region.
+//!
+//! When several servers handle the same injection language (e.g., pyright and
+//! ruff for Python), position-based requests are sent to each capable server and
+//! the responses are combined according to the feature's `bridgeMerge` strategy.
+//! Inputs are always in server priority order; the first server wins ties.
+
+use std::collections::HashSet;
+
+use tower_lsp_server::ls_types::{
+    CompletionList, DocumentHighlight, Hover, HoverContents, Location, LocationLink, MarkedString,
+    MarkupContent, MarkupKind, SignatureHelp,
+};
+
+/// Separator placed between hover contents from different servers.
+const HOVER_SEPARATOR: &str = "\n\n---\n\n";
+
+/// Concatenate hovers into a single markdown hover separated by horizontal rules.
+///
+/// A single hover is returned unchanged. The range of the first hover is kept.
+pub(crate) fn merge_hovers(hovers: Vec<Hover>) -> Option<Hover> {
+    if hovers.len() <= 1 {
+        return hovers.into_iter().next();
+    }
+    let range = hovers.iter().find_map(|hover| hover.range);
+    let value = hovers
+        .into_iter()
+        .map(|hover| hover_contents_to_markdown(hover.contents))
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(HOVER_SEPARATOR);
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range,
+    })
+}
+
+fn hover_contents_to_markdown(contents: HoverContents) -> String {
+    fn marked_string_to_markdown(marked: MarkedString) -> String {
+        match marked {
+            MarkedString::String(text) => text,
+            MarkedString::LanguageString(code) => {
+                format!("```{}\n{}\n```", code.language, code.value)
+            }
+        }
+    }
+
+    match contents {
+        HoverContents::Scalar(marked) => marked_string_to_markdown(marked),
+        HoverContents::Array(items) => items
+            .into_iter()
+            .map(marked_string_to_markdown)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        HoverContents::Markup(markup) => markup.value,
+    }
+}
+
+/// Union completion lists, dropping items whose label was already offered by a
+/// higher-priority server.
+///
+/// Each item's `sortText` is prefixed with its server's rank so that clients
+/// group items by server priority. A single list is returned unchanged.
+pub(crate) fn merge_completion_lists(lists: Vec<CompletionList>) -> Option<CompletionList> {
+    if lists.len() <= 1 {
+        return lists.into_iter().next();
+    }
+    let is_incomplete = lists.iter().any(|list| list.is_incomplete);
+    let mut seen_labels = HashSet::new();
+    let mut items = Vec::new();
+    for (rank, list) in lists.into_iter().enumerate() {
+        for mut item in list.items {
+            if !seen_labels.insert(item.label.clone()) {
+                continue;
+            }
+            let sort_text = item.sort_text.take().unwrap_or_else(|| item.label.clone());
+            item.sort_text = Some(format!("{rank:02}{sort_text}"));
+            items.push(item);
+        }
+    }
+    Some(CompletionList {
+        is_incomplete,
+        items,
+    })
+}
+
+/// Concatenate signatures from all servers.
+///
+/// The active signature and parameter of the first help are kept; signatures
+/// contributed by later servers are appended after it.
+pub(crate) fn merge_signature_helps(helps: Vec<SignatureHelp>) -> Option<SignatureHelp> {
+    let mut helps = helps.into_iter();
+    let mut merged = helps.next()?;
+    for help in helps {
+        merged.signatures.extend(help.signatures);
+    }
+    Some(merged)
+}
+
+/// Union location lists, dropping duplicates of the same range in the same document.
+pub(crate) fn merge_locations(lists: Vec<Vec<Location>>) -> Vec<Location> {
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .filter(|location| seen.insert((location.uri.clone(), location.range)))
+        .collect()
+}
+
+/// Union location links, dropping duplicates of the same target range.
+pub(crate) fn merge_location_links(lists: Vec<Vec<LocationLink>>) -> Vec<LocationLink> {
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .filter(|link| seen.insert((link.target_uri.clone(), link.target_range)))
+        .collect()
+}
+
+/// Union document highlights, keeping the kind reported by the first server for a range.
+pub(crate) fn merge_document_highlights(
+    lists: Vec<Vec<DocumentHighlight>>,
+) -> Vec<DocumentHighlight> {
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .filter(|highlight| seen.insert(highlight.range))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use tower_lsp_server::ls_types::{
+        CompletionItem, DocumentHighlightKind, LanguageString, Position, Range,
+        SignatureInformation, Uri,
+    };
+
+    fn range(line: u32) -> Range {
+        Range::new(Position::new(line, 0), Position::new(line, 5))
+    }
+
+    fn item(label: &str, sort_text: Option<&str>) -> CompletionItem {
+        CompletionItem {
+            label: label.to_string(),
+            sort_text: sort_text.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_hover_is_returned_unchanged() {
+        let hover = Hover {
+            contents: HoverContents::Scalar(MarkedString::String("x: int".to_string())),
+            range: None,
+        };
+        assert_eq!(merge_hovers(vec![hover.clone()]), Some(hover));
+        assert_eq!(merge_hovers(vec![]), None);
+    }
+
+    #[test]
+    fn hovers_are_concatenated_with_separators() {
+        let pyright = Hover {
+            contents: HoverContents::Scalar(MarkedString::LanguageString(LanguageString {
+                language: "python".to_string(),
+                value: "x: int".to_string(),
+            })),
+            range: Some(range(1)),
+        };
+        let ruff = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "**F841** unused variable".to_string(),
+            }),
+            range: Some(range(2)),
+        };
+
+        let merged = merge_hovers(vec![pyright, ruff]).unwrap();
+
+        assert_eq!(merged.range, Some(range(1)));
+        assert_eq!(
+            merged.contents,
+            HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "```python\nx: int\n```\n\n---\n\n**F841** unused variable".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn completion_lists_are_deduplicated_and_ranked() {
+        let first = CompletionList {
+            is_incomplete: false,
+            items: vec![item("print", Some("a")), item("len", None)],
+        };
+        let second = CompletionList {
+            is_incomplete: true,
+            items: vec![item("print", None), item("pathlib", None)],
+        };
+
+        let merged = merge_completion_lists(vec![first, second]).unwrap();
+
+        assert!(merged.is_incomplete);
+        let summary: Vec<(&str, Option<&str>)> = merged
+            .items
+            .iter()
+            .map(|i| (i.label.as_str(), i.sort_text.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("print", Some("00a")),
+                ("len", Some("00len")),
+                ("pathlib", Some("01pathlib")),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_completion_list_keeps_sort_text() {
+        let list = CompletionList {
+            is_incomplete: false,
+            items: vec![item("print", None)],
+        };
+        assert_eq!(merge_completion_lists(vec![list.clone()]), Some(list));
+    }
+
+    #[test]
+    fn signatures_are_appended_after_first_help() {
+        let signature = |label: &str| SignatureInformation {
+            label: label.to_string(),
+            documentation: None,
+            parameters: None,
+            active_parameter: None,
+        };
+        let first = SignatureHelp {
+            signatures: vec![signature("f(a)")],
+            active_signature: Some(0),
+            active_parameter: Some(0),
+        };
+        let second = SignatureHelp {
+            signatures: vec![signature("f(a, b)")],
+            active_signature: Some(0),
+            active_parameter: Some(1),
+        };
+
+        let merged = merge_signature_helps(vec![first, second]).unwrap();
+
+        assert_eq!(merged.signatures.len(), 2);
+        assert_eq!(merged.signatures[1].label, "f(a, b)");
+        assert_eq!(merged.active_parameter, Some(0));
+    }
+
+    #[test]
+    fn locations_are_unioned_without_duplicates() {
+        let uri = Uri::from_str("file:///test.md").unwrap();
+        let other = Uri::from_str("file:///other.py").unwrap();
+        let merged = merge_locations(vec![
+            vec![Location::new(uri.clone(), range(1))],
+            vec![
+                Location::new(uri.clone(), range(1)),
+                Location::new(other.clone(), range(1)),
+            ],
+        ]);
+        assert_eq!(
+            merged,
+            vec![Location::new(uri, range(1)), Location::new(other, range(1))]
+        );
+    }
+
+    #[test]
+    fn document_highlights_keep_first_kind_per_range() {
+        let highlight = |line, kind| DocumentHighlight {
+            range: range(line),
+            kind: Some(kind),
+        };
+        let merged = merge_document_highlights(vec![
+            vec![highlight(1, DocumentHighlightKind::WRITE)],
+            vec![
+                highlight(1, DocumentHighlightKind::READ),
+                highlight(2, DocumentHighlightKind::READ),
+            ],
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                highlight(1, DocumentHighlightKind::WRITE),
+                highlight(2, DocumentHighlightKind::READ),
+            ]
+        );
+    }
+}
@@ -1,5 +1,8 @@
 //! Text document related LSP methods.
 
+mod call_hierarchy;
+mod code_action;
+mod code_lens;
 #[cfg(feature = "experimental")]
 mod color_presentation;
 mod completion;
@@ -11,10 +14,14 @@ mod document_color;
 mod document_highlight;
 mod document_link;
 mod document_symbol;
+mod folding_range;
+mod formatting;
 mod hover;
 mod implementation;
 mod inlay_hint;
+mod linked_editing_range;
 mod moniker;
+mod on_type_formatting;
 mod publish_diagnostic;
 mod references;
 mod rename;
@@ -22,5 +29,6 @@ mod selection_range;
 mod semantic_tokens;
 mod signature_help;
 mod type_definition;
+mod type_hierarchy;
 
 // Re-export the methods (they are implemented as impl blocks on Kakehashi)
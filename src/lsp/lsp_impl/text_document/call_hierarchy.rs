@@ -0,0 +1,140 @@
+//! Call hierarchy methods for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    MessageType,
+};
+
+use super::super::Kakehashi;
+
+impl Kakehashi {
+    pub(crate) async fn prepare_call_hierarchy_impl(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let lsp_uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let Some(ctx) = self
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "prepareCallHierarchy",
+                "textDocument/prepareCallHierarchy",
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // Send prepare call hierarchy request via language server pool
+        let response = self
+            .bridge
+            .pool()
+            .send_prepare_call_hierarchy_request(
+                &ctx.resolved_config.server_name,
+                &ctx.resolved_config.config,
+                &ctx.uri,
+                ctx.position,
+                &ctx.resolved.injection_language,
+                &ctx.resolved.region.region_id,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge prepare call hierarchy request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) async fn incoming_calls_impl(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let Some(ctx) = self
+            .resolve_bridge_item_context(params.item.data.as_ref(), "incomingCalls")
+            .await
+        else {
+            return Ok(None);
+        };
+
+        let response = self
+            .bridge
+            .pool()
+            .send_incoming_calls_request(
+                &ctx.resolved_config.config,
+                params.item,
+                ctx.origin,
+                &ctx.uri,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(calls) => Ok(calls),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge incoming calls request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) async fn outgoing_calls_impl(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let Some(ctx) = self
+            .resolve_bridge_item_context(params.item.data.as_ref(), "outgoingCalls")
+            .await
+        else {
+            return Ok(None);
+        };
+
+        let response = self
+            .bridge
+            .pool()
+            .send_outgoing_calls_request(
+                &ctx.resolved_config.config,
+                params.item,
+                ctx.origin,
+                &ctx.uri,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(calls) => Ok(calls),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge outgoing calls request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+}
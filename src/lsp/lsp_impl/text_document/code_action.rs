@@ -0,0 +1,128 @@
+//! Code action methods for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionContext, CodeActionParams, CodeActionResponse, MessageType,
+};
+
+use crate::lsp::bridge::BridgeResolveData;
+
+use super::super::Kakehashi;
+use super::super::bridge_context::current_upstream_request_id;
+
+impl Kakehashi {
+    pub(crate) async fn code_action_impl(
+        &self,
+        params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>> {
+        let lsp_uri = params.text_document.uri;
+        let range = params.range;
+
+        // The injection region is resolved from range.start, like inlay hints.
+        let Some(ctx) = self
+            .resolve_bridge_context(
+                &lsp_uri,
+                range.start,
+                "code_action",
+                "textDocument/codeAction",
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // Only forward diagnostics that belong to this injection region; the
+        // downstream server cannot make sense of anything outside it.
+        let line_range = &ctx.resolved.region.line_range;
+        let context = CodeActionContext {
+            diagnostics: params
+                .context
+                .diagnostics
+                .into_iter()
+                .filter(|d| {
+                    d.range.start.line >= line_range.start && d.range.end.line <= line_range.end
+                })
+                .collect(),
+            ..params.context
+        };
+
+        // Send code action request via language server pool
+        let response = self
+            .bridge
+            .pool()
+            .send_code_action_request(
+                &ctx.resolved_config.server_name,
+                &ctx.resolved_config.config,
+                &ctx.uri,
+                range,
+                &context,
+                &ctx.resolved.injection_language,
+                &ctx.resolved.region.region_id,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(actions) => Ok(actions),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge code action request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) async fn code_action_resolve_impl(&self, action: CodeAction) -> Result<CodeAction> {
+        // Actions without a bridge envelope were not produced by a downstream
+        // server; there is nothing to resolve them against.
+        let Some(origin) = BridgeResolveData::from_data(action.data.as_ref()) else {
+            return Ok(action);
+        };
+
+        let Some(resolved_config) = self.get_bridge_config_for_server(&origin.server_name) else {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured with name: {}",
+                        origin.server_name
+                    ),
+                )
+                .await;
+            return Ok(action);
+        };
+
+        let upstream_request_id = current_upstream_request_id();
+
+        let response = self
+            .bridge
+            .pool()
+            .send_code_action_resolve_request(
+                &resolved_config.config,
+                action.clone(),
+                origin,
+                upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(Some(resolved)) => Ok(resolved),
+            Ok(None) => Ok(action),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge code action resolve request failed: {}", e),
+                    )
+                    .await;
+                Ok(action)
+            }
+        }
+    }
+}
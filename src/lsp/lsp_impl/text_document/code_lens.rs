@@ -0,0 +1,150 @@
+//! Code lens methods for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{CodeLens, CodeLensParams, MessageType};
+
+use crate::lsp::bridge::BridgeResolveData;
+
+use super::super::bridge_context::current_upstream_request_id;
+use super::super::{Kakehashi, uri_to_url};
+
+impl Kakehashi {
+    pub(crate) async fn code_lens_impl(
+        &self,
+        params: CodeLensParams,
+    ) -> Result<Option<Vec<CodeLens>>> {
+        let lsp_uri = params.text_document.uri;
+
+        // Convert ls_types::Uri to url::Url for internal use
+        let Ok(uri) = uri_to_url(&lsp_uri) else {
+            log::warn!("Invalid URI in codeLens: {}", lsp_uri.as_str());
+            return Ok(None);
+        };
+
+        // Get document snapshot (minimizes lock duration)
+        let snapshot = match self.documents.get(&uri) {
+            None => return Ok(None),
+            Some(doc) => match doc.snapshot() {
+                None => return Ok(None),
+                Some(snapshot) => snapshot,
+            },
+            // doc automatically dropped here, lock released
+        };
+
+        // Get the language for this document
+        let Some(language_name) = self.get_language_for_document(&uri) else {
+            log::debug!(target: "kakehashi::code_lens", "No language detected");
+            return Ok(None);
+        };
+
+        // Collect all injection regions, plus the host document if it is bridged
+        let all_regions = self.resolve_bridge_regions(&uri, &language_name, &snapshot);
+
+        if all_regions.is_empty() {
+            return Ok(None);
+        }
+
+        let upstream_request_id = current_upstream_request_id();
+
+        // Collect code lenses from all injection regions
+        let mut all_lenses: Vec<CodeLens> = Vec::new();
+
+        for resolved in all_regions {
+            // Servers lacking textDocument/codeLens are skipped by select_bridge_config
+            let Some(resolved_config) = self
+                .select_bridge_config(
+                    &uri,
+                    &language_name,
+                    &resolved.injection_language,
+                    "textDocument/codeLens",
+                )
+                .await
+            else {
+                continue; // No bridge configured for this language
+            };
+
+            // Send code lens request via language server pool
+            let response = self
+                .bridge
+                .pool()
+                .send_code_lens_request(
+                    &resolved_config.server_name,
+                    &resolved_config.config,
+                    &uri,
+                    &resolved.injection_language,
+                    &resolved.region.region_id,
+                    resolved.region.line_range.start,
+                    &resolved.virtual_content,
+                    upstream_request_id.clone(),
+                )
+                .await;
+
+            match response {
+                Ok(Some(lenses)) => all_lenses.extend(lenses),
+                Ok(None) => {}
+                Err(e) => {
+                    self.client
+                        .log_message(
+                            MessageType::ERROR,
+                            format!("Bridge code lens request failed: {}", e),
+                        )
+                        .await;
+                }
+            }
+        }
+
+        if all_lenses.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(all_lenses))
+        }
+    }
+
+    pub(crate) async fn code_lens_resolve_impl(&self, lens: CodeLens) -> Result<CodeLens> {
+        // Lenses without a bridge envelope were not produced by a downstream
+        // server; there is nothing to resolve them against.
+        let Some(origin) = BridgeResolveData::from_data(lens.data.as_ref()) else {
+            return Ok(lens);
+        };
+
+        let Some(resolved_config) = self.get_bridge_config_for_server(&origin.server_name) else {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured with name: {}",
+                        origin.server_name
+                    ),
+                )
+                .await;
+            return Ok(lens);
+        };
+
+        let upstream_request_id = current_upstream_request_id();
+
+        let response = self
+            .bridge
+            .pool()
+            .send_code_lens_resolve_request(
+                &resolved_config.config,
+                lens.clone(),
+                origin,
+                upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(Some(resolved)) => Ok(resolved),
+            Ok(None) => Ok(lens),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge code lens resolve request failed: {}", e),
+                    )
+                    .await;
+                Ok(lens)
+            }
+        }
+    }
+}
@@ -16,7 +16,12 @@ impl Kakehashi {
 
         // Use resolve_bridge_context() to handle injection resolution via range.start
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, range.start, "colorPresentation")
+            .resolve_bridge_context(
+                &lsp_uri,
+                range.start,
+                "colorPresentation",
+                "textDocument/colorPresentation",
+            )
             .await
         else {
             return Ok(Vec::new());
@@ -1,14 +1,15 @@
 //! Completion method for Kakehashi.
 
 use tower_lsp_server::jsonrpc::{Id, Result};
-use tower_lsp_server::ls_types::{CompletionParams, CompletionResponse, MessageType};
+use tower_lsp_server::ls_types::{
+    CompletionItem, CompletionList, CompletionParams, CompletionResponse, MessageType,
+};
 
-use crate::language::InjectionResolver;
-use crate::lsp::bridge::UpstreamId;
+use crate::lsp::bridge::{BridgeResolveData, UpstreamId};
 use crate::lsp::get_current_request_id;
-use crate::text::PositionMapper;
 
-use super::super::{Kakehashi, uri_to_url};
+use super::super::Kakehashi;
+use super::super::bridge_merge::merge_completion_lists;
 
 impl Kakehashi {
     pub(crate) async fn completion_impl(
@@ -18,85 +19,70 @@ impl Kakehashi {
         let lsp_uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
 
-        // Convert ls_types::Uri to url::Url for internal use
-        let Ok(uri) = uri_to_url(&lsp_uri) else {
-            log::warn!("Invalid URI in completion: {}", lsp_uri.as_str());
+        // Servers lacking textDocument/completion are skipped; the bridge filter
+        // is checked while resolving the context
+        let Some(ctx) = self
+            .resolve_bridge_fan_out_context(
+                &lsp_uri,
+                position,
+                "completion",
+                "textDocument/completion",
+            )
+            .await
+        else {
             return Ok(None);
         };
 
-        self.client
-            .log_message(
-                MessageType::INFO,
-                format!(
-                    "completion called for {} at line {} col {}",
-                    uri, position.line, position.character
-                ),
+        // Send completion request to every capable server via language server pool
+        let lists = self
+            .fan_out_bridge_request(
+                &ctx,
+                "completion",
+                |list: &CompletionList| list.items.is_empty() && !list.is_incomplete,
+                |server| {
+                    self.bridge.pool().send_completion_request(
+                        &server.server_name,
+                        &server.config,
+                        &ctx.uri,
+                        ctx.position,
+                        &ctx.resolved.injection_language,
+                        &ctx.resolved.region.region_id,
+                        ctx.resolved.region.line_range.start,
+                        &ctx.resolved.virtual_content,
+                        ctx.upstream_request_id.clone(),
+                    )
+                },
             )
             .await;
 
-        // Get document snapshot (minimizes lock duration)
-        let (snapshot, missing_message) = match self.documents.get(&uri) {
-            None => (None, Some("No document found")),
-            Some(doc) => match doc.snapshot() {
-                None => (None, Some("Document not fully initialized")),
-                Some(snapshot) => (Some(snapshot), None),
-            },
-            // doc automatically dropped here, lock released
-        };
-        if let Some(message) = missing_message {
-            self.client.log_message(MessageType::INFO, message).await;
-            return Ok(None);
-        }
-        let snapshot = snapshot.expect("snapshot set when missing_message is None");
-
-        // Get the language for this document
-        let Some(language_name) = self.get_language_for_document(&uri) else {
-            log::debug!(target: "kakehashi::completion", "No language detected");
-            return Ok(None);
-        };
-
-        // Get injection query to detect injection regions
-        let Some(injection_query) = self.language.get_injection_query(&language_name) else {
-            return Ok(None);
-        };
-
-        // Resolve injection region at position (centralizes 29-86 lines of duplication)
-        let mapper = PositionMapper::new(snapshot.text());
-        let Some(byte_offset) = mapper.position_to_byte(position) else {
-            return Ok(None);
-        };
+        // Items keep their bridge envelope, so completionItem/resolve is still
+        // routed to the server that produced each item
+        Ok(merge_completion_lists(lists).map(CompletionResponse::List))
+    }
 
-        let Some(resolved) = InjectionResolver::resolve_at_byte_offset(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            &uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-            byte_offset,
-        ) else {
-            // Not in an injection region - return None
-            return Ok(None);
+    pub(crate) async fn completion_resolve_impl(
+        &self,
+        item: CompletionItem,
+    ) -> Result<CompletionItem> {
+        // Items without a bridge envelope were not produced by a downstream
+        // server; there is nothing to resolve them against.
+        let Some(origin) = BridgeResolveData::from_data(item.data.as_ref()) else {
+            return Ok(item);
         };
 
-        // Get bridge server config for this language
-        // The bridge filter is checked inside get_bridge_config_for_language
-        let Some(resolved_config) =
-            self.get_bridge_config_for_language(&language_name, &resolved.injection_language)
-        else {
+        let Some(resolved_config) = self.get_bridge_config_for_server(&origin.server_name) else {
             self.client
                 .log_message(
                     MessageType::INFO,
                     format!(
-                        "No bridge server configured for language: {} (host: {})",
-                        resolved.injection_language, language_name
+                        "No bridge server configured with name: {}",
+                        origin.server_name
                     ),
                 )
                 .await;
-            return Ok(None);
+            return Ok(item);
         };
 
-        // Send completion request via language server pool
         // Get upstream request ID from task-local storage (set by RequestIdCapture middleware)
         let upstream_request_id = match get_current_request_id() {
             Some(Id::Number(n)) => UpstreamId::Number(n),
@@ -104,33 +90,29 @@ impl Kakehashi {
             // For notifications without ID or null ID, use Null to avoid collision with ID 0
             None | Some(Id::Null) => UpstreamId::Null,
         };
+
         let response = self
             .bridge
             .pool()
-            .send_completion_request(
-                &resolved_config.server_name,
+            .send_completion_resolve_request(
                 &resolved_config.config,
-                &uri,
-                position,
-                &resolved.injection_language,
-                &resolved.region.region_id,
-                resolved.region.line_range.start,
-                &resolved.virtual_content,
+                item.clone(),
+                origin,
                 upstream_request_id,
             )
             .await;
 
         match response {
-            Ok(Some(completion_list)) => Ok(Some(CompletionResponse::List(completion_list))),
-            Ok(None) => Ok(None),
+            Ok(Some(resolved)) => Ok(resolved),
+            Ok(None) => Ok(item),
             Err(e) => {
                 self.client
                     .log_message(
                         MessageType::ERROR,
-                        format!("Bridge completion request failed: {}", e),
+                        format!("Bridge completion resolve request failed: {}", e),
                     )
                     .await;
-                Ok(None)
+                Ok(item)
             }
         }
     }
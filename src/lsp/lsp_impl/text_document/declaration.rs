@@ -17,7 +17,12 @@ impl Kakehashi {
         let position = params.text_document_position_params.position;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "goto_declaration")
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "goto_declaration",
+                "textDocument/declaration",
+            )
             .await
         else {
             return Ok(None);
@@ -27,7 +27,6 @@ use tower_lsp_server::ls_types::{
 use url::Url;
 
 use crate::config::settings::BridgeServerConfig;
-use crate::language::InjectionResolver;
 use crate::lsp::bridge::{LanguageServerPool, UpstreamId};
 use crate::lsp::get_current_request_id;
 use crate::lsp::request_id::CancelSubscriptionGuard;
@@ -200,6 +199,7 @@ impl Kakehashi {
     /// This is `pub(super)` for use by both pull diagnostics and synthetic push diagnostics.
     pub(super) fn build_diagnostic_request_infos(
         &self,
+        uri: &Url,
         language_name: &str,
         all_regions: &[crate::language::injection::ResolvedInjection],
     ) -> Vec<DiagnosticRequestInfo> {
@@ -212,8 +212,11 @@ impl Kakehashi {
             // Get ALL bridge server configs for this language (N-server fan-out).
             // For each region, we emit one DiagnosticRequestInfo per matching server,
             // enabling diagnostics from multiple servers (e.g., pyright + ruff for Python).
-            let configs = self
-                .get_all_bridge_configs_for_language(language_name, &resolved.injection_language);
+            let configs = self.get_all_bridge_configs_for_language(
+                uri,
+                language_name,
+                &resolved.injection_language,
+            );
 
             if configs.is_empty() {
                 log::debug!(
@@ -289,20 +292,8 @@ impl Kakehashi {
             return Ok(empty_diagnostic_report());
         };
 
-        // Get injection query to detect injection regions
-        let Some(injection_query) = self.language.get_injection_query(&language_name) else {
-            return Ok(empty_diagnostic_report());
-        };
-
-        // Collect all injection regions
-        let all_regions = InjectionResolver::resolve_all(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            &uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-        );
+        // Collect all injection regions, plus the host document if it is bridged
+        let all_regions = self.resolve_bridge_regions(&uri, &language_name, &snapshot);
 
         if all_regions.is_empty() {
             return Ok(empty_diagnostic_report());
@@ -358,7 +349,7 @@ impl Kakehashi {
         };
 
         // Build request infos using the factored-out method
-        let request_infos = self.build_diagnostic_request_infos(&language_name, &all_regions);
+        let request_infos = self.build_diagnostic_request_infos(&uri, &language_name, &all_regions);
 
         if request_infos.is_empty() {
             return Ok(empty_diagnostic_report());
@@ -1,9 +1,10 @@
 //! Document highlight method for Kakehashi.
 
 use tower_lsp_server::jsonrpc::Result;
-use tower_lsp_server::ls_types::{DocumentHighlight, DocumentHighlightParams, MessageType};
+use tower_lsp_server::ls_types::{DocumentHighlight, DocumentHighlightParams};
 
 use super::super::Kakehashi;
+use super::super::bridge_merge::merge_document_highlights;
 
 impl Kakehashi {
     pub(crate) async fn document_highlight_impl(
@@ -14,40 +15,37 @@ impl Kakehashi {
         let position = params.text_document_position_params.position;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "document_highlight")
+            .resolve_bridge_fan_out_context(
+                &lsp_uri,
+                position,
+                "document_highlight",
+                "textDocument/documentHighlight",
+            )
             .await
         else {
             return Ok(None);
         };
 
         // Send document highlight request via language server pool
-        let response = self
-            .bridge
-            .pool()
-            .send_document_highlight_request(
-                &ctx.resolved_config.server_name,
-                &ctx.resolved_config.config,
-                &ctx.uri,
-                ctx.position,
-                &ctx.resolved.injection_language,
-                &ctx.resolved.region.region_id,
-                ctx.resolved.region.line_range.start,
-                &ctx.resolved.virtual_content,
-                ctx.upstream_request_id,
-            )
+        let responses = self
+            .fan_out_bridge_request(&ctx, "document highlight", Vec::is_empty, |server| {
+                self.bridge.pool().send_document_highlight_request(
+                    &server.server_name,
+                    &server.config,
+                    &ctx.uri,
+                    ctx.position,
+                    &ctx.resolved.injection_language,
+                    &ctx.resolved.region.region_id,
+                    ctx.resolved.region.line_range.start,
+                    &ctx.resolved.virtual_content,
+                    ctx.upstream_request_id.clone(),
+                )
+            })
             .await;
 
-        match response {
-            Ok(highlights) => Ok(highlights),
-            Err(e) => {
-                self.client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("Bridge document highlight request failed: {}", e),
-                    )
-                    .await;
-                Ok(None)
-            }
+        if responses.is_empty() {
+            return Ok(None);
         }
+        Ok(Some(merge_document_highlights(responses)))
     }
 }
@@ -3,7 +3,6 @@
 use tower_lsp_server::jsonrpc::{Id, Result};
 use tower_lsp_server::ls_types::{DocumentLink, DocumentLinkParams, MessageType};
 
-use crate::language::InjectionResolver;
 use crate::lsp::bridge::UpstreamId;
 use crate::lsp::get_current_request_id;
 
@@ -50,20 +49,8 @@ impl Kakehashi {
             return Ok(None);
         };
 
-        // Get injection query to detect injection regions
-        let Some(injection_query) = self.language.get_injection_query(&language_name) else {
-            return Ok(None);
-        };
-
-        // Collect all injection regions
-        let all_regions = InjectionResolver::resolve_all(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            &uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-        );
+        // Collect all injection regions, plus the host document if it is bridged
+        let all_regions = self.resolve_bridge_regions(&uri, &language_name, &snapshot);
 
         if all_regions.is_empty() {
             return Ok(None);
@@ -82,9 +69,16 @@ impl Kakehashi {
 
         for resolved in all_regions {
             // Get bridge server config for this language
-            // The bridge filter is checked inside get_bridge_config_for_language
-            let Some(resolved_config) =
-                self.get_bridge_config_for_language(&language_name, &resolved.injection_language)
+            // The bridge filter is checked inside select_bridge_config; servers
+            // lacking textDocument/documentLink are skipped
+            let Some(resolved_config) = self
+                .select_bridge_config(
+                    &uri,
+                    &language_name,
+                    &resolved.injection_language,
+                    "textDocument/documentLink",
+                )
+                .await
             else {
                 continue; // No bridge configured for this language
             };
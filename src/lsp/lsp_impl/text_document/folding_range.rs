@@ -0,0 +1,108 @@
+//! Folding range method for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{FoldingRange, FoldingRangeParams, MessageType};
+
+use crate::lsp::bridge::UpstreamId;
+use crate::lsp::get_current_request_id;
+
+use super::super::{Kakehashi, uri_to_url};
+
+impl Kakehashi {
+    pub(crate) async fn folding_range_impl(
+        &self,
+        params: FoldingRangeParams,
+    ) -> Result<Option<Vec<FoldingRange>>> {
+        let lsp_uri = params.text_document.uri;
+
+        // Convert ls_types::Uri to url::Url for internal use
+        let Ok(uri) = uri_to_url(&lsp_uri) else {
+            log::warn!("Invalid URI in foldingRange: {}", lsp_uri.as_str());
+            return Ok(None);
+        };
+
+        // Get document snapshot (minimizes lock duration)
+        let snapshot = match self.documents.get(&uri) {
+            None => return Ok(None),
+            Some(doc) => match doc.snapshot() {
+                None => return Ok(None),
+                Some(snapshot) => snapshot,
+            },
+            // doc automatically dropped here, lock released
+        };
+
+        // Get the language for this document
+        let Some(language_name) = self.get_language_for_document(&uri) else {
+            log::debug!(target: "kakehashi::folding_range", "No language detected");
+            return Ok(None);
+        };
+
+        // Collect all injection regions, plus the host document if it is bridged
+        let all_regions = self.resolve_bridge_regions(&uri, &language_name, &snapshot);
+
+        if all_regions.is_empty() {
+            return Ok(None);
+        }
+
+        // Get upstream request ID from task-local storage (set by RequestIdCapture middleware)
+        let upstream_request_id = match get_current_request_id() {
+            Some(tower_lsp_server::jsonrpc::Id::Number(n)) => UpstreamId::Number(n),
+            Some(tower_lsp_server::jsonrpc::Id::String(s)) => UpstreamId::String(s),
+            // For notifications without ID or null ID, use Null to avoid collision with ID 0
+            None | Some(tower_lsp_server::jsonrpc::Id::Null) => UpstreamId::Null,
+        };
+
+        // Collect folding ranges from all injection regions
+        let mut all_ranges: Vec<FoldingRange> = Vec::new();
+
+        for resolved in all_regions {
+            // Servers lacking textDocument/foldingRange are skipped by select_bridge_config
+            let Some(resolved_config) = self
+                .select_bridge_config(
+                    &uri,
+                    &language_name,
+                    &resolved.injection_language,
+                    "textDocument/foldingRange",
+                )
+                .await
+            else {
+                continue; // No bridge configured for this language
+            };
+
+            // Send folding range request via language server pool
+            let response = self
+                .bridge
+                .pool()
+                .send_folding_range_request(
+                    &resolved_config.server_name,
+                    &resolved_config.config,
+                    &uri,
+                    &resolved.injection_language,
+                    &resolved.region.region_id,
+                    resolved.region.line_range.start,
+                    &resolved.virtual_content,
+                    upstream_request_id.clone(),
+                )
+                .await;
+
+            match response {
+                Ok(Some(ranges)) => all_ranges.extend(ranges),
+                Ok(None) => {}
+                Err(e) => {
+                    self.client
+                        .log_message(
+                            MessageType::ERROR,
+                            format!("Bridge folding range request failed: {}", e),
+                        )
+                        .await;
+                }
+            }
+        }
+
+        if all_ranges.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(all_ranges))
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Formatting methods for Kakehashi (textDocument/formatting, textDocument/rangeFormatting).
+//!
+//! Both requests fan out to every injection region of the host document (or
+//! every region intersecting the requested range), using all bridge servers
+//! configured for the region's language. For each region the first server (by
+//! priority, then name) that supports formatting and returns edits wins; the
+//! region's edits are then merged into a single re-indented replacement in host
+//! coordinates.
+
+use std::sync::Arc;
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{
+    DocumentFormattingParams, DocumentRangeFormattingParams, FormattingOptions, MessageType,
+    Position, Range, TextEdit, Uri,
+};
+
+use crate::language::InjectionResolver;
+use crate::lsp::bridge::{ResolvedServerConfig, region_formatting_edit};
+use crate::text::PositionMapper;
+
+use super::super::bridge_context::current_upstream_request_id;
+use super::super::{Kakehashi, uri_to_url};
+
+/// Logging target for formatting.
+const LOG_TARGET: &str = "kakehashi::formatting";
+
+impl Kakehashi {
+    pub(crate) async fn formatting_impl(
+        &self,
+        params: DocumentFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.format_injection_regions(&params.text_document.uri, params.options, None)
+            .await
+    }
+
+    pub(crate) async fn range_formatting_impl(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        self.format_injection_regions(
+            &params.text_document.uri,
+            params.options,
+            Some(params.range),
+        )
+        .await
+    }
+
+    /// Format injection regions, optionally limited to those intersecting `range`.
+    ///
+    /// With a range, regions are sent `textDocument/rangeFormatting` with the
+    /// range clamped to the region; otherwise `textDocument/formatting`.
+    async fn format_injection_regions(
+        &self,
+        lsp_uri: &Uri,
+        options: FormattingOptions,
+        range: Option<Range>,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        // Convert ls_types::Uri to url::Url for internal use
+        let Ok(uri) = uri_to_url(lsp_uri) else {
+            log::warn!("Invalid URI in formatting: {}", lsp_uri.as_str());
+            return Ok(None);
+        };
+
+        self.client
+            .log_message(MessageType::INFO, format!("formatting called for {}", uri))
+            .await;
+
+        // Get document snapshot (minimizes lock duration)
+        let (snapshot, missing_message) = match self.documents.get(&uri) {
+            None => (None, Some("No document found")),
+            Some(doc) => match doc.snapshot() {
+                None => (None, Some("Document not fully initialized")),
+                Some(snapshot) => (Some(snapshot), None),
+            },
+            // doc automatically dropped here, lock released
+        };
+        if let Some(message) = missing_message {
+            self.client.log_message(MessageType::INFO, message).await;
+            return Ok(None);
+        }
+        let snapshot = snapshot.expect("snapshot set when missing_message is None");
+
+        // Get the language for this document
+        let Some(language_name) = self.get_language_for_document(&uri) else {
+            log::debug!(target: LOG_TARGET, "No language detected");
+            return Ok(None);
+        };
+
+        // Get injection query to detect injection regions
+        let Some(injection_query) = self.language.get_injection_query(&language_name) else {
+            return Ok(None);
+        };
+
+        let all_regions = InjectionResolver::resolve_all(
+            &self.language,
+            self.bridge.region_id_tracker(),
+            &uri,
+            snapshot.tree(),
+            snapshot.text(),
+            injection_query.as_ref(),
+        );
+
+        // All regions share the upstream request ID, so a cancel reaches every downstream request.
+        let upstream_request_id = current_upstream_request_id();
+
+        let mapper = PositionMapper::new(snapshot.text());
+        let pool = self.bridge.pool_arc();
+        let options = Arc::new(options);
+        let mut join_set = tokio::task::JoinSet::new();
+
+        for resolved in all_regions {
+            let line_range = resolved.region.line_range.clone();
+
+            // For range formatting, skip regions outside the range and clamp to the region
+            let region_range = match range {
+                None => None,
+                Some(range) => {
+                    let Some(region_end) = mapper.byte_to_position(resolved.region.byte_range.end)
+                    else {
+                        continue;
+                    };
+                    match clamp_to_region(range, line_range.start, region_end) {
+                        Some(region_range) => Some(region_range),
+                        None => continue,
+                    }
+                }
+            };
+
+            let configs: Vec<ResolvedServerConfig> = self.get_all_bridge_configs_for_language(
+                &uri,
+                &language_name,
+                &resolved.injection_language,
+            );
+            if configs.is_empty() {
+                continue;
+            }
+
+            let pool = Arc::clone(&pool);
+            let uri = uri.clone();
+            let options = Arc::clone(&options);
+            let upstream_id = upstream_request_id.clone();
+
+            join_set.spawn(async move {
+                // First server that supports formatting and returns edits wins;
+                // applying several formatters to one region would conflict.
+                for resolved_config in configs {
+                    let response = match region_range {
+                        None => {
+                            pool.send_formatting_request(
+                                &resolved_config.server_name,
+                                &resolved_config.config,
+                                &uri,
+                                &options,
+                                &resolved.injection_language,
+                                &resolved.region.region_id,
+                                line_range.start,
+                                &resolved.virtual_content,
+                                upstream_id.clone(),
+                            )
+                            .await
+                        }
+                        Some(region_range) => {
+                            pool.send_range_formatting_request(
+                                &resolved_config.server_name,
+                                &resolved_config.config,
+                                &uri,
+                                region_range,
+                                &options,
+                                &resolved.injection_language,
+                                &resolved.region.region_id,
+                                line_range.start,
+                                &resolved.virtual_content,
+                                upstream_id.clone(),
+                            )
+                            .await
+                        }
+                    };
+
+                    match response {
+                        Ok(Some(edits)) => return Some((resolved.region.byte_range, edits)),
+                        Ok(None) => {}
+                        Err(e) => {
+                            log::warn!(
+                                target: LOG_TARGET,
+                                "Formatting request to {} failed for region {}: {}",
+                                resolved_config.server_name,
+                                resolved.region.region_id,
+                                e
+                            );
+                        }
+                    }
+                }
+                None
+            });
+        }
+
+        if join_set.is_empty() {
+            return Ok(None);
+        }
+
+        // Merge each region's edits into one re-indented replacement
+        let mut edits = Vec::new();
+        while let Some(result) = join_set.join_next().await {
+            match result {
+                Ok(Some((byte_range, region_edits))) => {
+                    if let Some(edit) =
+                        region_formatting_edit(snapshot.text(), byte_range, &region_edits)
+                    {
+                        edits.push(edit);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::error!(target: LOG_TARGET, "Formatting task panicked: {}", e);
+                }
+            }
+        }
+
+        // Regions never overlap; sort for a deterministic edit order
+        edits.sort_by_key(|edit| edit.range.start);
+        Ok(Some(edits))
+    }
+}
+
+/// Clamp a requested formatting range to an injection region starting on
+/// `start_line` and ending at `end`, or `None` if they do not intersect.
+///
+/// The region's content may end mid-line, before the closing delimiter; a
+/// selection starting there (e.g., on a closing fence) misses the region.
+fn clamp_to_region(range: Range, start_line: u32, end: Position) -> Option<Range> {
+    if range.end.line < start_line || range.start >= end {
+        return None;
+    }
+    Some(Range {
+        start: range.start.max(Position::new(start_line, 0)),
+        end: range.end.min(end),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_to_region_keeps_last_line_of_region() {
+        // Region content on lines 1-2, ending mid-line before a closing fence
+        let end = Position::new(2, 6);
+        let whole = Range::new(Position::new(0, 0), Position::new(4, 0));
+        assert_eq!(
+            clamp_to_region(whole, 1, end),
+            Some(Range::new(Position::new(1, 0), end))
+        );
+
+        let inside = Range::new(Position::new(2, 1), Position::new(2, 3));
+        assert_eq!(clamp_to_region(inside, 1, end), Some(inside));
+    }
+
+    #[test]
+    fn clamp_to_region_skips_selection_after_region_end() {
+        let end = Position::new(2, 6);
+        let on_fence = Range::new(Position::new(2, 6), Position::new(2, 9));
+        assert_eq!(clamp_to_region(on_fence, 1, end), None);
+
+        let before = Range::new(Position::new(0, 0), Position::new(0, 3));
+        assert_eq!(clamp_to_region(before, 1, end), None);
+    }
+}
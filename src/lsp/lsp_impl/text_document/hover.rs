@@ -1,134 +1,48 @@
 //! Hover method for Kakehashi.
 
-use tower_lsp_server::jsonrpc::{Id, Result};
-use tower_lsp_server::ls_types::{Hover, HoverParams, MessageType};
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{Hover, HoverParams};
 
-use crate::language::InjectionResolver;
-use crate::lsp::bridge::UpstreamId;
-use crate::lsp::get_current_request_id;
-use crate::text::PositionMapper;
-
-use super::super::{Kakehashi, uri_to_url};
+use super::super::Kakehashi;
+use super::super::bridge_merge::merge_hovers;
 
 impl Kakehashi {
     pub(crate) async fn hover_impl(&self, params: HoverParams) -> Result<Option<Hover>> {
         let lsp_uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
-        // Convert ls_types::Uri to url::Url for internal use
-        let Ok(uri) = uri_to_url(&lsp_uri) else {
-            log::warn!("Invalid URI in hover: {}", lsp_uri.as_str());
-            return Ok(None);
-        };
-
-        self.client
-            .log_message(
-                MessageType::INFO,
-                format!(
-                    "hover called for {} at line {} col {}",
-                    uri, position.line, position.character
-                ),
-            )
-            .await;
-
-        // Get document snapshot (minimizes lock duration)
-        let (snapshot, missing_message) = match self.documents.get(&uri) {
-            None => (None, Some("No document found")),
-            Some(doc) => match doc.snapshot() {
-                None => (None, Some("Document not fully initialized")),
-                Some(snapshot) => (Some(snapshot), None),
-            },
-            // doc automatically dropped here, lock released
-        };
-        if let Some(message) = missing_message {
-            self.client.log_message(MessageType::INFO, message).await;
-            return Ok(None);
-        }
-        let snapshot = snapshot.expect("snapshot set when missing_message is None");
-
-        // Get the language for this document
-        let Some(language_name) = self.get_language_for_document(&uri) else {
-            log::debug!(target: "kakehashi::hover", "No language detected");
-            return Ok(None);
-        };
-
-        // Get injection query to detect injection regions
-        let Some(injection_query) = self.language.get_injection_query(&language_name) else {
-            return Ok(None);
-        };
-
-        // Resolve injection region at position (centralizes 29-86 lines of duplication)
-        let mapper = PositionMapper::new(snapshot.text());
-        let Some(byte_offset) = mapper.position_to_byte(position) else {
-            return Ok(None);
-        };
-
-        let Some(resolved) = InjectionResolver::resolve_at_byte_offset(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            &uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-            byte_offset,
-        ) else {
-            // Not in an injection region - return None
-            return Ok(None);
-        };
-
-        // Get bridge server config for this language
-        // The bridge filter is checked inside get_bridge_config_for_language
-        let Some(resolved_config) =
-            self.get_bridge_config_for_language(&language_name, &resolved.injection_language)
+        // Servers lacking textDocument/hover are skipped; the bridge filter is
+        // checked while resolving the context
+        let Some(ctx) = self
+            .resolve_bridge_fan_out_context(&lsp_uri, position, "hover", "textDocument/hover")
+            .await
         else {
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!(
-                        "No bridge server configured for language: {} (host: {})",
-                        resolved.injection_language, language_name
-                    ),
-                )
-                .await;
             return Ok(None);
         };
 
-        // Send hover request via language server pool
-        // Get upstream request ID from task-local storage (set by RequestIdCapture middleware)
-        // Supports both numeric and string IDs per LSP spec
-        let upstream_request_id = match get_current_request_id() {
-            Some(Id::Number(n)) => UpstreamId::Number(n),
-            Some(Id::String(s)) => UpstreamId::String(s),
-            // For notifications without ID or null ID, use Null to avoid collision with ID 0
-            None | Some(Id::Null) => UpstreamId::Null,
-        };
-        let response = self
-            .bridge
-            .pool()
-            .send_hover_request(
-                &resolved_config.server_name,
-                &resolved_config.config,
-                &uri,
-                position,
-                &resolved.injection_language,
-                &resolved.region.region_id,
-                resolved.region.line_range.start,
-                &resolved.virtual_content,
-                upstream_request_id,
+        // Send hover request to every capable server via language server pool
+        let hovers = self
+            .fan_out_bridge_request(
+                &ctx,
+                "hover",
+                |_| false,
+                |server| {
+                    self.bridge.pool().send_hover_request(
+                        &server.server_name,
+                        &server.config,
+                        &ctx.uri,
+                        ctx.position,
+                        &ctx.resolved.injection_language,
+                        &ctx.resolved.region.region_id,
+                        ctx.resolved.region.line_range.start,
+                        &ctx.resolved.virtual_content,
+                        ctx.upstream_request_id.clone(),
+                    )
+                },
             )
             .await;
 
-        match response {
-            Ok(hover) => Ok(hover),
-            Err(e) => {
-                self.client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("Bridge hover request failed: {}", e),
-                    )
-                    .await;
-                Ok(None)
-            }
-        }
+        // Concatenate hovers from all servers (or keep the first, per bridgeMerge)
+        Ok(merge_hovers(hovers))
     }
 }
@@ -82,9 +82,16 @@ impl Kakehashi {
         };
 
         // Get bridge server config for this language
-        // The bridge filter is checked inside get_bridge_config_for_language
-        let Some(resolved_config) =
-            self.get_bridge_config_for_language(&language_name, &resolved.injection_language)
+        // The bridge filter is checked inside select_bridge_config; servers
+        // lacking textDocument/inlayHint are skipped
+        let Some(resolved_config) = self
+            .select_bridge_config(
+                &uri,
+                &language_name,
+                &resolved.injection_language,
+                "textDocument/inlayHint",
+            )
+            .await
         else {
             self.client
                 .log_message(
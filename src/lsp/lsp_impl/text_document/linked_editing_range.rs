@@ -0,0 +1,58 @@
+//! Linked editing range method for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{LinkedEditingRangeParams, LinkedEditingRanges, MessageType};
+
+use super::super::Kakehashi;
+
+impl Kakehashi {
+    pub(crate) async fn linked_editing_range_impl(
+        &self,
+        params: LinkedEditingRangeParams,
+    ) -> Result<Option<LinkedEditingRanges>> {
+        let lsp_uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let Some(ctx) = self
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "linkedEditingRange",
+                "textDocument/linkedEditingRange",
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // Send linked editing range request via language server pool
+        let response = self
+            .bridge
+            .pool()
+            .send_linked_editing_range_request(
+                &ctx.resolved_config.server_name,
+                &ctx.resolved_config.config,
+                &ctx.uri,
+                ctx.position,
+                &ctx.resolved.injection_language,
+                &ctx.resolved.region.region_id,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(ranges) => Ok(ranges),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge linked editing range request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+}
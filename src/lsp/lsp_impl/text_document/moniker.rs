@@ -11,7 +11,7 @@ impl Kakehashi {
         let position = params.text_document_position_params.position;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "moniker")
+            .resolve_bridge_context(&lsp_uri, position, "moniker", "textDocument/moniker")
             .await
         else {
             return Ok(None);
@@ -0,0 +1,60 @@
+//! On-type formatting method for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{DocumentOnTypeFormattingParams, MessageType, TextEdit};
+
+use super::super::Kakehashi;
+
+impl Kakehashi {
+    pub(crate) async fn on_type_formatting_impl(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let lsp_uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        let Some(ctx) = self
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "onTypeFormatting",
+                "textDocument/onTypeFormatting",
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // Send on-type formatting request via language server pool
+        let response = self
+            .bridge
+            .pool()
+            .send_on_type_formatting_request(
+                &ctx.resolved_config.server_name,
+                &ctx.resolved_config.config,
+                &ctx.uri,
+                ctx.position,
+                &params.ch,
+                &params.options,
+                &ctx.resolved.injection_language,
+                &ctx.resolved.region.region_id,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(edits) => Ok(edits),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge on-type formatting request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+}
@@ -32,8 +32,6 @@
 use tower_lsp_server::ls_types::Uri;
 use url::Url;
 
-use crate::language::InjectionResolver;
-
 use super::super::Kakehashi;
 use super::diagnostic::{DiagnosticRequestInfo, fan_out_diagnostic_requests};
 
@@ -138,24 +136,16 @@ impl Kakehashi {
         // Get language for document
         let language_name = self.get_language_for_document(uri)?;
 
-        // Get injection query
-        let injection_query = self.language.get_injection_query(&language_name)?;
-
-        // Collect all injection regions
-        let all_regions = InjectionResolver::resolve_all(
-            &self.language,
-            self.bridge.region_id_tracker(),
-            uri,
-            snapshot.tree(),
-            snapshot.text(),
-            injection_query.as_ref(),
-        );
+        // Collect all injection regions, plus the host document if it is bridged
+        let all_regions = self.resolve_bridge_regions(uri, &language_name, &snapshot);
 
         if all_regions.is_empty() {
+            // Documents without injection support have nothing to clear
+            self.language.get_injection_query(&language_name)?;
             return Some(Vec::new());
         }
 
         // Build request infos for background task
-        Some(self.build_diagnostic_request_infos(&language_name, &all_regions))
+        Some(self.build_diagnostic_request_infos(uri, &language_name, &all_regions))
     }
 }
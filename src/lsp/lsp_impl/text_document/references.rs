@@ -1,9 +1,10 @@
 //! Find references method for Kakehashi.
 
 use tower_lsp_server::jsonrpc::Result;
-use tower_lsp_server::ls_types::{Location, MessageType, ReferenceParams};
+use tower_lsp_server::ls_types::{Location, ReferenceParams};
 
 use super::super::Kakehashi;
+use super::super::bridge_merge::merge_locations;
 
 impl Kakehashi {
     pub(crate) async fn references_impl(
@@ -15,41 +16,38 @@ impl Kakehashi {
         let include_declaration = params.context.include_declaration;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "references")
+            .resolve_bridge_fan_out_context(
+                &lsp_uri,
+                position,
+                "references",
+                "textDocument/references",
+            )
             .await
         else {
             return Ok(None);
         };
 
         // Send references request via language server pool
-        let response = self
-            .bridge
-            .pool()
-            .send_references_request(
-                &ctx.resolved_config.server_name,
-                &ctx.resolved_config.config,
-                &ctx.uri,
-                ctx.position,
-                &ctx.resolved.injection_language,
-                &ctx.resolved.region.region_id,
-                ctx.resolved.region.line_range.start,
-                &ctx.resolved.virtual_content,
-                include_declaration,
-                ctx.upstream_request_id,
-            )
+        let responses = self
+            .fan_out_bridge_request(&ctx, "references", Vec::is_empty, |server| {
+                self.bridge.pool().send_references_request(
+                    &server.server_name,
+                    &server.config,
+                    &ctx.uri,
+                    ctx.position,
+                    &ctx.resolved.injection_language,
+                    &ctx.resolved.region.region_id,
+                    ctx.resolved.region.line_range.start,
+                    &ctx.resolved.virtual_content,
+                    include_declaration,
+                    ctx.upstream_request_id.clone(),
+                )
+            })
             .await;
 
-        match response {
-            Ok(locations) => Ok(locations),
-            Err(e) => {
-                self.client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("Bridge references request failed: {}", e),
-                    )
-                    .await;
-                Ok(None)
-            }
+        if responses.is_empty() {
+            return Ok(None);
         }
+        Ok(Some(merge_locations(responses)))
     }
 }
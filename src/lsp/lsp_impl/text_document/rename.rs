@@ -12,7 +12,7 @@ impl Kakehashi {
         let new_name = params.new_name;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "rename")
+            .resolve_bridge_context(&lsp_uri, position, "rename", "textDocument/rename")
             .await
         else {
             return Ok(None);
@@ -28,18 +28,24 @@ use tower_lsp_server::ls_types::{
 };
 
 use crate::analysis::{
-    calculate_delta_or_full, handle_semantic_tokens_full,
+    BridgedSemanticToken, calculate_delta_or_full, handle_semantic_tokens_full_with_overlay,
     handle_semantic_tokens_range_parallel_async, next_result_id,
 };
+use crate::language::InjectionResolver;
 use crate::lsp::bridge::UpstreamId;
 use crate::lsp::get_current_request_id;
 use crate::lsp::request_id::{CancelReceiver, CancelSubscriptionGuard};
 
+use super::super::bridge_context::current_upstream_request_id;
 use super::super::{Kakehashi, uri_to_url};
 
 /// Timeout for spawn_blocking parse operations to prevent hangs on pathological inputs.
 const PARSE_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// How long to wait for bridged servers' semantic tokens before falling back to
+/// tree-sitter-only tokens for their regions.
+const BRIDGE_SEMANTIC_TOKENS_TIMEOUT: Duration = Duration::from_millis(500);
+
 /// Reason why a semantic token request was cancelled.
 #[derive(Debug, Clone, Copy)]
 enum CancellationReason {
@@ -98,6 +104,105 @@ impl Kakehashi {
         }
     }
 
+    /// Request semantic tokens from bridged servers for every injection region.
+    ///
+    /// Only servers configured with `semanticTokens = true` are asked, at most one
+    /// per region (the first in priority order supporting
+    /// `textDocument/semanticTokens/full`). Regions whose server fails or does not
+    /// answer within `BRIDGE_SEMANTIC_TOKENS_TIMEOUT` contribute no tokens, leaving
+    /// the tree-sitter tokens in place.
+    async fn collect_bridged_semantic_tokens(
+        &self,
+        uri: &Url,
+        language_name: &str,
+        tree: &Tree,
+        text: &str,
+    ) -> Vec<BridgedSemanticToken> {
+        let settings = self.settings_manager.load_settings();
+        let opted_in = settings
+            .language_servers
+            .as_ref()
+            .is_some_and(|servers| servers.values().any(|c| c.semantic_tokens == Some(true)));
+        if !opted_in {
+            return Vec::new();
+        }
+        let Some(injection_query) = self.language.get_injection_query(language_name) else {
+            return Vec::new();
+        };
+
+        let regions = InjectionResolver::resolve_all(
+            &self.language,
+            self.bridge.region_id_tracker(),
+            uri,
+            tree,
+            text,
+            injection_query.as_ref(),
+        );
+        let pool = self.bridge.pool();
+        let upstream_request_id = current_upstream_request_id();
+
+        let mut requests = Vec::new();
+        for resolved in &regions {
+            let candidates = self
+                .bridge
+                .get_all_configs_for_language(
+                    &settings,
+                    uri,
+                    language_name,
+                    &resolved.injection_language,
+                )
+                .into_iter()
+                .filter(|c| c.config.semantic_tokens == Some(true))
+                .collect();
+            let Some(server) = pool
+                .capable_servers_for_method("textDocument/semanticTokens/full", candidates)
+                .await
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            let upstream_request_id = upstream_request_id.clone();
+            requests.push(async move {
+                let request = pool.send_semantic_tokens_request(
+                    &server.server_name,
+                    &server.config,
+                    uri,
+                    &resolved.injection_language,
+                    &resolved.region.region_id,
+                    resolved.region.line_range.start,
+                    &resolved.virtual_content,
+                    upstream_request_id,
+                );
+                match tokio::time::timeout(BRIDGE_SEMANTIC_TOKENS_TIMEOUT, request).await {
+                    Ok(Ok(tokens)) => tokens.unwrap_or_default(),
+                    Ok(Err(e)) => {
+                        log::warn!(
+                            target: "kakehashi::semantic",
+                            "Bridge semantic tokens request to {} failed: {}",
+                            server.server_name, e
+                        );
+                        Vec::new()
+                    }
+                    Err(_) => {
+                        log::debug!(
+                            target: "kakehashi::semantic",
+                            "Bridge semantic tokens from {} timed out after {:?}; using tree-sitter tokens",
+                            server.server_name, BRIDGE_SEMANTIC_TOKENS_TIMEOUT
+                        );
+                        Vec::new()
+                    }
+                }
+            });
+        }
+
+        futures::future::join_all(requests)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     /// Get the syntax tree for a document, waiting for parse completion or parsing on-demand.
     ///
     /// This handles the race condition where semantic tokens are requested before
@@ -376,15 +481,23 @@ impl Kakehashi {
             let coordinator = std::sync::Arc::clone(&self.language);
 
             // Compute tokens, racing against cancel notification if provided
-            let compute_future = handle_semantic_tokens_full(
-                text.clone(),
-                tree.clone(),
-                query,
-                Some(language_name.clone()),
-                Some(capture_mappings),
-                coordinator,
-                supports_multiline,
-            );
+            let compute_future = async {
+                // Bridged server tokens (opt-in) are overlaid on tree-sitter tokens
+                let bridged_tokens = self
+                    .collect_bridged_semantic_tokens(&uri, &language_name, &tree, &text)
+                    .await;
+                handle_semantic_tokens_full_with_overlay(
+                    text.clone(),
+                    tree.clone(),
+                    query,
+                    Some(language_name.clone()),
+                    Some(capture_mappings),
+                    coordinator,
+                    supports_multiline,
+                    bridged_tokens,
+                )
+                .await
+            };
 
             let result = if let Some(cancel_rx) = cancel_rx {
                 // Race between computation and cancel notification
@@ -600,15 +713,23 @@ impl Kakehashi {
             let coordinator = std::sync::Arc::clone(&self.language);
 
             // Compute tokens, racing against cancel notification if provided
-            let compute_future = handle_semantic_tokens_full(
-                text.clone(),
-                tree.clone(),
-                query,
-                Some(language_name.clone()),
-                Some(capture_mappings),
-                coordinator,
-                supports_multiline,
-            );
+            let compute_future = async {
+                // Bridged server tokens (opt-in) are overlaid on tree-sitter tokens
+                let bridged_tokens = self
+                    .collect_bridged_semantic_tokens(&uri, &language_name, &tree, &text)
+                    .await;
+                handle_semantic_tokens_full_with_overlay(
+                    text.clone(),
+                    tree.clone(),
+                    query,
+                    Some(language_name.clone()),
+                    Some(capture_mappings),
+                    coordinator,
+                    supports_multiline,
+                    bridged_tokens,
+                )
+                .await
+            };
 
             let result = if let Some(cancel_rx) = cancel_rx {
                 // Race between computation and cancel notification
@@ -17,7 +17,12 @@ impl Kakehashi {
         let position = params.text_document_position_params.position;
 
         let Some(ctx) = self
-            .resolve_bridge_context(&lsp_uri, position, "goto_type_definition")
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "goto_type_definition",
+                "textDocument/typeDefinition",
+            )
             .await
         else {
             return Ok(None);
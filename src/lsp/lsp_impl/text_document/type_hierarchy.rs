@@ -0,0 +1,119 @@
+//! Type hierarchy methods for Kakehashi.
+
+use tower_lsp_server::jsonrpc::Result;
+use tower_lsp_server::ls_types::{
+    MessageType, TypeHierarchyItem, TypeHierarchyPrepareParams, TypeHierarchySubtypesParams,
+    TypeHierarchySupertypesParams,
+};
+
+use super::super::Kakehashi;
+
+impl Kakehashi {
+    pub(crate) async fn prepare_type_hierarchy_impl(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let lsp_uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let Some(ctx) = self
+            .resolve_bridge_context(
+                &lsp_uri,
+                position,
+                "prepareTypeHierarchy",
+                "textDocument/prepareTypeHierarchy",
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // Send prepare type hierarchy request via language server pool
+        let response = self
+            .bridge
+            .pool()
+            .send_prepare_type_hierarchy_request(
+                &ctx.resolved_config.server_name,
+                &ctx.resolved_config.config,
+                &ctx.uri,
+                ctx.position,
+                &ctx.resolved.injection_language,
+                &ctx.resolved.region.region_id,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge prepare type hierarchy request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) async fn supertypes_impl(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.type_hierarchy_item_request(params.item, "typeHierarchy/supertypes")
+            .await
+    }
+
+    pub(crate) async fn subtypes_impl(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        self.type_hierarchy_item_request(params.item, "typeHierarchy/subtypes")
+            .await
+    }
+
+    /// Forward a supertypes/subtypes request to the server that prepared `item`.
+    async fn type_hierarchy_item_request(
+        &self,
+        item: TypeHierarchyItem,
+        method: &str,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let Some(ctx) = self
+            .resolve_bridge_item_context(item.data.as_ref(), method)
+            .await
+        else {
+            return Ok(None);
+        };
+
+        let response = self
+            .bridge
+            .pool()
+            .send_type_hierarchy_item_request(
+                &ctx.resolved_config.config,
+                item,
+                ctx.origin,
+                &ctx.uri,
+                ctx.resolved.region.line_range.start,
+                &ctx.resolved.virtual_content,
+                method,
+                ctx.upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge {} request failed: {}", method, e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+}
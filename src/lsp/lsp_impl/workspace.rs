@@ -0,0 +1,5 @@
+//! Workspace related LSP methods.
+
+mod execute_command;
+
+// Re-export the methods (they are implemented as impl blocks on Kakehashi)
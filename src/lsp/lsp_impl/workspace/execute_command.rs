@@ -0,0 +1,171 @@
+//! Execute command method for Kakehashi.
+
+use tower_lsp_server::jsonrpc::{Error, Result};
+use tower_lsp_server::ls_types::{ExecuteCommandParams, LSPAny, MessageType};
+
+use crate::lsp::bridge::{
+    BRIDGE_RESTART_COMMAND, BRIDGE_STATUS_COMMAND, BRIDGE_STOP_COMMAND, unwrap_bridge_command,
+};
+
+use super::super::Kakehashi;
+use super::super::bridge_context::current_upstream_request_id;
+
+impl Kakehashi {
+    pub(crate) async fn execute_command_impl(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<LSPAny>> {
+        match params.command.as_str() {
+            BRIDGE_STATUS_COMMAND => return self.bridge_status_command().await,
+            BRIDGE_RESTART_COMMAND => return self.bridge_restart_command(&params.arguments).await,
+            BRIDGE_STOP_COMMAND => return self.bridge_stop_command(&params.arguments).await,
+            _ => {}
+        }
+
+        // Only bridged commands are advertised; anything else is a client error.
+        let Some((origin, command)) = unwrap_bridge_command(&params.command, &params.arguments)
+        else {
+            return Err(Error::invalid_params(format!(
+                "Unknown command: {}",
+                params.command
+            )));
+        };
+
+        let Some(resolved_config) = self.get_bridge_config_for_server(&origin.server_name) else {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "No bridge server configured with name: {}",
+                        origin.server_name
+                    ),
+                )
+                .await;
+            return Ok(None);
+        };
+
+        let upstream_request_id = current_upstream_request_id();
+
+        let response = self
+            .bridge
+            .pool()
+            .send_execute_command_request(
+                &origin.server_name,
+                &resolved_config.config,
+                command,
+                upstream_request_id,
+            )
+            .await;
+
+        match response {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Bridge execute command request failed: {}", e),
+                    )
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    /// `kakehashi.bridge.status`: state, PID and load of every downstream server.
+    async fn bridge_status_command(&self) -> Result<Option<LSPAny>> {
+        let statuses = self.bridge.pool().server_statuses().await;
+        Ok(Some(serde_json::json!(statuses)))
+    }
+
+    /// `kakehashi.bridge.restart`: restart the named (or every running) server
+    /// and re-open its virtual documents. Returns the restarted server names.
+    async fn bridge_restart_command(&self, arguments: &[LSPAny]) -> Result<Option<LSPAny>> {
+        let server_names = self.target_server_names(arguments).await?;
+
+        let mut restarted = Vec::new();
+        for server_name in server_names {
+            let Some(resolved_config) = self.get_bridge_config_for_server(&server_name) else {
+                return Err(Error::invalid_params(format!(
+                    "No bridge server configured with name: {}",
+                    server_name
+                )));
+            };
+
+            let result = self
+                .bridge
+                .pool()
+                .restart_server(&server_name, &resolved_config.config, |host_uri| {
+                    self.documents
+                        .get(host_uri)
+                        .map(|doc| doc.text().to_string())
+                        .map(|text| self.collect_bridge_contents(host_uri, &text))
+                        .unwrap_or_default()
+                })
+                .await;
+
+            match result {
+                Ok(reopened) => {
+                    self.client
+                        .log_message(
+                            MessageType::INFO,
+                            format!(
+                                "Restarted {} ({} virtual documents re-opened)",
+                                server_name, reopened
+                            ),
+                        )
+                        .await;
+                    restarted.push(server_name);
+                }
+                Err(e) => {
+                    self.client
+                        .show_message(
+                            MessageType::ERROR,
+                            format!("kakehashi: failed to restart {}: {}", server_name, e),
+                        )
+                        .await;
+                }
+            }
+        }
+        Ok(Some(serde_json::json!(restarted)))
+    }
+
+    /// `kakehashi.bridge.stop`: stop the named (or every running) server until
+    /// it is restarted. Returns the stopped server names.
+    async fn bridge_stop_command(&self, arguments: &[LSPAny]) -> Result<Option<LSPAny>> {
+        let server_names = self.target_server_names(arguments).await?;
+
+        let pool = self.bridge.pool();
+        for server_name in &server_names {
+            pool.stop_server(server_name).await;
+        }
+        Ok(Some(serde_json::json!(server_names)))
+    }
+
+    /// Server named by the first command argument, or every running server.
+    ///
+    /// A configured server name covers all of its per-root instances; an
+    /// instance key (`<server>@<root>`) targets just that instance.
+    async fn target_server_names(&self, arguments: &[LSPAny]) -> Result<Vec<String>> {
+        match arguments.first() {
+            None | Some(LSPAny::Null) => Ok(self.bridge.pool().server_names().await),
+            Some(LSPAny::String(name)) => {
+                let pool = self.bridge.pool();
+                let instances: Vec<String> = pool
+                    .server_names()
+                    .await
+                    .into_iter()
+                    .filter(|key| pool.instance(key).server_name == *name)
+                    .collect();
+                if instances.is_empty() {
+                    Ok(vec![name.clone()])
+                } else {
+                    Ok(instances)
+                }
+            }
+            Some(other) => Err(Error::invalid_params(format!(
+                "Expected a server name, got: {}",
+                other
+            ))),
+        }
+    }
+}
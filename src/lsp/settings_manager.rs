@@ -171,6 +171,17 @@ impl SettingsManager {
             .unwrap_or(false)
     }
 
+    /// Returns true if client declared textDocument.typeHierarchy.dynamicRegistration.
+    /// Returns false if initialize() hasn't been called yet (OnceLock is empty).
+    pub(crate) fn supports_dynamic_type_hierarchy(&self) -> bool {
+        self.client_capabilities
+            .get()
+            .and_then(|caps| caps.text_document.as_ref())
+            .and_then(|td| td.type_hierarchy.as_ref())
+            .and_then(|th| th.dynamic_registration)
+            .unwrap_or(false)
+    }
+
     /// Check if auto-install is enabled.
     ///
     /// Returns `false` if:
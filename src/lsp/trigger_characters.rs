@@ -0,0 +1,669 @@
+//! Dynamic registration of completion, signature help and on-type formatting
+//! trigger characters.
+//!
+//! Which characters should auto-trigger completion or signature help depends on
+//! the downstream servers: HTML completes on `<`, Python decorators on `@`, path
+//! completers on `/`. A static `initialize` response cannot know them, so when
+//! the client supports dynamic registration this module registers
+//! `textDocument/completion` and `textDocument/signatureHelp` via
+//! `client/registerCapability` with the union of the downstream trigger
+//! characters.
+//!
+//! `textDocument/onTypeFormatting` is registered the same way, but has no
+//! default characters: it is only registered while some Ready server supports
+//! on-type formatting, and unregistered again once none does.
+//!
+//! # Refresh triggers
+//!
+//! ```text
+//! downstream handshake completes ─┐
+//! downstream (un)registers       ─┼─► TriggerCharactersChanged ─┐
+//!   completion / signatureHelp / ─┤                             ├─► refresh()
+//!   onTypeFormatting             ─┘                             │
+//! host language bridges a server for the first time ────────────┘
+//! ```
+//!
+//! `refresh()` recomputes the registration options and, if they differ from
+//! the ones currently registered, unregisters the old registration and
+//! registers the new one. Refreshes are serialized so the client never sees
+//! interleaved unregister/register pairs.
+//!
+//! The document selector lists the host languages that bridged the Ready
+//! servers, by the `languageId`s the client opened their documents with
+//! (kakehashi's own language names need not match the client's). Until any
+//! server is Ready, the default triggers are registered without a selector,
+//! which matches the previous static behavior.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, OnceLock};
+
+use serde::Serialize;
+use tower_lsp_server::Client;
+use tower_lsp_server::ls_types::{
+    ClientCapabilities, CompletionOptions, CompletionRegistrationOptions, DocumentFilter,
+    DocumentOnTypeFormattingRegistrationOptions, DocumentSelector, Registration,
+    SignatureHelpOptions, TextDocumentRegistrationOptions, Unregistration,
+};
+
+use super::bridge::{LanguageServerPool, ServerTriggerCharacters};
+
+/// Logging target for trigger character registration.
+const LOG_TARGET: &str = "kakehashi::trigger_characters";
+
+/// Registration ID used for the dynamic `textDocument/completion` registration.
+const COMPLETION_REGISTRATION_ID: &str = "kakehashi-completion";
+/// Registration ID used for the dynamic `textDocument/signatureHelp` registration.
+const SIGNATURE_HELP_REGISTRATION_ID: &str = "kakehashi-signature-help";
+/// Registration ID used for the dynamic `textDocument/onTypeFormatting` registration.
+const ON_TYPE_FORMATTING_REGISTRATION_ID: &str = "kakehashi-on-type-formatting";
+
+const COMPLETION_METHOD: &str = "textDocument/completion";
+const SIGNATURE_HELP_METHOD: &str = "textDocument/signatureHelp";
+const ON_TYPE_FORMATTING_METHOD: &str = "textDocument/onTypeFormatting";
+
+/// Completion triggers used until a downstream server reports its own.
+const DEFAULT_COMPLETION_TRIGGERS: &[&str] = &[".", ":"];
+/// Signature help triggers used until a downstream server reports its own.
+const DEFAULT_SIGNATURE_HELP_TRIGGERS: &[&str] = &["(", ","];
+/// Signature help retriggers used until a downstream server reports its own.
+const DEFAULT_SIGNATURE_HELP_RETRIGGERS: &[&str] = &[","];
+
+/// Which capabilities the client allows to be registered dynamically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DynamicTriggerSupport {
+    pub(crate) completion: bool,
+    pub(crate) signature_help: bool,
+    pub(crate) on_type_formatting: bool,
+}
+
+impl DynamicTriggerSupport {
+    /// Read dynamic registration support from the client capabilities.
+    pub(crate) fn from_capabilities(caps: &ClientCapabilities) -> Self {
+        let text_document = caps.text_document.as_ref();
+        Self {
+            completion: text_document
+                .and_then(|td| td.completion.as_ref())
+                .and_then(|c| c.dynamic_registration)
+                .unwrap_or(false),
+            signature_help: text_document
+                .and_then(|td| td.signature_help.as_ref())
+                .and_then(|c| c.dynamic_registration)
+                .unwrap_or(false),
+            on_type_formatting: text_document
+                .and_then(|td| td.on_type_formatting.as_ref())
+                .and_then(|c| c.dynamic_registration)
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Signature help registration options.
+///
+/// `SignatureHelpRegistrationOptions` in ls_types lacks the trigger
+/// characters, so the options are flattened here instead.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SignatureHelpRegistration {
+    #[serde(flatten)]
+    text_document_registration_options: TextDocumentRegistrationOptions,
+    #[serde(flatten)]
+    signature_help_options: SignatureHelpOptions,
+}
+
+/// Options currently registered with the client, if any.
+#[derive(Debug, Default)]
+struct Registered {
+    completion: Option<serde_json::Value>,
+    signature_help: Option<serde_json::Value>,
+    on_type_formatting: Option<serde_json::Value>,
+}
+
+/// Keeps the client's completion / signature help / on-type formatting
+/// registrations in sync with
+/// the trigger characters of the downstream servers.
+pub(crate) struct TriggerCharacterRegistrar {
+    client: Client,
+    pool: Arc<LanguageServerPool>,
+    /// Set once during `initialize()`; unset means nothing is registered.
+    support: OnceLock<DynamicTriggerSupport>,
+    /// Host languages that bridged each server, keyed by server name.
+    host_languages: std::sync::Mutex<BTreeMap<String, BTreeSet<String>>>,
+    /// `languageId`s of the documents opened as each host language.
+    language_ids: std::sync::Mutex<BTreeMap<String, BTreeSet<String>>>,
+    /// Currently registered options; the lock also serializes refreshes.
+    registered: tokio::sync::Mutex<Registered>,
+}
+
+impl std::fmt::Debug for TriggerCharacterRegistrar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TriggerCharacterRegistrar")
+            .field("support", &self.support.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TriggerCharacterRegistrar {
+    pub(crate) fn new(client: Client, pool: Arc<LanguageServerPool>) -> Self {
+        Self {
+            client,
+            pool,
+            support: OnceLock::new(),
+            host_languages: std::sync::Mutex::new(BTreeMap::new()),
+            language_ids: std::sync::Mutex::new(BTreeMap::new()),
+            registered: tokio::sync::Mutex::new(Registered::default()),
+        }
+    }
+
+    /// Record the client's dynamic registration support.
+    ///
+    /// Returns the support so `initialize()` can omit the static providers
+    /// that will be registered dynamically instead.
+    pub(crate) fn init(&self, caps: &ClientCapabilities) -> DynamicTriggerSupport {
+        *self
+            .support
+            .get_or_init(|| DynamicTriggerSupport::from_capabilities(caps))
+    }
+
+    /// Record that `host_language` bridges `server_names`.
+    ///
+    /// Returns `true` if this added a new host language for any server, i.e.
+    /// the document selector needs a refresh.
+    pub(crate) fn record_host_language<'a>(
+        &self,
+        host_language: &str,
+        server_names: impl IntoIterator<Item = &'a str>,
+    ) -> bool {
+        let mut host_languages = self
+            .host_languages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut changed = false;
+        for server_name in server_names {
+            changed |= host_languages
+                .entry(server_name.to_string())
+                .or_default()
+                .insert(host_language.to_string());
+        }
+        changed
+    }
+
+    /// Record that the client opened a document of `host_language` with
+    /// `language_id` (from `textDocument/didOpen`).
+    ///
+    /// Returns `true` if the `languageId` is new for the host language, i.e.
+    /// the document selector may need a refresh.
+    pub(crate) fn record_language_id(&self, host_language: &str, language_id: &str) -> bool {
+        self.language_ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(host_language.to_string())
+            .or_default()
+            .insert(language_id.to_string())
+    }
+
+    /// Recompute the registrations and update the client if they changed.
+    pub(crate) async fn refresh(&self) {
+        let Some(support) = self.support.get().copied() else {
+            return;
+        };
+        if !support.completion && !support.signature_help && !support.on_type_formatting {
+            return;
+        }
+
+        // Hold the lock across computation and client round-trips so refreshes
+        // never interleave and a stale refresh cannot overwrite a newer one
+        let mut registered = self.registered.lock().await;
+
+        let servers = self.pool.ready_server_trigger_characters().await;
+        let host_languages = client_language_ids(
+            &self
+                .host_languages
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()),
+            &self.language_ids.lock().unwrap_or_else(|e| e.into_inner()),
+        );
+
+        if support.completion {
+            let options = serde_json::to_value(completion_registration(&servers, &host_languages))
+                .expect("registration options serialize");
+            if registered.completion.as_ref() != Some(&options) {
+                self.reregister(
+                    COMPLETION_REGISTRATION_ID,
+                    COMPLETION_METHOD,
+                    registered.completion.is_some(),
+                    options.clone(),
+                )
+                .await;
+                registered.completion = Some(options);
+            }
+        }
+
+        if support.signature_help {
+            let options =
+                serde_json::to_value(signature_help_registration(&servers, &host_languages))
+                    .expect("registration options serialize");
+            if registered.signature_help.as_ref() != Some(&options) {
+                self.reregister(
+                    SIGNATURE_HELP_REGISTRATION_ID,
+                    SIGNATURE_HELP_METHOD,
+                    registered.signature_help.is_some(),
+                    options.clone(),
+                )
+                .await;
+                registered.signature_help = Some(options);
+            }
+        }
+
+        if support.on_type_formatting {
+            let options = on_type_formatting_registration(&servers, &host_languages)
+                .map(|o| serde_json::to_value(o).expect("registration options serialize"));
+            if registered.on_type_formatting != options {
+                match &options {
+                    Some(options) => {
+                        self.reregister(
+                            ON_TYPE_FORMATTING_REGISTRATION_ID,
+                            ON_TYPE_FORMATTING_METHOD,
+                            registered.on_type_formatting.is_some(),
+                            options.clone(),
+                        )
+                        .await
+                    }
+                    None => {
+                        self.unregister(
+                            ON_TYPE_FORMATTING_REGISTRATION_ID,
+                            ON_TYPE_FORMATTING_METHOD,
+                        )
+                        .await
+                    }
+                }
+                registered.on_type_formatting = options;
+            }
+        }
+    }
+
+    /// Replace a registration: unregister the previous one (if any), then register.
+    async fn reregister(
+        &self,
+        id: &str,
+        method: &str,
+        was_registered: bool,
+        register_options: serde_json::Value,
+    ) {
+        if was_registered {
+            self.unregister(id, method).await;
+        }
+
+        log::debug!(
+            target: LOG_TARGET,
+            "Registering {} with options {}",
+            method,
+            register_options
+        );
+        if let Err(e) = self
+            .client
+            .register_capability(vec![Registration {
+                id: id.to_string(),
+                method: method.to_string(),
+                register_options: Some(register_options),
+            }])
+            .await
+        {
+            log::warn!(target: LOG_TARGET, "Failed to register {}: {}", method, e);
+        }
+    }
+
+    /// Remove a registration previously made by [`reregister`](Self::reregister).
+    async fn unregister(&self, id: &str, method: &str) {
+        if let Err(e) = self
+            .client
+            .unregister_capability(vec![Unregistration {
+                id: id.to_string(),
+                method: method.to_string(),
+            }])
+            .await
+        {
+            log::debug!(target: LOG_TARGET, "Failed to unregister {}: {}", method, e);
+        }
+    }
+}
+
+/// Build the completion registration from the Ready servers' trigger characters.
+fn completion_registration(
+    servers: &[(String, ServerTriggerCharacters)],
+    host_languages: &BTreeMap<String, BTreeSet<String>>,
+) -> CompletionRegistrationOptions {
+    let triggers = union_or_default(
+        servers.iter().map(|(_, t)| &t.completion),
+        DEFAULT_COMPLETION_TRIGGERS,
+    );
+    CompletionRegistrationOptions {
+        text_document_registration_options: TextDocumentRegistrationOptions {
+            document_selector: document_selector(servers, host_languages),
+        },
+        completion_options: CompletionOptions {
+            trigger_characters: Some(triggers),
+            resolve_provider: Some(true),
+            ..Default::default()
+        },
+    }
+}
+
+/// Build the signature help registration from the Ready servers' trigger characters.
+fn signature_help_registration(
+    servers: &[(String, ServerTriggerCharacters)],
+    host_languages: &BTreeMap<String, BTreeSet<String>>,
+) -> SignatureHelpRegistration {
+    let triggers = union_or_default(
+        servers.iter().map(|(_, t)| &t.signature_help),
+        DEFAULT_SIGNATURE_HELP_TRIGGERS,
+    );
+    let retriggers = union_or_default(
+        servers.iter().map(|(_, t)| &t.signature_help_retrigger),
+        DEFAULT_SIGNATURE_HELP_RETRIGGERS,
+    );
+    SignatureHelpRegistration {
+        text_document_registration_options: TextDocumentRegistrationOptions {
+            document_selector: document_selector(servers, host_languages),
+        },
+        signature_help_options: SignatureHelpOptions {
+            trigger_characters: Some(triggers),
+            retrigger_characters: Some(retriggers),
+            ..Default::default()
+        },
+    }
+}
+
+/// Build the on-type formatting registration from the Ready servers' trigger characters.
+///
+/// Returns `None` if no Ready server supports on-type formatting. Servers
+/// list their own `firstTriggerCharacter` first, so the first server's first
+/// character becomes the registration's `firstTriggerCharacter`.
+fn on_type_formatting_registration(
+    servers: &[(String, ServerTriggerCharacters)],
+    host_languages: &BTreeMap<String, BTreeSet<String>>,
+) -> Option<DocumentOnTypeFormattingRegistrationOptions> {
+    let mut triggers: Vec<String> = Vec::new();
+    for c in servers.iter().flat_map(|(_, t)| &t.on_type_formatting) {
+        if !triggers.contains(c) {
+            triggers.push(c.clone());
+        }
+    }
+    let mut triggers = triggers.into_iter();
+    let first_trigger_character = triggers.next()?;
+    let more: Vec<String> = triggers.collect();
+    Some(DocumentOnTypeFormattingRegistrationOptions {
+        document_selector: document_selector(servers, host_languages),
+        first_trigger_character,
+        more_trigger_character: (!more.is_empty()).then_some(more),
+    })
+}
+
+/// Replace the host languages of each server with the `languageId`s the
+/// client opened them with; a host language not seen in `didOpen` is kept
+/// as is.
+fn client_language_ids(
+    host_languages: &BTreeMap<String, BTreeSet<String>>,
+    language_ids: &BTreeMap<String, BTreeSet<String>>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    host_languages
+        .iter()
+        .map(|(server, hosts)| {
+            let ids = hosts
+                .iter()
+                .flat_map(|host| match language_ids.get(host) {
+                    Some(ids) if !ids.is_empty() => ids.iter().cloned().collect(),
+                    _ => vec![host.clone()],
+                })
+                .collect();
+            (server.clone(), ids)
+        })
+        .collect()
+}
+
+/// Union of all servers' characters, or `defaults` if no server reports any.
+fn union_or_default<'a>(
+    per_server: impl Iterator<Item = &'a Vec<String>>,
+    defaults: &[&str],
+) -> Vec<String> {
+    let union: BTreeSet<&String> = per_server.flatten().collect();
+    if union.is_empty() {
+        defaults.iter().map(|c| c.to_string()).collect()
+    } else {
+        union.into_iter().cloned().collect()
+    }
+}
+
+/// Document selector covering the host languages (as client `languageId`s)
+/// that bridged the given servers.
+///
+/// Returns `None` (the client's own selector, i.e. every document) until any
+/// Ready server has been bridged from a known host language.
+fn document_selector(
+    servers: &[(String, ServerTriggerCharacters)],
+    host_languages: &BTreeMap<String, BTreeSet<String>>,
+) -> Option<DocumentSelector> {
+    let languages: BTreeSet<&String> = servers
+        .iter()
+        .filter_map(|(name, _)| host_languages.get(name))
+        .flatten()
+        .collect();
+    if languages.is_empty() {
+        return None;
+    }
+    Some(
+        languages
+            .into_iter()
+            .map(|language| DocumentFilter {
+                language: Some(language.clone()),
+                scheme: None,
+                pattern: None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{
+        CompletionClientCapabilities, SignatureHelpClientCapabilities,
+        TextDocumentClientCapabilities,
+    };
+
+    fn server(
+        name: &str,
+        completion: &[&str],
+        signature_help: &[&str],
+    ) -> (String, ServerTriggerCharacters) {
+        let strings = |chars: &[&str]| chars.iter().map(|c| c.to_string()).collect();
+        (
+            name.to_string(),
+            ServerTriggerCharacters {
+                completion: strings(completion),
+                signature_help: strings(signature_help),
+                signature_help_retrigger: Vec::new(),
+                on_type_formatting: Vec::new(),
+            },
+        )
+    }
+
+    fn hosts(entries: &[(&str, &[&str])]) -> BTreeMap<String, BTreeSet<String>> {
+        entries
+            .iter()
+            .map(|(server, langs)| {
+                (
+                    server.to_string(),
+                    langs.iter().map(|l| l.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn selector_languages(selector: Option<DocumentSelector>) -> Option<Vec<String>> {
+        selector.map(|filters| filters.into_iter().filter_map(|f| f.language).collect())
+    }
+
+    #[test]
+    fn completion_triggers_are_union_of_servers() {
+        let servers = vec![
+            server("html_ls", &["<", "/"], &[]),
+            server("pyright", &[".", "@"], &["("]),
+        ];
+
+        let options = completion_registration(&servers, &BTreeMap::new());
+
+        assert_eq!(
+            options.completion_options.trigger_characters,
+            Some(vec![
+                ".".to_string(),
+                "/".to_string(),
+                "<".to_string(),
+                "@".to_string()
+            ])
+        );
+        assert_eq!(options.completion_options.resolve_provider, Some(true));
+    }
+
+    #[test]
+    fn defaults_are_used_without_server_triggers() {
+        let completion = completion_registration(&[], &BTreeMap::new());
+        let signature_help = signature_help_registration(&[], &BTreeMap::new());
+
+        assert_eq!(
+            completion.completion_options.trigger_characters,
+            Some(vec![".".to_string(), ":".to_string()])
+        );
+        assert_eq!(
+            signature_help.signature_help_options.trigger_characters,
+            Some(vec!["(".to_string(), ",".to_string()])
+        );
+        assert_eq!(
+            signature_help.signature_help_options.retrigger_characters,
+            Some(vec![",".to_string()])
+        );
+        assert!(
+            completion
+                .text_document_registration_options
+                .document_selector
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn selector_lists_host_languages_of_ready_servers() {
+        let servers = vec![server("pyright", &["."], &[])];
+        let host_languages = hosts(&[
+            ("pyright", &["markdown", "quarto"]),
+            // Not Ready: its host languages are not advertised
+            ("lua_ls", &["vimdoc"]),
+        ]);
+
+        let selector = document_selector(&servers, &host_languages);
+
+        assert_eq!(
+            selector_languages(selector),
+            Some(vec!["markdown".to_string(), "quarto".to_string()])
+        );
+    }
+
+    #[test]
+    fn selector_uses_language_ids_the_client_opened_documents_with() {
+        let servers = vec![server("pyright", &["."], &[])];
+        let host_languages = hosts(&[("pyright", &["markdown", "quarto"])]);
+        // Opened as "rmd" and "markdown.mdx" although parsed as markdown
+        let language_ids = hosts(&[("markdown", &["rmd", "markdown.mdx"])]);
+
+        let selector = document_selector(
+            &servers,
+            &client_language_ids(&host_languages, &language_ids),
+        );
+
+        assert_eq!(
+            selector_languages(selector),
+            Some(vec![
+                "markdown.mdx".to_string(),
+                "quarto".to_string(),
+                "rmd".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn signature_help_registration_serializes_flat() {
+        let servers = vec![server("pyright", &[], &["(", ","])];
+        let host_languages = hosts(&[("pyright", &["markdown"])]);
+
+        let value =
+            serde_json::to_value(signature_help_registration(&servers, &host_languages)).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "documentSelector": [{ "language": "markdown" }],
+                "triggerCharacters": ["(", ","],
+                "retriggerCharacters": [","]
+            })
+        );
+    }
+
+    #[test]
+    fn on_type_formatting_registration_keeps_first_trigger_character() {
+        let mut lua = server("lua_ls", &[], &[]);
+        lua.1.on_type_formatting = vec!["\n".to_string()];
+        let mut rust = server("rust-analyzer", &[], &[]);
+        rust.1.on_type_formatting = vec!["=".to_string(), ".".to_string(), "\n".to_string()];
+        let host_languages = hosts(&[("rust-analyzer", &["markdown"])]);
+
+        let value = serde_json::to_value(on_type_formatting_registration(
+            &[lua, rust],
+            &host_languages,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "documentSelector": [{ "language": "markdown" }],
+                "firstTriggerCharacter": "\n",
+                "moreTriggerCharacter": ["=", "."]
+            })
+        );
+    }
+
+    #[test]
+    fn on_type_formatting_is_not_registered_without_server_triggers() {
+        let servers = vec![server("pyright", &["."], &["("])];
+
+        assert!(on_type_formatting_registration(&servers, &BTreeMap::new()).is_none());
+    }
+
+    #[test]
+    fn dynamic_support_reads_client_capabilities() {
+        let caps = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                completion: Some(CompletionClientCapabilities {
+                    dynamic_registration: Some(true),
+                    ..Default::default()
+                }),
+                signature_help: Some(SignatureHelpClientCapabilities {
+                    dynamic_registration: Some(false),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            DynamicTriggerSupport::from_capabilities(&caps),
+            DynamicTriggerSupport {
+                completion: true,
+                signature_help: false,
+                on_type_formatting: false,
+            }
+        );
+        assert_eq!(
+            DynamicTriggerSupport::from_capabilities(&ClientCapabilities::default()),
+            DynamicTriggerSupport::default()
+        );
+    }
+}
@@ -751,3 +751,702 @@ fn test_language_uninstall_cancel() {
     // Clean up
     let _ = fs::remove_dir_all(test_dir);
 }
+
+/// Test that language lock pins the versions recorded in the install manifest
+#[test]
+fn test_language_lock_writes_lockfile_from_manifest() {
+    use std::fs;
+
+    let test_dir = "/tmp/test-language-lock";
+    let lockfile = format!("{}/kakehashi.lock", test_dir);
+
+    // Clean up and setup
+    let _ = fs::remove_dir_all(test_dir);
+    fs::create_dir_all(test_dir).expect("Failed to create data dir");
+    fs::write(
+        format!("{}/manifest.toml", test_dir),
+        r#"
+[languages.testlang]
+installed_at = 1700000000
+
+[languages.testlang.parser]
+url = "https://github.com/example/tree-sitter-testlang"
+revision = "v1.2.3"
+sha256 = "abc"
+
+[languages.testlang.queries]
+revision = "0123456789abcdef0123456789abcdef01234567"
+
+[languages.testlang.queries.files]
+"highlights.scm" = "def"
+"#,
+    )
+    .expect("Failed to write manifest");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "lock",
+            "--data-dir",
+            test_dir,
+            "--output",
+            &lockfile,
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Lock should exit with success. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let content = fs::read_to_string(&lockfile).expect("Lockfile should be written");
+    assert!(content.contains("version = 1"), "Got: {}", content);
+    assert!(
+        content.contains("revision = \"v1.2.3\""),
+        "Got: {}",
+        content
+    );
+    assert!(
+        content.contains("0123456789abcdef0123456789abcdef01234567"),
+        "Got: {}",
+        content
+    );
+    assert!(!content.contains("installed_at"), "Got: {}", content);
+
+    // Locking a language without an install record fails
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "lock",
+            "otherlang",
+            "--data-dir",
+            test_dir,
+            "--output",
+            &lockfile,
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("otherlang"),
+        "Should name the unrecorded language"
+    );
+
+    // Clean up
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+/// Test that language install without a language requires a lockfile
+#[test]
+fn test_language_install_without_language_requires_lockfile() {
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "install",
+            "--no-lockfile",
+            "--data-dir",
+            "/tmp/test-install-no-lock",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("kakehashi.lock"),
+        "Should mention the missing lockfile. Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Test that language outdated compares the manifest with parser metadata
+#[test]
+fn test_language_outdated_and_update_use_manifest_and_metadata() {
+    use std::fs;
+
+    let test_dir = "/tmp/test-language-outdated";
+
+    // Clean up and setup: a fresh metadata cache avoids network access
+    let _ = fs::remove_dir_all(test_dir);
+    fs::create_dir_all(format!("{}/cache", test_dir)).expect("Failed to create cache dir");
+    fs::write(
+        format!("{}/cache/parsers.lua", test_dir),
+        r#"
+return {
+  testlang = {
+    install_info = {
+      revision = 'v2.0.0',
+      url = 'https://github.com/example/tree-sitter-testlang',
+    },
+  },
+  current = {
+    install_info = {
+      revision = 'v1.0.0',
+      url = 'https://github.com/example/tree-sitter-current',
+    },
+  },
+}
+"#,
+    )
+    .expect("Failed to write metadata cache");
+    fs::write(
+        format!("{}/manifest.toml", test_dir),
+        r#"
+[languages.testlang]
+installed_at = 1700000000
+
+[languages.testlang.parser]
+url = "https://github.com/example/tree-sitter-testlang"
+revision = "v1.0.0"
+sha256 = "abc"
+
+[languages.current]
+installed_at = 1700000000
+
+[languages.current.parser]
+url = "https://github.com/example/tree-sitter-current"
+revision = "v1.0.0"
+sha256 = "abc"
+"#,
+    )
+    .expect("Failed to write manifest");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "outdated", "--data-dir", test_dir])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "Outdated should exit with success. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("testlang"), "Got: {}", stdout);
+    assert!(stdout.contains("v1.0.0 -> v2.0.0"), "Got: {}", stdout);
+    assert!(!stdout.contains("current"), "Got: {}", stdout);
+
+    // Up-to-date languages are left alone
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "update", "current", "--data-dir", test_dir])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("up to date"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Languages that are not installed cannot be updated
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "update", "otherlang", "--data-dir", test_dir])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+
+    // Clean up
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+/// Test that language update requires languages or --all
+#[test]
+fn test_language_update_requires_languages_or_all() {
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "update"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("<LANGUAGES>"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_language_install_from_missing_path_fails() {
+    let temp_dir = "/tmp/test-language-install-from-path";
+    let _ = std::fs::remove_dir_all(temp_dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "install",
+            "mylang",
+            "--from-path",
+            "/tmp/test-language-install-from-path/missing-grammar",
+            "--data-dir",
+            temp_dir,
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("missing-grammar"), "Got: {}", stderr);
+    // Nothing is recorded for a failed install
+    assert!(
+        !std::path::Path::new(temp_dir)
+            .join("manifest.toml")
+            .exists()
+    );
+
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_language_install_rev_requires_git() {
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "install", "mylang", "--rev", "v1.0.0"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--git"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_language_outdated_fetches_metadata_from_mirror() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let test_dir = "/tmp/test-language-outdated-mirror";
+    let _ = std::fs::remove_dir_all(test_dir);
+    std::fs::create_dir_all(test_dir).expect("Failed to create test dir");
+    std::fs::write(
+        format!("{}/manifest.toml", test_dir),
+        r#"
+[languages.testlang]
+installed_at = 1700000000
+
+[languages.testlang.parser]
+url = "https://github.com/example/tree-sitter-testlang"
+revision = "v1.0.0"
+sha256 = "abc"
+"#,
+    )
+    .expect("Failed to write manifest");
+
+    // A one-shot HTTP server standing in for an artifact proxy
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let mut request = [0u8; 4096];
+        let len = stream.read(&mut request).unwrap_or(0);
+        let body = r#"
+return {
+  testlang = {
+    install_info = {
+      revision = 'v3.0.0',
+      url = 'https://github.com/example/tree-sitter-testlang',
+    },
+  },
+}
+"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8_lossy(&request[..len]).into_owned()
+    });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "outdated",
+            "--data-dir",
+            test_dir,
+            "--no-cache",
+            "--metadata-url",
+            &format!("http://127.0.0.1:{}/mirror/parsers.lua", port),
+        ])
+        // Keep any user config out of the test
+        .env("XDG_CONFIG_HOME", test_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("v1.0.0 -> v3.0.0"), "Got: {}", stdout);
+    let request = server.join().expect("Server thread panicked");
+    assert!(
+        request.starts_with("GET /mirror/parsers.lua"),
+        "Got: {}",
+        request
+    );
+
+    let _ = std::fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn test_language_install_rejects_malformed_url_rewrite() {
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "install",
+            "lua",
+            "--rewrite-url",
+            "https://github.com/",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("PREFIX=REPLACEMENT"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Test that a packed bundle unpacks into another data directory and shows
+/// up as the origin in language status
+#[test]
+fn test_language_pack_and_unpack_round_trip() {
+    use std::fs;
+
+    let test_dir = "/tmp/test-language-pack";
+    let source = format!("{}/source", test_dir);
+    let target = format!("{}/target", test_dir);
+    let bundle = format!("{}/offline.tar", test_dir);
+    let _ = fs::remove_dir_all(test_dir);
+    let ext = if cfg!(target_os = "macos") {
+        "dylib"
+    } else if cfg!(target_os = "windows") {
+        "dll"
+    } else {
+        "so"
+    };
+    fs::create_dir_all(format!("{}/parser", source)).unwrap();
+    fs::write(format!("{}/parser/testlang.{}", source, ext), "fake").unwrap();
+    for (language, content) in [("testlang", "; inherits: base\n"), ("base", "(x) @x")] {
+        fs::create_dir_all(format!("{}/queries/{}", source, language)).unwrap();
+        fs::write(
+            format!("{}/queries/{}/highlights.scm", source, language),
+            content,
+        )
+        .unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "pack", "--installed", "-o", &bundle])
+        .args(["--data-dir", &source])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "pack failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("base") && stdout.contains("testlang"),
+        "Inherited queries should be packed. Got: {}",
+        stdout
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "unpack", &bundle, "--data-dir", &target])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "unpack failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(format!("{}/parser/testlang.{}", target, ext)).unwrap(),
+        "fake"
+    );
+    assert!(fs::metadata(format!("{}/queries/base/highlights.scm", target)).is_ok());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "status", "--data-dir", &target])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.contains("testlang") && line.contains("from bundle offline.tar")),
+        "Status should show the bundle as origin. Got: {}",
+        stdout
+    );
+
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+/// Test that pack refuses languages without an installed parser
+#[test]
+fn test_language_pack_requires_installed_parser() {
+    let test_dir = "/tmp/test-language-pack-missing";
+    let _ = std::fs::remove_dir_all(test_dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["language", "pack", "lua", "-o"])
+        .arg(format!("{}/bundle.tar", test_dir))
+        .args(["--data-dir", test_dir])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No parser installed for 'lua'"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Test that language verify detects files changed since installation
+#[test]
+fn test_language_verify_detects_modified_files() {
+    use kakehashi::install::manifest::sha256_file;
+    use std::fs;
+
+    let test_dir = "/tmp/test-language-verify";
+    let query = format!("{}/queries/testlang/highlights.scm", test_dir);
+    let _ = fs::remove_dir_all(test_dir);
+    fs::create_dir_all(format!("{}/queries/testlang", test_dir)).unwrap();
+    fs::write(&query, "(comment) @comment").unwrap();
+    fs::write(
+        format!("{}/manifest.toml", test_dir),
+        format!(
+            r#"
+[languages.testlang]
+installed_at = 1700000000
+
+[languages.testlang.queries]
+revision = "0123456789abcdef0123456789abcdef01234567"
+
+[languages.testlang.queries.files]
+"highlights.scm" = "{}"
+"#,
+            sha256_file(std::path::Path::new(&query)).unwrap()
+        ),
+    )
+    .unwrap();
+    let verify = || {
+        Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+            .args(["language", "verify", "--data-dir", test_dir])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = verify();
+    assert!(
+        output.status.success(),
+        "Unchanged files should verify. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    fs::write(&query, "(comment) @tampered").unwrap();
+    let output = verify();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "Got: {}", stderr);
+    assert!(
+        stderr.contains("highlights.scm changed since installation"),
+        "Got: {}",
+        stderr
+    );
+
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn test_doctor_json_reports_installation_problems() {
+    use std::fs;
+
+    let test_dir = "/tmp/test-doctor";
+    let _ = fs::remove_dir_all(test_dir);
+    fs::create_dir_all(format!("{}/parser", test_dir)).unwrap();
+    let extension = if cfg!(target_os = "macos") {
+        "dylib"
+    } else if cfg!(windows) {
+        "dll"
+    } else {
+        "so"
+    };
+    fs::write(
+        format!("{}/parser/broken.{}", test_dir, extension),
+        "not a library",
+    )
+    .unwrap();
+    fs::write(format!("{}/failed_parsers", test_dir), "lua\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args(["doctor", "--json", "--data-dir", test_dir])
+        .current_dir(test_dir)
+        .env("XDG_CONFIG_HOME", test_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        !output.status.success(),
+        "An unloadable parser should fail the check"
+    );
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be JSON");
+    let checks = report["checks"].as_array().unwrap();
+    let find = |category: &str, name: &str| {
+        checks
+            .iter()
+            .find(|check| check["category"] == category && check["name"] == name)
+            .unwrap_or_else(|| panic!("No {} check for {}: {}", category, name, report))
+    };
+    assert_eq!(find("data-dir", test_dir)["status"], "ok");
+    assert_eq!(find("parser", "broken")["status"], "error");
+    assert_eq!(find("failed-parser", "lua")["status"], "warning");
+
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn test_language_prune_removes_unused_languages() {
+    use std::fs;
+
+    let test_dir = "/tmp/test-language-prune";
+    let _ = fs::remove_dir_all(test_dir);
+    for language in ["oldlang", "newlang"] {
+        fs::create_dir_all(format!("{}/queries/{}", test_dir, language)).unwrap();
+        fs::write(
+            format!("{}/queries/{}/highlights.scm", test_dir, language),
+            "(comment) @comment",
+        )
+        .unwrap();
+    }
+    fs::write(
+        format!("{}/manifest.toml", test_dir),
+        r#"
+[languages.oldlang]
+installed_at = 1700000000
+
+[languages.newlang]
+installed_at = 1700000000
+"#,
+    )
+    .unwrap();
+    // Loaded just now, so it is kept
+    fs::write(
+        format!("{}/usage.toml", test_dir),
+        "[last_loaded]\nnewlang = 99999999999\n",
+    )
+    .unwrap();
+    let prune = |dry_run: bool| {
+        let mut args = vec!["language", "prune", "--data-dir", test_dir];
+        if dry_run {
+            args.push("--dry-run");
+        }
+        Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+            .args(&args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = prune(true);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Got: {}", stderr);
+    assert!(stderr.contains("Would remove oldlang"), "Got: {}", stderr);
+    assert!(!stderr.contains("newlang"), "Got: {}", stderr);
+    assert!(std::path::Path::new(&format!("{}/queries/oldlang", test_dir)).exists());
+
+    let output = prune(false);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Got: {}", stderr);
+    assert!(!std::path::Path::new(&format!("{}/queries/oldlang", test_dir)).exists());
+    assert!(std::path::Path::new(&format!("{}/queries/newlang", test_dir)).exists());
+
+    let _ = fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn test_language_install_several_languages_reports_failures() {
+    let test_dir = "/tmp/test-language-install-batch";
+    let _ = std::fs::remove_dir_all(test_dir);
+    std::fs::create_dir_all(test_dir).expect("Failed to create test dir");
+    std::fs::write(
+        format!("{}/langs.txt", test_dir),
+        "# Languages for this project\nlangb\n\nlanga  # listed twice\n",
+    )
+    .expect("Failed to write language list");
+    std::fs::write(format!("{}/doc.langc", test_dir), "hello\n").expect("Failed to write doc");
+
+    // Nothing listens on the metadata URL, so every install fails offline
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "install",
+            "langa",
+            "--from-file",
+            &format!("{}/langs.txt", test_dir),
+            "--for",
+            &format!("{}/doc.langc", test_dir),
+            "--jobs",
+            "2",
+            "--no-lockfile",
+            "--no-cache",
+            "--data-dir",
+            &format!("{}/data", test_dir),
+            "--metadata-url",
+            "http://127.0.0.1:1/parsers.lua",
+            "--queries-url",
+            &format!("file://{}/queries", test_dir),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "Got: {}", stderr);
+    assert!(
+        stderr.contains("Installed 0 of 3 language(s)."),
+        "Got: {}",
+        stderr
+    );
+    assert!(
+        stderr.contains("Failed: langa, langb, langc"),
+        "Got: {}",
+        stderr
+    );
+    // The output of each language is kept together
+    assert_eq!(
+        stderr.matches("Failed to install").count(),
+        3,
+        "Got: {}",
+        stderr
+    );
+
+    let _ = std::fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn test_language_install_from_path_takes_one_language() {
+    let output = Command::new(env!("CARGO_BIN_EXE_kakehashi"))
+        .args([
+            "language",
+            "install",
+            "langa",
+            "langb",
+            "--from-path",
+            "/tmp/test-language-install-one-path",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("single language"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
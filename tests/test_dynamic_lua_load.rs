@@ -19,6 +19,8 @@ fn test_dynamic_lua_load_from_search_paths() {
         capture_mappings: HashMap::new(),
         auto_install: None,
         language_servers: None,
+        bridge_merge: None,
+        install: None,
     };
 
     // Load settings into coordinator